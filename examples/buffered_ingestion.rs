@@ -0,0 +1,67 @@
+//! Buffered ingestion: accumulate rows locally and hand them to the channel in
+//! batches, letting the crate split each batch into requests of at most 16MB.
+//!
+//! Run with `cargo run --example buffered_ingestion`.
+
+mod support;
+
+use snowpipe_streaming::{Config, Error, StreamingIngestClient};
+
+#[derive(serde::Serialize, Clone)]
+struct Event {
+    id: u64,
+    kind: String,
+}
+
+const BATCH_SIZE: usize = 250;
+
+/// Streams `total` events through a local buffer, returning bytes written.
+async fn run(cfg: Config, total: u64) -> Result<usize, Error> {
+    let mut client =
+        StreamingIngestClient::<Event>::new("buffered", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+    let mut ch = client.open_channel("buffered_channel").await?;
+
+    let mut buffer = Vec::with_capacity(BATCH_SIZE);
+    let mut bytes = 0;
+    for id in 0..total {
+        buffer.push(Event {
+            id,
+            kind: "click".into(),
+        });
+        if buffer.len() == BATCH_SIZE {
+            bytes += ch.append_rows_iter(buffer.drain(..)).await?;
+        }
+    }
+    if !buffer.is_empty() {
+        bytes += ch.append_rows_iter(buffer.drain(..)).await?;
+    }
+
+    ch.close().await?;
+    Ok(bytes)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    let bytes = run(mock.config(), 1_000).await?;
+    println!(
+        "sent {bytes} bytes in {} requests",
+        mock.append_requests("buffered_channel").await
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flushes_one_request_per_full_batch() {
+        let mock = support::MockSnowflake::start().await;
+        let bytes = run(mock.config(), 1_000).await.expect("buffered run");
+        assert!(bytes > 0);
+        assert_eq!(mock.append_requests("buffered_channel").await, 4);
+    }
+}
@@ -0,0 +1,112 @@
+//! Change-data-capture ingestion from Debezium-style events.
+//!
+//! Each event is mapped to a row carrying the changed record plus `_CDC_OP`,
+//! `_CDC_DELETED`, and `_CDC_TS_MS` columns, then appended through a channel
+//! pool keyed by primary key: every change to a given customer lands on the
+//! same channel in order, while different customers are ingested in parallel.
+//! A downstream task or dynamic table can then merge the latest row per key.
+//!
+//! Run with `cargo run --example cdc_pipeline`.
+
+mod support;
+
+use snowpipe_streaming::cdc::{CdcRow, DebeziumEnvelope};
+use snowpipe_streaming::{ChannelReport, Config, Error, Ordering, StreamingIngestClient};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+struct Customer {
+    id: u64,
+    email: String,
+}
+
+/// What a Kafka topic fed by a Debezium connector might contain.
+fn change_events() -> Vec<String> {
+    let event = |op: &str, before: Option<(u64, &str)>, after: Option<(u64, &str)>, ts: u64| {
+        let image = |c: Option<(u64, &str)>| {
+            c.map(|(id, email)| serde_json::json!({"id": id, "email": email}))
+        };
+        serde_json::json!({
+            "payload": {
+                "before": image(before),
+                "after": image(after),
+                "op": op,
+                "ts_ms": ts,
+                "source": {"db": "crm", "table": "customers"},
+            }
+        })
+        .to_string()
+    };
+    vec![
+        event("r", None, Some((1, "ada@example.com")), 1),
+        event("c", None, Some((2, "grace@example.com")), 2),
+        event(
+            "u",
+            Some((1, "ada@example.com")),
+            Some((1, "ada@lovelace.dev")),
+            3,
+        ),
+        event("c", None, Some((3, "alan@example.com")), 4),
+        event("d", Some((2, "grace@example.com")), None, 5),
+        event(
+            "u",
+            Some((3, "alan@example.com")),
+            Some((3, "alan@turing.dev")),
+            6,
+        ),
+    ]
+}
+
+async fn run(cfg: Config, events: &[String]) -> Result<Vec<ChannelReport>, Error> {
+    let mut client =
+        StreamingIngestClient::<CdcRow<Customer>>::new("cdc", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+    let mut pool = client
+        .open_channel_pool("customers", 2, Ordering::Relaxed)
+        .await?;
+
+    let rows = events
+        .iter()
+        .map(|event| DebeziumEnvelope::<Customer>::from_json(event)?.into_row())
+        .collect::<Result<Vec<_>, Error>>()?;
+    pool.append_rows_keyed(rows, |row| row.data.id).await?;
+    pool.close().await
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    let reports = run(mock.config(), &change_events()).await?;
+    for (index, report) in reports.iter().enumerate() {
+        println!("customers_{index}: {} change rows", report.rows);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn routes_changes_by_primary_key() {
+        let mock = support::MockSnowflake::start().await;
+        let reports = run(mock.config(), &change_events())
+            .await
+            .expect("cdc pipeline");
+        assert_eq!(reports.iter().map(|r| r.rows).sum::<u64>(), 6);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let mut channel_of = std::collections::HashMap::new();
+        for request in requests.iter().filter(|r| r.url.path().ends_with("/rows")) {
+            for line in String::from_utf8_lossy(&request.body).lines() {
+                let row: serde_json::Value = serde_json::from_str(line).unwrap();
+                let previous = channel_of.insert(row["id"].as_u64().unwrap(), request.url.path());
+                assert!(previous.is_none_or(|p| p == request.url.path()));
+                if row["id"] == 2 && row["_CDC_OP"] == "d" {
+                    assert_eq!(row["_CDC_DELETED"], true);
+                }
+            }
+        }
+        assert_eq!(channel_of.len(), 3);
+    }
+}
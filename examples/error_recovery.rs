@@ -0,0 +1,132 @@
+//! Recovering from append failures.
+//!
+//! * Transient server errors: the failed request did not advance the offset
+//!   token, so retrying the same rows after a back-off is safe.
+//! * `Error::DataTooLarge`: the row can never be sent as-is; divert it to a
+//!   dead-letter store instead of retrying.
+//!
+//! Run with `cargo run --example error_recovery`.
+
+mod support;
+
+use std::time::Duration;
+
+use snowpipe_streaming::{Config, Error, StreamingIngestClient};
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, ResponseTemplate};
+
+#[derive(serde::Serialize, Clone)]
+struct Doc {
+    id: u64,
+    body: String,
+}
+
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Default)]
+struct Outcome {
+    sent: u64,
+    retried: u32,
+    dead_lettered: Vec<u64>,
+}
+
+fn is_transient(err: &Error) -> bool {
+    match err.root() {
+        Error::Reqwest(e) => e.status().is_some_and(|s| s.is_server_error()) || e.is_timeout(),
+        Error::Http(status, _) => status.is_server_error(),
+        _ => false,
+    }
+}
+
+async fn run(cfg: Config, docs: Vec<Doc>) -> Result<Outcome, Error> {
+    let mut client =
+        StreamingIngestClient::<Doc>::new("recovery", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg).await?;
+    let mut ch = client.open_channel("docs").await?;
+    let mut outcome = Outcome::default();
+
+    for doc in docs {
+        let mut attempt = 1;
+        loop {
+            match ch.append_row(&doc).await {
+                Ok(()) => {
+                    outcome.sent += 1;
+                    break;
+                }
+                Err(Error::DataTooLarge(actual, max)) => {
+                    eprintln!(
+                        "doc {} is {actual} bytes (max {max}); dead-lettering",
+                        doc.id
+                    );
+                    outcome.dead_lettered.push(doc.id);
+                    break;
+                }
+                Err(err) if is_transient(&err) && attempt < MAX_ATTEMPTS => {
+                    eprintln!(
+                        "attempt {attempt} for doc {} failed: {err}; retrying",
+                        doc.id
+                    );
+                    tokio::time::sleep(Duration::from_millis(50 * u64::from(attempt))).await;
+                    outcome.retried += 1;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    ch.close().await?;
+    Ok(outcome)
+}
+
+fn docs() -> Vec<Doc> {
+    vec![
+        Doc {
+            id: 1,
+            body: "small".into(),
+        },
+        Doc {
+            id: 2,
+            body: "x".repeat(17 * 1024 * 1024),
+        },
+        Doc {
+            id: 3,
+            body: "also small".into(),
+        },
+    ]
+}
+
+/// Makes the next append fail with a 503, as a stand-in for a transient outage.
+async fn inject_one_failure(mock: &support::MockSnowflake) {
+    Mock::given(method("POST"))
+        .and(path_regex(support::ROWS_PATH))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&mock.server)
+        .await;
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    inject_one_failure(&mock).await;
+    let outcome = run(mock.config(), docs()).await?;
+    println!("{outcome:?}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn retries_transient_and_dead_letters_oversized() {
+        let mock = support::MockSnowflake::start().await;
+        inject_one_failure(&mock).await;
+        let outcome = run(mock.config(), docs()).await.expect("recovery run");
+        assert_eq!(outcome.sent, 2);
+        assert_eq!(outcome.retried, 1);
+        assert_eq!(outcome.dead_lettered, vec![2]);
+    }
+}
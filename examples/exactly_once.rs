@@ -0,0 +1,73 @@
+//! Exactly-once delivery using offset tokens.
+//!
+//! Each `append_row` call advances the channel's offset token by one, so when
+//! one source record is appended per call the offset token doubles as the
+//! source sequence number. On restart the channel reports the last committed
+//! offset and the producer resumes from the next record, skipping anything
+//! Snowflake already has.
+//!
+//! Run with `cargo run --example exactly_once`.
+
+mod support;
+
+use snowpipe_streaming::{Config, Error, StreamingIngestClient};
+
+#[derive(serde::Serialize, Clone)]
+struct Record {
+    seq: u64,
+    payload: String,
+}
+
+/// A durable, replayable source (think Kafka partition or a WAL) whose
+/// sequence numbers start at 1.
+fn source(len: u64) -> impl Iterator<Item = Record> {
+    (1..=len).map(|seq| Record {
+        seq,
+        payload: format!("record-{seq}"),
+    })
+}
+
+/// Resumes ingestion after the committed offset, returning how many records
+/// were sent in this run.
+async fn run(cfg: Config, source_len: u64) -> Result<u64, Error> {
+    let mut client =
+        StreamingIngestClient::<Record>::new("exactly-once", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+    let mut ch = client.open_channel("orders").await?;
+
+    let committed = ch.get_latest_committed_offset_token().await;
+    let mut sent = 0;
+    for record in source(source_len).skip_while(|r| r.seq <= committed) {
+        ch.append_row(&record).await?;
+        sent += 1;
+    }
+
+    // Waits until Snowflake reports every appended offset as committed.
+    ch.close().await?;
+    Ok(sent)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    // Simulate a previous run that crashed after 7 records were committed.
+    mock.seed_committed("orders", 7);
+    let sent = run(mock.config(), 10).await?;
+    println!("resumed after offset 7 and sent {sent} records");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resumes_after_committed_offset() {
+        let mock = support::MockSnowflake::start().await;
+        mock.seed_committed("orders", 7);
+        let sent = run(mock.config(), 10).await.expect("exactly-once run");
+        assert_eq!(sent, 3);
+        assert_eq!(mock.append_requests("orders").await, 3);
+    }
+}
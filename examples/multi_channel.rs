@@ -0,0 +1,80 @@
+//! Fan rows out across several channels of the same pipe and append to them
+//! concurrently. Ordering is preserved within each channel only.
+//!
+//! Run with `cargo run --example multi_channel`.
+
+mod support;
+
+use snowpipe_streaming::{Config, Error, StreamingIngestClient};
+use tokio::task::JoinSet;
+
+#[derive(serde::Serialize, Clone)]
+struct Reading {
+    sensor: u64,
+    value: f64,
+}
+
+const CHANNELS: u64 = 3;
+
+/// Partitions readings by sensor id and appends each partition on its own
+/// channel, returning bytes written across all channels.
+async fn run(cfg: Config, readings: Vec<Reading>) -> Result<usize, Error> {
+    let mut client =
+        StreamingIngestClient::<Reading>::new("multi", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+
+    let mut partitions: Vec<Vec<Reading>> = (0..CHANNELS).map(|_| Vec::new()).collect();
+    for reading in readings {
+        partitions[(reading.sensor % CHANNELS) as usize].push(reading);
+    }
+
+    let mut tasks = JoinSet::new();
+    for (idx, rows) in partitions.into_iter().enumerate() {
+        let mut ch = client.open_channel(&format!("sensors_{idx}")).await?;
+        tasks.spawn(async move {
+            let bytes = ch.append_rows_iter(rows).await?;
+            ch.close().await?;
+            Ok::<_, Error>(bytes)
+        });
+    }
+
+    let mut total = 0;
+    while let Some(res) = tasks.join_next().await {
+        total += res.expect("channel task panicked")?;
+    }
+    Ok(total)
+}
+
+fn readings() -> Vec<Reading> {
+    (0..300)
+        .map(|i| Reading {
+            sensor: i,
+            value: i as f64 * 0.5,
+        })
+        .collect()
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    let bytes = run(mock.config(), readings()).await?;
+    println!("sent {bytes} bytes across {CHANNELS} channels");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_channel_receives_its_partition() {
+        let mock = support::MockSnowflake::start().await;
+        run(mock.config(), readings())
+            .await
+            .expect("multi-channel run");
+        for idx in 0..CHANNELS {
+            assert_eq!(mock.append_requests(&format!("sensors_{idx}")).await, 1);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Authenticating with an OAuth access token instead of a key pair.
+//!
+//! With `auth_scheme = oauth` the client sends the access token in
+//! `jwt_token` as-is, marked as `OAUTH`, and never generates a JWT, so no
+//! private key is configured. The token comes from your identity provider
+//! (Okta, Entra ID, an external OAuth security integration); this example
+//! reads it from `SNOWFLAKE_OAUTH_TOKEN` and falls back to a placeholder the
+//! mock accepts. A client keeps the token it was built with, so build a new
+//! client when the provider issues a fresh one.
+//!
+//! Run with `cargo run --example oauth`.
+
+mod support;
+
+use snowpipe_streaming::{AuthScheme, Config, Error, StreamingIngestClient};
+
+#[derive(serde::Serialize, Clone)]
+struct Event {
+    id: u64,
+    kind: &'static str,
+}
+
+/// Config for `base`'s account that authenticates with `access_token`.
+fn oauth_config(mut base: Config, access_token: String) -> Config {
+    base.auth_scheme = Some(AuthScheme::Oauth);
+    base.jwt_token = Some(access_token);
+    base
+}
+
+/// Appends a few events with an OAuth-authenticated client, returning how
+/// many rows were sent.
+async fn run(cfg: Config) -> Result<u64, Error> {
+    let mut client =
+        StreamingIngestClient::<Event>::new("oauth-example", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+    let mut ch = client.open_channel("events").await?;
+    let events = (1..=3).map(|id| Event { id, kind: "login" });
+    ch.append_rows_iter(events).await?;
+    let report = ch.close().await?;
+    Ok(report.rows)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    let access_token =
+        std::env::var("SNOWFLAKE_OAUTH_TOKEN").unwrap_or_else(|_| "idp-access-token".into());
+    let rows = run(oauth_config(mock.config(), access_token)).await?;
+    println!("appended {rows} rows with an OAuth access token");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn control_plane_requests_carry_the_oauth_token() {
+        let mock = support::MockSnowflake::start().await;
+        let cfg = oauth_config(mock.config(), "idp-access-token".into());
+        assert_eq!(run(cfg).await.expect("oauth run"), 3);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let control = requests
+            .iter()
+            .filter(|r| ["/v2/streaming/hostname", "/oauth/token"].contains(&r.url.path()))
+            .collect::<Vec<_>>();
+        assert_eq!(control.len(), 2);
+        for request in control {
+            assert_eq!(
+                request.headers["authorization"], "Bearer idp-access-token",
+                "{}",
+                request.url
+            );
+            assert_eq!(
+                request.headers["x-snowflake-authorization-token-type"],
+                "OAUTH"
+            );
+        }
+        assert_eq!(mock.append_requests("events").await, 1);
+    }
+}
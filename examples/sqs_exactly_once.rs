@@ -0,0 +1,173 @@
+//! Exactly-once delivery from an at-least-once queue such as SQS FIFO.
+//!
+//! The queue redelivers any message that is not deleted, so deleting too
+//! early loses data and deleting too late duplicates it. Each batch is
+//! appended with its last sequence number as the offset token, and its
+//! receipt handles are held in an [`AckTracker`] until Snowflake reports
+//! that token committed; only then are the messages deleted. After a crash
+//! the redelivered messages at or below the committed token are deleted
+//! without being appended again.
+//!
+//! The queue here is simulated in memory; with the AWS SDK, `receive` and
+//! `delete` map to `ReceiveMessage` and `DeleteMessageBatch`, and the
+//! sequence number to the FIFO `SequenceNumber` attribute (or a Kinesis
+//! record's sequence number, checkpointing instead of deleting).
+//!
+//! Run with `cargo run --example sqs_exactly_once`.
+
+mod support;
+
+use std::collections::BTreeMap;
+
+use snowpipe_streaming::{AckTracker, Config, Error, StreamingIngestClient};
+
+#[derive(serde::Serialize, Clone)]
+struct Event {
+    seq: u64,
+    body: String,
+}
+
+struct Message {
+    seq: u64,
+    receipt: String,
+    body: String,
+}
+
+/// A FIFO queue with SQS semantics: received messages stay hidden until
+/// they are deleted or the consumer goes away, then they are redelivered.
+#[derive(Default)]
+struct Queue {
+    messages: BTreeMap<u64, String>,
+    in_flight: Vec<u64>,
+    receives: u64,
+}
+
+impl Queue {
+    fn with_messages(count: u64) -> Self {
+        let mut queue = Self::default();
+        for seq in 1..=count {
+            queue.messages.insert(seq, format!("event-{seq}"));
+        }
+        queue
+    }
+
+    fn receive(&mut self, max: usize) -> Vec<Message> {
+        self.receives += 1;
+        let batch: Vec<_> = self
+            .messages
+            .iter()
+            .filter(|(seq, _)| !self.in_flight.contains(seq))
+            .take(max)
+            .map(|(&seq, body)| Message {
+                seq,
+                receipt: format!("{seq}-{}", self.receives),
+                body: body.clone(),
+            })
+            .collect();
+        self.in_flight.extend(batch.iter().map(|m| m.seq));
+        batch
+    }
+
+    fn delete(&mut self, receipts: &[String]) {
+        for receipt in receipts {
+            let seq = receipt.split('-').next().unwrap().parse().unwrap();
+            self.messages.remove(&seq);
+            self.in_flight.retain(|s| *s != seq);
+        }
+    }
+
+    /// The visibility timeout of a crashed consumer's messages expires.
+    fn expire_in_flight(&mut self) {
+        self.in_flight.clear();
+    }
+}
+
+/// Drains the queue into Snowflake, returning how many events were
+/// appended. With `crash_after` set, stops after that many batches without
+/// deleting anything, as if the process died.
+async fn run(cfg: Config, queue: &mut Queue, crash_after: Option<usize>) -> Result<u64, Error> {
+    let mut client =
+        StreamingIngestClient::<Event>::new("sqs-consumer", "MY_DB", "MY_SCHEMA", "MY_PIPE", cfg)
+            .await?;
+    let mut ch = client.open_channel("events").await?;
+
+    let mut committed = ch.get_latest_committed_offset_token().await;
+    let mut acks = AckTracker::new();
+    let mut appended = 0;
+    for batch in 0.. {
+        let messages = queue.receive(5);
+        if messages.is_empty() {
+            break;
+        }
+        let (done, fresh): (Vec<_>, Vec<_>) =
+            messages.into_iter().partition(|m| m.seq <= committed);
+        // Already in Snowflake from a run that crashed before deleting them.
+        queue.delete(&done.into_iter().map(|m| m.receipt).collect::<Vec<_>>());
+        let Some(last_seq) = fresh.last().map(|m| m.seq) else {
+            continue;
+        };
+        let receipts = fresh.iter().map(|m| m.receipt.clone()).collect::<Vec<_>>();
+        appended += fresh.len() as u64;
+        ch.append_rows_at(
+            last_seq,
+            fresh.into_iter().map(|m| Event {
+                seq: m.seq,
+                body: m.body,
+            }),
+        )
+        .await?;
+        acks.track(last_seq, receipts);
+
+        if crash_after == Some(batch + 1) {
+            return Ok(appended);
+        }
+        committed = ch.get_latest_committed_offset_token().await;
+        for receipts in acks.release(committed) {
+            queue.delete(&receipts);
+        }
+    }
+
+    // Waits until every appended batch is committed, then deletes the rest.
+    ch.close().await?;
+    for receipts in acks.release(u64::MAX) {
+        queue.delete(&receipts);
+    }
+    Ok(appended)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = tracing_subscriber::fmt().try_init();
+    let mock = support::MockSnowflake::start().await;
+    let mut queue = Queue::with_messages(12);
+    let before_crash = run(mock.config(), &mut queue, Some(1)).await?;
+    queue.expire_in_flight();
+    let after_restart = run(mock.config(), &mut queue, None).await?;
+    println!(
+        "appended {before_crash} events before the crash and {after_restart} after; \
+         {} left in the queue",
+        queue.messages.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_message_lands_once_across_a_crash() {
+        let mock = support::MockSnowflake::start().await;
+        let mut queue = Queue::with_messages(12);
+
+        let before_crash = run(mock.config(), &mut queue, Some(1)).await.unwrap();
+        assert_eq!(before_crash, 5);
+        assert_eq!(queue.messages.len(), 12, "nothing deleted before commit");
+
+        queue.expire_in_flight();
+        let after_restart = run(mock.config(), &mut queue, None).await.unwrap();
+        assert_eq!(after_restart, 7, "redelivered 1..=5 are not appended again");
+        assert!(queue.messages.is_empty());
+        assert_eq!(mock.append_requests("events").await, 3);
+    }
+}
@@ -0,0 +1,171 @@
+//! In-process stand-in for the Snowflake control and ingest planes.
+//!
+//! The examples run against this server so they can be executed (and tested)
+//! without an account. Committed offsets track the highest `offsetToken` each
+//! channel has received, mimicking a pipe that keeps up with ingestion.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use snowpipe_streaming::Config;
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const CHANNEL_PATH: &str =
+    r"^/v2/streaming/databases/[^/]+/schemas/[^/]+/pipes/[^/]+/channels/[^/]+$";
+pub const ROWS_PATH: &str =
+    r"^/v2/streaming/data/databases/[^/]+/schemas/[^/]+/pipes/[^/]+/channels/[^/]+/rows$";
+const STATUS_PATH: &str =
+    r"^/v2/streaming/databases/[^/]+/schemas/[^/]+/pipes/[^/]+:bulk-channel-status$";
+
+pub struct MockSnowflake {
+    pub server: MockServer,
+    committed: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl MockSnowflake {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let committed: Arc<Mutex<HashMap<String, u64>>> = Arc::default();
+
+        Mock::given(method("GET"))
+            .and(path("/v2/streaming/hostname"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+            .mount(&server)
+            .await;
+
+        let open_state = committed.clone();
+        Mock::given(method("PUT"))
+            .and(path_regex(CHANNEL_PATH))
+            .respond_with(move |req: &Request| {
+                let channel = last_segment(req);
+                let offset = *open_state
+                    .lock()
+                    .unwrap()
+                    .entry(channel.clone())
+                    .or_default();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "next_continuation_token": "ctok-0",
+                    "channel_status": status_json(&channel, offset),
+                }))
+            })
+            .mount(&server)
+            .await;
+
+        let rows_state = committed.clone();
+        Mock::given(method("POST"))
+            .and(path_regex(ROWS_PATH))
+            .respond_with(move |req: &Request| {
+                let segments: Vec<_> = req.url.path_segments().unwrap().collect();
+                let channel = segments[segments.len() - 2].to_string();
+                let offset = req
+                    .url
+                    .query_pairs()
+                    .find(|(k, _)| k == "offsetToken")
+                    .and_then(|(_, v)| v.parse::<u64>().ok())
+                    .unwrap_or_default();
+                let mut state = rows_state.lock().unwrap();
+                let committed = state.entry(channel).or_default();
+                *committed = (*committed).max(offset);
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "next_continuation_token": format!("ctok-{offset}"),
+                }))
+            })
+            .mount(&server)
+            .await;
+
+        let status_state = committed.clone();
+        Mock::given(method("POST"))
+            .and(path_regex(STATUS_PATH))
+            .respond_with(move |req: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap_or_default();
+                let state = status_state.lock().unwrap();
+                let statuses: serde_json::Map<_, _> = body["channel_names"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|n| n.as_str())
+                    .map(|name| {
+                        let offset = state.get(name).copied().unwrap_or_default();
+                        (name.to_string(), status_json(name, offset))
+                    })
+                    .collect();
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "channel_statuses": statuses }))
+            })
+            .mount(&server)
+            .await;
+
+        Mock::given(method("DELETE"))
+            .and(path_regex(CHANNEL_PATH))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        Self { server, committed }
+    }
+
+    /// Configuration pointing at the mock server with a pre-supplied token.
+    pub fn config(&self) -> Config {
+        Config::from_values(
+            "EXAMPLE_USER",
+            None,
+            "EXAMPLE_ACCOUNT",
+            self.server.uri(),
+            Some("example-jwt".into()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Pretend rows up to `offset` were already committed by a previous run.
+    pub fn seed_committed(&self, channel: &str, offset: u64) {
+        self.committed
+            .lock()
+            .unwrap()
+            .insert(channel.to_string(), offset);
+    }
+
+    /// Number of append requests received for `channel`.
+    pub async fn append_requests(&self, channel: &str) -> usize {
+        let suffix = format!("/channels/{channel}/rows");
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.url.path().ends_with(&suffix))
+            .count()
+    }
+}
+
+fn last_segment(req: &Request) -> String {
+    req.url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn status_json(channel: &str, offset: u64) -> serde_json::Value {
+    serde_json::json!({
+        "database_name": "MY_DB",
+        "schema_name": "MY_SCHEMA",
+        "pipe_name": "MY_PIPE",
+        "channel_name": channel,
+        "channel_status_code": "OPEN",
+        "last_committed_offset_token": offset.to_string(),
+        "created_on_ms": 0
+    })
+}
@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# Lints the crate with no features, with each optional feature on its own,
+# with the defaults, and with `full`, so every feature is additive and
+# builds without relying on another it does not enable. Also fails when
+# `full` misses a feature.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+# Feature names from the [features] table, minus the meta and example ones.
+features=$(sed -n '/^\[features\]/,/^\[/p' Cargo.toml |
+    sed -n 's/^\([a-z0-9-]*\) = .*/\1/p' |
+    grep -vx -e default -e full -e unstable-example)
+
+full=$(sed -n '/^full = \[/,/^\]/p' Cargo.toml)
+for feature in $features; do
+    if ! grep -q "\"$feature\"" <<<"$full"; then
+        echo "feature '$feature' is missing from 'full'" >&2
+        exit 1
+    fi
+done
+
+clippy() {
+    echo "==> cargo clippy $*"
+    cargo clippy --all-targets --quiet "$@" -- -D warnings
+}
+
+clippy --no-default-features
+for feature in $features; do
+    clippy --no-default-features --features "$feature"
+done
+clippy
+clippy --features full
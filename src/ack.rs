@@ -0,0 +1,126 @@
+//! Holding source acknowledgements until Snowflake commits the rows they
+//! cover, for at-least-once sources such as SQS queues or Kinesis shards.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{Error, StreamingIngestChannel};
+
+/// Acknowledgements (e.g. SQS receipt handles or a Kinesis checkpoint) held
+/// per pushed offset token until Snowflake reports that offset committed.
+///
+/// Deleting a message as soon as its append request succeeds loses it if the
+/// rows are never committed; deleting it only once the offset token is
+/// committed means a crash leaves it in the source to be delivered again.
+/// Combined with
+/// [`StreamingIngestChannel::append_rows_at`](crate::StreamingIngestChannel::append_rows_at),
+/// which carries the source's sequence number as the offset token, a
+/// restarted producer skips redelivered records at or below the committed
+/// token, so every record lands exactly once.
+///
+/// ```
+/// use snowpipe_streaming::AckTracker;
+///
+/// let mut acks = AckTracker::new();
+/// acks.track(10, vec!["receipt-1", "receipt-2"]);
+/// acks.track(12, vec!["receipt-3"]);
+/// // Snowflake reports offset 10 committed:
+/// assert_eq!(acks.release(10), [vec!["receipt-1", "receipt-2"]]);
+/// assert_eq!(acks.pending(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AckTracker<A> {
+    pending: VecDeque<(u64, A)>,
+}
+
+impl<A> Default for AckTracker<A> {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<A> AckTracker<A> {
+    /// A tracker holding nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Holds `ack` until `offset_token`, the token of the append request
+    /// that carried its rows, is committed. Tokens are expected in the order
+    /// they were pushed.
+    pub fn track(&mut self, offset_token: u64, ack: A) {
+        debug_assert!(
+            self.pending
+                .back()
+                .is_none_or(|(last, _)| *last <= offset_token),
+            "offset tokens must be tracked in push order"
+        );
+        self.pending.push_back((offset_token, ack));
+    }
+
+    /// Returns, oldest first, the acknowledgements whose offset token is at
+    /// or below `committed`, the channel's last committed offset token.
+    pub fn release(&mut self, committed: u64) -> Vec<A> {
+        let ready = self
+            .pending
+            .iter()
+            .take_while(|(offset, _)| *offset <= committed)
+            .count();
+        self.pending.drain(..ready).map(|(_, ack)| ack).collect()
+    }
+
+    /// Number of acknowledgements still waiting for a commit.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether nothing is waiting for a commit.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Offset token that must be committed to release everything tracked.
+    pub fn last_offset(&self) -> Option<u64> {
+        self.pending.back().map(|(offset, _)| *offset)
+    }
+
+    /// Flushes `channel` and waits, polling at the pace of
+    /// `Config::commit_wait_strategy`, up to `timeout` for what it pushed to
+    /// commit, then returns the acknowledgements released by whatever did.
+    /// Those still pending when time runs out stay tracked.
+    pub async fn wait_and_release<R: Serialize + Clone>(
+        &mut self,
+        channel: &mut StreamingIngestChannel<R>,
+        timeout: Duration,
+    ) -> Result<Vec<A>, Error> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let report = channel.flush_and_wait(timeout).await?;
+        Ok(self.release(report.committed_offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_in_push_order_up_to_the_committed_token() {
+        let mut acks = AckTracker::new();
+        assert!(acks.release(100).is_empty());
+        acks.track(3, 'a');
+        acks.track(5, 'b');
+        acks.track(9, 'c');
+        assert_eq!(acks.last_offset(), Some(9));
+        assert!(acks.release(2).is_empty());
+        assert_eq!(acks.release(6), ['a', 'b']);
+        assert_eq!(acks.pending(), 1);
+        assert_eq!(acks.release(9), ['c']);
+        assert!(acks.is_empty());
+    }
+}
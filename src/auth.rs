@@ -0,0 +1,145 @@
+//! Standalone key-pair JWT generation.
+//!
+//! [`generate_jwt`] builds the same signed assertion the client uses for the
+//! control plane, so it can authenticate other Snowflake REST APIs (SQL API,
+//! Cortex, ...) with the key-pair settings of a [`Config`]. Requires the
+//! `crypto` feature.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), snowpipe_streaming::Error> {
+//! use snowpipe_streaming::{Config, auth};
+//!
+//! let jwt = auth::generate_jwt(&Config::from_env()?)?;
+//! println!("signed for {} with {}", jwt.claims().subject, jwt.fingerprint());
+//! let header = format!("Bearer {}", jwt.token());
+//! # let _ = header;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use jiff::Timestamp;
+
+use crate::client::crypto::build_assertion;
+use crate::{Config, Error};
+
+/// Claims of a [`SignedJwt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtClaims {
+    /// `iss` claim: `<ACCOUNT>.<USER>.<fingerprint>`.
+    pub issuer: String,
+    /// `sub` claim: `<ACCOUNT>.<USER>`.
+    pub subject: String,
+    /// `iat` claim.
+    pub issued_at: Timestamp,
+    /// `exp` claim.
+    pub expires_at: Timestamp,
+}
+
+/// A key-pair JWT returned by [`generate_jwt`].
+///
+/// `Debug` omits the token itself, which is a bearer credential until it
+/// expires.
+#[derive(Clone)]
+pub struct SignedJwt {
+    token: String,
+    claims: JwtClaims,
+    fingerprint: String,
+}
+
+impl SignedJwt {
+    /// The encoded token, for an `Authorization: Bearer` header with
+    /// `X-Snowflake-Authorization-Token-Type: KEYPAIR_JWT`.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Consumes the JWT, returning the encoded token.
+    pub fn into_token(self) -> String {
+        self.token
+    }
+
+    /// The token's claims.
+    pub fn claims(&self) -> &JwtClaims {
+        &self.claims
+    }
+
+    /// Public key fingerprint (`SHA256:...`) embedded in the issuer; compare
+    /// it with `RSA_PUBLIC_KEY_FP` from `DESC USER`.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Whether the token has expired.
+    pub fn is_expired(&self) -> bool {
+        Timestamp::now() >= self.claims.expires_at
+    }
+}
+
+impl fmt::Debug for SignedJwt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedJwt")
+            .field("claims", &self.claims)
+            .field("fingerprint", &self.fingerprint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Signs a key-pair JWT from the private key, passphrase, user, account, and
+/// `jwt_exp` in `config`, exactly as the client does.
+///
+/// `jwt_exp` is clamped to `[30, 3600]` seconds (default 3600). The
+/// pre-generated `jwt_token` is ignored; use [`debug_auth`](crate::debug_auth)
+/// to inspect one. This always signs with the local private key, not a
+/// [`Config::jwt_signer`].
+pub fn generate_jwt(config: &Config) -> Result<SignedJwt, Error> {
+    let bundle = build_assertion(config, None)?;
+    let timestamp = |millis: u64| {
+        i64::try_from(millis)
+            .ok()
+            .and_then(|ms| Timestamp::from_millisecond(ms).ok())
+            .ok_or_else(|| Error::Config(format!("JWT timestamp {millis} out of range")))
+    };
+    Ok(SignedJwt {
+        claims: JwtClaims {
+            issuer: bundle.issuer,
+            subject: bundle.subject,
+            issued_at: timestamp(bundle.issued_at)?,
+            expires_at: timestamp(bundle.expires_at)?,
+        },
+        fingerprint: bundle.fingerprint,
+        token: bundle.token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_support::base_config;
+
+    #[test]
+    fn generated_jwt_exposes_claims_and_fingerprint() {
+        let mut config = base_config("https://example");
+        config.jwt_exp = Some(std::time::Duration::from_secs(7200));
+        let jwt = generate_jwt(&config).expect("sign");
+
+        let claims = jwt.claims();
+        assert_eq!(claims.subject, "ACCT.USER");
+        assert_eq!(
+            claims.issuer,
+            format!("{}.{}", claims.subject, jwt.fingerprint())
+        );
+        assert!(jwt.fingerprint().starts_with("SHA256:"));
+        assert_eq!(
+            claims.expires_at.duration_since(claims.issued_at),
+            jiff::SignedDuration::from_secs(3600)
+        );
+        assert!(!jwt.is_expired());
+        assert_eq!(jwt.token().split('.').count(), 3);
+        assert!(!format!("{jwt:?}").contains(jwt.token()));
+
+        let report = crate::debug_auth(&config).expect("debug");
+        assert_eq!(report.fingerprint, jwt.fingerprint());
+    }
+}
@@ -0,0 +1,190 @@
+//! Change-data-capture envelopes mapped to rows with metadata columns.
+//!
+//! [`DebeziumEnvelope`] parses Debezium-style change events (`before`,
+//! `after`, and an `op` code), with or without the `schema`/`payload` wrapper
+//! of the JSON converter. [`DebeziumEnvelope::into_row`] turns an event into a
+//! [`CdcRow`]: the row image flattened into top-level columns, plus
+//! `_CDC_OP`, `_CDC_DELETED`, `_CDC_TS_MS`, and `_CDC_SOURCE` columns, so a
+//! pipe can upsert or soft-delete by primary key.
+//!
+//! ```
+//! use snowpipe_streaming::cdc::{CdcOp, DebeziumEnvelope};
+//!
+//! #[derive(serde::Deserialize, serde::Serialize, Clone)]
+//! struct Customer {
+//!     id: u64,
+//!     email: String,
+//! }
+//!
+//! let event = r#"{"payload": {
+//!     "before": {"id": 7, "email": "old@example.com"},
+//!     "after": null,
+//!     "op": "d",
+//!     "ts_ms": 1700000000000
+//! }}"#;
+//! let row = DebeziumEnvelope::<Customer>::from_json(event)?.into_row()?;
+//! assert_eq!(row.op, CdcOp::Delete);
+//! assert_eq!(
+//!     serde_json::to_value(&row)?,
+//!     serde_json::json!({
+//!         "id": 7,
+//!         "email": "old@example.com",
+//!         "_CDC_OP": "d",
+//!         "_CDC_DELETED": true,
+//!         "_CDC_TS_MS": 1700000000000u64,
+//!     })
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Kind of change, serialized as the Debezium op code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CdcOp {
+    /// A row was inserted (`c`).
+    #[serde(rename = "c")]
+    Create,
+    /// A row was updated (`u`).
+    #[serde(rename = "u")]
+    Update,
+    /// A row was deleted (`d`).
+    #[serde(rename = "d")]
+    Delete,
+    /// A row was read during an initial snapshot (`r`).
+    #[serde(rename = "r")]
+    Read,
+    /// The table was truncated (`t`); carries no row image.
+    #[serde(rename = "t")]
+    Truncate,
+}
+
+/// A Debezium-style change event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DebeziumEnvelope<T> {
+    /// Row image before the change; set for updates and deletes.
+    pub before: Option<T>,
+    /// Row image after the change; set for creates, updates, and reads.
+    pub after: Option<T>,
+    /// Kind of change.
+    pub op: CdcOp,
+    /// When the connector processed the event, in epoch milliseconds.
+    #[serde(default)]
+    pub ts_ms: Option<u64>,
+    /// Connector source metadata (database, table, log position, ...).
+    #[serde(default)]
+    pub source: Option<serde_json::Value>,
+}
+
+/// A change event as a row: the row image's fields followed by CDC metadata
+/// columns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CdcRow<T> {
+    /// The `after` image, or the `before` image for deletes.
+    #[serde(flatten)]
+    pub data: T,
+    /// Kind of change.
+    #[serde(rename = "_CDC_OP")]
+    pub op: CdcOp,
+    /// `true` for deletes, so the row can be soft-deleted or filtered.
+    #[serde(rename = "_CDC_DELETED")]
+    pub deleted: bool,
+    /// When the connector processed the event, in epoch milliseconds.
+    #[serde(rename = "_CDC_TS_MS", skip_serializing_if = "Option::is_none")]
+    pub ts_ms: Option<u64>,
+    /// Connector source metadata.
+    #[serde(rename = "_CDC_SOURCE", skip_serializing_if = "Option::is_none")]
+    pub source: Option<serde_json::Value>,
+}
+
+impl<T: DeserializeOwned> DebeziumEnvelope<T> {
+    /// Parses an event, unwrapping the `payload` field written by the JSON
+    /// converter with schemas enabled.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        if let Some(payload) = value.get_mut("payload").filter(|p| p.is_object()) {
+            value = payload.take();
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl<T> DebeziumEnvelope<T> {
+    /// Maps the event to a row, using the `before` image for deletes and the
+    /// `after` image otherwise.
+    ///
+    /// Fails with [`Error::InvalidCdcEvent`] for truncates, which carry no
+    /// row, and when the needed image is missing (e.g. a delete from a table
+    /// without full before-images).
+    pub fn into_row(self) -> Result<CdcRow<T>, Error> {
+        let (image, name) = match self.op {
+            CdcOp::Delete => (self.before, "before"),
+            CdcOp::Create | CdcOp::Update | CdcOp::Read => (self.after, "after"),
+            CdcOp::Truncate => {
+                return Err(Error::InvalidCdcEvent(
+                    "truncate events carry no row image".into(),
+                ));
+            }
+        };
+        let data = image.ok_or_else(|| {
+            Error::InvalidCdcEvent(format!("{:?} event is missing its '{name}' image", self.op))
+        })?;
+        Ok(CdcRow {
+            data,
+            op: self.op,
+            deleted: self.op == CdcOp::Delete,
+            ts_ms: self.ts_ms,
+            source: self.source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        id: u64,
+        name: String,
+    }
+
+    #[test]
+    fn maps_each_op_to_the_right_image() {
+        let update = r#"{"before": {"id": 1, "name": "a"}, "after": {"id": 1, "name": "b"},
+            "op": "u", "source": {"table": "users"}}"#;
+        let row = DebeziumEnvelope::<Row>::from_json(update)
+            .unwrap()
+            .into_row()
+            .unwrap();
+        assert_eq!(row.data.name, "b");
+        assert!(!row.deleted);
+        assert_eq!(
+            serde_json::to_value(&row).unwrap(),
+            serde_json::json!({"id": 1, "name": "b", "_CDC_OP": "u", "_CDC_DELETED": false,
+                "_CDC_SOURCE": {"table": "users"}})
+        );
+
+        let snapshot = r#"{"after": {"id": 2, "name": "c"}, "op": "r"}"#;
+        let row = DebeziumEnvelope::<Row>::from_json(snapshot)
+            .unwrap()
+            .into_row()
+            .unwrap();
+        assert_eq!((row.op, row.data.id), (CdcOp::Read, 2));
+
+        let bare_delete = r#"{"before": null, "after": null, "op": "d"}"#;
+        let err = DebeziumEnvelope::<Row>::from_json(bare_delete)
+            .unwrap()
+            .into_row()
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidCdcEvent(msg) if msg.contains("before")));
+
+        let truncate = r#"{"before": null, "after": null, "op": "t"}"#;
+        let envelope = DebeziumEnvelope::<Row>::from_json(truncate).unwrap();
+        assert!(envelope.into_row().is_err());
+        assert!(DebeziumEnvelope::<Row>::from_json(r#"{"op": "x"}"#).is_err());
+    }
+}
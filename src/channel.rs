@@ -1,30 +1,322 @@
-use bytes::Bytes;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use reqwest::StatusCode;
 use serde::Serialize;
-use tracing::{error, info, trace, warn};
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{Instrument, Span, debug, error, info, info_span, trace, warn};
+use uuid::Uuid;
+use xxhash_rust::xxh3::{Xxh3, xxh3_64};
 
 use crate::{
-    Error, StreamingIngestClient,
-    types::{AppendRowsResponse, ChannelStatus, OpenChannelResponse},
+    BatchPartitioner, ColumnPartitioner, DedupWindow, Error, Lint, OperationKind, Sampler,
+    StreamingIngestClient, Warning,
+    client::{CHUNKING_AVG_BYTES, CHUNKING_REQUESTS, SINGLE_ROW_GAP, SINGLE_ROW_STREAK},
+    commit_wait::CommitWaiter,
+    endpoints,
+    telemetry::{Tags, TelemetryEvent, TelemetrySink},
+    types::{
+        AppendRowsResponse, ChannelStatus, OpenChannelOptions, OpenChannelResponse,
+        debug_unknown_fields,
+    },
 };
 
+mod background;
+mod dry_run;
+mod ndjson;
+mod schema_drift;
+
+pub use background::AppendHandle;
+use background::{Limits, Worker};
+pub use dry_run::{DryRunChannel, DryRunRequest};
+use schema_drift::DriftWatch;
+pub use schema_drift::SchemaDrift;
+
 const MAX_REQUEST_SIZE: usize = 16 * 1024 * 1024; // 16MB
-const USER_AGENT: &str = "snowpipe-streaming-rust-sdk/0.1.0";
+const USER_AGENT: &str = concat!("snowpipe-streaming-rust-sdk/", env!("CARGO_PKG_VERSION"));
+/// Offset tokens carry the writer generation above this bit when
+/// `Config::writer_generation` is set.
+const GENERATION_SHIFT: u32 = 40;
+/// Exclusive upper bound on `Config::writer_generation`: the generations
+/// that fit in the bits above [`GENERATION_SHIFT`].
+pub(crate) const WRITER_GENERATION_LIMIT: u32 = 1 << (u64::BITS - GENERATION_SHIFT);
+/// Error codes Snowflake returns when another writer has reopened a channel.
+const FENCED_ERROR_CODES: [&str; 2] = [
+    "STALE_CONTINUATION_TOKEN_SEQUENCER",
+    "INVALID_CONTINUATION_TOKEN",
+];
 
+/// An open channel on a pipe that rows are appended to, in order.
+///
+/// Every append request advances the channel's offset token by one;
+/// [`close`](Self::close) waits until Snowflake has committed every pushed
+/// offset.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), snowpipe_streaming::Error> {
+/// # let server = wiremock::MockServer::start().await;
+/// # let ok = |b: serde_json::Value| wiremock::ResponseTemplate::new(200).set_body_json(b);
+/// # let status = serde_json::json!({"channel_statuses": {"ch": {"last_committed_offset_token": "5"}}});
+/// # for (verb, route, resp) in [
+/// #     ("GET", "/hostname$", wiremock::ResponseTemplate::new(200).set_body_string(server.uri())),
+/// #     ("POST", "/oauth/token$", wiremock::ResponseTemplate::new(200).set_body_string("scoped")),
+/// #     ("PUT", "/channels/ch$", ok(serde_json::json!({"next_continuation_token": "c0", "channel_status": {}}))),
+/// #     ("POST", "/rows$", ok(serde_json::json!({"next_continuation_token": "c1"}))),
+/// #     ("POST", ":bulk-channel-status$", ok(status)),
+/// #     ("DELETE", "/channels/ch$", wiremock::ResponseTemplate::new(200)),
+/// # ] {
+/// #     wiremock::Mock::given(wiremock::matchers::method(verb))
+/// #         .and(wiremock::matchers::path_regex(route))
+/// #         .respond_with(resp)
+/// #         .mount(&server)
+/// #         .await;
+/// # }
+/// # let config = snowpipe_streaming::Config::from_values(
+/// #     "user", None, "acct", server.uri(), Some("jwt".into()), None, None, None, None, None,
+/// # );
+/// # let mut client = snowpipe_streaming::StreamingIngestClient::<serde_json::Value>::new(
+/// #     "svc", "MY_DB", "MY_SCHEMA", "MY_PIPE", config,
+/// # )
+/// # .await?;
+/// let mut channel = client.open_channel("ch").await?;
+/// channel.append_row(&serde_json::json!({"id": 1})).await?;
+/// let rows = (2..=100).map(|id| serde_json::json!({"id": id}));
+/// channel.append_rows_iter(rows).await?;
+///
+/// let committed = channel.get_latest_committed_offset_token().await;
+/// assert!(committed >= 2);
+/// channel
+///     .close_with_timeout(std::time::Duration::from_secs(30))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct StreamingIngestChannel<R> {
     _marker: std::marker::PhantomData<R>,
     client: StreamingIngestClient<R>,
     channel_name: String,
+    /// Name the channel was opened with; fenced reopens derive from it.
+    base_name: String,
+    fence_reopens: u32,
     continuation_token: String,
     last_committed_offset_token: u64,
     last_pushed_offset_token: u64,
+    /// Pushed offsets not yet reported committed, with the time they were pushed.
+    uncommitted: VecDeque<(u64, Instant)>,
+    lag_alert: Option<CommitLagAlert>,
+    quarantine: Option<Quarantine>,
+    schema_drift: Option<DriftWatch>,
+    opened_at: Instant,
+    stats: AppendStats,
+    /// `stats` as of the last `flush_and_wait`, to report what came after.
+    flushed_stats: AppendStats,
+    background: Option<Worker>,
+    /// Background append failure not yet returned to the caller.
+    deferred_error: Option<Error>,
+    /// Newer writer generation seen in a committed offset token.
+    superseded_by: Option<u32>,
+    /// Latest average processing latency reported by Snowflake.
+    server_latency: Option<Duration>,
+    /// XXH3 of the last status response, to skip re-reading an identical one.
+    status_digest: Option<u64>,
+    max_rows_per_request: Option<usize>,
+    /// Most pushed offsets allowed in `uncommitted` before appends wait.
+    max_uncommitted: Option<usize>,
+    fail_on_uncommitted_limit: bool,
+    pacer: Pacer,
+    /// Largest body the background appender builds.
+    max_batch_bytes: usize,
+    /// How long the background appender waits for more rows per batch.
+    flush_interval: Option<Duration>,
+    /// Groups each batch's rows by partition before it is split into requests.
+    partitioner: Option<Arc<dyn BatchPartitioner>>,
+    /// Keys of recently appended rows; repeats are dropped before sending.
+    dedup: Option<DedupWindow>,
+    /// Keeps a sample of rows; the rest are dropped before buffering.
+    sampler: Option<Sampler>,
+    /// Serialization buffer reused by every `append_rows` call.
+    buffer: BytesMut,
+    last_append: Option<AppendReport>,
+    /// Back-to-back `append_row` calls so far, and when the last returned.
+    single_row_streak: u32,
+    last_single_row: Option<Instant>,
+    /// Set once `close` succeeds, or when this handle's channel was taken
+    /// over by another handle.
+    closed: bool,
+    /// Publishes [`ChannelStatusSnapshot`]s to `status_watch` subscribers.
+    status_tx: watch::Sender<ChannelStatusSnapshot>,
+    /// Parent of the spans around this channel's requests.
+    span: Span,
+}
+
+/// The `channel` span that groups a channel's activity.
+pub(crate) fn channel_span<R>(client: &StreamingIngestClient<R>, channel_name: &str) -> Span {
+    info_span!(
+        "channel",
+        db = %client.db_name,
+        schema = %client.schema_name,
+        pipe = %client.pipe_name,
+        channel = %channel_name,
+    )
+}
+
+/// Commit progress of a channel, published to
+/// [`StreamingIngestChannel::status_watch`] subscribers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelStatusSnapshot {
+    /// Current channel name (see
+    /// [`StreamingIngestChannel::channel_name`]).
+    pub channel_name: String,
+    /// Last offset token Snowflake reported committed.
+    pub committed_offset: u64,
+    /// Last offset token pushed.
+    pub pushed_offset: u64,
+    /// Pushed offsets not yet reported committed.
+    pub uncommitted_offsets: usize,
+    /// Age of the oldest uncommitted offset when the snapshot was taken.
+    pub commit_lag: Duration,
+    /// Snowflake's average processing latency, if reported.
+    pub server_latency: Option<Duration>,
+}
+
+/// One append request Snowflake accepted, returned by
+/// [`StreamingIngestChannel::last_append`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendReport {
+    /// Random ID assigned to the request. It is also logged, reported in
+    /// [`TelemetryEvent::AppendCompleted`], and written to every row when
+    /// `Config::batch_id_column` is set, so client logs can be matched to
+    /// rows in Snowflake.
+    pub batch_id: Uuid,
+    /// Offset token the request carried.
+    pub offset: u64,
+    /// Rows in the request.
+    pub rows: usize,
+    /// Request body size in bytes.
+    pub bytes: usize,
+    /// Time from sending the request to receiving the response, including
+    /// retries.
+    pub elapsed: Duration,
+    /// XXH3-64 of the request's rows as newline-delimited JSON, before the
+    /// batch ID and checksum columns are added, when `Config::batch_checksum`
+    /// or `Config::checksum_column` is set.
+    pub checksum: Option<u64>,
+}
+
+/// What [`StreamingIngestChannel::append_rows_with_deadline`] sent before its
+/// deadline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadlineReport {
+    /// Rows sent in requests that started before the deadline.
+    pub rows_sent: usize,
+    /// Rows left unsent because the deadline passed.
+    pub rows_remaining: usize,
+    /// Request body bytes sent.
+    pub bytes: usize,
+}
+
+impl DeadlineReport {
+    /// Whether every row was sent.
+    pub fn is_complete(&self) -> bool {
+        self.rows_remaining == 0
+    }
+}
+
+/// What [`StreamingIngestChannel::flush_and_wait`] sent and whether it
+/// committed in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushReport {
+    /// Rows appended since the previous `flush_and_wait` (or since the
+    /// channel was opened).
+    pub rows: u64,
+    /// Request body bytes sent for those rows.
+    pub bytes: u64,
+    /// Append requests sent for those rows.
+    pub requests: u64,
+    /// Last offset token pushed when the flush finished.
+    pub pushed_offset: u64,
+    /// Last offset token Snowflake reported committed.
+    pub committed_offset: u64,
+    /// Time spent sending rows queued with `append_row_async`.
+    pub flush_duration: Duration,
+    /// Time spent polling channel status for commits.
+    pub commit_wait: Duration,
+}
+
+impl FlushReport {
+    /// Whether everything pushed was committed before the timeout.
+    pub fn is_committed(&self) -> bool {
+        self.committed_offset >= self.pushed_offset
+    }
+}
+
+/// Ingestion statistics for a channel, returned by
+/// [`StreamingIngestChannel::close`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelReport {
+    /// Rows appended through this channel handle.
+    pub rows: u64,
+    /// Request body bytes sent for appends.
+    pub bytes: u64,
+    /// Successful append requests.
+    pub requests: u64,
+    /// Append attempts retried after a 401 or 429 response.
+    pub retries: u64,
+    /// Time from opening the channel until it was closed.
+    pub duration: Duration,
+    /// Mean latency of successful append requests, including retries.
+    pub avg_latency: Duration,
+    /// Time spent in `close` waiting for Snowflake to commit pushed offsets.
+    pub commit_wait: Duration,
+    /// Latest average processing latency reported by Snowflake, if any status
+    /// response included it.
+    pub server_latency: Option<Duration>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct AppendStats {
+    rows: u64,
+    bytes: u64,
+    requests: u64,
+    retries: u64,
+    total_latency: Duration,
+}
+
+type CommitLagCallback = Box<dyn Fn(&str, Duration) + Send + Sync>;
+
+struct CommitLagAlert {
+    threshold: Duration,
+    callback: CommitLagCallback,
+    /// Set once the alert has fired; cleared when lag drops back under the threshold.
+    fired: bool,
+}
+
+/// Rejected-row accounting for
+/// [`StreamingIngestChannel::set_quarantine_threshold`].
+struct Quarantine {
+    max_reject_rate: f64,
+    min_rows: u64,
+    sent: u64,
+    rejected: u64,
+    /// Set once the rate was exceeded; cleared by `release_quarantine`.
+    tripped: bool,
 }
 
 impl<R: Serialize + Clone> StreamingIngestChannel<R> {
+    /// Builds a channel from an open-channel response.
     pub fn from_response(
         client: &StreamingIngestClient<R>,
         resp: OpenChannelResponse,
         channel_name: &str,
     ) -> Self {
+        debug_unknown_fields("open channel", &resp.extra);
+        debug_unknown_fields("channel status", &resp.channel_status.extra);
         let token = resp
             .channel_status
             .last_committed_offset_token
@@ -37,124 +329,1100 @@ impl<R: Serialize + Clone> StreamingIngestChannel<R> {
                     resp.channel_status.last_committed_offset_token
                 )
             });
-        StreamingIngestChannel {
+        let mut channel = StreamingIngestChannel {
             _marker: std::marker::PhantomData,
             client: client.clone(),
             channel_name: channel_name.to_string(),
+            base_name: channel_name.to_string(),
+            fence_reopens: 0,
             continuation_token: resp.next_continuation_token,
             last_committed_offset_token: token,
             last_pushed_offset_token: token,
+            uncommitted: VecDeque::new(),
+            lag_alert: None,
+            quarantine: None,
+            schema_drift: None,
+            opened_at: Instant::now(),
+            stats: AppendStats::default(),
+            flushed_stats: AppendStats::default(),
+            background: None,
+            deferred_error: None,
+            superseded_by: None,
+            server_latency: None,
+            status_digest: None,
+            max_rows_per_request: client.auth_config.max_rows_per_request,
+            max_uncommitted: client.auth_config.max_uncommitted_offsets,
+            fail_on_uncommitted_limit: client
+                .auth_config
+                .fail_on_uncommitted_limit
+                .unwrap_or(false),
+            pacer: Pacer::new(client.auth_config.max_requests_per_second),
+            max_batch_bytes: MAX_REQUEST_SIZE,
+            flush_interval: None,
+            partitioner: client.auth_config.partition_column.clone().map(|column| {
+                Arc::new(ColumnPartitioner::new(column)) as Arc<dyn BatchPartitioner>
+            }),
+            dedup: None,
+            sampler: None,
+            buffer: BytesMut::new(),
+            last_append: None,
+            single_row_streak: 0,
+            last_single_row: None,
+            closed: false,
+            status_tx: watch::Sender::new(ChannelStatusSnapshot::default()),
+            span: channel_span(client, channel_name),
+        };
+        channel.record_server_latency(&resp.channel_status);
+        channel.report_offsets();
+        channel
+    }
+
+    /// The `channel` span (with `db`, `schema`, `pipe`, and `channel` fields)
+    /// that every append, status, and close request of this channel runs
+    /// in, as `append_rows`, `append_async`, `channel_status`,
+    /// `close_channel`, and `drop_channel` child spans. Enter it, or pass it
+    /// as `parent:`, to group application events with the channel's.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub(crate) fn set_span(&mut self, span: Span) {
+        self.span = span;
+    }
+
+    /// Current channel name; differs from the opened name after an automatic
+    /// reopen (see `Config::fenced_channel_suffix`).
+    pub fn channel_name(&self) -> &str {
+        &self.channel_name
+    }
+
+    /// Age of the oldest pushed offset that Snowflake has not yet reported as
+    /// committed, or zero when everything pushed is committed.
+    ///
+    /// Commit progress is learned from channel status polls
+    /// ([`get_latest_committed_offset_token`](Self::get_latest_committed_offset_token)
+    /// and [`close`](Self::close)), so the lag keeps growing between polls.
+    pub fn commit_lag(&self) -> Duration {
+        self.uncommitted
+            .front()
+            .map(|(_, pushed_at)| pushed_at.elapsed())
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Snowflake's average processing latency for this channel, as last
+    /// reported in a channel status response, or `None` before Snowflake has
+    /// reported one.
+    ///
+    /// This is server-side lag from receiving rows to committing them, apart
+    /// from request latency ([`ChannelReport::avg_latency`]). It is refreshed
+    /// on open and on every status poll, each of which also emits a
+    /// [`TelemetryEvent::ServerLatency`].
+    pub fn server_latency(&self) -> Option<Duration> {
+        self.server_latency
+    }
+
+    /// Registers `callback` to be invoked with the channel name and current
+    /// [`commit_lag`](Self::commit_lag) when the lag exceeds `threshold`.
+    ///
+    /// The lag is checked after every append and status poll. The callback
+    /// fires once per excursion and is re-armed when the lag falls back under
+    /// the threshold. Registering a new alert replaces the previous one.
+    pub fn set_commit_lag_alert<F>(&mut self, threshold: Duration, callback: F)
+    where
+        F: Fn(&str, Duration) + Send + Sync + 'static,
+    {
+        self.lag_alert = Some(CommitLagAlert {
+            threshold,
+            callback: Box::new(callback),
+            fired: false,
+        });
+    }
+
+    /// Quarantines the channel once more than `max_reject_rate` (a fraction,
+    /// e.g. `0.05`) of its rows are rejected by Snowflake, judged after at
+    /// least `min_rows` rows have been sent.
+    ///
+    /// A row counts as rejected when its append request is answered with a
+    /// 4xx other than 401 or 429, typically because it no longer matches the
+    /// pipe's mapping after a schema change. A quarantined channel reports
+    /// [`Warning::ChannelQuarantined`] once and fails every further append
+    /// immediately with [`Error::ChannelQuarantined`], without sending it,
+    /// until [`release_quarantine`](Self::release_quarantine). Setting a new
+    /// threshold restarts the count.
+    pub fn set_quarantine_threshold(&mut self, max_reject_rate: f64, min_rows: u64) {
+        self.quarantine = Some(Quarantine {
+            max_reject_rate,
+            min_rows,
+            sent: 0,
+            rejected: 0,
+            tripped: false,
+        });
+    }
+
+    /// Whether the channel was quarantined; see
+    /// [`set_quarantine_threshold`](Self::set_quarantine_threshold).
+    pub fn is_quarantined(&self) -> bool {
+        self.quarantine.as_ref().is_some_and(|q| q.tripped)
+    }
+
+    /// Accepts appends again after a quarantine, restarting the rejected-row
+    /// count under the same threshold.
+    pub fn release_quarantine(&mut self) {
+        if let Some(quarantine) = self.quarantine.as_mut() {
+            quarantine.sent = 0;
+            quarantine.rejected = 0;
+            quarantine.tripped = false;
+        }
+    }
+
+    fn check_quarantine(&self) -> Result<(), Error> {
+        if self.is_quarantined() {
+            return Err(Error::ChannelQuarantined(self.channel_name.clone()));
+        }
+        Ok(())
+    }
+
+    /// Counts `sent` rows, `rejected` of them by Snowflake, and quarantines
+    /// the channel when the rejection rate crosses the threshold.
+    fn count_rejections(&mut self, sent: usize, rejected: usize) {
+        let Some(quarantine) = self.quarantine.as_mut() else {
+            return;
+        };
+        quarantine.sent += sent as u64;
+        quarantine.rejected += rejected as u64;
+        if quarantine.tripped
+            || quarantine.sent < quarantine.min_rows.max(1)
+            || quarantine.rejected as f64 <= quarantine.max_reject_rate * quarantine.sent as f64
+        {
+            return;
+        }
+        quarantine.tripped = true;
+        warn!(
+            "Channel '{}' quarantined: {} of {} rows rejected, threshold {}",
+            self.channel_name, quarantine.rejected, quarantine.sent, quarantine.max_reject_rate
+        );
+        self.client.auth_config.warn(Warning::ChannelQuarantined {
+            channel: self.channel_name.clone(),
+            rejected: quarantine.rejected,
+            sent: quarantine.sent,
+        });
+    }
+
+    /// Registers `callback` to be invoked with the channel name and the
+    /// [`SchemaDrift`] when incoming rows carry top-level fields that the
+    /// rows last appended successfully did not, which usually means an
+    /// upstream schema change needs a pipe or table migration.
+    ///
+    /// The first row of every append request is checked before it is sent;
+    /// rows that are not JSON objects are ignored. Each new field set is
+    /// reported once, also as a [`Warning::SchemaDrift`], and becomes the
+    /// baseline once rows with it are appended. Rows that merely omit fields
+    /// are not reported. Registering a new alert replaces the previous one
+    /// and restarts from the next row.
+    pub fn set_schema_drift_alert<F>(&mut self, callback: F)
+    where
+        F: Fn(&str, &SchemaDrift) + Send + Sync + 'static,
+    {
+        self.schema_drift = Some(DriftWatch::new(Box::new(callback)));
+    }
+
+    fn check_schema_drift(&mut self, row: &[u8]) {
+        let Some(watch) = self.schema_drift.as_mut() else {
+            return;
+        };
+        let Some(drift) = watch.observe(row) else {
+            return;
+        };
+        warn!(
+            "Channel '{}' schema drift: added {:?}, removed {:?}",
+            self.channel_name, drift.added, drift.removed
+        );
+        (watch.callback)(&self.channel_name, &drift);
+        self.client.auth_config.warn(Warning::SchemaDrift {
+            channel: self.channel_name.clone(),
+            drift,
+        });
+    }
+
+    /// Adds `headers` to every request this channel sends, replacing client
+    /// default or provider headers of the same name.
+    ///
+    /// Rows already queued with [`append_row_async`](Self::append_row_async)
+    /// keep the headers in effect when queuing began until the next
+    /// [`flush`](Self::flush).
+    pub fn set_extra_headers(&mut self, headers: reqwest::header::HeaderMap) {
+        self.client.extra_headers.set_channel(headers);
+    }
+
+    /// Caps the rows sent in one append request, overriding
+    /// `Config::max_rows_per_request` for this channel. `None` removes the cap.
+    pub fn set_max_rows_per_request(&mut self, max_rows: Option<NonZeroUsize>) {
+        self.max_rows_per_request = max_rows.map(NonZeroUsize::get);
+    }
+
+    /// Caps this channel's pushed offsets that Snowflake has not yet reported
+    /// committed, overriding `Config::max_uncommitted_offsets`. `None` removes
+    /// the cap.
+    ///
+    /// At the cap, each append request first polls channel status until an
+    /// offset commits, or fails with [`Error::UncommittedLimit`] when
+    /// [`set_fail_on_uncommitted_limit`](Self::set_fail_on_uncommitted_limit)
+    /// is on. Rows queued with [`append_row_async`](Self::append_row_async)
+    /// are not held back.
+    pub fn set_max_uncommitted_offsets(&mut self, max_offsets: Option<NonZeroUsize>) {
+        self.max_uncommitted = max_offsets.map(NonZeroUsize::get);
+    }
+
+    /// Whether an append at the uncommitted-offset cap fails instead of
+    /// waiting, overriding `Config::fail_on_uncommitted_limit`.
+    pub fn set_fail_on_uncommitted_limit(&mut self, fail: bool) {
+        self.fail_on_uncommitted_limit = fail;
+    }
+
+    /// Groups the rows of each `append_rows*` batch by `partitioner` before
+    /// the batch is split into requests, overriding `Config::partition_column`
+    /// for this channel. `None` sends rows in append order.
+    ///
+    /// Partitions keep the order in which they first appear in the batch, and
+    /// rows keep their order within a partition, but rows of different
+    /// partitions are reordered relative to each other. Rows queued with
+    /// [`append_row_async`](Self::append_row_async) are not regrouped.
+    pub fn set_batch_partitioner(&mut self, partitioner: Option<Arc<dyn BatchPartitioner>>) {
+        self.partitioner = partitioner;
+    }
+
+    /// Drops rows whose key `window` has seen, or that repeat an earlier row
+    /// of the same batch, from every `append_row*` call, to absorb duplicates
+    /// from at-least-once sources. `None` sends every row. Rows queued with
+    /// [`append_row_async`](Self::append_row_async) are not deduplicated.
+    pub fn set_dedup_window(&mut self, window: Option<DedupWindow>) {
+        self.dedup = window;
+    }
+
+    /// The channel's dedup window, for its size and
+    /// [`dropped`](DedupWindow::dropped) count.
+    pub fn dedup_window(&self) -> Option<&DedupWindow> {
+        self.dedup.as_ref()
+    }
+
+    /// Keeps only the rows `sampler` selects from every `append_row*` call,
+    /// dropping the rest before they are deduplicated, buffered, or sent.
+    /// `None` sends every row. Rows queued with
+    /// [`append_row_async`](Self::append_row_async) are not sampled.
+    pub fn set_sampler(&mut self, sampler: Option<Sampler>) {
+        self.sampler = sampler;
+    }
+
+    /// The channel's sampler, for its [`kept`](Sampler::kept) and
+    /// [`sampled_out`](Sampler::sampled_out) counts.
+    pub fn sampler(&self) -> Option<&Sampler> {
+        self.sampler.as_ref()
+    }
+
+    /// Applies the per-channel overrides in `options`.
+    pub(crate) fn apply_options(&mut self, options: &OpenChannelOptions) {
+        self.client.override_retries(
+            options.retry_on_unauthorized,
+            options.rate_limit_backoff,
+            options.attempt_timeout,
+        );
+        if let Some(max_rows) = options.max_rows_per_request {
+            self.set_max_rows_per_request(Some(max_rows));
+        }
+        if let Some(per_second) = options.max_requests_per_second {
+            self.set_max_requests_per_second(Some(per_second));
+        }
+        if let Some(bytes) = options.max_batch_bytes {
+            self.max_batch_bytes = bytes.clamp(1, MAX_REQUEST_SIZE);
+        }
+        self.flush_interval = options.flush_interval.or(self.flush_interval);
+    }
+
+    /// Adds `tags` to the client's `Config::tags` for telemetry events from
+    /// this channel, replacing values for the same keys. Applies to events
+    /// emitted after the call, including from the background appender once it
+    /// next starts.
+    pub fn set_tags(&mut self, tags: Tags) {
+        self.client.telemetry = Arc::new(self.client.telemetry.with_tags(tags));
+    }
+
+    /// Tags sent with this channel's telemetry events: the client's
+    /// `Config::tags` plus any set with [`set_tags`](Self::set_tags).
+    pub fn tags(&self) -> &Tags {
+        self.client.telemetry.tags()
+    }
+
+    /// Pre-allocates the serialization buffer for batches of about `bytes`
+    /// serialized bytes, so early `append_rows` calls don't grow it row by row.
+    ///
+    /// The buffer is reused across calls and keeps its capacity once the
+    /// previous batch's requests have been sent.
+    pub fn reserve_batch_capacity(&mut self, bytes: usize) {
+        self.buffer.reserve(bytes);
+    }
+
+    /// Spaces this channel's append requests so no more than `per_second`
+    /// are sent each second, overriding `Config::max_requests_per_second`.
+    /// `None` removes the limit.
+    ///
+    /// Like [`set_extra_headers`](Self::set_extra_headers), rows already
+    /// queued with [`append_row_async`](Self::append_row_async) keep the
+    /// previous limits until the next [`flush`](Self::flush).
+    pub fn set_max_requests_per_second(&mut self, per_second: Option<NonZeroU32>) {
+        self.pacer = Pacer::new(per_second.map(NonZeroU32::get));
+    }
+
+    fn record_server_latency(&mut self, status: &ChannelStatus) {
+        let Some(ms) = status
+            .snowflake_avg_processing_latency_ms
+            .and_then(|ms| u64::try_from(ms).ok())
+        else {
+            return;
+        };
+        let latency = Duration::from_millis(ms);
+        self.server_latency = Some(latency);
+        self.client
+            .telemetry
+            .record(&TelemetryEvent::ServerLatency {
+                channel: self.channel_name.clone(),
+                latency,
+            });
+    }
+
+    fn report_offsets(&self) {
+        self.client.diagnostics.channel_offsets(
+            &self.channel_name,
+            self.last_committed_offset_token,
+            self.last_pushed_offset_token,
+        );
+        self.status_tx.send_replace(ChannelStatusSnapshot {
+            channel_name: self.channel_name.clone(),
+            committed_offset: self.last_committed_offset_token,
+            pushed_offset: self.last_pushed_offset_token,
+            uncommitted_offsets: self.uncommitted.len(),
+            commit_lag: self.commit_lag(),
+            server_latency: self.server_latency,
+        });
+    }
+
+    /// Subscribes to this channel's commit progress, so health endpoints and
+    /// UIs can follow it without polling channel status themselves.
+    ///
+    /// The receiver starts with the latest snapshot and sees a new one after
+    /// every append request and every status poll that sees new commits (on
+    /// [`get_latest_committed_offset_token`](Self::get_latest_committed_offset_token),
+    /// [`flush_and_wait`](Self::flush_and_wait), [`close`](Self::close), and
+    /// while waiting at `Config::max_uncommitted_offsets`). Rows queued with
+    /// [`append_row_async`](Self::append_row_async) are reflected once the
+    /// channel next waits for them. Once the channel handle is dropped,
+    /// `changed()` on the receiver returns an error.
+    pub fn status_watch(&self) -> watch::Receiver<ChannelStatusSnapshot> {
+        self.status_tx.subscribe()
+    }
+
+    fn check_commit_lag(&mut self) {
+        let lag = self.commit_lag();
+        let Some(alert) = self.lag_alert.as_mut() else {
+            return;
+        };
+        if lag <= alert.threshold {
+            alert.fired = false;
+        } else if !alert.fired {
+            alert.fired = true;
+            warn!(
+                "Channel '{}' commit lag {:?} exceeds threshold {:?}; committed={} pushed={}",
+                self.channel_name,
+                lag,
+                alert.threshold,
+                self.last_committed_offset_token,
+                self.last_pushed_offset_token
+            );
+            (alert.callback)(&self.channel_name, lag);
+            self.client.auth_config.warn(Warning::CommitLagExceeded {
+                channel: self.channel_name.clone(),
+                lag,
+                threshold: alert.threshold,
+            });
         }
     }
 
     /// Batches are sent as newline-delimited JSON rows in a single POST body
     /// up to 16MB per request, matching Snowflake Snowpipe Streaming guidance.
     pub async fn append_row(&mut self, row: &R) -> Result<(), Error> {
-        let data = serde_json::to_string(row).expect("Failed to serialize row");
-        self.append_rows_call(data).await?;
-        Ok(())
+        self.track_single_row();
+        self.flush().await?;
+        let data = serde_json::to_vec(row).expect("Failed to serialize row");
+        if !self.sample_one(&data) {
+            self.last_single_row = Some(Instant::now());
+            return Ok(());
+        }
+        let key = match &self.dedup {
+            Some(window) => window.key(&data),
+            None => None,
+        };
+        if let Some(key) = &key
+            && self.is_duplicate(key)
+        {
+            self.last_single_row = Some(Instant::now());
+            return Ok(());
+        }
+        let result = self
+            .append_rows_call(AppendBody::Whole(Bytes::from(data)), 1, None)
+            .await;
+        if result.is_ok() {
+            self.remember_keys(key);
+        }
+        self.last_single_row = Some(Instant::now());
+        result
     }
 
-    pub async fn append_rows(&mut self, rows: &mut dyn Iterator<Item = R>) -> Result<usize, Error> {
-        let serialized_rows = rows
-            .into_iter()
-            .map(|r| serde_json::to_string(&r))
-            .collect::<Result<Vec<String>, serde_json::Error>>()?;
-        let row_size = serialized_rows.first().map(|r| r.len()).unwrap_or(0);
-        let chunk_size = if row_size == 0 {
-            MAX_REQUEST_SIZE
-        } else {
-            let denom = 2 * row_size + 1; // +1 for newline
-            let cs = MAX_REQUEST_SIZE / denom;
-            cs.max(1)
+    /// Bytes `row` adds to a request body: its serialized JSON plus, with
+    /// `Config::batch_id_column` or `Config::checksum_column`, those fields.
+    /// Rows of a request are separated by one more byte each. The row is
+    /// serialized into a counter, not a buffer.
+    pub fn estimate_serialized_size(&self, row: &R) -> Result<usize, Error> {
+        let mut counter = ByteCounter::default();
+        serde_json::to_writer(&mut counter, row)?;
+        Ok(counter.0 + self.batch_column_bytes())
+    }
+
+    /// Checks `row` the way appending it would, without sending anything, so
+    /// oversized or malformed records can be rejected or split at the source.
+    /// Returns its [estimated size](Self::estimate_serialized_size).
+    ///
+    /// Fails with [`Error::Json`] when the row cannot be serialized,
+    /// [`Error::InvalidRow`] when it is not a JSON object, contains a raw
+    /// newline (e.g. from a pretty-printed `RawValue`) that would split it in
+    /// the newline-delimited body, or, with a
+    /// [schema drift alert](Self::set_schema_drift_alert) set, has fields the
+    /// rows last appended lacked, and [`Error::DataTooLarge`] when it alone
+    /// exceeds the request size limit.
+    pub fn validate_row(&self, row: &R) -> Result<usize, Error> {
+        let data = serde_json::to_vec(row)?;
+        if data.first() != Some(&b'{') {
+            let kind = serde_json::from_slice(&data).map_or("value", |value| json_type(&value));
+            return Err(Error::InvalidRow(format!(
+                "row is a JSON {kind}, not an object"
+            )));
+        }
+        if data.contains(&b'\n') {
+            return Err(Error::InvalidRow(
+                "row contains a raw newline, which would split it in the request body".into(),
+            ));
+        }
+        if let Some(watch) = &self.schema_drift {
+            let unknown = watch.unknown_fields(&data);
+            if !unknown.is_empty() {
+                return Err(Error::InvalidRow(format!(
+                    "row adds fields {:?} that the rows last appended lacked",
+                    unknown
+                )));
+            }
+        }
+        let size = data.len() + self.batch_column_bytes();
+        let max = self.client.max_request_bytes();
+        if size > max {
+            return Err(Error::DataTooLarge(size, max));
+        }
+        Ok(size)
+    }
+
+    /// Bytes the `Config::batch_id_column` and `Config::checksum_column`
+    /// fields add to a row: `,"<column>":"<uuid>"` and
+    /// `,"<column>":"<16 hex digits>"`.
+    fn batch_column_bytes(&self) -> usize {
+        let config = &self.client.auth_config;
+        let field = |column: &Option<String>, value_len: usize| {
+            column.as_ref().map_or(0, |column| {
+                serde_json::to_string(column).map_or(0, |column| column.len()) + value_len + 4
+            })
         };
-        let requests = serialized_rows
-            .chunks(chunk_size)
-            .map(|batch| batch.join("\n"))
-            .collect::<Vec<String>>();
-        let mut bytes_written = 0;
-        for req in requests {
-            bytes_written += req.len();
-            self.append_rows_call(req).await?;
+        field(&config.batch_id_column, 36) + field(&config.checksum_column, 16)
+    }
+
+    /// Reports [`Lint::SingleRowAppends`] after a run of `append_row` calls
+    /// that each began right after the previous one returned.
+    fn track_single_row(&mut self) {
+        let tight = self
+            .last_single_row
+            .is_some_and(|last| last.elapsed() < SINGLE_ROW_GAP);
+        self.single_row_streak = if tight { self.single_row_streak + 1 } else { 1 };
+        if self.single_row_streak >= SINGLE_ROW_STREAK {
+            self.client.misuse.report(
+                &self.client.auth_config,
+                Lint::SingleRowAppends,
+                &self.channel_name,
+            );
         }
-        Ok(bytes_written)
+    }
+
+    /// Appends rows from an iterator, splitting them into requests of at most
+    /// 16MB. Returns the number of body bytes sent.
+    pub async fn append_rows(&mut self, rows: &mut dyn Iterator<Item = R>) -> Result<usize, Error> {
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.append_serialized_rows(serialized_rows).await
     }
 
     /// Append many rows using any IntoIterator of rows. This is a convenience wrapper
     /// around `append_rows` that avoids requiring a `&mut Iterator` at call sites.
+    ///
+    /// Rows are serialized before the first request is issued, so the returned
+    /// future is `Send` whenever `R` is, and can be driven from `tokio::spawn`.
     pub async fn append_rows_iter<I>(&mut self, rows: I) -> Result<usize, Error>
     where
         I: IntoIterator<Item = R>,
     {
-        let mut iter = rows.into_iter();
-        self.append_rows(&mut iter).await
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.append_serialized_rows(serialized_rows).await
     }
 
-    async fn append_rows_call(&mut self, data: String) -> Result<(), Error> {
-        if data.len() > MAX_REQUEST_SIZE {
-            error!(
-                "Data size {} exceeds maximum request size {}",
-                data.len(),
-                MAX_REQUEST_SIZE
+    /// Appends `rows` in a single request carrying `offset_token` instead of
+    /// the next offset in sequence, e.g. the source sequence number of the
+    /// batch's last record. Once Snowflake reports `offset_token` committed,
+    /// every row of the batch is durable, so the source can be acknowledged
+    /// (see [`AckTracker`](crate::AckTracker)) and a restarted producer can
+    /// skip records up to the committed token. Returns the body bytes sent.
+    ///
+    /// `offset_token` must be greater than any token pushed before, and the
+    /// rows must fit in one request (at most 16MB and
+    /// `max_rows_per_request`); otherwise nothing is sent and
+    /// [`Error::Config`] is returned. Explicit tokens do not carry a
+    /// `Config::writer_generation`.
+    pub async fn append_rows_at<I>(&mut self, offset_token: u64, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.flush().await?;
+        let serialized_rows = self.sampled(serialized_rows);
+        let (serialized_rows, keys) = self.deduplicated(serialized_rows);
+        if offset_token <= self.last_pushed_offset_token {
+            return Err(Error::Config(format!(
+                "offset token {} must be greater than the last pushed token {}",
+                offset_token, self.last_pushed_offset_token
+            )));
+        }
+        let mut chunks = chunk_rows(serialized_rows, self.max_rows_per_request).into_iter();
+        let Some((body, rows)) = chunks.next() else {
+            return Ok(0);
+        };
+        if chunks.next().is_some() {
+            return Err(Error::Config(format!(
+                "rows appended at offset token {} do not fit in one request",
+                offset_token
+            )));
+        }
+        let bytes = body.len();
+        self.append_rows_call(body, rows, Some(offset_token))
+            .await?;
+        self.remember_keys(keys.into_iter().flatten());
+        Ok(bytes)
+    }
+
+    /// Like [`append_rows_iter`](Self::append_rows_iter), but serializes the
+    /// rows on a rayon thread pool first, for batches whose serialization is
+    /// CPU-bound (e.g. wide rows). Rows keep their order in the request
+    /// bodies, and requests are sent in order.
+    ///
+    /// The pool has `Config::serialization_threads` threads, or one per CPU
+    /// when unset. The calling task is blocked while rows are serialized.
+    /// Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub async fn append_rows_parallel<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+        R: Sync,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let serialized_rows =
+            serialize_rows_parallel(&mut self.buffer, &rows, self.client.serializer.as_deref())?;
+        drop(rows);
+        self.append_serialized_rows(serialized_rows).await
+    }
+
+    pub(crate) async fn append_serialized_rows(
+        &mut self,
+        serialized_rows: SerializedRows,
+    ) -> Result<usize, Error> {
+        self.flush().await?;
+        let serialized_rows = self.sampled(serialized_rows);
+        let serialized_rows = self.partitioned(serialized_rows);
+        let (serialized_rows, keys) = self.deduplicated(serialized_rows);
+        let mut keys = keys.into_iter();
+        let mut bytes_written = 0;
+        let mut requests = 0;
+        for (body, rows) in chunk_rows(serialized_rows, self.max_rows_per_request) {
+            bytes_written += body.len();
+            requests += 1;
+            self.append_rows_call(body, rows, None).await?;
+            self.remember_keys(keys.by_ref().take(rows).flatten());
+        }
+        if self.max_rows_per_request.is_some()
+            && requests >= CHUNKING_REQUESTS
+            && bytes_written / requests < CHUNKING_AVG_BYTES
+        {
+            self.client.misuse.report(
+                &self.client.auth_config,
+                Lint::ChunkingThrash,
+                &self.channel_name,
             );
-            return Err(Error::DataTooLarge(data.len(), MAX_REQUEST_SIZE));
         }
+        Ok(bytes_written)
+    }
 
-        let data_len = data.len();
-        trace!(
-            "append rows: channel='{}' bytes={}",
-            self.channel_name, data_len
-        );
-        let offset = self.last_pushed_offset_token + 1;
-        let ingest = self
-            .client
-            .ingest_host
-            .as_ref()
-            .expect("ingest_host not set");
-        let base = if ingest.contains("://") {
-            ingest.trim_end_matches('/').to_string()
-        } else {
-            format!("https://{}", ingest)
+    /// Like [`append_rows_iter`](Self::append_rows_iter), but starts no
+    /// request once `deadline` has passed, so a latency-sensitive caller can
+    /// shed the rest of a batch instead of falling behind. Returns how many
+    /// rows were sent and how many remain.
+    ///
+    /// A request already in flight at the deadline is not cancelled, so the
+    /// call can overrun it by one request's latency (bound it with
+    /// `RetryPlan::attempt_timeout`). Unless a batch partitioner regrouped
+    /// the batch, the rows sent are the first `rows_sent` rows of `rows`.
+    pub async fn append_rows_with_deadline<I>(
+        &mut self,
+        rows: I,
+        deadline: impl Into<Instant>,
+    ) -> Result<DeadlineReport, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let deadline = deadline.into();
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.flush().await?;
+        let serialized_rows = self.sampled(serialized_rows);
+        let serialized_rows = self.partitioned(serialized_rows);
+        let (serialized_rows, keys) = self.deduplicated(serialized_rows);
+        let mut keys = keys.into_iter();
+        let mut report = DeadlineReport {
+            rows_remaining: serialized_rows.len(),
+            ..DeadlineReport::default()
         };
-        let url = format!(
-            "{}/v2/streaming/data/databases/{}/schemas/{}/pipes/{}/channels/{}/rows?continuationToken={}&offsetToken={}",
-            base,
-            self.client.db_name,
-            self.client.schema_name,
-            self.client.pipe_name,
+        for (body, rows) in chunk_rows(serialized_rows, self.max_rows_per_request) {
+            if Instant::now() >= deadline {
+                warn!(
+                    "append deadline passed on channel '{}'; {} rows not sent",
+                    self.channel_name, report.rows_remaining
+                );
+                break;
+            }
+            let bytes = body.len();
+            self.append_rows_call(body, rows, None).await?;
+            self.remember_keys(keys.by_ref().take(rows).flatten());
+            report.rows_sent += rows;
+            report.rows_remaining -= rows;
+            report.bytes += bytes;
+        }
+        Ok(report)
+    }
+
+    /// Drops the rows the channel's sampler, if any, does not keep.
+    fn sampled(&mut self, rows: SerializedRows) -> SerializedRows {
+        let Some(sampler) = &mut self.sampler else {
+            return rows;
+        };
+        let keep = (0..rows.len())
+            .map(|index| sampler.keep(rows.row(index)))
+            .collect::<Vec<_>>();
+        let dropped = keep.iter().filter(|keep| !**keep).count();
+        if dropped == 0 {
+            return rows;
+        }
+        debug!(
+            "channel '{}': sampled out {} of {} rows",
             self.channel_name,
-            self.continuation_token,
-            offset
+            dropped,
+            rows.len()
         );
+        rows.retained(&keep)
+    }
 
-        let payload = Bytes::from(data);
-        let response = self
-            .client
-            .send_with_scoped_token(move |client, scoped| {
-                client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", scoped))
-                    .header("Content-Type", "application/json")
-                    .header("User-Agent", USER_AGENT)
-                    .body(payload.clone())
-            })
-            .await?;
+    /// Whether the channel's sampler, if any, keeps the serialized `row`.
+    fn sample_one(&mut self, row: &[u8]) -> bool {
+        self.sampler
+            .as_mut()
+            .is_none_or(|sampler| sampler.keep(row))
+    }
 
-        let resp = response
-            .error_for_status()?
-            .json::<AppendRowsResponse>()
-            .await?;
+    /// Regroups `rows` with the channel's batch partitioner, if any.
+    fn partitioned(&self, rows: SerializedRows) -> SerializedRows {
+        match &self.partitioner {
+            Some(partitioner) => rows.grouped_by(partitioner.as_ref()),
+            None => rows,
+        }
+    }
 
-        self.last_pushed_offset_token = offset;
-        self.continuation_token = resp.next_continuation_token;
-        trace!(
-            "append rows ok: channel='{}' pushed_offset={} next_ctok='{}'",
-            self.channel_name, self.last_pushed_offset_token, self.continuation_token
+    /// Drops rows the dedup window has seen and repeats within `rows`.
+    /// Returns the remaining rows with their keys, to remember once sent.
+    fn deduplicated(&mut self, rows: SerializedRows) -> (SerializedRows, Vec<Option<String>>) {
+        let Some(window) = &mut self.dedup else {
+            return (rows, Vec::new());
+        };
+        let now = Instant::now();
+        let mut batch = HashSet::new();
+        let mut keys = Vec::with_capacity(rows.len());
+        let keep = (0..rows.len())
+            .map(|index| {
+                let key = window.key(rows.row(index));
+                let fresh = key
+                    .as_ref()
+                    .is_none_or(|key| !window.check(key, now) && batch.insert(key.clone()));
+                if fresh {
+                    keys.push(key);
+                }
+                fresh
+            })
+            .collect::<Vec<_>>();
+        let dropped = rows.len() - keys.len();
+        if dropped == 0 {
+            return (rows, keys);
+        }
+        window.count_dropped(dropped);
+        debug!(
+            "channel '{}': dropped {} duplicate rows",
+            self.channel_name, dropped
         );
+        (rows.retained(&keep), keys)
+    }
+
+    /// Whether the dedup window has seen `key`, counting the row as dropped
+    /// if so.
+    fn is_duplicate(&mut self, key: &str) -> bool {
+        let Some(window) = &mut self.dedup else {
+            return false;
+        };
+        let duplicate = window.check(key, Instant::now());
+        if duplicate {
+            window.count_dropped(1);
+            debug!("channel '{}': dropped 1 duplicate row", self.channel_name);
+        }
+        duplicate
+    }
+
+    /// Adds the keys of rows Snowflake accepted to the dedup window.
+    fn remember_keys(&mut self, keys: impl IntoIterator<Item = String>) {
+        if let Some(window) = &mut self.dedup {
+            window.remember(keys, Instant::now());
+        }
+    }
+
+    /// Sends one append request at offset token `pinned`, or the next token
+    /// in sequence when `None`.
+    async fn append_rows_call(
+        &mut self,
+        body: AppendBody,
+        rows: usize,
+        pinned: Option<u64>,
+    ) -> Result<(), Error> {
+        let span = info_span!(parent: &self.span, "append_rows", rows);
+        self.send_rows(body, rows, pinned).instrument(span).await
+    }
+
+    async fn send_rows(
+        &mut self,
+        body: AppendBody,
+        rows: usize,
+        pinned: Option<u64>,
+    ) -> Result<(), Error> {
+        self.check_writer_generation()?;
+        self.check_quarantine()?;
+        if self.schema_drift.is_some() {
+            self.check_schema_drift(&body.first_row());
+        }
+        self.wait_for_commit_room().await?;
+        self.pacer.wait().await;
+        let result = send_append(
+            &self.client,
+            &self.channel_name,
+            &self.continuation_token,
+            pinned.unwrap_or_else(|| self.next_offset()),
+            body.clone(),
+            rows,
+        )
+        .await;
+        let pushed = match result {
+            Err(Error::ChannelFenced(_)) if self.reopen_fenced().await? => {
+                self.pacer.wait().await;
+                send_append(
+                    &self.client,
+                    &self.channel_name,
+                    &self.continuation_token,
+                    pinned.unwrap_or_else(|| self.next_offset()),
+                    body,
+                    rows,
+                )
+                .await?
+            }
+            result => result.inspect_err(|err| {
+                if err.is_rejection() {
+                    self.count_rejections(rows, rows);
+                }
+            })?,
+        };
+        self.record_push(pushed);
+        if let Some(watch) = self.schema_drift.as_mut() {
+            watch.commit();
+        }
         Ok(())
     }
 
+    /// Polls channel status while `max_uncommitted` pushed offsets await
+    /// commit, failing after the first poll with
+    /// [`Error::UncommittedLimit`] when `fail_on_uncommitted_limit` is set.
+    async fn wait_for_commit_room(&mut self) -> Result<(), Error> {
+        let Some(limit) = self.max_uncommitted else {
+            return Ok(());
+        };
+        if self.uncommitted.len() < limit {
+            return Ok(());
+        }
+        let mut wait = self.commit_waiter();
+        loop {
+            self.get_channel_status().await?;
+            if self.uncommitted.len() < limit {
+                return Ok(());
+            }
+            if self.fail_on_uncommitted_limit {
+                return Err(Error::UncommittedLimit(self.channel_name.clone(), limit));
+            }
+            tokio::time::sleep(wait.next_poll(self.uncommitted.len())).await;
+        }
+    }
+
+    /// Starts a commit wait on this channel's uncommitted offsets, paced by
+    /// `Config::commit_wait_strategy`.
+    fn commit_waiter(&self) -> CommitWaiter {
+        CommitWaiter::new(&self.client.auth_config, self.uncommitted.len())
+    }
+
+    /// Gives up this handle without closing the channel, e.g. after failing
+    /// over to another account, and returns the last committed and pushed
+    /// offset tokens it knew of. Rows pushed past the committed token may or
+    /// may not be committed by Snowflake later.
+    pub(crate) fn abandon(&mut self) -> (u64, u64) {
+        self.closed = true;
+        (
+            self.last_committed_offset_token,
+            self.last_pushed_offset_token,
+        )
+    }
+
+    /// Offset token for the next append: one past the last pushed token, or
+    /// the first token of this writer's generation when taking over from an
+    /// older one.
+    fn next_offset(&self) -> u64 {
+        let next = self.last_pushed_offset_token + 1;
+        match self.client.auth_config.writer_generation {
+            Some(generation) => next.max((u64::from(generation) << GENERATION_SHIFT) + 1),
+            None => next,
+        }
+    }
+
+    /// Fails with [`Error::WriterFenced`] when a newer writer generation has
+    /// committed to this channel.
+    pub(crate) fn check_writer_generation(&self) -> Result<(), Error> {
+        let Some(ours) = self.client.auth_config.writer_generation else {
+            return Ok(());
+        };
+        let committed = (self.last_committed_offset_token >> GENERATION_SHIFT) as u32;
+        match self
+            .superseded_by
+            .or((committed > ours).then_some(committed))
+        {
+            Some(newer) => Err(Error::WriterFenced(ours, newer)),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves this handle onto a fresh channel after it was fenced, when
+    /// `Config::fenced_channel_suffix` is set. Returns `false` otherwise.
+    ///
+    /// Offsets pushed to the fenced channel but not yet committed are no
+    /// longer tracked.
+    async fn reopen_fenced(&mut self) -> Result<bool, Error> {
+        let Some(suffix) = self.client.auth_config.fenced_channel_suffix.clone() else {
+            return Ok(false);
+        };
+        self.fence_reopens += 1;
+        let name = format!("{}_{}_{}", self.base_name, suffix, self.fence_reopens);
+        warn!(
+            "channel '{}' was fenced by another writer; reopening as '{}'",
+            self.channel_name, name
+        );
+        self.client.auth_config.warn(Warning::ChannelReopened {
+            from: self.channel_name.clone(),
+            to: name.clone(),
+        });
+        let mut reopened = self.client.clone().open_channel(&name).await?;
+        reopened.closed = true;
+        self.client.diagnostics.channel_closed(&self.channel_name);
+        self.client.append_log.channel_closed(&self.channel_name);
+        self.span.record("channel", name.as_str());
+        self.channel_name = name;
+        self.continuation_token = reopened.continuation_token.clone();
+        self.last_committed_offset_token = reopened.last_committed_offset_token;
+        self.last_pushed_offset_token = reopened.last_pushed_offset_token;
+        self.uncommitted.clear();
+        self.status_digest = None;
+        self.report_offsets();
+        Ok(true)
+    }
+
+    /// Queues `row` to be sent by a background task and returns immediately.
+    ///
+    /// Queued rows are batched (up to 16MB per request) and sent in order; the
+    /// returned handle resolves to the offset token of the request that
+    /// carried the row, or may be dropped for fire-and-forget use. Any other
+    /// call on this channel first waits for queued rows to be sent, so
+    /// ordering is preserved; a background failure is also returned from that
+    /// call (or from [`flush`](Self::flush)) in addition to the handles.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn append_row_async(&mut self, row: &R) -> AppendHandle
+    where
+        R: 'static,
+    {
+        let data = match serde_json::to_string(row) {
+            Ok(data) => data,
+            Err(e) => return AppendHandle::ready(Err(e.into())),
+        };
+        if let Err(e) = self
+            .check_writer_generation()
+            .and_then(|()| self.check_quarantine())
+        {
+            return AppendHandle::ready(Err(e));
+        }
+        self.check_schema_drift(data.as_bytes());
+        let next_offset = self.next_offset();
+        let worker = self.background.get_or_insert_with(|| {
+            Worker::spawn(
+                info_span!(parent: &self.span, "append_async"),
+                self.client.clone(),
+                self.channel_name.clone(),
+                self.continuation_token.clone(),
+                next_offset,
+                Limits {
+                    max_rows: self.max_rows_per_request,
+                    max_bytes: self.max_batch_bytes,
+                    flush_interval: self.flush_interval,
+                    pacer: self.pacer.clone(),
+                },
+            )
+        });
+        worker.submit(Bytes::from(data))
+    }
+
+    /// Waits until every row queued with
+    /// [`append_row_async`](Self::append_row_async) has been sent, returning
+    /// the first background failure since the last flush.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.join_background().await;
+        match self.deferred_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Sends queued rows like [`flush`](Self::flush), then polls channel
+    /// status until everything pushed is committed or `timeout` passes,
+    /// leaving the channel open, e.g. at the end of a micro-batch.
+    ///
+    /// Running out of time is not an error: check
+    /// [`FlushReport::is_committed`]. Counts cover rows appended since the
+    /// previous call.
+    pub async fn flush_and_wait(&mut self, timeout: Duration) -> Result<FlushReport, Error> {
+        let start = Instant::now();
+        self.flush().await?;
+        let flush_duration = start.elapsed();
+        let deadline = Instant::now() + timeout;
+        let mut wait = self.commit_waiter();
+        while self.last_committed_offset_token < self.last_pushed_offset_token {
+            self.get_channel_status().await?;
+            if self.last_committed_offset_token >= self.last_pushed_offset_token {
+                break;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                warn!(
+                    "Channel '{}' flush timed out after {:?}; committed={} pushed={}",
+                    self.channel_name,
+                    timeout,
+                    self.last_committed_offset_token,
+                    self.last_pushed_offset_token
+                );
+                break;
+            }
+            tokio::time::sleep(wait.next_poll(self.uncommitted.len()).min(deadline - now)).await;
+        }
+        let report = FlushReport {
+            rows: self.stats.rows - self.flushed_stats.rows,
+            bytes: self.stats.bytes - self.flushed_stats.bytes,
+            requests: self.stats.requests - self.flushed_stats.requests,
+            pushed_offset: self.last_pushed_offset_token,
+            committed_offset: self.last_committed_offset_token,
+            flush_duration,
+            commit_wait: start.elapsed() - flush_duration,
+        };
+        self.flushed_stats = self.stats;
+        Ok(report)
+    }
+
+    async fn join_background(&mut self) {
+        let Some(worker) = self.background.take() else {
+            return;
+        };
+        let outcome = worker.finish().await;
+        for pushed in outcome.pushed {
+            self.record_push(pushed);
+        }
+        if outcome.rejected_rows > 0 {
+            self.count_rejections(outcome.rejected_rows, outcome.rejected_rows);
+        }
+        if let Some(watch) = self.schema_drift.as_mut() {
+            match outcome.error {
+                Some(_) => watch.discard(),
+                None => watch.commit(),
+            }
+        }
+        if let Some(err) = outcome.error {
+            self.deferred_error.get_or_insert(err);
+        }
+    }
+
+    /// The most recent append request Snowflake accepted on this channel. A
+    /// batch split across several requests gets one batch ID per request;
+    /// every request is reported to the client's telemetry sink.
+    pub fn last_append(&self) -> Option<&AppendReport> {
+        self.last_append.as_ref()
+    }
+
+    fn record_push(&mut self, pushed: PushedAppend) {
+        self.last_append = Some(AppendReport {
+            batch_id: pushed.batch_id,
+            offset: pushed.offset,
+            rows: pushed.rows,
+            bytes: pushed.bytes,
+            elapsed: pushed.elapsed,
+            checksum: pushed.checksum,
+        });
+        self.last_pushed_offset_token = pushed.offset;
+        self.uncommitted
+            .push_back((pushed.offset, pushed.pushed_at));
+        self.report_offsets();
+        self.continuation_token = pushed.continuation_token;
+        self.stats.rows += pushed.rows as u64;
+        self.stats.bytes += pushed.bytes as u64;
+        self.stats.requests += 1;
+        self.stats.retries += u64::from(pushed.retries);
+        self.stats.total_latency += pushed.elapsed;
+        self.count_rejections(pushed.rows, 0);
+        self.check_commit_lag();
+    }
+
+    /// Polls channel status and returns the last offset token Snowflake has
+    /// committed.
     pub async fn get_latest_committed_offset_token(&mut self) -> u64 {
+        self.join_background().await;
         self.get_channel_status()
             .await
             .expect("Failed to get channel status");
@@ -162,58 +1430,119 @@ impl<R: Serialize + Clone> StreamingIngestChannel<R> {
     }
 
     async fn get_channel_status(&mut self) -> Result<(), Error> {
-        let ingest = self
-            .client
-            .ingest_host
-            .as_ref()
-            .expect("ingest_host not set");
-        let base = if ingest.contains("://") {
-            ingest.trim_end_matches('/').to_string()
-        } else {
-            format!("https://{}", ingest)
-        };
-        let url = format!(
-            "{}/v2/streaming/databases/{}/schemas/{}/pipes/{}:bulk-channel-status",
-            base, self.client.db_name, self.client.schema_name, self.client.pipe_name,
-        );
-
-        let body = format!("{{\"channel_names\": [\"{}\"]}}", self.channel_name);
+        let span = info_span!(parent: &self.span, "channel_status");
+        self.poll_channel_status().instrument(span).await
+    }
 
-        let response = self
+    async fn poll_channel_status(&mut self) -> Result<(), Error> {
+        let body = self
             .client
-            .send_with_scoped_token(|client, scoped| {
-                client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", scoped))
-                    .header("Content-Type", "application/json")
-                    .header("User-Agent", USER_AGENT)
-                    .body(body.clone())
-            })
-            .await?;
-
-        let resp = response
-            .error_for_status()?
-            .json::<serde_json::Value>()
-            .await?;
-
+            .fetch_channel_statuses(&[&self.channel_name])
+            .await
+            .map_err(|e| e.in_operation(OperationKind::ChannelStatus, Some(&self.channel_name)))?;
+        // Long commit waits mostly see the same response again.
+        let digest = xxh3_64(&body);
+        if self.status_digest == Some(digest) {
+            debug!("channel status unchanged");
+            self.check_commit_lag();
+            return Ok(());
+        }
+        self.status_digest = Some(digest);
+        let resp = serde_json::from_slice::<serde_json::Value>(&body).map_err(|e| {
+            Error::from(e).in_operation(OperationKind::ChannelStatus, Some(&self.channel_name))
+        })?;
         let status = resp
             .get("channel_statuses")
-            .and_then(|cs| cs.get(&self.channel_name))
-            .map(|s| serde_json::from_value::<ChannelStatus>(s.clone()));
+            .and_then(|cs| cs.get(&self.channel_name));
+        self.apply_status(status)
+    }
+
+    /// Starts a commit wait on the uncommitted offsets of `channels`, which
+    /// share a client, paced by its `Config::commit_wait_strategy`.
+    pub(crate) fn pool_commit_waiter(channels: &[Self]) -> Option<CommitWaiter> {
+        let first = channels.first()?;
+        let uncommitted = channels.iter().map(|c| c.uncommitted.len()).sum();
+        Some(CommitWaiter::new(&first.client.auth_config, uncommitted))
+    }
+
+    /// Polls status for those of `channels` (all on one pipe) with pushed
+    /// offsets awaiting commit, in a single bulk request, and returns how
+    /// many offsets still await commit (at least one per channel behind).
+    pub(crate) async fn poll_outstanding(channels: &mut [Self]) -> Result<usize, Error> {
+        let mut outstanding = channels
+            .iter_mut()
+            .filter(|c| c.last_committed_offset_token < c.last_pushed_offset_token)
+            .collect::<Vec<_>>();
+        let Some(first) = outstanding.first() else {
+            return Ok(0);
+        };
+        let names = outstanding
+            .iter()
+            .map(|c| c.channel_name.as_str())
+            .collect::<Vec<_>>();
+        let resp = async {
+            let body = first.client.fetch_channel_statuses(&names).await?;
+            serde_json::from_slice::<serde_json::Value>(&body).map_err(Error::from)
+        }
+        .await
+        .map_err(|e| e.in_operation(OperationKind::ChannelStatus, None))?;
+        let mut remaining = 0;
+        for channel in &mut outstanding {
+            let status = resp
+                .get("channel_statuses")
+                .and_then(|cs| cs.get(&channel.channel_name));
+            channel.apply_status(status)?;
+            if channel.last_committed_offset_token < channel.last_pushed_offset_token {
+                remaining += channel.uncommitted.len().max(1);
+            }
+        }
+        Ok(remaining)
+    }
+
+    /// Applies this channel's entry of a bulk status response. A committed
+    /// token that has not advanced only re-checks the commit lag.
+    fn apply_status(&mut self, status: Option<&serde_json::Value>) -> Result<(), Error> {
+        let status = status.map(|s| serde_json::from_value::<ChannelStatus>(s.clone()));
 
         match status {
             Some(Ok(status)) => {
-                info!(
-                    "channel status: committed={:?}",
-                    status.last_committed_offset_token
-                );
+                debug_unknown_fields("channel status", &status.extra);
+                self.record_server_latency(&status);
                 let token_str = status
                     .last_committed_offset_token
                     .clone()
                     .unwrap_or_else(|| "0".to_string());
                 match token_str.parse::<u64>() {
+                    Ok(value) if value == self.last_committed_offset_token => {
+                        debug!("channel status: committed={} (unchanged)", value);
+                        self.check_commit_lag();
+                    }
                     Ok(value) => {
+                        info!("channel status: committed={}", value);
                         self.last_committed_offset_token = value;
+                        if let Err(Error::WriterFenced(ours, newer)) =
+                            self.check_writer_generation()
+                        {
+                            warn!(
+                                "channel '{}': writer generation {} superseded by {}",
+                                self.channel_name, ours, newer
+                            );
+                            self.client.auth_config.warn(Warning::WriterSuperseded {
+                                channel: self.channel_name.clone(),
+                                generation: ours,
+                                newer,
+                            });
+                            self.superseded_by = Some(newer);
+                        }
+                        while self
+                            .uncommitted
+                            .front()
+                            .is_some_and(|(offset, _)| *offset <= value)
+                        {
+                            self.uncommitted.pop_front();
+                        }
+                        self.report_offsets();
+                        self.check_commit_lag();
                     }
                     Err(err) => {
                         error!(
@@ -235,19 +1564,27 @@ impl<R: Serialize + Clone> StreamingIngestChannel<R> {
         Ok(())
     }
 
-    pub async fn close(&mut self) -> Result<(), Error> {
+    /// Waits up to five minutes for all pushed rows to commit, then drops the
+    /// channel, returning the channel's ingestion statistics.
+    pub async fn close(&mut self) -> Result<ChannelReport, Error> {
         self.close_with_timeout(std::time::Duration::from_secs(5 * 60))
             .await
     }
 
-    pub async fn close_with_timeout(&mut self, timeout: std::time::Duration) -> Result<(), Error> {
+    /// Like [`close`](Self::close) with a caller-chosen commit wait timeout;
+    /// returns `Error::Timeout` when commits do not catch up in time.
+    pub async fn close_with_timeout(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<ChannelReport, Error> {
+        self.flush().await?;
         let start = tokio::time::Instant::now();
         let mut last_warn_minute = 0u64;
+        let mut wait = self.commit_waiter();
+        let mut delay = Duration::ZERO;
         while self.last_committed_offset_token < self.last_pushed_offset_token {
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-            self.get_channel_status()
-                .await
-                .expect("Failed to get channel status");
+            tokio::time::sleep(delay).await;
+            self.get_channel_status().await?;
 
             let elapsed = start.elapsed();
             let elapsed_mins = elapsed.as_secs() / 60;
@@ -260,6 +1597,12 @@ impl<R: Serialize + Clone> StreamingIngestChannel<R> {
                     self.last_committed_offset_token,
                     self.last_pushed_offset_token
                 );
+                self.client.auth_config.warn(Warning::LongCommitWait {
+                    channel: self.channel_name.clone(),
+                    waited: elapsed,
+                    committed: self.last_committed_offset_token,
+                    pushed: self.last_pushed_offset_token,
+                });
             }
             if elapsed >= timeout {
                 error!(
@@ -271,42 +1614,676 @@ impl<R: Serialize + Clone> StreamingIngestChannel<R> {
                 );
                 return Err(Error::Timeout(timeout));
             }
+            delay = wait
+                .next_poll(self.uncommitted.len())
+                .min(timeout - elapsed);
         }
+        let commit_wait = start.elapsed();
+
+        self.delete_channel(OperationKind::CloseChannel).await?;
+
+        let avg_latency = match u32::try_from(self.stats.requests) {
+            Ok(0) | Err(_) => Duration::ZERO,
+            Ok(requests) => self.stats.total_latency / requests,
+        };
+        Ok(ChannelReport {
+            rows: self.stats.rows,
+            bytes: self.stats.bytes,
+            requests: self.stats.requests,
+            retries: self.stats.retries,
+            duration: self.opened_at.elapsed(),
+            avg_latency,
+            commit_wait,
+            server_latency: self.server_latency,
+        })
+    }
+
+    /// Drops the channel without waiting for pushed rows to commit, e.g. when
+    /// abandoning a writer during shutdown. Queued rows are sent first; rows
+    /// not yet committed may still be ingested. A later `open_channel` with
+    /// the same name resumes from whatever offset token did commit.
+    pub async fn drop_channel(&mut self) -> Result<(), Error> {
+        self.flush().await?;
+        self.delete_channel(OperationKind::DropChannel).await
+    }
 
+    async fn delete_channel(&mut self, operation: OperationKind) -> Result<(), Error> {
+        let span = match operation {
+            OperationKind::DropChannel => info_span!(parent: &self.span, "drop_channel"),
+            _ => info_span!(parent: &self.span, "close_channel"),
+        };
+        self.send_delete(operation).instrument(span).await
+    }
+
+    async fn send_delete(&mut self, operation: OperationKind) -> Result<(), Error> {
         let ingest = self
             .client
             .ingest_host
             .as_ref()
             .expect("ingest_host not set");
-        let base = if ingest.contains("://") {
-            ingest.trim_end_matches('/').to_string()
-        } else {
-            format!("https://{}", ingest)
-        };
-        let url = format!(
-            "{}/v2/streaming/databases/{}/schemas/{}/pipes/{}/channels/{}",
-            base,
-            self.client.db_name,
-            self.client.schema_name,
-            self.client.pipe_name,
-            self.channel_name
-        );
+        let url = endpoints::channel(ingest, self.client.pipe_ref(), &self.channel_name)?;
 
         self.client
-            .send_with_scoped_token(|client, scoped| {
+            .send_with_scoped_token(operation, |client, scoped| {
                 client
-                    .delete(&url)
+                    .delete(url.clone())
                     .header("Authorization", format!("Bearer {}", scoped))
                     .header("Content-Type", "application/json")
                     .header("User-Agent", USER_AGENT)
             })
-            .await?
-            .error_for_status()?;
+            .await
+            .and_then(|response| Ok(response.error_for_status()?))
+            .map_err(|e| e.in_operation(operation, Some(&self.channel_name)))?;
 
         info!("channel closed: name='{}'", self.channel_name);
+        self.closed = true;
+        self.client.diagnostics.channel_closed(&self.channel_name);
+        self.client.append_log.channel_closed(&self.channel_name);
+        Ok(())
+    }
+}
+
+impl StreamingIngestChannel<serde_json::Value> {
+    /// Appends dynamic rows, e.g. from a forwarder without row structs, like
+    /// [`append_rows_iter`](Self::append_rows_iter). Every row must be a JSON
+    /// object mapping column names to values; otherwise this fails with
+    /// [`Error::InvalidRow`] naming the first offending row, before any
+    /// request is sent. Returns the number of body bytes sent.
+    pub async fn append_serde_values<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = serde_json::Value>,
+    {
+        let mut invalid = None;
+        let rows = rows.into_iter().enumerate().map_while(|(index, row)| {
+            if row.is_object() {
+                Some(row)
+            } else {
+                invalid = Some((index, json_type(&row)));
+                None
+            }
+        });
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        if let Some((index, kind)) = invalid {
+            return Err(Error::InvalidRow(format!(
+                "row {index} is a JSON {kind}, not an object"
+            )));
+        }
+        self.append_serialized_rows(serialized_rows).await
+    }
+}
+
+/// Counts the bytes written to it.
+#[derive(Default)]
+struct ByteCounter(usize);
 
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
         Ok(())
     }
 }
 
+fn json_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+impl<R> Drop for StreamingIngestChannel<R> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.client.misuse.report(
+                &self.client.auth_config,
+                Lint::ChannelNotClosed,
+                &self.channel_name,
+            );
+        }
+    }
+}
+
+/// An append request Snowflake accepted.
+struct PushedAppend {
+    batch_id: Uuid,
+    checksum: Option<u64>,
+    offset: u64,
+    continuation_token: String,
+    rows: usize,
+    bytes: usize,
+    retries: u32,
+    elapsed: Duration,
+    pushed_at: Instant,
+}
+
+/// Sends one append request at `offset` under a new batch ID and reports it
+/// to the client's telemetry sink.
+async fn send_append<R: Serialize + Clone>(
+    client: &StreamingIngestClient<R>,
+    channel_name: &str,
+    continuation_token: &str,
+    offset: u64,
+    body: AppendBody,
+    rows: usize,
+) -> Result<PushedAppend, Error> {
+    let batch_id = Uuid::new_v4();
+    let config = &client.auth_config;
+    let checksum = (config.batch_checksum.unwrap_or(false) || config.checksum_column.is_some())
+        .then(|| body.checksum());
+    let mut fields = Vec::new();
+    if let Some(column) = &config.batch_id_column {
+        fields.push((column.as_str(), batch_id.to_string()));
+    }
+    if let (Some(column), Some(checksum)) = (&config.checksum_column, checksum) {
+        fields.push((column.as_str(), format!("{checksum:016x}")));
+    }
+    let body = if fields.is_empty() {
+        body
+    } else {
+        body.with_row_fields(&fields)?
+    };
+    let tokens_in_body = client.tokens_in_body();
+    let body = if tokens_in_body {
+        body.with_tokens(continuation_token, offset)?
+    } else {
+        body
+    };
+    let data_len = body.len();
+    check_request_size(data_len, client.max_request_bytes())?;
+
+    trace!(
+        "append rows: channel='{}' batch_id={} bytes={}",
+        channel_name, batch_id, data_len
+    );
+    let ingest = client.ingest_host.as_ref().expect("ingest_host not set");
+    let url = if tokens_in_body {
+        endpoints::rows(ingest, client.pipe_ref(), channel_name)?
+    } else {
+        endpoints::rows_with_tokens(
+            ingest,
+            client.pipe_ref(),
+            channel_name,
+            continuation_token,
+            &offset.to_string(),
+        )?
+    };
+
+    let context = |err: Error| {
+        err.in_operation(OperationKind::AppendRows, Some(channel_name))
+            .at_url(url.as_str())
+    };
+
+    let started = std::time::Instant::now();
+    let (response, retries) = client
+        .send_with_scoped_token_counting_retries(OperationKind::AppendRows, |client, scoped| {
+            client
+                .post(url.clone())
+                .header("Authorization", format!("Bearer {}", scoped))
+                .header("Content-Type", "application/json")
+                .header("User-Agent", USER_AGENT)
+                .header("Content-Length", data_len)
+                .body(body.to_body())
+        })
+        .await
+        .map_err(context)?;
+
+    let status = response.status();
+    if matches!(status, StatusCode::BAD_REQUEST | StatusCode::CONFLICT) {
+        let body = client.read_error_text(response).await.map_err(context)?;
+        let err = if FENCED_ERROR_CODES.iter().any(|code| body.contains(code)) {
+            Error::ChannelFenced(channel_name.to_string())
+        } else {
+            Error::Http(status, body)
+        };
+        client.diagnostics.error("append", &err);
+        return Err(err);
+    }
+    let resp = match response.error_for_status() {
+        Ok(response) => client.read_json::<AppendRowsResponse>(response).await,
+        Err(err) => Err(Error::from(err)),
+    }
+    .map_err(context)
+    .inspect_err(|err| client.diagnostics.error("append", err))?;
+    debug_unknown_fields("append rows", &resp.extra);
+
+    let elapsed = started.elapsed();
+    client.metrics.append(rows, data_len);
+    client
+        .append_log
+        .completed(channel_name, batch_id, offset, rows, data_len, elapsed);
+    client.telemetry.record(&TelemetryEvent::AppendCompleted {
+        channel: channel_name.to_string(),
+        batch_id,
+        offset,
+        rows,
+        bytes: data_len,
+        elapsed,
+        checksum,
+    });
+    Ok(PushedAppend {
+        batch_id,
+        checksum,
+        offset,
+        continuation_token: resp.next_continuation_token,
+        rows,
+        bytes: data_len,
+        retries,
+        elapsed,
+        pushed_at: Instant::now(),
+    })
+}
+
+/// Body of one append request.
+///
+/// Batches from `append_rows` are a contiguous slice of the channel's
+/// serialization buffer. Rows queued by the background appender are
+/// serialized one at a time, so they keep separate buffers and are streamed
+/// with newline separators instead of being joined. Either way the body is
+/// rebuilt from shared buffers on each (re)send, without copying.
+#[derive(Clone)]
+enum AppendBody {
+    Whole(Bytes),
+    Rows {
+        rows: Arc<[Bytes]>,
+        range: Range<usize>,
+    },
+}
+
+impl AppendBody {
+    fn len(&self) -> usize {
+        match self {
+            AppendBody::Whole(data) => data.len(),
+            AppendBody::Rows { rows, range } => {
+                let rows = &rows[range.clone()];
+                rows.iter().map(Bytes::len).sum::<usize>() + rows.len().saturating_sub(1)
+            }
+        }
+    }
+
+    /// The first serialized row in the body.
+    fn first_row(&self) -> Bytes {
+        match self {
+            AppendBody::Whole(data) => {
+                let end = data.iter().position(|b| *b == b'\n').unwrap_or(data.len());
+                data.slice(..end)
+            }
+            AppendBody::Rows { rows, range } => rows.get(range.start).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// The request body as one contiguous buffer.
+    fn to_bytes(&self) -> Bytes {
+        match self {
+            AppendBody::Whole(data) => data.clone(),
+            AppendBody::Rows { rows, range } => {
+                let mut data = BytesMut::with_capacity(self.len());
+                for (i, row) in rows[range.clone()].iter().enumerate() {
+                    if i > 0 {
+                        data.put_u8(b'\n');
+                    }
+                    data.extend_from_slice(row);
+                }
+                data.freeze()
+            }
+        }
+    }
+
+    /// XXH3-64 of the rows as newline-delimited JSON.
+    fn checksum(&self) -> u64 {
+        match self {
+            AppendBody::Whole(data) => xxh3_64(data),
+            AppendBody::Rows { rows, range } => {
+                let mut hasher = Xxh3::new();
+                for (i, row) in rows[range.clone()].iter().enumerate() {
+                    if i > 0 {
+                        hasher.update(b"\n");
+                    }
+                    hasher.update(row);
+                }
+                hasher.digest()
+            }
+        }
+    }
+
+    /// The body with `"<column>":"<value>"` added to every row for each of
+    /// `fields`; the rows must be JSON objects.
+    fn with_row_fields(&self, fields: &[(&str, String)]) -> Result<AppendBody, Error> {
+        let mut field = fields
+            .iter()
+            .map(|(column, value)| Ok(format!("{}:\"{}\"", serde_json::to_string(column)?, value)))
+            .collect::<Result<Vec<_>, serde_json::Error>>()?
+            .join(",");
+        field.push('}');
+        let data = self.to_bytes();
+        let mut out = BytesMut::with_capacity(data.len() + data.len() / 8 + field.len() + 1);
+        for (i, row) in data.split(|b| *b == b'\n').enumerate() {
+            let Some(fields) = row.strip_prefix(b"{").and_then(|r| r.strip_suffix(b"}")) else {
+                return Err(Error::Config(
+                    "batch_id_column and checksum_column require rows that serialize to JSON objects"
+                        .into(),
+                ));
+            };
+            if i > 0 {
+                out.put_u8(b'\n');
+            }
+            out.put_u8(b'{');
+            if !fields.trim_ascii().is_empty() {
+                out.extend_from_slice(fields);
+                out.put_u8(b',');
+            }
+            out.extend_from_slice(field.as_bytes());
+        }
+        Ok(AppendBody::Whole(out.freeze()))
+    }
+
+    /// The body as a JSON object carrying the append's tokens next to its
+    /// rows, for [`ApiVersion::Preview`](crate::ApiVersion::Preview).
+    fn with_tokens(&self, continuation_token: &str, offset: u64) -> Result<AppendBody, Error> {
+        let head = format!(
+            "{{\"continuation_token\":{},\"offset_token\":\"{}\",\"rows\":[",
+            serde_json::to_string(continuation_token)?,
+            offset
+        );
+        let data = self.to_bytes();
+        let mut out = BytesMut::with_capacity(head.len() + data.len() + 2);
+        out.extend_from_slice(head.as_bytes());
+        for (i, row) in data.split(|b| *b == b'\n').enumerate() {
+            if i > 0 {
+                out.put_u8(b',');
+            }
+            out.extend_from_slice(row);
+        }
+        out.extend_from_slice(b"]}");
+        Ok(AppendBody::Whole(out.freeze()))
+    }
+
+    fn to_body(&self) -> reqwest::Body {
+        match self {
+            AppendBody::Whole(data) => reqwest::Body::from(data.clone()),
+            AppendBody::Rows { rows, range } => {
+                let newline = Bytes::from_static(b"\n");
+                let parts = rows[range.clone()]
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, row)| {
+                        (i > 0)
+                            .then(|| newline.clone())
+                            .into_iter()
+                            .chain([row.clone()])
+                    })
+                    .map(Ok::<_, std::convert::Infallible>)
+                    .collect::<Vec<_>>();
+                reqwest::Body::wrap_stream(futures_util::stream::iter(parts))
+            }
+        }
+    }
+}
+
+/// Splits serialized rows into request bodies of at most 16MB and
+/// `max_rows` rows, paired with their row counts. Each body is a slice of the
+/// serialized rows' buffer.
+fn chunk_rows(
+    serialized_rows: SerializedRows,
+    max_rows: Option<usize>,
+) -> Vec<(AppendBody, usize)> {
+    let row_size = serialized_rows.ends.first().copied().unwrap_or(0);
+    let chunk_size = if row_size == 0 {
+        MAX_REQUEST_SIZE
+    } else {
+        let denom = 2 * row_size + 1; // +1 for newline
+        let cs = MAX_REQUEST_SIZE / denom;
+        cs.max(1)
+    };
+    let chunk_size = max_rows.map_or(chunk_size, |max| chunk_size.min(max.max(1)));
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < serialized_rows.len() {
+        let end = (start + chunk_size).min(serialized_rows.len());
+        let body = AppendBody::Whole(serialized_rows.body(start..end));
+        chunks.push((body, end - start));
+        start = end;
+    }
+    chunks
+}
+
+/// Spaces a channel's append requests at least `1 / max_requests_per_second`
+/// apart. Clones share the schedule, so a channel and its background appender
+/// are paced together.
+#[derive(Clone, Default)]
+struct Pacer {
+    interval: Option<Duration>,
+    next_send: Arc<std::sync::Mutex<Option<Instant>>>,
+}
+
+impl Pacer {
+    fn new(per_second: Option<u32>) -> Self {
+        Self {
+            interval: per_second
+                .filter(|n| *n > 0)
+                .map(|n| Duration::from_secs(1) / n),
+            next_send: Arc::default(),
+        }
+    }
+
+    /// Reserves the next send slot and waits for it.
+    async fn wait(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next_send = self.next_send.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_send.map_or(now, |next| next.max(now));
+            *next_send = Some(slot + interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Fails requests over `max_bytes`: 16MB, or a larger limit the deployment
+/// reported in its [`Capabilities`](crate::Capabilities). Batches are still
+/// split at 16MB.
+fn check_request_size(data_len: usize, max_bytes: usize) -> Result<(), Error> {
+    if data_len > max_bytes {
+        error!(
+            "Data size {} exceeds maximum request size {}",
+            data_len, max_bytes
+        );
+        return Err(Error::DataTooLarge(data_len, max_bytes));
+    }
+    Ok(())
+}
+
+/// Rows serialized as newline-delimited JSON into one buffer, with the end
+/// offset of each row.
+pub(crate) struct SerializedRows {
+    data: Bytes,
+    ends: Vec<usize>,
+}
+
+impl SerializedRows {
+    pub(crate) fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    fn row_start(&self, index: usize) -> usize {
+        match index {
+            0 => 0,
+            _ => self.ends[index - 1] + 1,
+        }
+    }
+
+    /// The NDJSON body for `rows`, without copying.
+    fn body(&self, rows: Range<usize>) -> Bytes {
+        self.data
+            .slice(self.row_start(rows.start)..self.ends[rows.end - 1])
+    }
+
+    pub(crate) fn row(&self, index: usize) -> &[u8] {
+        &self.data[self.row_start(index)..self.ends[index]]
+    }
+
+    /// The rows reordered so each partition's rows are contiguous, with
+    /// partitions in order of first appearance.
+    fn grouped_by(self, partitioner: &dyn BatchPartitioner) -> SerializedRows {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of = HashMap::new();
+        for index in 0..self.len() {
+            let group = *group_of
+                .entry(partitioner.partition_key(self.row(index)))
+                .or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+            groups[group].push(index);
+        }
+        if groups.len() <= 1 {
+            return self;
+        }
+        let mut data = BytesMut::with_capacity(self.data.len());
+        let mut ends = Vec::with_capacity(self.len());
+        for index in groups.into_iter().flatten() {
+            if !ends.is_empty() {
+                data.put_u8(b'\n');
+            }
+            data.extend_from_slice(self.row(index));
+            ends.push(data.len());
+        }
+        SerializedRows {
+            data: data.freeze(),
+            ends,
+        }
+    }
+
+    /// The rows whose entry in `keep` is true, in order.
+    fn retained(self, keep: &[bool]) -> SerializedRows {
+        let rows = (0..self.len())
+            .filter(|&index| keep[index])
+            .collect::<Vec<_>>();
+        self.select(&rows)
+    }
+
+    /// Copies of the rows at `rows`, in that order.
+    pub(crate) fn select(&self, rows: &[usize]) -> SerializedRows {
+        let mut data = BytesMut::new();
+        let mut ends = Vec::with_capacity(rows.len());
+        for &index in rows {
+            if !ends.is_empty() {
+                data.put_u8(b'\n');
+            }
+            data.extend_from_slice(self.row(index));
+            ends.push(data.len());
+        }
+        SerializedRows {
+            data: data.freeze(),
+            ends,
+        }
+    }
+
+    /// Splits off the rows from `at` onwards, like [`Vec::split_off`].
+    pub(crate) fn split_off(&mut self, at: usize) -> SerializedRows {
+        let offset = self.row_start(at.min(self.len()));
+        let ends = self
+            .ends
+            .split_off(at.min(self.len()))
+            .into_iter()
+            .map(|end| end - offset)
+            .collect();
+        SerializedRows {
+            data: self.data.split_off(offset.min(self.data.len())),
+            ends,
+        }
+    }
+}
+
+/// Serializes rows into `buffer` as newline-delimited JSON.
+///
+/// The buffer is cleared first and the returned rows share its allocation, so
+/// once they are dropped the next call reuses the same memory instead of
+/// allocating per row or per batch.
+pub(crate) fn serialize_rows<R: Serialize>(
+    buffer: &mut BytesMut,
+    rows: impl IntoIterator<Item = R>,
+) -> Result<SerializedRows, Error> {
+    buffer.clear();
+    let mut ends = Vec::new();
+    let mut writer = (&mut *buffer).writer();
+    for row in rows {
+        if !ends.is_empty() {
+            writer.get_mut().put_u8(b'\n');
+        }
+        serde_json::to_writer(&mut writer, &row)?;
+        ends.push(writer.get_ref().len());
+    }
+    Ok(SerializedRows {
+        data: buffer.split().freeze(),
+        ends,
+    })
+}
+
+/// Serializes rows like [`serialize_rows`], splitting them into one
+/// contiguous part per thread of `pool` (rayon's global pool when `None`).
+/// Parts are serialized in parallel and then copied into `buffer` in order.
+#[cfg(feature = "parallel")]
+fn serialize_rows_parallel<R: Serialize + Sync>(
+    buffer: &mut BytesMut,
+    rows: &[R],
+    pool: Option<&rayon::ThreadPool>,
+) -> Result<SerializedRows, Error> {
+    use rayon::prelude::*;
+
+    let threads = pool.map_or_else(rayon::current_num_threads, |pool| {
+        pool.current_num_threads()
+    });
+    let per_part = rows.len().div_ceil(threads).max(1);
+    let serialize = || {
+        rows.par_chunks(per_part)
+            .map(|part| {
+                let mut data = Vec::new();
+                let mut ends = Vec::with_capacity(part.len());
+                for row in part {
+                    if !ends.is_empty() {
+                        data.push(b'\n');
+                    }
+                    serde_json::to_writer(&mut data, row)?;
+                    ends.push(data.len());
+                }
+                Ok((data, ends))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+    };
+    let parts = match pool {
+        Some(pool) => pool.install(serialize),
+        None => serialize(),
+    }?;
+
+    buffer.clear();
+    buffer.reserve(parts.iter().map(|(data, _)| data.len() + 1).sum());
+    let mut ends = Vec::with_capacity(rows.len());
+    for (data, part_ends) in parts {
+        if !ends.is_empty() {
+            buffer.put_u8(b'\n');
+        }
+        let offset = buffer.len();
+        buffer.extend_from_slice(&data);
+        ends.extend(part_ends.into_iter().map(|end| offset + end));
+    }
+    Ok(SerializedRows {
+        data: buffer.split().freeze(),
+        ends,
+    })
+}
+
 // (Unit tests live in integration to avoid constructing private client internals.)
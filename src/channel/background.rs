@@ -0,0 +1,227 @@
+//! Write-behind appends backing [`StreamingIngestChannel::append_row_async`].
+//!
+//! [`StreamingIngestChannel::append_row_async`]: super::StreamingIngestChannel::append_row_async
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use serde::Serialize;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{Instrument, Span};
+
+use super::{AppendBody, MAX_REQUEST_SIZE, Pacer, PushedAppend, send_append};
+use crate::{Error, StreamingIngestClient};
+
+/// Completion handle for a row queued with
+/// [`append_row_async`](super::StreamingIngestChannel::append_row_async).
+///
+/// Awaiting it yields the offset token of the request that carried the row.
+/// Dropping it does not cancel the append.
+#[must_use = "dropping the handle does not cancel the append, but discards its outcome"]
+pub struct AppendHandle {
+    rx: oneshot::Receiver<Result<u64, Error>>,
+}
+
+impl AppendHandle {
+    pub(super) fn ready(result: Result<u64, Error>) -> Self {
+        let (tx, rx) = oneshot::channel();
+        let _ = tx.send(result);
+        Self { rx }
+    }
+}
+
+impl Future for AppendHandle {
+    type Output = Result<u64, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx).poll(cx).map(|result| {
+            result.unwrap_or_else(|_| {
+                Err(Error::AppendFailed(
+                    "background appender stopped before the row was sent".into(),
+                ))
+            })
+        })
+    }
+}
+
+struct Job {
+    row: Bytes,
+    done: oneshot::Sender<Result<u64, Error>>,
+}
+
+/// What the worker pushed, in order, and the first error it hit.
+#[derive(Default)]
+pub(super) struct Outcome {
+    pub(super) pushed: Vec<PushedAppend>,
+    pub(super) error: Option<Error>,
+    /// Rows in the failed request, when Snowflake rejected them.
+    pub(super) rejected_rows: usize,
+}
+
+/// Background task that batches queued rows and sends them in order,
+/// continuing the channel's offset and continuation-token sequence.
+pub(super) struct Worker {
+    tx: mpsc::UnboundedSender<Job>,
+    task: JoinHandle<Outcome>,
+}
+
+impl Worker {
+    pub(super) fn spawn<R>(
+        span: Span,
+        client: StreamingIngestClient<R>,
+        channel_name: String,
+        continuation_token: String,
+        next_offset: u64,
+        limits: Limits,
+    ) -> Self
+    where
+        R: Serialize + Clone + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(
+            run(
+                client,
+                channel_name,
+                continuation_token,
+                next_offset,
+                limits,
+                rx,
+            )
+            .instrument(span),
+        );
+        Self { tx, task }
+    }
+
+    pub(super) fn submit(&self, row: Bytes) -> AppendHandle {
+        let (done, rx) = oneshot::channel();
+        if let Err(mpsc::error::SendError(job)) = self.tx.send(Job { row, done }) {
+            let _ = job.done.send(Err(Error::AppendFailed(
+                "background appender is no longer running".into(),
+            )));
+        }
+        AppendHandle { rx }
+    }
+
+    /// Stops accepting rows, waits for queued rows to be sent, and returns
+    /// what was pushed.
+    pub(super) async fn finish(self) -> Outcome {
+        drop(self.tx);
+        self.task.await.unwrap_or_else(|e| Outcome {
+            pushed: Vec::new(),
+            error: Some(Error::AppendFailed(format!(
+                "background appender panicked: {e}"
+            ))),
+            rejected_rows: 0,
+        })
+    }
+}
+
+/// The channel's per-request row and size caps, batching delay, and request
+/// pacing.
+pub(super) struct Limits {
+    pub(super) max_rows: Option<usize>,
+    pub(super) max_bytes: usize,
+    pub(super) flush_interval: Option<Duration>,
+    pub(super) pacer: Pacer,
+}
+
+async fn run<R: Serialize + Clone>(
+    client: StreamingIngestClient<R>,
+    channel_name: String,
+    mut continuation_token: String,
+    mut offset: u64,
+    limits: Limits,
+    mut rx: mpsc::UnboundedReceiver<Job>,
+) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut carry: Option<Job> = None;
+    loop {
+        let first = match carry.take() {
+            Some(job) => job,
+            None => match rx.recv().await {
+                Some(job) => job,
+                None => break,
+            },
+        };
+        if outcome.error.is_some() {
+            let _ = first.done.send(Err(Error::AppendFailed(
+                "not sent: an earlier background append on this channel failed".into(),
+            )));
+            continue;
+        }
+
+        // Take whatever else is already queued, or arrives within the flush
+        // interval, up to the request size and row limits.
+        let max_rows = limits.max_rows.unwrap_or(usize::MAX);
+        let deadline = limits
+            .flush_interval
+            .map(|interval| Instant::now() + interval);
+        let mut size = first.row.len();
+        let mut batch = vec![first];
+        while batch.len() < max_rows {
+            let job = match (rx.try_recv(), deadline) {
+                (Ok(job), _) => job,
+                (Err(TryRecvError::Empty), Some(deadline)) => {
+                    match tokio::time::timeout_at(deadline, rx.recv()).await {
+                        Ok(Some(job)) => job,
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+                (Err(_), _) => break,
+            };
+            if size + 1 + job.row.len() > limits.max_bytes.min(MAX_REQUEST_SIZE) {
+                carry = Some(job);
+                break;
+            }
+            size += 1 + job.row.len();
+            batch.push(job);
+        }
+
+        let (rows, senders): (Vec<Bytes>, Vec<_>) =
+            batch.into_iter().map(|job| (job.row, job.done)).unzip();
+        let count = rows.len();
+        let body = AppendBody::Rows {
+            rows: Arc::from(rows),
+            range: 0..count,
+        };
+        limits.pacer.wait().await;
+        match send_append(
+            &client,
+            &channel_name,
+            &continuation_token,
+            offset,
+            body,
+            count,
+        )
+        .await
+        {
+            Ok(pushed) => {
+                continuation_token = pushed.continuation_token.clone();
+                for done in senders {
+                    let _ = done.send(Ok(pushed.offset));
+                }
+                offset += 1;
+                outcome.pushed.push(pushed);
+            }
+            Err(err) => {
+                for done in senders {
+                    let _ = done.send(Err(Error::AppendFailed(format!(
+                        "background append failed: {err}"
+                    ))));
+                }
+                if err.is_rejection() {
+                    outcome.rejected_rows = count;
+                }
+                outcome.error = Some(err);
+            }
+        }
+    }
+    outcome
+}
@@ -0,0 +1,208 @@
+//! Offline stand-in for [`StreamingIngestChannel`](super::StreamingIngestChannel).
+
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use serde::Serialize;
+use tokio::time::Instant;
+
+use super::{
+    AppendBody, ChannelReport, MAX_REQUEST_SIZE, SerializedRows, check_request_size, chunk_rows,
+    serialize_rows,
+};
+use crate::Error;
+
+/// One request body a [`DryRunChannel`] would have sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunRequest {
+    /// Offset token the request would have carried.
+    pub offset: u64,
+    /// Rows in the body.
+    pub rows: usize,
+    /// Newline-delimited JSON body, exactly as it would be sent.
+    pub body: Bytes,
+}
+
+enum Sink {
+    Memory(Vec<DryRunRequest>),
+    Writer(Box<dyn Write + Send>),
+}
+
+/// A channel that never contacts Snowflake.
+///
+/// Rows go through the same serialization, 16MB request chunking, and size
+/// checks as [`StreamingIngestChannel`](super::StreamingIngestChannel), but
+/// each request body is kept in memory or written to a file instead of being
+/// sent. Use it to test pipelines in CI or to estimate request counts and
+/// volume before ingesting for real.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), snowpipe_streaming::Error> {
+/// use snowpipe_streaming::DryRunChannel;
+///
+/// let mut channel = DryRunChannel::new("ch");
+/// channel.append_row(&serde_json::json!({"id": 1})).await?;
+/// channel
+///     .append_rows_iter((2..=10).map(|id| serde_json::json!({"id": id})))
+///     .await?;
+/// assert_eq!(channel.requests().len(), 2);
+///
+/// let report = channel.close().await?;
+/// assert_eq!(report.rows, 10);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DryRunChannel<R> {
+    _marker: std::marker::PhantomData<fn(&R)>,
+    channel_name: String,
+    sink: Sink,
+    last_offset: u64,
+    rows: u64,
+    bytes: u64,
+    requests: u64,
+    max_rows_per_request: Option<usize>,
+    opened_at: Instant,
+    buffer: BytesMut,
+}
+
+impl<R: Serialize> DryRunChannel<R> {
+    /// Keeps request bodies in memory; inspect them with
+    /// [`requests`](Self::requests).
+    pub fn new(channel_name: &str) -> Self {
+        Self::with_sink(channel_name, Sink::Memory(Vec::new()))
+    }
+
+    /// Writes each request body to `writer`, followed by a newline, so the
+    /// output is the newline-delimited JSON of every appended row.
+    pub fn with_writer(channel_name: &str, writer: impl Write + Send + 'static) -> Self {
+        Self::with_sink(channel_name, Sink::Writer(Box::new(writer)))
+    }
+
+    /// Like [`with_writer`](Self::with_writer), creating (or truncating) the
+    /// file at `path`.
+    pub fn to_file(channel_name: &str, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::with_writer(
+            channel_name,
+            std::io::BufWriter::new(file),
+        ))
+    }
+
+    fn with_sink(channel_name: &str, sink: Sink) -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+            channel_name: channel_name.to_string(),
+            sink,
+            last_offset: 0,
+            rows: 0,
+            bytes: 0,
+            requests: 0,
+            max_rows_per_request: None,
+            opened_at: Instant::now(),
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Caps the rows per request, like
+    /// [`StreamingIngestChannel::set_max_rows_per_request`](super::StreamingIngestChannel::set_max_rows_per_request).
+    pub fn set_max_rows_per_request(&mut self, max_rows: Option<NonZeroUsize>) {
+        self.max_rows_per_request = max_rows.map(NonZeroUsize::get);
+    }
+
+    /// Pre-allocates the serialization buffer, like
+    /// [`StreamingIngestChannel::reserve_batch_capacity`](super::StreamingIngestChannel::reserve_batch_capacity).
+    pub fn reserve_batch_capacity(&mut self, bytes: usize) {
+        self.buffer.reserve(bytes);
+    }
+
+    /// The channel name given at construction.
+    pub fn channel_name(&self) -> &str {
+        &self.channel_name
+    }
+
+    /// Request bodies recorded so far; empty when writing to a file or writer.
+    pub fn requests(&self) -> &[DryRunRequest] {
+        match &self.sink {
+            Sink::Memory(requests) => requests,
+            Sink::Writer(_) => &[],
+        }
+    }
+
+    /// Statistics so far, as [`close`](Self::close) would report them.
+    pub fn report(&self) -> ChannelReport {
+        ChannelReport {
+            rows: self.rows,
+            bytes: self.bytes,
+            requests: self.requests,
+            retries: 0,
+            duration: self.opened_at.elapsed(),
+            avg_latency: Duration::ZERO,
+            commit_wait: Duration::ZERO,
+            server_latency: None,
+        }
+    }
+
+    /// Records `row` as its own request, like
+    /// [`StreamingIngestChannel::append_row`](super::StreamingIngestChannel::append_row).
+    pub async fn append_row(&mut self, row: &R) -> Result<(), Error> {
+        let data = serde_json::to_vec(row)?;
+        self.record(AppendBody::Whole(Bytes::from(data)), 1)
+    }
+
+    /// Splits rows into requests of at most 16MB and records them. Returns the
+    /// number of body bytes that would have been sent.
+    pub async fn append_rows(&mut self, rows: &mut dyn Iterator<Item = R>) -> Result<usize, Error> {
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.append_serialized_rows(serialized_rows)
+    }
+
+    /// Like [`append_rows`](Self::append_rows), taking any `IntoIterator`.
+    pub async fn append_rows_iter<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        self.append_serialized_rows(serialized_rows)
+    }
+
+    /// Flushes the sink and returns the channel's statistics. Latency, retry,
+    /// and commit-wait figures are always zero.
+    pub async fn close(mut self) -> Result<ChannelReport, Error> {
+        if let Sink::Writer(writer) = &mut self.sink {
+            writer.flush()?;
+        }
+        Ok(self.report())
+    }
+
+    fn append_serialized_rows(&mut self, serialized_rows: SerializedRows) -> Result<usize, Error> {
+        let mut bytes_written = 0;
+        for (body, rows) in chunk_rows(serialized_rows, self.max_rows_per_request) {
+            bytes_written += body.len();
+            self.record(body, rows)?;
+        }
+        Ok(bytes_written)
+    }
+
+    fn record(&mut self, body: AppendBody, rows: usize) -> Result<(), Error> {
+        let len = body.len();
+        check_request_size(len, MAX_REQUEST_SIZE)?;
+        let offset = self.last_offset + 1;
+        let body = body.to_bytes();
+        match &mut self.sink {
+            Sink::Memory(requests) => requests.push(DryRunRequest { offset, rows, body }),
+            Sink::Writer(writer) => {
+                writer.write_all(&body)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        self.last_offset = offset;
+        self.rows += rows as u64;
+        self.bytes += len as u64;
+        self.requests += 1;
+        Ok(())
+    }
+}
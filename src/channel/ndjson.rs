@@ -0,0 +1,112 @@
+//! Forwarding newline-delimited JSON from an async reader, for relays that
+//! pass records through from files or sockets without a row type.
+
+use bytes::{BufMut, BytesMut};
+use serde::Serialize;
+use serde::de::IgnoredAny;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use super::{SerializedRows, StreamingIngestChannel};
+use crate::Error;
+
+impl<R: Serialize + Clone> StreamingIngestChannel<R> {
+    /// Appends the newline-delimited JSON read from `reader` (a file, socket,
+    /// or decompressor) until it ends, forwarding each line as it was
+    /// written instead of deserializing it into `R`. Lines are sent as they
+    /// are read, in chunks of up to
+    /// [`OpenChannelOptions::max_batch_bytes`](crate::OpenChannelOptions::max_batch_bytes)
+    /// (16MB by default), so the input is never held in memory whole.
+    /// Returns the body bytes sent.
+    ///
+    /// Each line must be a single JSON object; blank lines and surrounding
+    /// whitespace, including `\r\n` endings, are dropped. A line that is not
+    /// fails with [`Error::InvalidRow`] naming its 1-based line number,
+    /// after the lines before it have been appended, so the caller can skip
+    /// it and continue from the next line. A line over the request size
+    /// limit on its own fails with [`Error::DataTooLarge`] the same way.
+    ///
+    /// ```no_run
+    /// # async fn run(
+    /// #     channel: &mut snowpipe_streaming::StreamingIngestChannel<serde_json::Value>,
+    /// # ) -> Result<(), Box<dyn std::error::Error>> {
+    /// let file = tokio::fs::File::open("/var/spool/events.ndjson").await?;
+    /// let bytes = channel.append_ndjson_reader(file).await?;
+    /// # let _ = bytes;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn append_ndjson_reader(
+        &mut self,
+        reader: impl AsyncRead + Unpin,
+    ) -> Result<usize, Error> {
+        let max_row = self.client.max_request_bytes();
+        let chunk_bytes = self.max_batch_bytes.min(max_row);
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        let mut line_number = 0;
+        let mut data = BytesMut::new();
+        let mut ends = Vec::new();
+        let mut sent = 0;
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).await?;
+            if read == 0 {
+                break;
+            }
+            line_number += 1;
+            let row = line.trim_ascii();
+            if row.is_empty() {
+                continue;
+            }
+            let invalid = match check_line(row) {
+                Ok(()) if row.len() + self.batch_column_bytes() > max_row => Some(
+                    Error::DataTooLarge(row.len() + self.batch_column_bytes(), max_row),
+                ),
+                Ok(()) => None,
+                Err(problem) => Some(Error::InvalidRow(format!("line {line_number} {problem}"))),
+            };
+            if let Some(err) = invalid {
+                self.send_ndjson_chunk(&mut data, &mut ends).await?;
+                return Err(err);
+            }
+            if !ends.is_empty() && data.len() + 1 + row.len() > chunk_bytes {
+                sent += self.send_ndjson_chunk(&mut data, &mut ends).await?;
+            }
+            if !ends.is_empty() {
+                data.put_u8(b'\n');
+            }
+            data.extend_from_slice(row);
+            ends.push(data.len());
+        }
+        sent += self.send_ndjson_chunk(&mut data, &mut ends).await?;
+        Ok(sent)
+    }
+
+    /// Appends the rows buffered in `data`, leaving it empty.
+    async fn send_ndjson_chunk(
+        &mut self,
+        data: &mut BytesMut,
+        ends: &mut Vec<usize>,
+    ) -> Result<usize, Error> {
+        if ends.is_empty() {
+            return Ok(0);
+        }
+        let rows = SerializedRows {
+            data: data.split().freeze(),
+            ends: std::mem::take(ends),
+        };
+        self.append_serialized_rows(rows).await
+    }
+}
+
+/// Checks that `line` holds exactly one JSON object, describing what is
+/// wrong otherwise.
+fn check_line(line: &[u8]) -> Result<(), String> {
+    if let Err(e) = serde_json::from_slice::<IgnoredAny>(line) {
+        return Err(format!("is not valid JSON: {e}"));
+    }
+    match line.first() {
+        Some(b'{') => Ok(()),
+        _ => Err("is not a JSON object".into()),
+    }
+}
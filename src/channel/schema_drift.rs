@@ -0,0 +1,142 @@
+//! Detects rows whose top-level fields differ from the rows a channel last
+//! appended successfully.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::de::IgnoredAny;
+
+/// Fields that appeared or disappeared relative to the rows last appended
+/// successfully, reported by
+/// [`StreamingIngestChannel::set_schema_drift_alert`](super::StreamingIngestChannel::set_schema_drift_alert).
+///
+/// A renamed field shows up in both lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDrift {
+    /// Fields in the incoming row that earlier rows did not have, sorted.
+    pub added: Vec<String>,
+    /// Fields earlier rows had that the incoming row lacks, sorted.
+    pub removed: Vec<String>,
+}
+
+pub(super) type SchemaDriftCallback = Box<dyn Fn(&str, &SchemaDrift) + Send + Sync>;
+
+/// Field sets seen on a channel and the callback to notify.
+pub(super) struct DriftWatch {
+    pub(super) callback: SchemaDriftCallback,
+    /// Fields of the rows last appended successfully.
+    known: Option<BTreeSet<String>>,
+    /// Fields of the rows being appended, adopted once they succeed.
+    pending: Option<BTreeSet<String>>,
+    /// Field set last reported, so a drift is reported once.
+    reported: Option<BTreeSet<String>>,
+}
+
+impl DriftWatch {
+    pub(super) fn new(callback: SchemaDriftCallback) -> Self {
+        Self {
+            callback,
+            known: None,
+            pending: None,
+            reported: None,
+        }
+    }
+
+    /// Compares the fields of `row`, a serialized JSON object, against the
+    /// known set. Returns the drift when the row adds fields and this field
+    /// set has not been reported yet. Rows that are not objects are ignored.
+    pub(super) fn observe(&mut self, row: &[u8]) -> Option<SchemaDrift> {
+        let fields = serde_json::from_slice::<BTreeMap<String, IgnoredAny>>(row)
+            .ok()?
+            .into_keys()
+            .collect::<BTreeSet<_>>();
+        let known = self.known.as_ref().or(self.pending.as_ref());
+        let drift = known.map(|known| SchemaDrift {
+            added: fields.difference(known).cloned().collect(),
+            removed: known.difference(&fields).cloned().collect(),
+        });
+        let report = drift
+            .filter(|drift| !drift.added.is_empty() && self.reported.as_ref() != Some(&fields));
+        if report.is_some() {
+            self.reported = Some(fields.clone());
+        }
+        self.pending = Some(fields);
+        report
+    }
+
+    /// Top-level fields of `row` that the rows last appended successfully
+    /// lacked, sorted; empty before the first append or when `row` is not a
+    /// JSON object.
+    pub(super) fn unknown_fields(&self, row: &[u8]) -> Vec<String> {
+        let (Some(known), Ok(fields)) = (
+            self.known.as_ref(),
+            serde_json::from_slice::<BTreeMap<String, IgnoredAny>>(row),
+        ) else {
+            return Vec::new();
+        };
+        fields
+            .into_keys()
+            .filter(|field| !known.contains(field))
+            .collect()
+    }
+
+    /// Adopts the fields of the rows just appended as the known set.
+    pub(super) fn commit(&mut self) {
+        if let Some(fields) = self.pending.take() {
+            self.known = Some(fields);
+        }
+    }
+
+    /// Forgets the fields of rows that failed to append.
+    pub(super) fn discard(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch() -> DriftWatch {
+        DriftWatch::new(Box::new(|_, _| {}))
+    }
+
+    #[test]
+    fn first_row_sets_the_baseline() {
+        let mut watch = watch();
+        assert_eq!(watch.observe(br#"{"id":1,"name":"a"}"#), None);
+        watch.commit();
+        assert_eq!(watch.observe(br#"{"name":"b","id":2}"#), None);
+        assert_eq!(watch.observe(b"42"), None);
+    }
+
+    #[test]
+    fn renamed_field_is_reported_once() {
+        let mut watch = watch();
+        watch.observe(br#"{"id":1,"name":"a"}"#);
+        watch.commit();
+        let drift = watch.observe(br#"{"id":2,"full_name":"b"}"#).unwrap();
+        assert_eq!(drift.added, ["full_name"]);
+        assert_eq!(drift.removed, ["name"]);
+        assert_eq!(watch.observe(br#"{"id":3,"full_name":"c"}"#), None);
+    }
+
+    #[test]
+    fn dropped_fields_alone_are_not_drift() {
+        let mut watch = watch();
+        watch.observe(br#"{"id":1,"note":"a"}"#);
+        watch.commit();
+        assert_eq!(watch.observe(br#"{"id":2}"#), None);
+    }
+
+    #[test]
+    fn failed_rows_do_not_move_the_baseline() {
+        let mut watch = watch();
+        watch.observe(br#"{"id":1}"#);
+        watch.commit();
+        assert!(watch.observe(br#"{"id":2,"extra":true}"#).is_some());
+        watch.discard();
+        watch.commit();
+        let drift = watch.observe(br#"{"id":3,"other":true}"#).unwrap();
+        assert_eq!(drift.added, ["other"]);
+    }
+}
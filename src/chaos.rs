@@ -0,0 +1,335 @@
+//! Deterministic fault injection for exercising recovery logic.
+//!
+//! Enabled with the `chaos` feature. Attach a [`ChaosConfig`] to a client with
+//! [`StreamingIngestClient::with_chaos`] and every attempt of an append
+//! request, from the client or channels opened afterwards, rolls against the
+//! configured rates before it is sent. [`ChaosConfig::operations`] selects
+//! other requests instead, such as opening channels, status polls, or token
+//! exchanges. Rolls come from a seeded generator shared by the client and its
+//! channels, so a given seed and call order always produces the same faults.
+//!
+//! ```
+//! use std::time::Duration;
+//! use snowpipe_streaming::OperationKind;
+//! use snowpipe_streaming::chaos::ChaosConfig;
+//!
+//! let chaos = ChaosConfig::new(42)
+//!     .fail_appends(0.1)
+//!     .delay_appends(0.25, Duration::from_millis(200))
+//!     .drop_connections(0.05)
+//!     .operations([OperationKind::AppendRows, OperationKind::ChannelStatus]);
+//! # let _ = chaos;
+//! ```
+//!
+//! For auth failures, [`ScriptedTokenProvider`] is a [`ScopedTokenProvider`]
+//! that plays back a fixed sequence of tokens, failures, and delays, so tests
+//! of token broker handling are deterministic.
+//!
+//! [`StreamingIngestClient::with_chaos`]: crate::StreamingIngestClient::with_chaos
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use tracing::warn;
+
+use crate::{Error, OperationKind, ScopedTokenFuture, ScopedTokenProvider, StreamingIngestClient};
+
+/// Fault rates applied to append requests, or to the requests selected with
+/// [`operations`](Self::operations). Rates are probabilities in `0.0..=1.0`;
+/// values outside that range are clamped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    seed: u64,
+    append_failure_rate: f64,
+    append_failure_status: StatusCode,
+    delay_rate: f64,
+    delay: Duration,
+    connection_drop_rate: f64,
+    operations: Vec<OperationKind>,
+}
+
+impl ChaosConfig {
+    /// Creates a configuration that injects nothing, seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            append_failure_rate: 0.0,
+            append_failure_status: StatusCode::SERVICE_UNAVAILABLE,
+            delay_rate: 0.0,
+            delay: Duration::ZERO,
+            connection_drop_rate: 0.0,
+            operations: vec![OperationKind::AppendRows],
+        }
+    }
+
+    /// Fails `rate` of appends with `503 Service Unavailable`
+    /// (surfaced as [`Error::Http`]) without sending them.
+    pub fn fail_appends(mut self, rate: f64) -> Self {
+        self.append_failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Uses `status` instead of `503` for injected append failures.
+    pub fn failure_status(mut self, status: StatusCode) -> Self {
+        self.append_failure_status = status;
+        self
+    }
+
+    /// Delays `rate` of appends by `delay` before they are sent.
+    pub fn delay_appends(mut self, rate: f64, delay: Duration) -> Self {
+        self.delay_rate = rate.clamp(0.0, 1.0);
+        self.delay = delay;
+        self
+    }
+
+    /// Fails `rate` of appends as if the connection was reset
+    /// (surfaced as [`Error::Io`] with [`std::io::ErrorKind::ConnectionReset`]).
+    pub fn drop_connections(mut self, rate: f64) -> Self {
+        self.connection_drop_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Applies the faults to requests of `operations` instead of only
+    /// [`OperationKind::AppendRows`], e.g. to fail opening channels or
+    /// refreshing the scoped token. Requests the client makes while it is
+    /// built, before [`StreamingIngestClient::with_chaos`], are not affected.
+    pub fn operations(mut self, operations: impl IntoIterator<Item = OperationKind>) -> Self {
+        self.operations = operations.into_iter().collect();
+        self
+    }
+}
+
+/// Runtime state for a [`ChaosConfig`]: the configuration plus the shared
+/// generator position.
+#[derive(Debug)]
+pub(crate) struct FaultInjector {
+    config: ChaosConfig,
+    state: AtomicU64,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(config: ChaosConfig) -> Self {
+        // xorshift state must be non-zero.
+        let seed = match config.seed {
+            0 => 0x9E37_79B9_7F4A_7C15,
+            seed => seed,
+        };
+        let state = AtomicU64::new(seed);
+        Self { config, state }
+    }
+
+    /// Next value in `[0, 1)` from a xorshift64* sequence.
+    fn roll(&self) -> f64 {
+        let step = |mut x: u64| {
+            x ^= x >> 12;
+            x ^= x << 25;
+            x ^= x >> 27;
+            x
+        };
+        let prev = self
+            .state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x)))
+            .expect("update closure always returns Some");
+        let value = step(prev).wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Applies the configured faults to one attempt of an `operation`
+    /// request, if that operation is selected. Each fault rolls
+    /// independently, in the order: delay, connection drop, failure.
+    pub(crate) async fn before_request(&self, operation: OperationKind) -> Result<(), Error> {
+        let cfg = &self.config;
+        if !cfg.operations.contains(&operation) {
+            return Ok(());
+        }
+        if self.roll() < cfg.delay_rate {
+            warn!("chaos: delaying {} request by {:?}", operation, cfg.delay);
+            tokio::time::sleep(cfg.delay).await;
+        }
+        if self.roll() < cfg.connection_drop_rate {
+            warn!("chaos: dropping connection for {} request", operation);
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "chaos: injected connection drop",
+            )));
+        }
+        if self.roll() < cfg.append_failure_rate {
+            warn!("chaos: failing {} request", operation);
+            return Err(Error::Http(
+                cfg.append_failure_status,
+                format!("chaos: injected {operation} failure"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R> StreamingIngestClient<R> {
+    /// Injects faults described by `config` into requests made by the
+    /// returned client and by channels opened from it. Channels already open
+    /// are unaffected.
+    pub fn with_chaos(mut self, config: ChaosConfig) -> Self {
+        self.chaos = Some(std::sync::Arc::new(FaultInjector::new(config)));
+        self
+    }
+}
+
+/// A [`ScopedTokenProvider`] that answers calls from a script, e.g. two
+/// failures and then a token, for testing how an application handles a
+/// failing or slow token broker.
+///
+/// Each call consumes the next step; the last step repeats once the script
+/// runs out. Clones share the script and call count, so keep one to inspect
+/// after handing another to
+/// [`StreamingIngestClient::new_with_token_provider`].
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use snowpipe_streaming::chaos::ScriptedTokenProvider;
+///
+/// let provider = ScriptedTokenProvider::failing_then(2, "token")
+///     .slow(Duration::from_millis(50));
+/// let for_client: Arc<dyn snowpipe_streaming::ScopedTokenProvider> =
+///     Arc::new(provider.clone());
+/// # let _ = for_client;
+/// assert_eq!(provider.calls(), 0);
+/// ```
+#[derive(Clone, Default)]
+pub struct ScriptedTokenProvider {
+    script: Arc<Mutex<TokenScript>>,
+}
+
+#[derive(Default)]
+struct TokenScript {
+    steps: VecDeque<TokenStep>,
+    delay: Duration,
+    calls: usize,
+}
+
+#[derive(Clone)]
+enum TokenStep {
+    Token(String),
+    Fail(String),
+}
+
+impl ScriptedTokenProvider {
+    /// Creates an empty script; calls fail with [`Error::Auth`] until steps
+    /// are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails the first `failures` calls with [`Error::Auth`], then returns
+    /// `token` from every later call.
+    pub fn failing_then(failures: usize, token: impl Into<String>) -> Self {
+        Self::new()
+            .then_fail_times(failures, "scripted token failure")
+            .then_token(token)
+    }
+
+    /// Appends a call that returns `token`.
+    pub fn then_token(self, token: impl Into<String>) -> Self {
+        self.push(TokenStep::Token(token.into()));
+        self
+    }
+
+    /// Appends a call that fails with [`Error::Auth`] carrying `message`.
+    pub fn then_fail(self, message: impl Into<String>) -> Self {
+        self.push(TokenStep::Fail(message.into()));
+        self
+    }
+
+    /// Appends `times` calls that fail like [`then_fail`](Self::then_fail).
+    pub fn then_fail_times(self, times: usize, message: impl Into<String>) -> Self {
+        let message = message.into();
+        for _ in 0..times {
+            self.push(TokenStep::Fail(message.clone()));
+        }
+        self
+    }
+
+    /// Waits `delay` before answering each call, to simulate a slow broker.
+    pub fn slow(self, delay: Duration) -> Self {
+        self.script.lock().unwrap().delay = delay;
+        self
+    }
+
+    /// Number of calls made so far.
+    pub fn calls(&self) -> usize {
+        self.script.lock().unwrap().calls
+    }
+
+    fn push(&self, step: TokenStep) {
+        self.script.lock().unwrap().steps.push_back(step);
+    }
+
+    fn next_step(&self) -> (Option<TokenStep>, Duration) {
+        let mut script = self.script.lock().unwrap();
+        script.calls += 1;
+        let step = match script.steps.len() {
+            0 | 1 => script.steps.front().cloned(),
+            _ => script.steps.pop_front(),
+        };
+        (step, script.delay)
+    }
+}
+
+impl ScopedTokenProvider for ScriptedTokenProvider {
+    fn scoped_token(&self) -> ScopedTokenFuture<'_> {
+        let (step, delay) = self.next_step();
+        Box::pin(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            match step {
+                Some(TokenStep::Token(token)) => Ok(token),
+                Some(TokenStep::Fail(message)) => Err(Error::Auth(message)),
+                None => Err(Error::Auth("token script is empty".into())),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_yields_same_rolls() {
+        let a = FaultInjector::new(ChaosConfig::new(7));
+        let b = FaultInjector::new(ChaosConfig::new(7));
+        let c = FaultInjector::new(ChaosConfig::new(8));
+        let seq = |f: &FaultInjector| (0..16).map(|_| f.roll()).collect::<Vec<_>>();
+        let (sa, sb, sc) = (seq(&a), seq(&b), seq(&c));
+        assert_eq!(sa, sb);
+        assert_ne!(sa, sc);
+        assert!(sa.iter().all(|v| (0.0..1.0).contains(v)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_script_plays_in_order_and_repeats_its_last_step() {
+        let provider = ScriptedTokenProvider::new()
+            .then_token("a")
+            .then_fail("down")
+            .then_token("b")
+            .slow(Duration::from_secs(3));
+        let start = tokio::time::Instant::now();
+        assert_eq!(provider.scoped_token().await.unwrap(), "a");
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+        assert!(matches!(provider.scoped_token().await, Err(Error::Auth(m)) if m == "down"));
+        assert_eq!(provider.scoped_token().await.unwrap(), "b");
+        assert_eq!(provider.clone().scoped_token().await.unwrap(), "b");
+        assert_eq!(provider.calls(), 4);
+    }
+
+    #[test]
+    fn rates_are_clamped() {
+        let cfg = ChaosConfig::new(1).fail_appends(1.5).drop_connections(-1.0);
+        assert_eq!(cfg.append_failure_rate, 1.0);
+        assert_eq!(cfg.connection_drop_rate, 0.0);
+    }
+}
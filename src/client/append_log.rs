@@ -0,0 +1,116 @@
+//! INFO logging of completed appends according to [`LogPolicy`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::LogPolicy;
+
+/// Shared by a client, its clones, and its channels.
+pub(crate) struct AppendLog {
+    policy: LogPolicy,
+    channels: Mutex<HashMap<String, Window>>,
+}
+
+/// Appends seen on one channel since its last log line.
+struct Window {
+    started: Instant,
+    requests: u64,
+    rows: usize,
+    bytes: usize,
+    last_offset: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: 0,
+            rows: 0,
+            bytes: 0,
+            last_offset: 0,
+        }
+    }
+
+    fn log(&self, channel: &str) {
+        info!(
+            "appends: channel='{}' requests={} rows={} bytes={} last_offset={} window={:?}",
+            channel,
+            self.requests,
+            self.rows,
+            self.bytes,
+            self.last_offset,
+            self.started.elapsed()
+        );
+    }
+}
+
+impl AppendLog {
+    pub(crate) fn new(policy: LogPolicy) -> Self {
+        Self {
+            policy,
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn channels(&self) -> std::sync::MutexGuard<'_, HashMap<String, Window>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Counts one accepted append request and logs it if the policy says so.
+    pub(crate) fn completed(
+        &self,
+        channel: &str,
+        batch_id: Uuid,
+        offset: u64,
+        rows: usize,
+        bytes: usize,
+        elapsed: Duration,
+    ) {
+        let log_one = || {
+            info!(
+                "append rows ok: channel='{}' batch_id={} pushed_offset={} rows={} bytes={} elapsed={:?}",
+                channel, batch_id, offset, rows, bytes, elapsed
+            )
+        };
+        match self.policy {
+            LogPolicy::Off => {}
+            LogPolicy::Every => log_one(),
+            LogPolicy::Sample(n) => {
+                let mut channels = self.channels();
+                let window = channels
+                    .entry(channel.to_string())
+                    .or_insert_with(Window::new);
+                if window.requests.is_multiple_of(n.max(1)) {
+                    log_one();
+                }
+                window.requests += 1;
+            }
+            LogPolicy::Aggregate(secs) => {
+                let mut channels = self.channels();
+                let window = channels
+                    .entry(channel.to_string())
+                    .or_insert_with(Window::new);
+                window.requests += 1;
+                window.rows += rows;
+                window.bytes += bytes;
+                window.last_offset = offset;
+                if window.started.elapsed() >= Duration::from_secs(secs) {
+                    window.log(channel);
+                    *window = Window::new();
+                }
+            }
+        }
+    }
+
+    /// Logs what is left of the channel's aggregation window and forgets it.
+    pub(crate) fn channel_closed(&self, channel: &str) {
+        let window = self.channels().remove(channel);
+        if let (LogPolicy::Aggregate(_), Some(window)) = (self.policy, window) {
+            window.log(channel);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Reading response bodies under `Config::max_response_bytes`.
+
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+
+use crate::{Error, StreamingIngestClient};
+
+/// Response body limit when `Config::max_response_bytes` is unset. Snowflake's
+/// answers are a few kilobytes; anything near this is a proxy's error page.
+pub(crate) const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+impl<R> StreamingIngestClient<R> {
+    /// Largest (decompressed) response body this client reads.
+    pub(crate) fn max_response_bytes(&self) -> usize {
+        self.auth_config
+            .max_response_bytes
+            .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES)
+    }
+
+    /// Reads the body chunk by chunk, failing with [`Error::ResponseTooLarge`]
+    /// as soon as it exceeds the limit rather than buffering all of it.
+    pub(crate) async fn read_body(&self, mut response: Response) -> Result<Vec<u8>, Error> {
+        let status = response.status();
+        let limit = self.max_response_bytes();
+        if response
+            .content_length()
+            .is_some_and(|len| len > limit as u64)
+        {
+            return Err(Error::ResponseTooLarge(status, limit));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| self.transport_error(e))?
+        {
+            if body.len() + chunk.len() > limit {
+                return Err(Error::ResponseTooLarge(status, limit));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// Reads the body as text, replacing invalid UTF-8.
+    pub(crate) async fn read_text(&self, response: Response) -> Result<String, Error> {
+        let body = self.read_body(response).await?;
+        Ok(String::from_utf8(body)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+    }
+
+    /// Reads and parses a JSON body; a malformed one fails with
+    /// [`Error::Json`] in an [`Error::Context`] naming the URL.
+    pub(crate) async fn read_json<T: DeserializeOwned>(
+        &self,
+        response: Response,
+    ) -> Result<T, Error> {
+        let url = response.url().to_string();
+        let body = self.read_body(response).await?;
+        serde_json::from_slice(&body).map_err(|e| Error::Json(e).at_url(&url))
+    }
+
+    /// Reads a failed response's body for its error message. A transport
+    /// failure yields an empty message; an oversized body is still an error.
+    pub(crate) async fn read_error_text(&self, response: Response) -> Result<String, Error> {
+        match self.read_text(response).await {
+            Err(err @ Error::ResponseTooLarge(..)) => Err(err),
+            other => Ok(other.unwrap_or_default()),
+        }
+    }
+}
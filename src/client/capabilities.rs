@@ -0,0 +1,221 @@
+//! What the Snowpipe Streaming deployment behind a client supports.
+//!
+//! Deployments report their REST API version and optional features in
+//! `X-Snowflake-Streaming-Api-Version` (e.g. `2.1`) and
+//! `X-Snowflake-Streaming-Capabilities` (e.g. `tokens-in-body, gzip,
+//! max-request-bytes=67108864`). The first response carrying a version,
+//! usually host discovery, fixes them for the client's lifetime; until then,
+//! and against deployments that send neither header, the client assumes the
+//! baseline v2 API.
+
+use std::sync::OnceLock;
+
+use crate::telemetry::ResponseHeaders;
+
+const API_VERSION_HEADER: &str = "x-snowflake-streaming-api-version";
+const CAPABILITIES_HEADER: &str = "x-snowflake-streaming-capabilities";
+const BASELINE_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+/// REST API version reported by a Snowflake deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerApiVersion {
+    /// Major version; `2` for the current API.
+    pub major: u32,
+    /// Minor version, `0` when the deployment reports only a major version.
+    pub minor: u32,
+}
+
+impl std::str::FromStr for ServerApiVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().trim_start_matches(['v', 'V']);
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+        Ok(Self {
+            major: major.parse().map_err(|_| ())?,
+            minor: minor.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+impl std::fmt::Display for ServerApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Optional features of the deployment a client talks to; see
+/// [`StreamingIngestClient::capabilities`](crate::StreamingIngestClient::capabilities).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    api_version: Option<ServerApiVersion>,
+    tokens_in_body: bool,
+    gzip: bool,
+    max_request_bytes: usize,
+}
+
+impl Default for Capabilities {
+    /// The baseline v2 API: tokens in the query string, uncompressed bodies,
+    /// and 16MB requests.
+    fn default() -> Self {
+        Self {
+            api_version: None,
+            tokens_in_body: false,
+            gzip: false,
+            max_request_bytes: BASELINE_MAX_REQUEST_BYTES,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Reads the version and capability headers; `None` when the response
+    /// carries no (valid) version.
+    fn from_headers(headers: &ResponseHeaders) -> Option<Self> {
+        let api_version = headers.get(API_VERSION_HEADER)?.parse().ok()?;
+        let mut capabilities = Self {
+            api_version: Some(api_version),
+            ..Self::default()
+        };
+        for flag in headers
+            .get(CAPABILITIES_HEADER)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+        {
+            match flag.split_once('=') {
+                Some(("max-request-bytes", bytes)) => {
+                    if let Ok(bytes) = bytes.trim().parse() {
+                        capabilities.max_request_bytes = bytes;
+                    }
+                }
+                None if flag == "tokens-in-body" => capabilities.tokens_in_body = true,
+                None if flag == "gzip" => capabilities.gzip = true,
+                _ => {}
+            }
+        }
+        Some(capabilities)
+    }
+
+    /// Version the deployment reported, if any.
+    pub fn api_version(&self) -> Option<ServerApiVersion> {
+        self.api_version
+    }
+
+    /// Whether append requests may carry their tokens in the body
+    /// ([`ApiVersion::Preview`](crate::ApiVersion::Preview)).
+    pub fn tokens_in_body(&self) -> bool {
+        self.tokens_in_body
+    }
+
+    /// Whether the deployment accepts gzip-compressed request bodies.
+    pub fn gzip(&self) -> bool {
+        self.gzip
+    }
+
+    /// Largest append request body the deployment accepts; 16MB unless it
+    /// reports otherwise.
+    pub fn max_request_bytes(&self) -> usize {
+        self.max_request_bytes
+    }
+}
+
+/// Capabilities of the client's deployment, set from the first response that
+/// reports a version. Shared by clones and channels.
+#[derive(Default)]
+pub(crate) struct CapabilityProbe {
+    reported: OnceLock<Capabilities>,
+}
+
+impl CapabilityProbe {
+    /// Records the capabilities in `headers`, returning them if this is the
+    /// first response to report any.
+    pub(crate) fn observe(&self, headers: &ResponseHeaders) -> Option<&Capabilities> {
+        if self.reported.get().is_some() {
+            return None;
+        }
+        let capabilities = Capabilities::from_headers(headers)?;
+        let mut first = false;
+        let reported = self.reported.get_or_init(|| {
+            first = true;
+            capabilities
+        });
+        first.then_some(reported)
+    }
+
+    /// Capabilities reported so far, if any.
+    pub(crate) fn reported(&self) -> Option<&Capabilities> {
+        self.reported.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> ResponseHeaders {
+        let mut map = reqwest::header::HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        ResponseHeaders::capture(&map)
+    }
+
+    #[test]
+    fn parses_version_and_flags() {
+        let caps = Capabilities::from_headers(&headers(&[
+            ("x-snowflake-streaming-api-version", "2.1"),
+            (
+                "x-snowflake-streaming-capabilities",
+                "tokens-in-body, gzip, max-request-bytes=67108864, future-thing",
+            ),
+        ]))
+        .unwrap();
+        assert_eq!(
+            caps.api_version(),
+            Some(ServerApiVersion { major: 2, minor: 1 })
+        );
+        assert!(caps.tokens_in_body());
+        assert!(caps.gzip());
+        assert_eq!(caps.max_request_bytes(), 64 << 20);
+    }
+
+    #[test]
+    fn version_without_flags_is_the_baseline() {
+        let caps =
+            Capabilities::from_headers(&headers(&[("x-snowflake-streaming-api-version", "v2")]))
+                .unwrap();
+        assert_eq!(caps.api_version().unwrap().to_string(), "2.0");
+        assert_eq!(
+            Capabilities {
+                api_version: None,
+                ..caps
+            },
+            Capabilities::default()
+        );
+        assert!(Capabilities::from_headers(&headers(&[])).is_none());
+        assert!(
+            Capabilities::from_headers(&headers(&[(
+                "x-snowflake-streaming-api-version",
+                "latest"
+            )]))
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn first_reported_version_wins() {
+        let probe = CapabilityProbe::default();
+        assert!(probe.observe(&headers(&[])).is_none());
+        assert!(
+            probe
+                .observe(&headers(&[("x-snowflake-streaming-api-version", "2.0")]))
+                .is_some()
+        );
+        assert!(
+            probe
+                .observe(&headers(&[("x-snowflake-streaming-api-version", "3.0")]))
+                .is_none()
+        );
+        assert_eq!(probe.reported().unwrap().api_version().unwrap().major, 2);
+    }
+}
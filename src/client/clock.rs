@@ -0,0 +1,97 @@
+//! Local clock offset from Snowflake, measured from the `Date` header.
+//!
+//! Key-pair JWTs carry `iat`/`exp` claims in local time, so a host whose
+//! clock is off by more than a few seconds gets 401s that look like key
+//! problems. With `Config::clock_skew_check` the first response carrying a
+//! `Date` header fixes the offset for the client's lifetime, and locally
+//! generated JWTs are shifted by it.
+
+use std::sync::OnceLock;
+
+use jiff::{SignedDuration, Timestamp, fmt::rfc2822::DateTimeParser};
+use reqwest::header::{DATE, HeaderMap};
+
+/// `Date` has one-second resolution and arrives after a round trip, so
+/// smaller offsets are treated as no skew.
+const TOLERANCE: SignedDuration = SignedDuration::from_secs(2);
+
+/// Offset of Snowflake's clock from the local one, set once.
+#[derive(Default)]
+pub(crate) struct ClockSkewProbe {
+    measured: OnceLock<SignedDuration>,
+}
+
+impl ClockSkewProbe {
+    /// Measures the offset from the `Date` header in `headers`, returning it
+    /// if this is the first response to carry one.
+    pub(crate) fn observe(&self, headers: &HeaderMap) -> Option<SignedDuration> {
+        if self.measured.get().is_some() {
+            return None;
+        }
+        let server = parse_http_date(headers.get(DATE)?.to_str().ok()?)?;
+        let skew = server.duration_since(Timestamp::now());
+        let skew = if skew.abs() < TOLERANCE {
+            SignedDuration::ZERO
+        } else {
+            skew
+        };
+        let mut first = false;
+        let measured = *self.measured.get_or_init(|| {
+            first = true;
+            skew
+        });
+        first.then_some(measured)
+    }
+
+    /// The measured offset, if any: positive when the local clock is behind.
+    pub(crate) fn measured(&self) -> Option<SignedDuration> {
+        self.measured.get().copied()
+    }
+}
+
+fn parse_http_date(value: &str) -> Option<Timestamp> {
+    DateTimeParser::new().parse_timestamp(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_headers(date: Timestamp) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let value = jiff::fmt::rfc2822::DateTimePrinter::new()
+            .timestamp_to_rfc9110_string(&date)
+            .unwrap();
+        headers.insert(DATE, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(
+            parse_http_date("Tue, 15 Nov 1994 08:12:31 GMT").map(|t| t.as_second()),
+            Some(784887151)
+        );
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn first_date_sets_the_offset() {
+        let probe = ClockSkewProbe::default();
+        assert_eq!(probe.observe(&HeaderMap::new()), None);
+        let ahead = Timestamp::now() + SignedDuration::from_secs(300);
+        let skew = probe.observe(&date_headers(ahead)).unwrap();
+        assert!((298..=300).contains(&skew.as_secs()), "{skew:?}");
+        assert_eq!(probe.observe(&date_headers(Timestamp::now())), None);
+        assert_eq!(probe.measured(), Some(skew));
+    }
+
+    #[test]
+    fn small_offsets_are_no_skew() {
+        let probe = ClockSkewProbe::default();
+        assert_eq!(
+            probe.observe(&date_headers(Timestamp::now())),
+            Some(SignedDuration::ZERO)
+        );
+    }
+}
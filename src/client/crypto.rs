@@ -3,12 +3,15 @@ use pkcs8::{DecodePrivateKey as _, EncodePublicKey as _};
 use rsa::pkcs1::{DecodeRsaPrivateKey as _, EncodeRsaPrivateKey as _};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tracing::{debug, info, warn};
+use tracing::debug;
 
-use crate::{Config, Error};
+use super::TokenSnapshot;
+use crate::telemetry::{TelemetryEvent, TelemetrySink, TokenKind};
+use crate::{Config, Error, JwtSigner, Warning};
 
 const MIN_EXP_SECS: u64 = 30;
 const MAX_EXP_SECS: u64 = 3600;
+pub(crate) const DEFAULT_REFRESH_MARGIN_SECS: u64 = 30;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct ClampResult {
@@ -46,6 +49,22 @@ fn next_iat_millis() -> Result<u64, Error> {
     }
 }
 
+/// `iat` of a new JWT in epoch milliseconds, shifted by `clock_skew_ms` and
+/// no earlier than `min_issued_at`; whole seconds unless `millisecond_claims`
+/// is set.
+fn next_issued_at(
+    millisecond_claims: bool,
+    clock_skew_ms: i64,
+    min_issued_at: u64,
+) -> Result<u64, Error> {
+    if millisecond_claims {
+        let now = next_iat_millis()?.saturating_add_signed(clock_skew_ms);
+        return Ok(now.max(min_issued_at));
+    }
+    let now = now_millis()?.saturating_add_signed(clock_skew_ms) / 1_000;
+    Ok(now.max(min_issued_at.div_ceil(1_000)) * 1_000)
+}
+
 fn clamp_exp_secs(desired: Option<u64>) -> ClampResult {
     let requested = desired.unwrap_or(MAX_EXP_SECS);
     let effective = requested.clamp(MIN_EXP_SECS, MAX_EXP_SECS);
@@ -61,7 +80,7 @@ fn clamp_exp_secs(desired: Option<u64>) -> ClampResult {
 }
 
 /// Returns base64 encoded fingerprint of a public key derived from the RSA key.
-pub(super) fn compute_fingerprint(key: &rsa::RsaPublicKey) -> Result<String, Error> {
+pub(crate) fn compute_fingerprint(key: &rsa::RsaPublicKey) -> Result<String, Error> {
     let spki = key
         .to_public_key_der()
         .map_err(|e| Error::Key(format!("SubjectPublicKeyInfo DER encode failed: {e}")))?;
@@ -118,21 +137,119 @@ fn load_rsa_private_key_from_pem(
     ))
 }
 
-pub(super) struct AssertionBundle {
-    token: String,
-    issued_at: u64,
-    expires_at: u64,
+/// Offline key-pair checks for `Config::validate`, as `(field, problem)` pairs.
+pub(crate) fn validate_key_pair(cfg: &Config) -> Vec<(&'static str, String)> {
+    let mut issues = Vec::new();
+    if let Some(exp) = cfg.jwt_exp.map(|exp| exp.as_secs())
+        && !(MIN_EXP_SECS..=MAX_EXP_SECS).contains(&exp)
+    {
+        issues.push((
+            "jwt_exp",
+            format!("{exp} is outside [{MIN_EXP_SECS}, {MAX_EXP_SECS}] and would be clamped"),
+        ));
+    }
+    let margin = cfg
+        .jwt_refresh_margin
+        .map_or(DEFAULT_REFRESH_MARGIN_SECS, |margin| margin.as_secs());
+    if let Err(Error::Config(msg)) = JwtContext::new(cfg, margin) {
+        issues.push(("jwt_refresh_margin", msg));
+    }
+    if cfg.jwt_signer.is_some() {
+        // The signer holds the key; there is nothing local to check.
+        return issues;
+    }
+
+    let pem = match cfg.private_key() {
+        Ok(pem) => pem,
+        Err(err) => {
+            let field = if cfg.private_key.is_some() {
+                "private_key"
+            } else if cfg.private_key_path.is_some() || cfg.private_key_source.is_none() {
+                "private_key_path"
+            } else {
+                "private_key_source"
+            };
+            issues.push((field, err.to_string()));
+            return issues;
+        }
+    };
+    let passphrase = match cfg.private_key_passphrase() {
+        Ok(passphrase) => passphrase,
+        Err(err) => {
+            issues.push(("private_key_passphrase_path", err.to_string()));
+            return issues;
+        }
+    };
+    let key = match load_rsa_private_key_from_pem(&pem, passphrase.as_deref()) {
+        Ok(key) => key,
+        Err(err) => {
+            issues.push((
+                "private_key",
+                format!("{err}; check the key format and private_key_passphrase"),
+            ));
+            return issues;
+        }
+    };
+    match compute_fingerprint(&key.to_public_key()) {
+        Ok(computed) => {
+            if let Some(fp) = &cfg.public_key_fp
+                && fp != &computed
+            {
+                issues.push((
+                    "public_key_fp",
+                    format!("does not match the private key (computed {computed}); remove it to use the computed value"),
+                ));
+            }
+        }
+        Err(err) => issues.push(("private_key", err.to_string())),
+    }
+    issues
+}
+
+pub(crate) struct AssertionBundle {
+    pub(crate) token: String,
+    /// `iss` claim; empty for test assertions.
+    pub(crate) issuer: String,
+    /// `sub` claim; empty for test assertions.
+    pub(crate) subject: String,
+    /// Public key fingerprint embedded in the issuer.
+    pub(crate) fingerprint: String,
+    /// `iat` claim in epoch milliseconds, whatever the unit of the claim.
+    pub(crate) issued_at: u64,
+    /// `exp` claim in epoch milliseconds, whatever the unit of the claim.
+    pub(crate) expires_at: u64,
     lifetime_secs: u64,
     clamped_from: Option<u64>,
 }
 
-pub(super) fn build_assertion(cfg: &Config, log_clamp: bool) -> Result<AssertionBundle, Error> {
+/// Builds a signed assertion; a clamped lifetime is reported to `telemetry` when given.
+pub(crate) fn build_assertion(
+    cfg: &Config,
+    telemetry: Option<&dyn TelemetrySink>,
+) -> Result<AssertionBundle, Error> {
+    build_assertion_skewed(cfg, telemetry, 0, 0)
+}
+
+/// [`build_assertion`] with `iat`/`exp` shifted by `clock_skew_ms`, the
+/// measured offset of Snowflake's clock from the local one. `iat` is at least
+/// `min_issued_at` (epoch milliseconds), so a replacement JWT generated within
+/// the same second as its predecessor still differs from it.
+fn build_assertion_skewed(
+    cfg: &Config,
+    telemetry: Option<&dyn TelemetrySink>,
+    clock_skew_ms: i64,
+    min_issued_at: u64,
+) -> Result<AssertionBundle, Error> {
     let private_key = cfg.private_key()?;
     let prefix = "TEST://assertion:";
-    let now = next_iat_millis()?;
+    let millisecond_claims = cfg.jwt_millisecond_claims.unwrap_or(false);
+    let now = next_issued_at(millisecond_claims, clock_skew_ms, min_issued_at)?;
     if let Some(rest) = private_key.strip_prefix(prefix) {
         return Ok(AssertionBundle {
             token: rest.to_string(),
+            issuer: String::new(),
+            subject: String::new(),
+            fingerprint: String::new(),
             issued_at: now,
             expires_at: now + MIN_EXP_SECS * 1_000,
             lifetime_secs: MIN_EXP_SECS,
@@ -140,41 +257,13 @@ pub(super) fn build_assertion(cfg: &Config, log_clamp: bool) -> Result<Assertion
         });
     }
 
-    let clamp = clamp_exp_secs(cfg.jwt_exp_secs);
-    if let Some(original) = clamp.original.filter(|_| log_clamp) {
-        warn!(
-            original_seconds = original,
-            effective_seconds = clamp.effective,
-            "jwt_exp_secs outside [{MIN_EXP_SECS}, {MAX_EXP_SECS}] - clamped for safety"
-        );
-    }
-
-    let name = cfg.login.as_deref().unwrap_or(&cfg.user);
-    let rsa_key =
-        load_rsa_private_key_from_pem(&private_key, cfg.private_key_passphrase.as_deref())?;
+    let passphrase = cfg.private_key_passphrase()?;
+    let rsa_key = load_rsa_private_key_from_pem(&private_key, passphrase.as_deref())?;
     let fingerprint = match cfg.public_key_fp.as_ref() {
         Some(fp) => fp.clone(),
         None => compute_fingerprint(&rsa_key.to_public_key())?,
     };
-    let account_norm = cfg.account.to_uppercase().replace('.', "-");
-    let user_norm = name.to_uppercase();
-    let sub = format!("{}.{}", account_norm, user_norm);
-    let iss = format!("{}.{}", sub, fingerprint);
-    let exp = now + clamp.effective * 1_000;
-
-    #[derive(serde::Serialize)]
-    struct Claims {
-        iss: String,
-        sub: String,
-        iat: u64,
-        exp: u64,
-    }
-    let claims = Claims {
-        iss,
-        sub,
-        iat: now,
-        exp,
-    };
+    let unsigned = UnsignedAssertion::new(cfg, telemetry, fingerprint, now)?;
 
     let pkcs1 = rsa_key
         .to_pkcs1_der()
@@ -182,18 +271,118 @@ pub(super) fn build_assertion(cfg: &Config, log_clamp: bool) -> Result<Assertion
     let enc_key = jsonwebtoken::EncodingKey::from_rsa_der(pkcs1.as_bytes());
     let token = jsonwebtoken::encode(
         &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
-        &claims,
+        &unsigned.claims,
         &enc_key,
     )
     .map_err(|e| Error::JwtSign(format!("JWT signing failed: {e}")))?;
+    Ok(unsigned.signed(token))
+}
+
+/// [`build_assertion_skewed`] with the signature from `signer` instead of a
+/// local private key.
+async fn build_signed_assertion(
+    cfg: &Config,
+    signer: &dyn JwtSigner,
+    telemetry: Option<&dyn TelemetrySink>,
+    clock_skew_ms: i64,
+    min_issued_at: u64,
+) -> Result<AssertionBundle, Error> {
+    let fingerprint = match cfg.public_key_fp.as_ref() {
+        Some(fp) => fp.clone(),
+        None => signer.public_key_fp().await?,
+    };
+    let millisecond_claims = cfg.jwt_millisecond_claims.unwrap_or(false);
+    let now = next_issued_at(millisecond_claims, clock_skew_ms, min_issued_at)?;
+    let unsigned = UnsignedAssertion::new(cfg, telemetry, fingerprint, now)?;
 
-    Ok(AssertionBundle {
-        token,
-        issued_at: now,
-        expires_at: exp,
-        lifetime_secs: clamp.effective,
-        clamped_from: clamp.original,
-    })
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let header = serde_json::to_vec(&jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256))?;
+    let claims = serde_json::to_vec(&unsigned.claims)?;
+    let message = format!("{}.{}", engine.encode(header), engine.encode(claims));
+    let signature = signer.sign(message.as_bytes()).await?;
+    let token = format!("{message}.{}", engine.encode(signature));
+    Ok(unsigned.signed(token))
+}
+
+#[derive(serde::Serialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// Claims of a key-pair JWT, ready to sign.
+struct UnsignedAssertion {
+    claims: Claims,
+    fingerprint: String,
+    issued_at: u64,
+    expires_at: u64,
+    lifetime_secs: u64,
+    clamped_from: Option<u64>,
+}
+
+impl UnsignedAssertion {
+    /// Claims for a JWT issued at `now` (epoch milliseconds) by the key with
+    /// `fingerprint`; a clamped lifetime is reported to `telemetry` when given.
+    fn new(
+        cfg: &Config,
+        telemetry: Option<&dyn TelemetrySink>,
+        fingerprint: String,
+        now: u64,
+    ) -> Result<Self, Error> {
+        let clamp = clamp_exp_secs(cfg.jwt_exp.map(|exp| exp.as_secs()));
+        if let (Some(original), Some(telemetry)) = (clamp.original, telemetry) {
+            telemetry.record(&TelemetryEvent::JwtLifetimeClamped {
+                original_secs: original,
+                effective_secs: clamp.effective,
+            });
+            cfg.warn(Warning::JwtLifetimeClamped {
+                original_secs: original,
+                effective_secs: clamp.effective,
+            });
+        }
+
+        let name = cfg.login.as_deref().unwrap_or(&cfg.user);
+        let account_norm = cfg.jwt_account()?;
+        let user_norm = name.to_uppercase();
+        let sub = format!("{}.{}", account_norm, user_norm);
+        let iss = format!("{}.{}", sub, fingerprint);
+        let exp = now + clamp.effective * 1_000;
+
+        // Snowflake reads `iat`/`exp` as standard JWT NumericDates (seconds).
+        let unit = if cfg.jwt_millisecond_claims.unwrap_or(false) {
+            1
+        } else {
+            1_000
+        };
+        Ok(Self {
+            claims: Claims {
+                iss,
+                sub,
+                iat: now / unit,
+                exp: exp / unit,
+            },
+            fingerprint,
+            issued_at: now,
+            expires_at: exp,
+            lifetime_secs: clamp.effective,
+            clamped_from: clamp.original,
+        })
+    }
+
+    fn signed(self, token: String) -> AssertionBundle {
+        AssertionBundle {
+            token,
+            issuer: self.claims.iss,
+            subject: self.claims.sub,
+            fingerprint: self.fingerprint,
+            issued_at: self.issued_at,
+            expires_at: self.expires_at,
+            lifetime_secs: self.lifetime_secs,
+            clamped_from: self.clamped_from,
+        }
+    }
 }
 
 pub(crate) struct JwtContext {
@@ -204,16 +393,20 @@ pub(crate) struct JwtContext {
     refresh_margin_secs: u64,
     clamp_logged: bool,
     last_refresh_warning: Option<Instant>,
+    /// Offset of Snowflake's clock from the local one, in milliseconds.
+    clock_skew_ms: i64,
+    /// `iat` of the last JWT generated; the next one is later.
+    last_issued_at: u64,
 }
 
 impl JwtContext {
     pub(crate) fn new(cfg: &Config, refresh_margin_secs: u64) -> Result<Self, Error> {
         if refresh_margin_secs < MIN_EXP_SECS {
             return Err(Error::Config(format!(
-                "jwt_refresh_margin_secs must be at least {MIN_EXP_SECS} seconds (got {refresh_margin_secs})"
+                "jwt_refresh_margin must be at least {MIN_EXP_SECS} seconds (got {refresh_margin_secs})"
             )));
         }
-        let clamp = clamp_exp_secs(cfg.jwt_exp_secs);
+        let clamp = clamp_exp_secs(cfg.jwt_exp.map(|exp| exp.as_secs()));
         let lifetime_secs = clamp.effective;
         if refresh_margin_secs >= lifetime_secs {
             return Err(Error::Config(format!(
@@ -229,11 +422,17 @@ impl JwtContext {
             refresh_margin_secs,
             clamp_logged: false,
             last_refresh_warning: None,
+            clock_skew_ms: 0,
+            last_issued_at: 0,
         })
     }
 
-    pub(crate) fn ensure_valid(&mut self, cfg: &Config) -> Result<String, Error> {
-        let now = now_millis()?;
+    pub(crate) async fn ensure_valid(
+        &mut self,
+        cfg: &Config,
+        telemetry: &dyn TelemetrySink,
+    ) -> Result<String, Error> {
+        let now = now_millis()?.saturating_add_signed(self.clock_skew_ms);
         let needs_refresh = match self.token {
             None => true,
             Some(_) => {
@@ -245,11 +444,14 @@ impl JwtContext {
                         None => true,
                     };
                     if should_log {
-                        info!(
-                            remaining_seconds = remaining_secs,
-                            margin_seconds = self.refresh_margin_secs,
-                            "refreshing JWT because remaining lifetime is within safety margin"
-                        );
+                        telemetry.record(&TelemetryEvent::JwtRefresh {
+                            remaining_secs,
+                            margin_secs: self.refresh_margin_secs,
+                        });
+                        cfg.warn(Warning::TokenNearExpiry {
+                            token: TokenKind::Control,
+                            remaining_secs,
+                        });
                         self.last_refresh_warning = Some(Instant::now());
                     }
                     true
@@ -260,12 +462,27 @@ impl JwtContext {
         };
 
         if needs_refresh {
-            let bundle = build_assertion(cfg, !self.clamp_logged)?;
+            let telemetry = (!self.clamp_logged).then_some(telemetry);
+            let min_issued_at = self.last_issued_at.saturating_add(1);
+            let bundle = match &cfg.jwt_signer {
+                Some(signer) => {
+                    build_signed_assertion(
+                        cfg,
+                        signer.as_ref(),
+                        telemetry,
+                        self.clock_skew_ms,
+                        min_issued_at,
+                    )
+                    .await?
+                }
+                None => build_assertion_skewed(cfg, telemetry, self.clock_skew_ms, min_issued_at)?,
+            };
             if bundle.clamped_from.is_some() {
                 self.clamp_logged = true;
             }
             self.token = Some(bundle.token);
             self.issued_at = bundle.issued_at;
+            self.last_issued_at = bundle.issued_at;
             self.expires_at = bundle.expires_at;
             self.lifetime_secs = bundle.lifetime_secs;
         } else {
@@ -282,17 +499,48 @@ impl JwtContext {
             .clone())
     }
 
+    /// Refreshes `offset_secs` earlier than the configured margin, staying
+    /// below the JWT lifetime.
+    pub(crate) fn stagger_refresh(&mut self, offset_secs: u64) {
+        self.refresh_margin_secs = (self.refresh_margin_secs + offset_secs)
+            .min(self.lifetime_secs - 1)
+            .max(self.refresh_margin_secs);
+    }
+
+    pub(crate) fn snapshot(&self) -> TokenSnapshot {
+        if self.token.is_none() {
+            return TokenSnapshot::from_token(None, None, false, true);
+        }
+        TokenSnapshot::from_millis(self.issued_at, self.expires_at, false, true)
+    }
+
     #[cfg(test)]
     pub(crate) fn force_issued_at(&mut self, issued_at: u64) {
         self.issued_at = issued_at;
         self.expires_at = issued_at + self.lifetime_secs * 1_000;
     }
 
+    /// Shifts future JWTs by `skew_ms`, discarding the cached one if the
+    /// offset changed.
+    pub(crate) fn set_clock_skew(&mut self, skew_ms: i64) {
+        if skew_ms != self.clock_skew_ms {
+            self.clock_skew_ms = skew_ms;
+            // The previous `iat` was on the old clock; don't hold the next
+            // one back to it.
+            self.last_issued_at = 0;
+            self.invalidate();
+        }
+    }
+
     pub(crate) fn invalidate(&mut self) {
         self.token = None;
         self.last_refresh_warning = None;
     }
 }
 
+mod debug;
+
+pub use debug::{AuthDebugReport, KeyMetadata, debug_auth};
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,250 @@
+//! Offline inspection of the control-plane JWT for troubleshooting auth failures.
+
+use jiff::Timestamp;
+
+use super::{
+    MAX_EXP_SECS, MIN_EXP_SECS, build_assertion, compute_fingerprint, load_rsa_private_key_from_pem,
+};
+use crate::client::token_info::{claim_timestamp, decode_jwt};
+use crate::{AccountFormat, Config, Error};
+
+/// What [`debug_auth`] learned about the configured credentials.
+///
+/// Contains no secrets: only the JWT's public claims and key metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthDebugReport {
+    /// `true` when the JWT was generated from the private key, `false` when
+    /// the pre-generated `jwt_token` was decoded instead.
+    pub generated: bool,
+    /// Signing algorithm from the JWT header.
+    pub algorithm: String,
+    /// `iss` claim: `<ACCOUNT>.<USER>.<fingerprint>`.
+    pub issuer: String,
+    /// `sub` claim: `<ACCOUNT>.<USER>`.
+    pub subject: String,
+    /// Public key fingerprint embedded in the issuer.
+    pub fingerprint: String,
+    /// `iat` claim.
+    pub issued_at: Option<Timestamp>,
+    /// `exp` claim.
+    pub expires_at: Option<Timestamp>,
+    /// The private key, when one is configured.
+    pub key: Option<KeyMetadata>,
+    /// Likely misconfigurations, each with a suggested fix.
+    pub warnings: Vec<String>,
+}
+
+/// Non-secret facts about the configured private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMetadata {
+    /// `private_key`, `private_key_path`, or `private_key_source`.
+    pub source: &'static str,
+    /// PEM encoding, e.g. `PKCS#8` or `PKCS#1`.
+    pub format: &'static str,
+    /// Whether the PEM is passphrase-protected.
+    pub encrypted: bool,
+    /// RSA modulus size in bits.
+    pub bits: usize,
+    /// Fingerprint computed from the key, to compare with `RSA_PUBLIC_KEY_FP`
+    /// in `DESC USER`.
+    pub fingerprint: String,
+}
+
+impl std::fmt::Display for AuthDebugReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let origin = if self.generated {
+            "generated from private key"
+        } else {
+            "decoded from jwt_token"
+        };
+        let time = |t: &Option<Timestamp>| t.map_or("-".to_string(), |t| t.to_string());
+        writeln!(f, "JWT ({origin}, {})", self.algorithm)?;
+        writeln!(f, "  iss:         {}", self.issuer)?;
+        writeln!(f, "  sub:         {}", self.subject)?;
+        writeln!(f, "  fingerprint: {}", self.fingerprint)?;
+        writeln!(f, "  iat:         {}", time(&self.issued_at))?;
+        writeln!(f, "  exp:         {}", time(&self.expires_at))?;
+        if let Some(key) = &self.key {
+            let encrypted = if key.encrypted { ", encrypted" } else { "" };
+            writeln!(
+                f,
+                "Key ({}): {} RSA-{}{encrypted}",
+                key.source, key.format, key.bits
+            )?;
+            writeln!(f, "  fingerprint: {}", key.fingerprint)?;
+        }
+        if self.warnings.is_empty() {
+            write!(f, "No common pitfalls detected")
+        } else {
+            write!(f, "Warnings:")?;
+            for warning in &self.warnings {
+                write!(f, "\n  - {warning}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Builds (or, with `jwt_token`, decodes) the control-plane JWT locally and
+/// checks it for common key-pair auth mistakes, without contacting Snowflake.
+///
+/// Compare the printed fingerprint with `RSA_PUBLIC_KEY_FP` from
+/// `DESC USER <user>` when Snowflake rejects the JWT.
+///
+/// ```no_run
+/// # fn main() -> Result<(), snowpipe_streaming::Error> {
+/// let config = snowpipe_streaming::Config::from_env()?;
+/// println!("{}", snowpipe_streaming::debug_auth(&config)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn debug_auth(config: &Config) -> Result<AuthDebugReport, Error> {
+    let supplied = config.jwt_token.as_deref().filter(|t| !t.is_empty());
+    let token = match supplied {
+        Some(token) => token.to_string(),
+        None => build_assertion(config, None)?.token,
+    };
+    let (header, claims) = decode_jwt(&token)
+        .ok_or_else(|| Error::Config("The control-plane token is not a decodable JWT".into()))?;
+    let claim = |name: &str| claims[name].as_str().unwrap_or_default().to_string();
+    let issuer = claim("iss");
+    let subject = claim("sub");
+    let fingerprint = issuer
+        .strip_prefix(&subject)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .unwrap_or_default()
+        .to_string();
+
+    let key = match (&config.private_key, &config.private_key_path) {
+        (None, None) => None,
+        _ => Some(key_metadata(config)?),
+    };
+
+    let mut report = AuthDebugReport {
+        generated: supplied.is_none(),
+        algorithm: header["alg"].as_str().unwrap_or_default().to_string(),
+        issuer,
+        subject,
+        fingerprint,
+        issued_at: claims.get("iat").and_then(claim_timestamp),
+        expires_at: claims.get("exp").and_then(claim_timestamp),
+        key,
+        warnings: Vec::new(),
+    };
+    report.warnings = pitfalls(config, &report);
+    Ok(report)
+}
+
+fn key_metadata(config: &Config) -> Result<KeyMetadata, Error> {
+    use rsa::traits::PublicKeyParts as _;
+
+    let pem = config.private_key()?;
+    let passphrase = config.private_key_passphrase()?;
+    let key = load_rsa_private_key_from_pem(&pem, passphrase.as_deref())?;
+    let tags: Vec<String> = pem::parse_many(pem.as_bytes())
+        .map(|blocks| blocks.iter().map(|b| b.tag().to_string()).collect())
+        .unwrap_or_default();
+    let has = |tag: &str| tags.iter().any(|t| t == tag);
+    let (format, encrypted) = if has("ENCRYPTED PRIVATE KEY") {
+        ("PKCS#8", true)
+    } else if has("RSA PRIVATE KEY") {
+        ("PKCS#1", false)
+    } else {
+        ("PKCS#8", false)
+    };
+    Ok(KeyMetadata {
+        source: if config.private_key.is_some() {
+            "private_key"
+        } else if config.private_key_path.is_some() {
+            "private_key_path"
+        } else {
+            "private_key_source"
+        },
+        format,
+        encrypted,
+        bits: key.size() * 8,
+        fingerprint: compute_fingerprint(&key.to_public_key())?,
+    })
+}
+
+fn pitfalls(config: &Config, report: &AuthDebugReport) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let account = config.account.as_str();
+    let lower = account.to_lowercase();
+
+    if lower.contains("snowflakecomputing") || lower.contains("://") {
+        warnings.push(format!(
+            "account '{account}' looks like a host name; use only the account identifier, e.g. 'MYORG-MYACCOUNT'"
+        ));
+    } else if let Some((locator, rest)) = account.split_once('.')
+        && config.account_format.unwrap_or_default() == AccountFormat::Auto
+    {
+        let regional = rest
+            .split('.')
+            .any(|part| part.contains('-') || matches!(part, "aws" | "azure" | "gcp"));
+        if regional {
+            warnings.push(format!(
+                "account '{account}' includes region/cloud segments; key-pair JWTs expect the bare locator '{}' (set account_format to locator) or the '<ORG>-<ACCOUNT>' name",
+                locator.to_uppercase()
+            ));
+        }
+    }
+
+    if let Some((account_part, _)) = report.subject.split_once('.')
+        && account_part != account_part.to_uppercase()
+    {
+        warnings.push(format!(
+            "the JWT account '{account_part}' is not upper-case; Snowflake compares iss/sub against the upper-case account identifier"
+        ));
+    }
+    let name = config.login.as_deref().unwrap_or(&config.user);
+    let expected_sub = config
+        .jwt_account()
+        .map(|account| format!("{account}.{}", name.to_uppercase()));
+    if let Ok(expected_sub) = expected_sub
+        && !report.generated
+        && report.subject != expected_sub
+    {
+        warnings.push(format!(
+            "jwt_token was issued for '{}' but this config expects '{expected_sub}'",
+            report.subject
+        ));
+    }
+    if !report.fingerprint.starts_with("SHA256:") {
+        warnings.push(format!(
+            "fingerprint '{}' should have the form 'SHA256:<base64>'",
+            report.fingerprint
+        ));
+    }
+    if let Some(key) = &report.key {
+        if report.fingerprint != key.fingerprint {
+            let hint = if config.public_key_fp.is_some() {
+                "; remove public_key_fp to use the computed value"
+            } else {
+                ""
+            };
+            warnings.push(format!(
+                "the JWT fingerprint does not match the private key ({}){hint}",
+                key.fingerprint
+            ));
+        }
+        if key.bits < 2048 {
+            warnings.push(format!(
+                "{}-bit keys are rejected; generate a 2048-bit RSA key",
+                key.bits
+            ));
+        }
+    }
+    if let Some(exp) = config.jwt_exp.map(|exp| exp.as_secs())
+        && report.generated
+        && !(MIN_EXP_SECS..=MAX_EXP_SECS).contains(&exp)
+    {
+        warnings.push(format!(
+            "jwt_exp {exp} is clamped into [{MIN_EXP_SECS}, {MAX_EXP_SECS}]"
+        ));
+    }
+    if report.expires_at.is_some_and(|exp| exp <= Timestamp::now()) {
+        warnings.push("the JWT has already expired".into());
+    }
+    warnings
+}
@@ -10,11 +10,12 @@ use std::sync::{Arc, Barrier};
 use std::thread;
 
 use crate::client::crypto::{JwtContext, build_assertion, compute_fingerprint};
-use crate::tests::test_support::with_captured_logs;
+use crate::telemetry::TracingTelemetry;
+use crate::tests::test_support::{capture_logs, drain_logs, with_captured_logs};
 use crate::{Config, Error};
 
 fn generate_assertion(cfg: &Config) -> Result<String, Error> {
-    Ok(build_assertion(cfg, true)?.token)
+    Ok(build_assertion(cfg, Some(&TracingTelemetry))?.token)
 }
 
 fn decode_jwt_payload(jwt: &str) -> Value {
@@ -84,7 +85,7 @@ const TEST_PKCS8_ENCRYPTED_PEM: &str = include_str!("../../../tests/fixtures/id_
 /// We assert:
 /// - `iss` contains UPPERCASE <ACCOUNT>.<USER>.SHA256:<fingerprint>
 /// - `sub` equals UPPERCASE <ACCOUNT>.<USER>
-/// - `exp - iat == jwt_exp_secs * 1000` (claims now use milliseconds)
+/// - `exp - iat == jwt_exp` (claims are in seconds)
 /// - `iat` ≈ now (allow small skew)
 #[test]
 fn generates_snowflake_style_jwt_claims() {
@@ -102,16 +103,74 @@ fn generates_snowflake_style_jwt_claims() {
         account: account.to_string(),
         url: "https://xy12345.us-east-1.snowflakecomputing.com".to_string(),
         jwt_token: None,
+        auth_scheme: None,
+        programmatic_access_token: None,
         private_key: Some(TEST_PKCS8_PRIVKEY_PEM.to_string()),
         private_key_path: None,
+        private_key_source: None,
         private_key_passphrase: None,
+        private_key_passphrase_path: None,
+        passphrase_provider: None,
+        account_format: None,
+        default_headers: None,
+        control_host_pins: None,
+        ingest_host_pins: None,
+        tags: None,
+        header_provider: None,
+        request_interceptor: None,
+        commit_wait: None,
+        jwt_signer: None,
+        fenced_channel_suffix: None,
+        channel_open_conflict_retries: None,
+        writer_generation: None,
+        ingest_host: None,
+        warm_up_ingest_host: None,
+        scoped_token_max_age: None,
+        scoped_token_refresh_margin: None,
+        api_version: None,
+        token_refresh_cooldown: None,
+        startup_jitter: None,
+        refresh_jitter: None,
+        maintenance_retry_after: None,
+        maintenance_probe_interval: None,
+        maintenance_max_wait: None,
+        share_tokens: None,
+        token_cache: None,
+        warning_handler: None,
+        retry_handler: None,
+        #[cfg(feature = "replay")]
+        replay: None,
         public_key_fp: None,
-        jwt_exp_secs: Some(exp_secs),
-        jwt_refresh_margin_secs: None,
+        jwt_exp: Some(std::time::Duration::from_secs(exp_secs)),
+        jwt_refresh_margin: None,
+        jwt_millisecond_claims: None,
+        clock_skew_check: None,
+        max_clock_skew: None,
         retry_on_unauthorized: None,
+        retry: None,
+        endpoint_suffix: None,
+        privatelink: None,
+        http2_prior_knowledge: None,
+        http2_adaptive_window: None,
+        connect_timeout: None,
+        read_timeout: None,
+        max_concurrent_requests: None,
+        max_rows_per_request: None,
+        max_requests_per_second: None,
+        max_uncommitted_offsets: None,
+        fail_on_uncommitted_limit: None,
+        max_response_bytes: None,
+        serialization_threads: None,
+        append_log_policy: None,
+        batch_id_column: None,
+        batch_checksum: None,
+        checksum_column: None,
+        partition_column: None,
+        misuse_warnings: None,
+        strict_config: None,
     };
 
-    let t0 = super::now_millis().unwrap();
+    let t0 = super::now_millis().unwrap() / 1_000;
 
     // Act
     let jwt = generate_assertion(&cfg).expect("should generate a JWT");
@@ -159,25 +218,25 @@ fn generates_snowflake_style_jwt_claims() {
         iss
     );
 
-    // Time math: exp - iat == jwt_exp_secs and <= 3600
+    // Time math: exp - iat == jwt_exp and <= 3600
     assert_eq!(
         exp.saturating_sub(iat),
-        exp_secs * 1_000,
-        "exp - iat must equal jwt_exp_secs in milliseconds"
+        exp_secs,
+        "exp - iat must equal jwt_exp"
     );
     assert!(
         exp_secs <= 3600,
-        "jwt_exp_secs should be <= 3600 for Snowflake key-pair auth"
+        "jwt_exp should be <= 3600 for Snowflake key-pair auth"
     );
 
     // iat should be close to now (allow generous skew for CI)
-    let t1 = super::now_millis().unwrap();
+    let t1 = super::now_millis().unwrap() / 1_000;
     assert!(
-        iat >= t0.saturating_sub(30_000) && iat <= t1.saturating_add(30_000),
+        iat >= t0.saturating_sub(30) && iat <= t1.saturating_add(30),
         "iat should be near 'now' (±30s); got {}, window [{}, {}]",
         iat,
-        t0.saturating_sub(30_000),
-        t1.saturating_add(30_000)
+        t0.saturating_sub(30),
+        t1.saturating_add(30)
     );
 }
 
@@ -200,6 +259,82 @@ fn encrypted_pkcs8_with_passphrase_parses() {
     generate_assertion(&cfg).expect("should generate assertion with encrypted key");
 }
 
+#[test]
+fn passphrase_from_file_or_provider() {
+    let mut cfg = Config::from_values(
+        "user",
+        None,
+        "acct",
+        "https://example",
+        None,
+        Some(TEST_PKCS8_ENCRYPTED_PEM.to_string()),
+        None,
+        None,
+        None,
+        Some(60),
+    );
+    assert!(generate_assertion(&cfg).is_err());
+
+    let path = std::env::temp_dir().join(format!("snowpipe-pass-{}", std::process::id()));
+    std::fs::write(&path, "test-pass\n").unwrap();
+    cfg.private_key_passphrase_path = Some(path.to_string_lossy().into_owned());
+    let from_file = generate_assertion(&cfg);
+    std::fs::remove_file(&path).unwrap();
+    from_file.expect("passphrase read from file");
+
+    let cfg = cfg.passphrase_provider(|| Ok("test-pass".into()));
+    generate_assertion(&cfg).expect("passphrase from provider");
+    let cfg = cfg.passphrase_provider(|| Err(Error::Config("vault unavailable".into())));
+    assert!(matches!(generate_assertion(&cfg), Err(Error::Config(_))));
+}
+
+#[test]
+fn debug_auth_reports_claims_and_pitfalls() {
+    let mut cfg = Config::from_values(
+        "alice",
+        None,
+        "xy12345.us-east-2.aws",
+        "",
+        None,
+        Some(TEST_PKCS8_ENCRYPTED_PEM.to_string()),
+        None,
+        Some("test-pass".into()),
+        None,
+        Some(60),
+    );
+    let report = crate::debug_auth(&cfg).expect("report");
+    assert!(report.generated);
+    assert_eq!(report.algorithm, "RS256");
+    assert_eq!(report.subject, "XY12345-US-EAST-2-AWS.ALICE");
+    let key = report.key.as_ref().expect("key metadata");
+    assert!(key.encrypted);
+    assert_eq!(report.fingerprint, key.fingerprint);
+    assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+    assert!(report.warnings[0].contains("'XY12345'"));
+    let printed = report.to_string();
+    assert!(printed.contains(&report.issuer));
+    assert!(!printed.contains("test-pass"));
+
+    cfg.account = "myorg-myaccount".into();
+    cfg.public_key_fp = Some("SHA256:stale".into());
+    let report = crate::debug_auth(&cfg).expect("report");
+    assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+    assert!(report.warnings[0].contains("remove public_key_fp"));
+
+    let supplied = build_assertion(&cfg, None).unwrap().token;
+    cfg.jwt_token = Some(supplied);
+    cfg.user = "bob".into();
+    cfg.public_key_fp = None;
+    let report = crate::debug_auth(&cfg).expect("report");
+    assert!(!report.generated);
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w.contains("issued for 'MYORG-MYACCOUNT.ALICE'"))
+    );
+}
+
 #[test]
 fn correctly_generates_fingerprint() {
     let b64 = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA2RmwUycPmCSycr6WgS/NXcffCs6U025B+rT2zQDl1UWeKcSIh1TSdh7aHTyMuDaWcu3u+3+93L443D2nXJntZvcg8JV08a/QN+bI3RGdVabGL74ewqn3fuGleWYsIz3oLhse6zwbrhLGdVsD3ADOIl/nAmjOnalyuJ0fUjPgxLwRACEV5WIchVqrkG3wxRJCsj+ze8HrFMMsZ2rEtZb5XwoUiw5gbuvFhrU1y6b821Efe/ajI7h+h8qIIXcqTWSFZj93dmqWl8jUU9GkRouSVD8PrHUu0LMRNNsJ/ZC5e0u6mjVc47PyTKTUn+2q0ySoyWLRkyF0SWzqD4WI12gzIQIDAQAB";
@@ -216,13 +351,71 @@ fn config_with_exp_secs(exp: u64) -> Config {
         account: "acct".into(),
         url: "https://example".into(),
         jwt_token: None,
+        auth_scheme: None,
+        programmatic_access_token: None,
         private_key: Some(TEST_PKCS8_PRIVKEY_PEM.to_string()),
         private_key_path: None,
+        private_key_source: None,
         private_key_passphrase: None,
+        private_key_passphrase_path: None,
+        passphrase_provider: None,
+        account_format: None,
+        default_headers: None,
+        control_host_pins: None,
+        ingest_host_pins: None,
+        tags: None,
+        header_provider: None,
+        request_interceptor: None,
+        commit_wait: None,
+        jwt_signer: None,
+        fenced_channel_suffix: None,
+        channel_open_conflict_retries: None,
+        writer_generation: None,
+        ingest_host: None,
+        warm_up_ingest_host: None,
+        scoped_token_max_age: None,
+        scoped_token_refresh_margin: None,
+        api_version: None,
+        token_refresh_cooldown: None,
+        startup_jitter: None,
+        refresh_jitter: None,
+        maintenance_retry_after: None,
+        maintenance_probe_interval: None,
+        maintenance_max_wait: None,
+        share_tokens: None,
+        token_cache: None,
+        warning_handler: None,
+        retry_handler: None,
+        #[cfg(feature = "replay")]
+        replay: None,
         public_key_fp: None,
-        jwt_exp_secs: Some(exp),
-        jwt_refresh_margin_secs: None,
+        jwt_exp: Some(std::time::Duration::from_secs(exp)),
+        jwt_refresh_margin: None,
+        jwt_millisecond_claims: None,
+        clock_skew_check: None,
+        max_clock_skew: None,
         retry_on_unauthorized: None,
+        retry: None,
+        endpoint_suffix: None,
+        privatelink: None,
+        http2_prior_knowledge: None,
+        http2_adaptive_window: None,
+        connect_timeout: None,
+        read_timeout: None,
+        max_concurrent_requests: None,
+        max_rows_per_request: None,
+        max_requests_per_second: None,
+        max_uncommitted_offsets: None,
+        fail_on_uncommitted_limit: None,
+        max_response_bytes: None,
+        serialization_threads: None,
+        append_log_policy: None,
+        batch_id_column: None,
+        batch_checksum: None,
+        checksum_column: None,
+        partition_column: None,
+        misuse_warnings: None,
+        strict_config: None,
     }
 }
 
@@ -238,8 +431,8 @@ fn clamps_short_expiry_and_warns_once() {
 
     assert_eq!(
         exp.saturating_sub(iat),
-        30_000,
-        "short lifetimes should clamp to 30 seconds (milliseconds)"
+        30,
+        "short lifetimes should clamp to 30 seconds"
     );
 
     let warn_logs: Vec<&String> = logs
@@ -254,18 +447,77 @@ fn clamps_short_expiry_and_warns_once() {
     );
 }
 
+/// Regression test: `iat` and `exp` use the same unit, seconds unless the
+/// legacy millisecond claims are requested.
+#[test]
+fn claim_units_are_consistent() {
+    let mut cfg = config_with_exp_secs(120);
+    let secs = decode_jwt_payload(&generate_assertion(&cfg).expect("jwt"));
+    let now_secs = super::now_millis().unwrap() / 1_000;
+    let iat = secs["iat"].as_u64().unwrap();
+    assert!(
+        iat.abs_diff(now_secs) <= 5,
+        "iat {iat} should be in seconds"
+    );
+    assert_eq!(secs["exp"].as_u64().unwrap() - iat, 120);
+
+    cfg.jwt_millisecond_claims = Some(true);
+    let millis = decode_jwt_payload(&generate_assertion(&cfg).expect("jwt"));
+    let iat = millis["iat"].as_u64().unwrap();
+    assert!(
+        iat.abs_diff(now_secs * 1_000) <= 5_000,
+        "iat {iat} should be in milliseconds"
+    );
+    assert_eq!(millis["exp"].as_u64().unwrap() - iat, 120_000);
+}
+
+#[tokio::test]
+async fn regenerated_jwt_differs_within_a_second() {
+    let cfg = config_with_exp_secs(120);
+    let mut ctx = JwtContext::new(&cfg, 30).expect("context");
+    let first = ctx
+        .ensure_valid(&cfg, &TracingTelemetry)
+        .await
+        .expect("first jwt");
+    ctx.invalidate();
+    let second = ctx
+        .ensure_valid(&cfg, &TracingTelemetry)
+        .await
+        .expect("second jwt");
+    assert_ne!(first, second);
+}
+
+#[test]
+fn refresh_stagger_stays_below_lifetime() {
+    let cfg = config_with_exp_secs(120);
+    let mut ctx = JwtContext::new(&cfg, 30).expect("context");
+    ctx.stagger_refresh(20);
+    assert_eq!(ctx.refresh_margin_secs, 50);
+    ctx.stagger_refresh(500);
+    assert_eq!(ctx.refresh_margin_secs, 119);
+}
+
 #[tokio::test]
 async fn refreshes_token_when_near_expiry() {
     let cfg = config_with_exp_secs(60);
     let mut ctx = JwtContext::new(&cfg, 30).expect("context");
 
     // First call should produce a token we can reuse until margin threshold hit.
-    let first = ctx.ensure_valid(&cfg).expect("first token");
+    let first = ctx
+        .ensure_valid(&cfg, &TracingTelemetry)
+        .await
+        .expect("first token");
 
     // Simulate time passage so remaining TTL drops below margin.
     ctx.force_issued_at(super::now_millis().unwrap().saturating_sub(40_000));
 
-    let (logs, second) = with_captured_logs(|| ctx.ensure_valid(&cfg).expect("refresh token"));
+    let (lines, guard) = capture_logs();
+    let second = ctx
+        .ensure_valid(&cfg, &TracingTelemetry)
+        .await
+        .expect("refresh token");
+    drop(guard);
+    let logs = drain_logs(lines);
 
     assert_ne!(
         first, second,
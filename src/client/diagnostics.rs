@@ -0,0 +1,119 @@
+//! State retained for [`StreamingIngestClient::diagnostics`].
+//!
+//! [`StreamingIngestClient::diagnostics`]: crate::StreamingIngestClient::diagnostics
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use jiff::Timestamp;
+use serde_json::{Value, json};
+
+use crate::telemetry::{ResponseHeaders, TokenKind};
+
+/// Recent retries and errors kept per client.
+const HISTORY_LIMIT: usize = 20;
+
+/// Shared by a client, its clones, and its channels.
+#[derive(Default)]
+pub(crate) struct DiagnosticsRecorder {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    channels: BTreeMap<String, ChannelOffsets>,
+    retries: VecDeque<Value>,
+    errors: VecDeque<Value>,
+}
+
+struct ChannelOffsets {
+    committed: u64,
+    pushed: u64,
+}
+
+fn push_bounded(history: &mut VecDeque<Value>, entry: Value) {
+    if history.len() == HISTORY_LIMIT {
+        history.pop_front();
+    }
+    history.push_back(entry);
+}
+
+fn token_label(token: TokenKind) -> &'static str {
+    match token {
+        TokenKind::Control => "control",
+        TokenKind::Scoped => "scoped",
+    }
+}
+
+impl DiagnosticsRecorder {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn channel_offsets(&self, channel: &str, committed: u64, pushed: u64) {
+        self.state()
+            .channels
+            .insert(channel.to_string(), ChannelOffsets { committed, pushed });
+    }
+
+    pub(crate) fn channel_closed(&self, channel: &str) {
+        self.state().channels.remove(channel);
+    }
+
+    /// Records a retry decision; `retried` is false when the client gave up.
+    pub(crate) fn retry(
+        &self,
+        token: TokenKind,
+        status: u16,
+        retried: bool,
+        headers: &ResponseHeaders,
+    ) {
+        let mut entry = json!({
+            "at": Timestamp::now().to_string(),
+            "token": token_label(token),
+            "status": status,
+            "outcome": if retried { "retried" } else { "gave_up" },
+        });
+        if !headers.is_empty() {
+            entry["headers"] = headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), Value::from(value)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        push_bounded(&mut self.state().retries, entry);
+    }
+
+    pub(crate) fn error(&self, context: &str, error: &crate::Error) {
+        push_bounded(
+            &mut self.state().errors,
+            json!({
+                "at": Timestamp::now().to_string(),
+                "context": context,
+                "error": error.to_string(),
+            }),
+        );
+    }
+
+    pub(crate) fn snapshot(&self) -> (Value, Value, Value) {
+        let state = self.state();
+        let channels = state
+            .channels
+            .iter()
+            .map(|(name, offsets)| {
+                (
+                    name.clone(),
+                    json!({
+                        "last_committed_offset_token": offsets.committed,
+                        "last_pushed_offset_token": offsets.pushed,
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        (
+            Value::Object(channels),
+            Value::from(Vec::from(state.retries.clone())),
+            Value::from(Vec::from(state.errors.clone())),
+        )
+    }
+}
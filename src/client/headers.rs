@@ -0,0 +1,61 @@
+//! Caller-supplied headers added to every request.
+
+use reqwest::RequestBuilder;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::config::HeaderProvider;
+use crate::{Config, Error};
+
+/// Headers layered onto each request after the client's own, in order:
+/// `Config::default_headers`, the [`HeaderProvider`], then channel overrides.
+/// Later layers replace earlier values for the same name.
+#[derive(Clone, Default)]
+pub(crate) struct ExtraHeaders {
+    defaults: HeaderMap,
+    provider: Option<HeaderProvider>,
+    channel: HeaderMap,
+}
+
+impl ExtraHeaders {
+    pub(crate) fn from_config(config: &Config) -> Result<Self, Error> {
+        Ok(Self {
+            defaults: parse_default_headers(config)?,
+            provider: config.header_provider.clone(),
+            channel: HeaderMap::new(),
+        })
+    }
+
+    /// Replaces or adds channel-level headers.
+    pub(crate) fn set_channel(&mut self, headers: HeaderMap) {
+        for (name, value) in headers.iter() {
+            self.channel.insert(name.clone(), value.clone());
+        }
+    }
+
+    pub(crate) fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if !self.defaults.is_empty() {
+            request = request.headers(self.defaults.clone());
+        }
+        if let Some(provider) = &self.provider {
+            request = request.headers(provider());
+        }
+        if !self.channel.is_empty() {
+            request = request.headers(self.channel.clone());
+        }
+        request
+    }
+}
+
+/// Converts `Config::default_headers` into a [`HeaderMap`], rejecting invalid
+/// names and values.
+pub(crate) fn parse_default_headers(config: &Config) -> Result<HeaderMap, Error> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in config.default_headers.iter().flatten() {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| Error::Config(format!("Invalid header name '{name}': {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| Error::Config(format!("Invalid value for header '{name}': {e}")))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
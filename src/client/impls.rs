@@ -2,47 +2,64 @@ use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
+use bytes::Bytes;
 use reqwest::{Client, StatusCode};
 use serde::Serialize;
-use tokio::sync::Mutex;
-use tokio::time::sleep;
-use tracing::{error, info, warn};
+use tokio::sync::Semaphore;
+use tokio::time::{Instant, sleep, timeout};
+use tracing::{Instrument, error, info, info_span, warn};
 
-use super::AuthTokenState;
+use super::append_log::AppendLog;
+use super::headers::ExtraHeaders;
+use super::maintenance::MaintenanceWindow;
+use super::misuse::{MisuseDetector, STATIC_TOKEN_MARGIN_SECS};
+use super::stagger;
+use super::token_info::jwt_times;
+use super::{
+    AuthTokenState, Capabilities, ClientMetrics, ScopedTokenProvider, ServerApiVersion, TokenInfo,
+    TokenRefreshMetrics, TokenSnapshot,
+};
+#[cfg(feature = "crypto")]
+use crate::client::crypto::{DEFAULT_REFRESH_MARGIN_SECS, JwtContext};
+use crate::endpoints::{self, PipeRef};
+use crate::interceptor::intercept;
+use crate::telemetry::{
+    AuthMode, ClientSettings, ResponseHeaders, TaggedSink, TelemetryEvent, TelemetrySink,
+    TokenKind, TracingTelemetry, enabled_features,
+};
 use crate::{
-    StreamingIngestClient, channel::StreamingIngestChannel, client::crypto::JwtContext,
-    config::Config, errors::Error,
+    AuthScheme, Lint, LogPolicy, StreamingIngestClient, Warning,
+    channel::{StreamingIngestChannel, WRITER_GENERATION_LIMIT, channel_span},
+    commit_wait::doubled,
+    config::{Config, MAX_OPEN_CONFLICT_RETRIES},
+    errors::{ChannelConflict, Error, OperationKind, RetryEvent},
+    types::{ChannelStatus, OpenChannelOptions, OpenChannelResponse},
 };
 
-const USER_AGENT: &str = "snowpipe-streaming-rust-sdk/0.1.0";
-const DEFAULT_REFRESH_MARGIN_SECS: u64 = 30;
-const BACKOFF_DELAY_SECS: u64 = 2;
-
-struct TokenRequestPolicy<
-    FetchFn,
-    RefreshFn,
-    BuildAuthErrFn,
-    RetryLogFn,
-    FailLogFn,
-    RateLogFn,
-    FetchFut,
-    RefreshFut,
-> where
+const USER_AGENT: &str = concat!("snowpipe-streaming-rust-sdk/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_SCOPED_REFRESH_MARGIN_SECS: u64 = 60;
+const DEFAULT_REFRESH_COOLDOWN_SECS: u64 = 5;
+const DEFAULT_MAX_CLOCK_SKEW_SECS: u64 = 30;
+const DEFAULT_OPEN_CONFLICT_RETRIES: u32 = 3;
+/// Longest wait before the first retry of a conflicting channel open; it
+/// doubles with each further retry.
+const OPEN_CONFLICT_BACKOFF: Duration = Duration::from_millis(200);
+/// Cap on the doubling conflict back-off, before jitter.
+const MAX_OPEN_CONFLICT_BACKOFF: Duration = Duration::from_secs(10);
+
+struct TokenRequestPolicy<FetchFn, RefreshFn, BuildAuthErrFn, FetchFut, RefreshFut>
+where
     FetchFn: FnMut() -> FetchFut,
-    RefreshFn: FnMut() -> RefreshFut,
+    RefreshFn: FnMut(&str) -> RefreshFut,
     BuildAuthErrFn: Fn(String) -> Error,
-    RetryLogFn: Fn(),
-    FailLogFn: Fn(),
-    RateLogFn: Fn(u64),
     FetchFut: Future<Output = Result<String, Error>>,
     RefreshFut: Future<Output = Result<(), Error>>,
 {
+    operation: OperationKind,
+    token_kind: TokenKind,
     allow_unauthorized_retry: bool,
     fetch_token: FetchFn,
     refresh_token: RefreshFn,
-    unauthorized_retry_log: RetryLogFn,
-    unauthorized_fail_log: FailLogFn,
-    rate_limit_log: RateLogFn,
     build_auth_error: BuildAuthErrFn,
 }
 
@@ -59,19 +76,70 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
     /// * `SNOWFLAKE_ACCOUNT` - Snowflake account name
     /// * `SNOWFLAKE_USERNAME` - Snowflake username
     /// * `SNOWFLAKE_URL` - Snowflake control-plane base URL
+    ///
+    /// # Example
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), snowpipe_streaming::Error> {
+    /// # let server = wiremock::MockServer::start().await;
+    /// # let ok = |b: serde_json::Value| wiremock::ResponseTemplate::new(200).set_body_json(b);
+    /// # let status = serde_json::json!({"channel_statuses": {"ch": {"last_committed_offset_token": "5"}}});
+    /// # for (verb, route, resp) in [
+    /// #     ("GET", "/hostname$", wiremock::ResponseTemplate::new(200).set_body_string(server.uri())),
+    /// #     ("POST", "/oauth/token$", wiremock::ResponseTemplate::new(200).set_body_string("scoped")),
+    /// #     ("PUT", "/channels/ch$", ok(serde_json::json!({"next_continuation_token": "c0", "channel_status": {}}))),
+    /// #     ("POST", "/rows$", ok(serde_json::json!({"next_continuation_token": "c1"}))),
+    /// #     ("POST", ":bulk-channel-status$", ok(status)),
+    /// #     ("DELETE", "/channels/ch$", wiremock::ResponseTemplate::new(200)),
+    /// # ] {
+    /// #     wiremock::Mock::given(wiremock::matchers::method(verb))
+    /// #         .and(wiremock::matchers::path_regex(route))
+    /// #         .respond_with(resp)
+    /// #         .mount(&server)
+    /// #         .await;
+    /// # }
+    /// # let config = snowpipe_streaming::Config::from_values(
+    /// #     "user", None, "acct", server.uri(), Some("jwt".into()), None, None, None, None, None,
+    /// # );
+    /// use snowpipe_streaming::StreamingIngestClient;
+    ///
+    /// let client = StreamingIngestClient::<serde_json::Value>::new(
+    ///     "svc", "MY_DB", "MY_SCHEMA", "MY_PIPE", config,
+    /// )
+    /// .await?;
+    /// assert!(client.ingest_host.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn new(
+        client_name: &str,
+        db_name: &str,
+        schema_name: &str,
+        pipe_name: &str,
+        config: Config,
+    ) -> Result<Self, Error> {
+        Self::new_with_telemetry(
+            client_name,
+            db_name,
+            schema_name,
+            pipe_name,
+            config,
+            Arc::new(TracingTelemetry),
+        )
+        .await
+    }
+
+    /// Like [`new`](Self::new), reporting auth, retry, and append events to
+    /// `telemetry` instead of logging them through `tracing`.
+    pub async fn new_with_telemetry(
         _client_name: &str,
         db_name: &str,
         schema_name: &str,
         pipe_name: &str,
         config: Config,
+        telemetry: Arc<dyn TelemetrySink>,
     ) -> Result<Self, Error> {
-        let control_host = if config.url.starts_with("http") {
-            config.url.clone()
-        } else {
-            format!("https://{}", config.url)
-        };
-        let control_host = control_host.replace("_", "-").to_lowercase();
+        let control_host = config.control_host()?;
         // Validate control host is a proper URL before performing any network calls
         let _ = reqwest::Url::parse(&control_host).map_err(|e| {
             Error::Config(format!(
@@ -80,25 +148,221 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
             ))
         })?;
 
-        let refresh_margin_secs = config
-            .jwt_refresh_margin_secs
-            .unwrap_or(DEFAULT_REFRESH_MARGIN_SECS);
+        let auth_state = Self::auth_state(&config, config.effective_auth_scheme())?;
+        let mut client = Self::assemble(
+            db_name,
+            schema_name,
+            pipe_name,
+            config,
+            telemetry,
+            control_host,
+            auth_state,
+            None,
+        )?;
+        client.stagger_startup().await;
+        let shared_host = client
+            .shared_tokens
+            .as_ref()
+            .and_then(|shared| shared.ingest_host.get().cloned());
+        match client.auth_config.ingest_host.clone().or(shared_host) {
+            Some(host) => {
+                client.ingest_host = Some(host);
+                client.prefetch().await?;
+            }
+            None => {
+                client.discover_ingest_host().await?;
+                if let (Some(shared), Some(host)) = (&client.shared_tokens, &client.ingest_host) {
+                    let _ = shared.ingest_host.set(host.clone());
+                }
+                // Connect to the newly discovered host while the scoped token
+                // is exchanged with the control plane.
+                client.ready().await?;
+            }
+        }
+        client.report_initialized();
+        Ok(client)
+    }
 
-        let auth_state = if let Some(token) = config.jwt_token.clone().filter(|t| !t.is_empty()) {
-            warn!(
-                "jwt_token configuration is deprecated; supply a private key so the library can refresh automatically"
-            );
-            AuthTokenState::Provided { token }
-        } else {
-            let ctx = JwtContext::new(&config, refresh_margin_secs)?;
-            AuthTokenState::Managed(Arc::new(Mutex::new(ctx)))
-        };
+    /// Token broker mode: builds a client whose scoped tokens come from
+    /// `provider` instead of being exchanged for a JWT minted from a private
+    /// key, so ingest workers never hold Snowflake credentials.
+    ///
+    /// The control plane is never contacted, so `config.ingest_host` is
+    /// required and `user`, `url`, and key settings are unused. The provider
+    /// is called once here, again whenever the ingest host answers 401, and
+    /// ahead of expiry as configured by `scoped_token_refresh_margin` and
+    /// `scoped_token_max_age`.
+    ///
+    /// ```no_run
+    /// # async fn fetch_from_broker() -> Result<String, snowpipe_streaming::Error> { todo!() }
+    /// # async fn run() -> Result<(), snowpipe_streaming::Error> {
+    /// use std::sync::Arc;
+    /// use snowpipe_streaming::{Config, StreamingIngestClient};
+    ///
+    /// let mut config = Config::from_values("", None, "MY_ACCOUNT", "", None, None, None, None, None, None);
+    /// config.ingest_host = Some("my-account.ingest.snowflakecomputing.com".into());
+    /// let client = StreamingIngestClient::<serde_json::Value>::new_with_token_provider(
+    ///     "worker", "MY_DB", "MY_SCHEMA", "MY_PIPE", config,
+    ///     Arc::new(|| fetch_from_broker()),
+    /// )
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_with_token_provider(
+        client_name: &str,
+        db_name: &str,
+        schema_name: &str,
+        pipe_name: &str,
+        config: Config,
+        provider: Arc<dyn ScopedTokenProvider>,
+    ) -> Result<Self, Error> {
+        Self::new_with_token_provider_and_telemetry(
+            client_name,
+            db_name,
+            schema_name,
+            pipe_name,
+            config,
+            provider,
+            Arc::new(TracingTelemetry),
+        )
+        .await
+    }
+
+    /// Like [`new_with_token_provider`](Self::new_with_token_provider),
+    /// reporting token refreshes, retries, and appends to `telemetry`.
+    pub async fn new_with_token_provider_and_telemetry(
+        _client_name: &str,
+        db_name: &str,
+        schema_name: &str,
+        pipe_name: &str,
+        config: Config,
+        provider: Arc<dyn ScopedTokenProvider>,
+        telemetry: Arc<dyn TelemetrySink>,
+    ) -> Result<Self, Error> {
+        let ingest_host = config.ingest_host.clone().ok_or_else(|| {
+            Error::Config("ingest_host is required when scoped tokens come from a provider".into())
+        })?;
+        let control_host = config.control_host().unwrap_or_default();
+        let mut client = Self::assemble(
+            db_name,
+            schema_name,
+            pipe_name,
+            config,
+            telemetry,
+            control_host,
+            AuthTokenState::Brokered,
+            Some(provider),
+        )?;
+        client.ingest_host = Some(ingest_host);
+        client.stagger_startup().await;
+        client.prefetch().await?;
+        client.report_initialized();
+        Ok(client)
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn assemble(
+        db_name: &str,
+        schema_name: &str,
+        pipe_name: &str,
+        config: Config,
+        telemetry: Arc<dyn TelemetrySink>,
+        control_host: String,
+        auth_state: AuthTokenState,
+        scoped_token_provider: Option<Arc<dyn ScopedTokenProvider>>,
+    ) -> Result<Self, Error> {
+        for name in [db_name, schema_name, pipe_name] {
+            validate_identifier(name)?;
+        }
+        let auth_scheme = config.effective_auth_scheme();
+        let config_tags = config.tags.clone().unwrap_or_default();
+        let append_log_policy = config.append_log_policy.unwrap_or_default();
+        let misuse_warnings = config.misuse_warnings.unwrap_or(true);
+        let maintenance = Arc::new(MaintenanceWindow::new(&config));
         let account = config.account.clone();
         let retry_on_unauthorized = config.retry_on_unauthorized.unwrap_or(true);
-        let http_client = Client::new();
+        let retry_plan = config.retry.unwrap_or_default();
+        let scoped_refresh_offset_secs = stagger::jitter_secs(config.refresh_jitter);
+        let shared_tokens = config
+            .effective_token_cache()
+            .map(|cache| cache.entry(&config, &control_host));
+        #[cfg(feature = "crypto")]
+        let auth_state = match (&shared_tokens, auth_state) {
+            (Some(shared), AuthTokenState::Managed(ctx)) => {
+                AuthTokenState::Managed(shared.jwt.get_or_init(|| ctx).clone())
+            }
+            (_, auth_state) => auth_state,
+        };
+        let http_client = build_http_client(&config)?;
+        let extra_headers = ExtraHeaders::from_config(&config)?;
+        let request_limit = match config.max_concurrent_requests {
+            Some(0) => {
+                return Err(Error::Config(
+                    "max_concurrent_requests must be at least 1".into(),
+                ));
+            }
+            Some(limit) => Some(Arc::new(Semaphore::new(limit))),
+            None => None,
+        };
+        if config
+            .channel_open_conflict_retries
+            .is_some_and(|retries| retries > MAX_OPEN_CONFLICT_RETRIES)
+        {
+            return Err(Error::Config(format!(
+                "channel_open_conflict_retries must be at most {MAX_OPEN_CONFLICT_RETRIES}"
+            )));
+        }
+        if config
+            .writer_generation
+            .is_some_and(|generation| generation >= WRITER_GENERATION_LIMIT)
+        {
+            return Err(Error::Config(format!(
+                "writer_generation must be below {WRITER_GENERATION_LIMIT}"
+            )));
+        }
+        if config.maintenance_probe_interval == Some(Duration::ZERO) {
+            return Err(Error::Config(
+                "maintenance_probe_interval must be greater than zero".into(),
+            ));
+        }
+        if matches!(
+            append_log_policy,
+            LogPolicy::Sample(0) | LogPolicy::Aggregate(0)
+        ) {
+            return Err(Error::Config(
+                "append_log_policy interval must be at least 1".into(),
+            ));
+        }
+        if config.max_rows_per_request == Some(0)
+            || config.max_requests_per_second == Some(0)
+            || config.max_uncommitted_offsets == Some(0)
+        {
+            return Err(Error::Config(
+                "max_rows_per_request, max_requests_per_second, and max_uncommitted_offsets must be at least 1"
+                    .into(),
+            ));
+        }
+        #[cfg(feature = "parallel")]
+        let serializer = match config.serialization_threads {
+            Some(0) => {
+                return Err(Error::Config(
+                    "serialization_threads must be at least 1".into(),
+                ));
+            }
+            Some(threads) => Some(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("snowpipe-serialize-{i}"))
+                    .build()
+                    .map_err(|e| {
+                        Error::Config(format!("failed to start serialization threads: {e}"))
+                    })?,
+            )),
+            None => None,
+        };
 
-        let mut client = StreamingIngestClient {
+        Ok(StreamingIngestClient {
             _marker: std::marker::PhantomData,
             db_name: db_name.to_string(),
             schema_name: schema_name.to_string(),
@@ -108,37 +372,120 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
             auth_state,
             auth_config: config,
             retry_on_unauthorized,
-            backoff_delay: Duration::from_secs(BACKOFF_DELAY_SECS),
+            retry_plan,
             http_client,
-            auth_token_type: String::from("KEYPAIR_JWT"),
+            auth_scheme,
             ingest_host: None,
-            scoped_token: Arc::new(Mutex::new(None)),
-        };
-        client.discover_ingest_host().await?;
-        client.get_scoped_token().await?;
-        Ok(client)
+            scoped_token: shared_tokens
+                .as_ref()
+                .map_or_else(Arc::default, |shared| shared.scoped_token.clone()),
+            scoped_token_acquired_at: shared_tokens.as_ref().map_or_else(Arc::default, |shared| {
+                shared.scoped_token_acquired_at.clone()
+            }),
+            scoped_token_provider,
+            scoped_token_failure: shared_tokens
+                .as_ref()
+                .map_or_else(Arc::default, |shared| shared.scoped_token_failure.clone()),
+            telemetry: Arc::new(TaggedSink::new(telemetry, config_tags)),
+            request_limit,
+            extra_headers,
+            diagnostics: Arc::default(),
+            append_log: Arc::new(AppendLog::new(append_log_policy)),
+            metrics: Arc::default(),
+            misuse: Arc::new(MisuseDetector::new(misuse_warnings)),
+            maintenance,
+            capabilities: Arc::default(),
+            clock_skew: Arc::default(),
+            scoped_refresh_offset_secs,
+            shared_tokens,
+            #[cfg(feature = "chaos")]
+            chaos: None,
+            #[cfg(feature = "parallel")]
+            serializer,
+        })
+    }
+
+    fn auth_state(config: &Config, scheme: AuthScheme) -> Result<AuthTokenState, Error> {
+        let jwt_token = config.jwt_token.clone().filter(|t| !t.is_empty());
+        match scheme {
+            AuthScheme::KeypairJwt => match jwt_token {
+                Some(token) => {
+                    warn!(
+                        "jwt_token configuration is deprecated; supply a private key so the library can refresh automatically"
+                    );
+                    config.warn(Warning::DeprecatedConfig {
+                        field: "jwt_token",
+                        advice: "supply a private key so the library can refresh automatically",
+                    });
+                    Ok(AuthTokenState::Provided { token })
+                }
+                None => Self::managed_auth_state(config),
+            },
+            AuthScheme::Oauth => jwt_token
+                .map(|token| AuthTokenState::Provided { token })
+                .ok_or_else(|| {
+                    Error::Config("the oauth auth scheme requires an access token in jwt_token".into())
+                }),
+            AuthScheme::ProgrammaticAccessToken => config
+                .programmatic_access_token
+                .clone()
+                .filter(|t| !t.is_empty())
+                .map(|token| AuthTokenState::Provided { token })
+                .ok_or_else(|| {
+                    Error::Config(
+                        "the programmatic_access_token auth scheme requires programmatic_access_token"
+                            .into(),
+                    )
+                }),
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    fn managed_auth_state(config: &Config) -> Result<AuthTokenState, Error> {
+        let refresh_margin_secs = config
+            .jwt_refresh_margin
+            .map_or(DEFAULT_REFRESH_MARGIN_SECS, |margin| margin.as_secs());
+        let mut ctx = JwtContext::new(config, refresh_margin_secs)?;
+        ctx.stagger_refresh(stagger::jitter_secs(config.refresh_jitter));
+        Ok(AuthTokenState::Managed(Arc::new(tokio::sync::Mutex::new(
+            ctx,
+        ))))
+    }
+
+    #[cfg(not(feature = "crypto"))]
+    fn managed_auth_state(_config: &Config) -> Result<AuthTokenState, Error> {
+        Err(Error::Config(
+            "jwt_token or programmatic_access_token is required when built without the `crypto` feature".into(),
+        ))
     }
 
     // Removed get_control_plane_token; JWT is generated locally during construction.
 
     async fn discover_ingest_host(&mut self) -> Result<(), Error> {
-        let url = format!("{}/v2/streaming/hostname", self.control_host);
-        let auth_type = self.auth_token_type.clone();
+        let url = endpoints::hostname(&self.control_host)?;
+        let scheme = self.auth_scheme;
         let response = self
-            .send_with_jwt(move |client, token| {
+            .send_with_jwt(OperationKind::DiscoverIngestHost, move |client, token| {
                 client
-                    .get(&url)
+                    .get(url.clone())
                     .header("Authorization", format!("Bearer {}", token))
-                    .header("X-Snowflake-Authorization-Token-Type", auth_type.as_str())
+                    .header("X-Snowflake-Authorization-Token-Type", scheme.token_type())
                     .header("User-Agent", USER_AGENT)
             })
-            .await?;
+            .await
+            .map_err(|e| e.in_operation(OperationKind::DiscoverIngestHost, None))?;
 
         let status = response.status();
-        let body = response.text().await.unwrap_or_default();
+        let body = if status.is_success() {
+            self.read_text(response).await
+        } else {
+            self.read_error_text(response).await
+        }
+        .map_err(|e| e.in_operation(OperationKind::DiscoverIngestHost, None))?;
         if status.is_success() {
-            info!("discover ingest host ok: host='{}'", body);
-            self.ingest_host = Some(body);
+            let host = self.auth_config.resolve_ingest_host(&body);
+            info!("discover ingest host ok: host='{}'", host);
+            self.ingest_host = Some(host);
             Ok(())
         } else {
             error!(
@@ -150,33 +497,49 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
     }
 
     async fn get_scoped_token(&self) -> Result<(), Error> {
+        if let Some(provider) = &self.scoped_token_provider {
+            let token = provider.scoped_token().await?;
+            info!("scoped token received from provider (len={})", token.len());
+            *self.scoped_token.lock().await = Some(token);
+            *self.scoped_token_acquired_at.lock().await = Some(jiff::Timestamp::now());
+            return Ok(());
+        }
         let scope = self
             .ingest_host
             .as_ref()
             .expect("Ingest host not set before requesting scoped token")
             .to_string();
-        let url = format!("{}/oauth/token", self.control_host);
+        let url = endpoints::oauth_token(&self.control_host)?;
         let body = format!(
             "grant_type=urn:ietf:params:oauth:grant-type:jwt-bearer&scope={}",
             scope
         );
 
+        let scheme = self.auth_scheme;
         let response = self
-            .send_with_jwt(move |client, token| {
+            .send_with_jwt(OperationKind::ExchangeScopedToken, move |client, token| {
                 client
-                    .post(&url)
+                    .post(url.clone())
                     .header("Content-Type", "application/x-www-form-urlencoded")
                     .header("Authorization", format!("Bearer {}", token))
+                    .header("X-Snowflake-Authorization-Token-Type", scheme.token_type())
                     .header("User-Agent", USER_AGENT)
                     .body(body.clone())
             })
-            .await?;
+            .await
+            .map_err(|e| e.in_operation(OperationKind::ExchangeScopedToken, None))?;
 
         let status = response.status();
-        let text = response.text().await.unwrap_or_default();
+        let text = if status.is_success() {
+            self.read_text(response).await
+        } else {
+            self.read_error_text(response).await
+        }
+        .map_err(|e| e.in_operation(OperationKind::ExchangeScopedToken, None))?;
         if status.is_success() {
             info!("scoped token acquired (len={})", text.len());
             *self.scoped_token.lock().await = Some(text);
+            *self.scoped_token_acquired_at.lock().await = Some(jiff::Timestamp::now());
             Ok(())
         } else {
             error!(
@@ -187,154 +550,721 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
         }
     }
 
+    /// Returns the cached scoped token, first replacing it when it is missing,
+    /// within its refresh margin, or older than `scoped_token_max_age`.
+    /// If a proactive refresh fails, the cached token is used until it expires.
+    async fn ensure_scoped_token(&self) -> Result<String, Error> {
+        let Some(token) = self.scoped_token.lock().await.clone() else {
+            self.refresh_scoped_token(None, None).await?;
+            return self.cached_scoped_token().await;
+        };
+        let cfg = &self.auth_config;
+        let now = jiff::Timestamp::now().as_second();
+        let acquired_at = *self.scoped_token_acquired_at.lock().await;
+        let age_secs = acquired_at.map_or(0, |t| (now - t.as_second()).max(0) as u64);
+        let expires_at = jwt_times(&token).1.map(|t| t.as_second());
+        let remaining_secs = expires_at.map(|exp| (exp - now).max(0) as u64);
+        let margin = self.scoped_refresh_margin_secs();
+        let near_expiry = remaining_secs.is_some_and(|remaining| remaining <= margin);
+        let too_old = cfg
+            .scoped_token_max_age
+            .is_some_and(|max_age| age_secs >= max_age.as_secs());
+        if !near_expiry && !too_old {
+            return Ok(token);
+        }
+
+        let event = TelemetryEvent::ScopedTokenRefresh {
+            age_secs,
+            remaining_secs,
+        };
+        match self.refresh_scoped_token(Some(&token), Some(event)).await {
+            Ok(()) => self.cached_scoped_token().await,
+            Err(_) if expires_at.is_none_or(|exp| exp > now) => Ok(token),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolves once the client can open channels and append without
+    /// waiting on the control plane or a new connection: the scoped token is
+    /// fresh (exchanged again if it is within its refresh margin) and a
+    /// connection to the ingest host is open. Await it during service
+    /// warm-up, e.g. before reporting readiness, when time may have passed
+    /// since construction or `Config::warm_up_ingest_host` is off.
+    ///
+    /// Fails only when the scoped token cannot be obtained; a failed
+    /// connection is logged, and left for the first request to retry.
+    pub async fn ready(&self) -> Result<(), Error> {
+        let (token, warm_up) = tokio::join!(self.ensure_scoped_token(), self.warm_up());
+        token?;
+        if let Err(e) = warm_up {
+            warn!("warm-up of ingest host connection failed: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Obtains the scoped token for a configured ingest host during
+    /// construction, and also connects to it when
+    /// `Config::warm_up_ingest_host` is set.
+    async fn prefetch(&self) -> Result<(), Error> {
+        if self.auth_config.warm_up_ingest_host.unwrap_or(false) {
+            self.ready().await
+        } else {
+            self.ensure_scoped_token().await.map(drop)
+        }
+    }
+
+    /// Emits [`TelemetryEvent::ClientInitialized`] with the effective settings.
+    fn report_initialized(&self) {
+        let config = &self.auth_config;
+        let auth_mode = match self.auth_state {
+            #[cfg(feature = "crypto")]
+            AuthTokenState::Managed(_) => AuthMode::Managed,
+            AuthTokenState::Provided { .. } => AuthMode::Provided,
+            AuthTokenState::Brokered => AuthMode::Brokered,
+        };
+        #[cfg(feature = "crypto")]
+        let jwt_refresh_margin = (auth_mode == AuthMode::Managed).then(|| {
+            config
+                .jwt_refresh_margin
+                .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_MARGIN_SECS))
+        });
+        #[cfg(not(feature = "crypto"))]
+        let jwt_refresh_margin = None;
+        let settings = ClientSettings {
+            control_host: Some(self.control_host.clone()).filter(|host| !host.is_empty()),
+            ingest_host: self.ingest_host.clone().unwrap_or_default(),
+            auth_scheme: self.auth_scheme,
+            auth_mode,
+            api_version: config.api_version.unwrap_or_default(),
+            retry: self.retry_plan,
+            retry_on_unauthorized: self.retry_on_unauthorized,
+            jwt_refresh_margin,
+            scoped_token_refresh_margin: config
+                .scoped_token_refresh_margin
+                .unwrap_or(Duration::from_secs(DEFAULT_SCOPED_REFRESH_MARGIN_SECS)),
+            scoped_token_max_age: config.scoped_token_max_age,
+            token_refresh_cooldown: config
+                .token_refresh_cooldown
+                .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_COOLDOWN_SECS)),
+            refresh_jitter: config.refresh_jitter,
+            features: enabled_features(),
+        };
+        self.telemetry.record(&TelemetryEvent::ClientInitialized {
+            settings: Box::new(settings),
+        });
+    }
+
+    /// Remaining scoped token lifetime at which it is replaced, including
+    /// this client's refresh jitter.
+    fn scoped_refresh_margin_secs(&self) -> u64 {
+        self.auth_config
+            .scoped_token_refresh_margin
+            .map_or(DEFAULT_SCOPED_REFRESH_MARGIN_SECS, |margin| {
+                margin.as_secs()
+            })
+            + self.scoped_refresh_offset_secs
+    }
+
+    /// Sleeps for a random share of `Config::startup_jitter` before the
+    /// first request, so replicas started together spread out.
+    async fn stagger_startup(&self) {
+        let delay = stagger::jitter(self.auth_config.startup_jitter.unwrap_or_default());
+        if !delay.is_zero() {
+            info!("staggering client startup by {:?}", delay);
+            sleep(delay).await;
+        }
+    }
+
+    async fn cached_scoped_token(&self) -> Result<String, Error> {
+        self.scoped_token
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| Error::Auth("no scoped token available".into()))
+    }
+
+    /// Fetches a new scoped token, one caller at a time. Skips the fetch when
+    /// another caller already replaced `stale`, and fails fast while a recent
+    /// failure is cooling down.
+    async fn refresh_scoped_token(
+        &self,
+        stale: Option<&str>,
+        event: Option<TelemetryEvent>,
+    ) -> Result<(), Error> {
+        let mut last_failure = self.scoped_token_failure.lock().await;
+        if self.scoped_token.lock().await.as_deref() != stale {
+            self.metrics.token_refresh_coalesced();
+            return Ok(());
+        }
+        let cooldown = self
+            .auth_config
+            .token_refresh_cooldown
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_COOLDOWN_SECS));
+        if let Some((failed_at, message)) = last_failure.as_ref()
+            && failed_at.elapsed() < cooldown
+        {
+            return Err(Error::Auth(format!(
+                "scoped token refresh is cooling down after a failure: {message}"
+            )));
+        }
+        if let Some(event) = event {
+            let margin = self.scoped_refresh_margin_secs();
+            if let TelemetryEvent::ScopedTokenRefresh {
+                remaining_secs: Some(remaining_secs),
+                ..
+            } = event
+                && remaining_secs <= margin
+            {
+                self.auth_config.warn(Warning::TokenNearExpiry {
+                    token: TokenKind::Scoped,
+                    remaining_secs,
+                });
+            }
+            self.telemetry.record(&event);
+        }
+        let started = Instant::now();
+        let result = self.get_scoped_token().await;
+        self.metrics
+            .token_refresh(started.elapsed(), result.is_ok());
+        match result {
+            Ok(()) => {
+                *last_failure = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.telemetry
+                    .record(&TelemetryEvent::ScopedTokenRefreshFailed { cooldown });
+                *last_failure = Some((std::time::Instant::now(), err.to_string()));
+                Err(err)
+            }
+        }
+    }
+
     async fn ensure_valid_jwt(&self) -> Result<String, Error> {
         match &self.auth_state {
+            #[cfg(feature = "crypto")]
             AuthTokenState::Managed(ctx) => {
                 let mut guard = ctx.lock().await;
-                guard.ensure_valid(&self.auth_config)
+                guard
+                    .ensure_valid(&self.auth_config, self.telemetry.as_ref())
+                    .await
+            }
+            AuthTokenState::Provided { token } => {
+                if let Some(expires_at) = jwt_times(token).1
+                    && expires_at.as_second() - jiff::Timestamp::now().as_second()
+                        <= STATIC_TOKEN_MARGIN_SECS
+                {
+                    self.misuse
+                        .report(&self.auth_config, Lint::StaticTokenExpiring, "jwt_token");
+                }
+                Ok(token.clone())
+            }
+            AuthTokenState::Brokered => Err(Error::Config(
+                "no control-plane credential in token broker mode".into(),
+            )),
+        }
+    }
+
+    /// Reports issue and expiry times of the control-plane JWT and the scoped
+    /// token, e.g. for debugging authentication failures or scheduling
+    /// maintenance around token lifetimes.
+    pub async fn current_token_info(&self) -> TokenInfo {
+        let control = match &self.auth_state {
+            #[cfg(feature = "crypto")]
+            AuthTokenState::Managed(ctx) => ctx.lock().await.snapshot(),
+            AuthTokenState::Provided { token } => {
+                TokenSnapshot::from_token(Some(token), None, false, false)
             }
-            AuthTokenState::Provided { token } => Ok(token.clone()),
+            AuthTokenState::Brokered => TokenSnapshot::from_token(None, None, false, false),
+        };
+        let scoped_token = self.scoped_token.lock().await.clone();
+        let acquired_at = *self.scoped_token_acquired_at.lock().await;
+        let scoped = TokenSnapshot::from_token(scoped_token.as_deref(), acquired_at, true, true);
+        TokenInfo { control, scoped }
+    }
+
+    /// Replaces the 401 retry switch, 429 back-off, and per-attempt timeout
+    /// on this handle only; used for per-channel overrides.
+    pub(crate) fn override_retries(
+        &mut self,
+        retry_on_unauthorized: Option<bool>,
+        backoff: Option<Duration>,
+        attempt_timeout: Option<Duration>,
+    ) {
+        if let Some(retry) = retry_on_unauthorized {
+            self.retry_on_unauthorized = retry;
+        }
+        if let Some(backoff) = backoff {
+            self.retry_plan = self.retry_plan.with_initial_backoff(backoff);
+        }
+        if let Some(limit) = attempt_timeout {
+            self.retry_plan = self.retry_plan.with_attempt_timeout(limit);
+        }
+    }
+
+    /// Observes the retries made by this client and the channels opened
+    /// from it afterwards, replacing any observer set with
+    /// [`Config::on_retry`].
+    pub fn on_retry(
+        &mut self,
+        handler: impl Fn(&RetryEvent<'_>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.auth_config.retry_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Opens a connection to the ingest host ahead of the first append, so
+    /// that request does not pay for DNS resolution and the TLS handshake.
+    /// Returns how long the round trip took.
+    ///
+    /// Sends an unauthenticated `HEAD` to the host root and ignores the
+    /// status; only transport failures are errors. The connection stays in
+    /// the pool shared by all channels. Clients that discover the ingest host,
+    /// or have `Config::warm_up_ingest_host` set, warm up automatically
+    /// during construction. Does nothing when
+    /// replaying a cassette.
+    pub async fn warm_up(&self) -> Result<Duration, Error> {
+        #[cfg(feature = "replay")]
+        if self.auth_config.replay.is_some() {
+            return Ok(Duration::ZERO);
+        }
+        let ingest_host = self.ingest_host.as_ref().expect("Ingest host not set");
+        let url = endpoints::ingest_root(ingest_host)?;
+        let started = std::time::Instant::now();
+        let request = self.http_client.head(url).header("User-Agent", USER_AGENT);
+        intercept(
+            self.auth_config.request_interceptor.as_deref(),
+            self.extra_headers.apply(request),
+        )
+        .map_err(|e| e.in_operation(OperationKind::WarmUp, None))?
+        .send()
+        .await
+        .map_err(|e| {
+            self.transport_error(e)
+                .in_operation(OperationKind::WarmUp, None)
+        })?;
+        let elapsed = started.elapsed();
+        info!("ingest host connection warmed up in {:?}", elapsed);
+        Ok(elapsed)
+    }
+
+    /// Optional features of the Snowflake deployment, as reported by the
+    /// first response carrying a version (usually host discovery). The
+    /// baseline v2 capabilities until then, or if the deployment reports none.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.reported().cloned().unwrap_or_default()
+    }
+
+    /// REST API version reported by the deployment, if any.
+    pub fn server_api_version(&self) -> Option<ServerApiVersion> {
+        self.capabilities.reported()?.api_version()
+    }
+
+    /// Offset of Snowflake's clock from the local one, positive when the
+    /// local clock is behind, as measured by `Config::clock_skew_check` from
+    /// the first response. `None` when the check is off or has not run yet;
+    /// offsets under two seconds are reported as zero.
+    pub fn clock_skew(&self) -> Option<jiff::SignedDuration> {
+        self.clock_skew.measured()
+    }
+
+    /// Append volume since the client was created, across its clones and
+    /// channels. Use [`ClientMetrics::cost_estimate`] to project billing from
+    /// it.
+    pub fn metrics(&self) -> ClientMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Scoped token refreshes since the client was created, across its
+    /// clones and channels; also part of [`metrics`](Self::metrics).
+    pub fn token_refresh_metrics(&self) -> TokenRefreshMetrics {
+        self.metrics.token_refresh_metrics()
+    }
+
+    /// Builds a JSON bundle describing the client's state for bug reports and
+    /// support cases: a configuration summary, hosts, token lifetimes, offsets
+    /// of open channels, and recent retries and errors.
+    ///
+    /// Secrets are never included; credentials are reported only as present
+    /// or absent.
+    pub async fn diagnostics(&self) -> serde_json::Value {
+        let cfg = &self.auth_config;
+        let tokens = self.current_token_info().await;
+        let token_json = |snapshot: &TokenSnapshot| {
+            serde_json::json!({
+                "issued_at": snapshot.issued_at.map(|t| t.to_string()),
+                "expires_at": snapshot.expires_at.map(|t| t.to_string()),
+                "managed": snapshot.managed,
+            })
+        };
+        let config = serde_json::json!({
+            "account": cfg.account,
+            "account_format": cfg.account_format,
+            "user": cfg.user,
+            "login": cfg.login,
+            "url": cfg.url,
+            "auth_scheme": self.auth_scheme.token_type(),
+            "jwt_token": cfg.jwt_token.is_some(),
+            "programmatic_access_token": cfg.programmatic_access_token.is_some(),
+            "private_key": cfg.private_key.is_some(),
+            "private_key_path": cfg.private_key_path.is_some(),
+            "private_key_source": cfg.private_key_source,
+            "private_key_passphrase": cfg.private_key_passphrase.is_some(),
+            "private_key_passphrase_path": cfg.private_key_passphrase_path.is_some(),
+            "passphrase_provider": cfg.passphrase_provider.is_some(),
+            "public_key_fp": cfg.public_key_fp,
+            "jwt_exp": cfg.jwt_exp,
+            "jwt_refresh_margin": cfg.jwt_refresh_margin,
+            "retry_on_unauthorized": self.retry_on_unauthorized,
+            "endpoint_suffix": cfg.endpoint_suffix,
+            "privatelink": cfg.privatelink,
+            "http2_prior_knowledge": cfg.http2_prior_knowledge,
+            "http2_adaptive_window": cfg.http2_adaptive_window,
+            "max_concurrent_requests": cfg.max_concurrent_requests,
+            "max_rows_per_request": cfg.max_rows_per_request,
+            "max_requests_per_second": cfg.max_requests_per_second,
+            "serialization_threads": cfg.serialization_threads,
+            "append_log_policy": cfg.append_log_policy,
+            "batch_id_column": cfg.batch_id_column,
+            "partition_column": cfg.partition_column,
+            "misuse_warnings": cfg.misuse_warnings,
+            "tags": cfg.tags,
+            "default_headers": cfg
+                .default_headers
+                .as_ref()
+                .map(|headers| headers.keys().collect::<Vec<_>>()),
+            "header_provider": cfg.header_provider.is_some(),
+            "request_interceptor": cfg.request_interceptor.is_some(),
+            "fenced_channel_suffix": cfg.fenced_channel_suffix,
+            "writer_generation": cfg.writer_generation,
+            "ingest_host": cfg.ingest_host,
+            "scoped_token_max_age": cfg.scoped_token_max_age,
+            "scoped_token_refresh_margin": cfg.scoped_token_refresh_margin,
+            "token_refresh_cooldown": cfg.token_refresh_cooldown,
+            "warning_handler": cfg.warning_handler.is_some(),
+        });
+        let (channels, recent_retries, recent_errors) = self.diagnostics.snapshot();
+        serde_json::json!({
+            "generated_at": jiff::Timestamp::now().to_string(),
+            "crate_version": env!("CARGO_PKG_VERSION"),
+            "pipe": {
+                "database": self.db_name,
+                "schema": self.schema_name,
+                "pipe": self.pipe_name,
+            },
+            "config": config,
+            "hosts": {
+                "control": self.control_host,
+                "ingest": self.ingest_host,
+            },
+            "tokens": {
+                "control": token_json(&tokens.control),
+                "scoped": token_json(&tokens.scoped),
+            },
+            "channels": channels,
+            "recent_retries": recent_retries,
+            "recent_errors": recent_errors,
+        })
+    }
+
+    fn capabilities_reported(&self, capabilities: &Capabilities) {
+        let Some(version) = capabilities.api_version() else {
+            return;
+        };
+        info!(
+            "server API version {}: tokens_in_body={} gzip={} max_request_bytes={}",
+            version,
+            capabilities.tokens_in_body(),
+            capabilities.gzip(),
+            capabilities.max_request_bytes()
+        );
+        if self
+            .auth_config
+            .api_version
+            .unwrap_or_default()
+            .tokens_in_body()
+            && !capabilities.tokens_in_body()
+        {
+            warn!(
+                "api_version 'preview' is not supported by server API {}; sending append tokens in the query string",
+                version
+            );
+            self.auth_config.warn(Warning::CapabilityUnavailable {
+                capability: "tokens-in-body",
+                server_version: version,
+            });
+        }
+    }
+
+    async fn clock_skew_measured(&self, skew: jiff::SignedDuration) {
+        let threshold = self
+            .auth_config
+            .max_clock_skew
+            .unwrap_or(Duration::from_secs(DEFAULT_MAX_CLOCK_SKEW_SECS));
+        info!("clock skew from Snowflake: {:?}", skew);
+        if skew.unsigned_abs() > threshold {
+            warn!(
+                "local clock differs from Snowflake by {:?} (threshold {:?}); adjusting JWT times",
+                skew, threshold
+            );
+            self.auth_config
+                .warn(Warning::ClockSkew { skew, threshold });
+        }
+        #[cfg(feature = "crypto")]
+        if let AuthTokenState::Managed(ctx) = &self.auth_state {
+            ctx.lock().await.set_clock_skew(skew.as_millis() as i64);
         }
     }
 
     async fn invalidate_jwt(&self) {
+        #[cfg(feature = "crypto")]
         if let AuthTokenState::Managed(ctx) = &self.auth_state {
             let mut guard = ctx.lock().await;
             guard.invalidate();
         }
     }
 
-    async fn send_with_token_strategy<
-        F,
-        FetchFn,
-        FetchFut,
-        RefreshFn,
-        RefreshFut,
-        BuildAuthErrFn,
-        RetryLogFn,
-        FailLogFn,
-        RateLogFn,
-    >(
+    async fn send_with_token_strategy<F, FetchFn, FetchFut, RefreshFn, RefreshFut, BuildAuthErrFn>(
         &self,
         builder: F,
-        mut policy: TokenRequestPolicy<
-            FetchFn,
-            RefreshFn,
-            BuildAuthErrFn,
-            RetryLogFn,
-            FailLogFn,
-            RateLogFn,
-            FetchFut,
-            RefreshFut,
-        >,
-    ) -> Result<reqwest::Response, Error>
+        mut policy: TokenRequestPolicy<FetchFn, RefreshFn, BuildAuthErrFn, FetchFut, RefreshFut>,
+    ) -> Result<(reqwest::Response, u32), Error>
     where
         F: Fn(&Client, &str) -> reqwest::RequestBuilder,
         FetchFn: FnMut() -> FetchFut,
         FetchFut: Future<Output = Result<String, Error>>,
-        RefreshFn: FnMut() -> RefreshFut,
+        RefreshFn: FnMut(&str) -> RefreshFut,
         RefreshFut: Future<Output = Result<(), Error>>,
         BuildAuthErrFn: Fn(String) -> Error,
-        RetryLogFn: Fn(),
-        FailLogFn: Fn(),
-        RateLogFn: Fn(u64),
     {
-        let mut unauthorized_retry = false;
-        let mut rate_limit_retry = false;
-
-        loop {
-            let token = (policy.fetch_token)().await?;
-
-            let response = builder(&self.http_client, &token).send().await?;
-            let status = response.status();
-
-            if status == StatusCode::UNAUTHORIZED {
-                let body = response.text().await.unwrap_or_default();
-                if policy.allow_unauthorized_retry && !unauthorized_retry {
-                    (policy.unauthorized_retry_log)();
-                    (policy.refresh_token)().await?;
-                    unauthorized_retry = true;
-                    continue;
+        let token_kind = policy.token_kind;
+        let result = async {
+            let mut unauthorized_retry = false;
+            let mut rate_limit_retries = 0;
+            let mut retries = 0;
+            let mut maintenance_since = None;
+
+            loop {
+                let token = (policy.fetch_token)().await?;
+
+                let permit = match &self.request_limit {
+                    Some(limit) => Some(
+                        limit
+                            .acquire()
+                            .await
+                            .expect("request limit semaphore is never closed"),
+                    ),
+                    None => None,
+                };
+                #[cfg(feature = "chaos")]
+                if let Some(chaos) = &self.chaos {
+                    chaos
+                        .before_request(policy.operation)
+                        .await
+                        .map_err(|e| e.on_attempt(retries + 1))?;
+                }
+                let request = builder(&self.http_client, &token);
+                let attempt = self.dispatch(self.extra_headers.apply(request));
+                let response = match self.retry_plan.attempt_timeout() {
+                    Some(limit) => timeout(limit, attempt)
+                        .await
+                        .unwrap_or(Err(Error::Timeout(limit))),
+                    None => attempt.await,
+                }
+                .map_err(|e| e.on_attempt(retries + 1))?;
+                drop(permit);
+                if self.auth_config.clock_skew_check.unwrap_or(false)
+                    && let Some(skew) = self.clock_skew.observe(response.headers())
+                {
+                    self.clock_skew_measured(skew).await;
+                }
+                let status = response.status();
+                let headers = ResponseHeaders::capture(response.headers());
+                if let Some(reported) = self.capabilities.observe(&headers) {
+                    self.capabilities_reported(reported);
+                }
+                if !headers.is_empty() {
+                    self.telemetry.record(&TelemetryEvent::RateLimitHeaders {
+                        token: policy.token_kind,
+                        status: status.as_u16(),
+                        headers: headers.clone(),
+                    });
+                }
+
+                if status == StatusCode::SERVICE_UNAVAILABLE
+                    && let Some(retry_after) = headers.retry_after()
+                    && self.maintenance.applies(retry_after)
+                {
+                    let held_since = *maintenance_since.get_or_insert_with(Instant::now);
+                    if let Some(delay) = self
+                        .maintenance
+                        .next_probe(retry_after, held_since.elapsed())
+                    {
+                        if self.maintenance.begin() {
+                            self.telemetry
+                                .record(&TelemetryEvent::MaintenanceStarted { retry_after });
+                        }
+                        self.diagnostics
+                            .retry(policy.token_kind, status.as_u16(), true, &headers);
+                        if self.auth_config.retry_handler.is_some() {
+                            let body = self.read_error_text(response).await?;
+                            self.auth_config.retrying(RetryEvent {
+                                operation: policy.operation,
+                                attempt: retries + 1,
+                                error: &Error::Http(status, body),
+                                delay,
+                            });
+                        }
+                        sleep(delay).await;
+                        retries += 1;
+                        continue;
+                    }
+                    self.diagnostics
+                        .retry(policy.token_kind, status.as_u16(), false, &headers);
+                    let body = self.read_error_text(response).await?;
+                    return Err(Error::Http(status, body));
+                }
+                if let Some(elapsed) = self.maintenance.end() {
+                    self.telemetry
+                        .record(&TelemetryEvent::MaintenanceEnded { elapsed });
+                }
+
+                if status == StatusCode::UNAUTHORIZED {
+                    let body = self.read_error_text(response).await?;
+                    if policy.allow_unauthorized_retry && !unauthorized_retry {
+                        self.telemetry.record(&TelemetryEvent::UnauthorizedRetry {
+                            token: policy.token_kind,
+                        });
+                        self.diagnostics
+                            .retry(policy.token_kind, status.as_u16(), true, &headers);
+                        self.auth_config.retrying(RetryEvent {
+                            operation: policy.operation,
+                            attempt: retries + 1,
+                            error: &Error::Http(status, body),
+                            delay: Duration::ZERO,
+                        });
+                        (policy.refresh_token)(&token).await?;
+                        unauthorized_retry = true;
+                        retries += 1;
+                        continue;
+                    }
+                    self.telemetry.record(&TelemetryEvent::UnauthorizedFailure {
+                        token: policy.token_kind,
+                    });
+                    self.diagnostics
+                        .retry(policy.token_kind, status.as_u16(), false, &headers);
+                    return Err((policy.build_auth_error)(body));
                 }
-                (policy.unauthorized_fail_log)();
-                return Err((policy.build_auth_error)(body));
-            }
 
-            if status == StatusCode::TOO_MANY_REQUESTS {
-                if !rate_limit_retry {
-                    (policy.rate_limit_log)(self.backoff_delay.as_secs());
-                    sleep(self.backoff_delay).await;
-                    rate_limit_retry = true;
-                    continue;
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    if rate_limit_retries + 1 < self.retry_plan.max_attempts() {
+                        rate_limit_retries += 1;
+                        let delay = self.retry_plan.delay(rate_limit_retries);
+                        self.telemetry.record(&TelemetryEvent::RateLimited {
+                            token: policy.token_kind,
+                            delay,
+                        });
+                        self.diagnostics
+                            .retry(policy.token_kind, status.as_u16(), true, &headers);
+                        if self.auth_config.retry_handler.is_some() {
+                            let body = self.read_error_text(response).await?;
+                            self.auth_config.retrying(RetryEvent {
+                                operation: policy.operation,
+                                attempt: retries + 1,
+                                error: &Error::Http(status, body),
+                                delay,
+                            });
+                        }
+                        sleep(delay).await;
+                        retries += 1;
+                        continue;
+                    }
+                    self.diagnostics
+                        .retry(policy.token_kind, status.as_u16(), false, &headers);
+                    let body = self.read_error_text(response).await?;
+                    return Err(Error::Http(status, body));
                 }
-                let body = response.text().await.unwrap_or_default();
-                return Err(Error::Http(status, body));
+
+                return Ok((response, retries));
             }
+        }
+        .await;
+        if let Err(err) = &result {
+            let context = match token_kind {
+                TokenKind::Control => "control-plane request",
+                TokenKind::Scoped => "ingest request",
+            };
+            self.diagnostics.error(context, err);
+        }
+        result
+    }
 
-            return Ok(response);
+    /// Sends `request`, or hands it to the record/replay hook when one is set.
+    async fn dispatch(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+        let request = intercept(self.auth_config.request_interceptor.as_deref(), request)?;
+        #[cfg(feature = "replay")]
+        if let Some(replay) = &self.auth_config.replay {
+            return replay.send(request).await;
         }
+        request.send().await.map_err(|e| self.transport_error(e))
     }
 
-    async fn send_with_jwt<F>(&self, builder: F) -> Result<reqwest::Response, Error>
+    async fn send_with_jwt<F>(
+        &self,
+        operation: OperationKind,
+        builder: F,
+    ) -> Result<reqwest::Response, Error>
     where
         F: Fn(&Client, &str) -> reqwest::RequestBuilder,
     {
         let policy = TokenRequestPolicy {
+            operation,
+            token_kind: TokenKind::Control,
             allow_unauthorized_retry: self.retry_on_unauthorized,
             fetch_token: || async { self.ensure_valid_jwt().await },
-            refresh_token: || async {
+            refresh_token: |_: &str| async {
                 self.invalidate_jwt().await;
                 Ok(())
             },
-            unauthorized_retry_log: || {
-                warn!("received 401 from Snowflake; refreshing JWT and retrying")
-            },
-            unauthorized_fail_log: || {
-                warn!("received 401 from Snowflake after retry; surfacing authentication failure")
-            },
-            rate_limit_log: |delay| {
-                warn!(
-                    "received 429 TOO MANY REQUESTS; sleeping {} seconds before retry",
-                    delay
-                );
-            },
             build_auth_error: |body| Error::Auth(format!("401 Unauthorized: {}", body)),
         };
 
-        self.send_with_token_strategy(builder, policy).await
+        let (response, _) = self.send_with_token_strategy(builder, policy).await?;
+        Ok(response)
     }
 
     pub(crate) async fn send_with_scoped_token<F>(
         &self,
+        operation: OperationKind,
         builder: F,
     ) -> Result<reqwest::Response, Error>
     where
         F: Fn(&Client, &str) -> reqwest::RequestBuilder,
     {
-        if self.scoped_token.lock().await.is_none() {
-            self.get_scoped_token().await?;
-        }
+        let (response, _) = self
+            .send_with_scoped_token_counting_retries(operation, builder)
+            .await?;
+        Ok(response)
+    }
 
+    /// Like `send_with_scoped_token`, also returning how many times the request
+    /// was retried after a 401 or 429.
+    pub(crate) async fn send_with_scoped_token_counting_retries<F>(
+        &self,
+        operation: OperationKind,
+        builder: F,
+    ) -> Result<(reqwest::Response, u32), Error>
+    where
+        F: Fn(&Client, &str) -> reqwest::RequestBuilder,
+    {
         let policy = TokenRequestPolicy {
+            operation,
+            token_kind: TokenKind::Scoped,
             allow_unauthorized_retry: true,
-            fetch_token: || async {
-                let guard = self.scoped_token.lock().await;
-                Ok(guard
-                    .clone()
-                    .expect("scoped token should be available before request"))
-            },
-            refresh_token: || async { self.get_scoped_token().await },
-            unauthorized_retry_log: || {
-                warn!("scoped token rejected with 401; refreshing scoped token and retrying")
-            },
-            unauthorized_fail_log: || {
-                warn!("scoped token rejected again after retry; surfacing authentication failure")
-            },
-            rate_limit_log: |delay| {
-                warn!(
-                    "received 429 from ingest endpoint; sleeping {} seconds before retry",
-                    delay
-                );
+            fetch_token: || self.ensure_scoped_token(),
+            refresh_token: |rejected: &str| {
+                let rejected = rejected.to_string();
+                async move { self.refresh_scoped_token(Some(&rejected), None).await }
             },
             build_auth_error: |body| Error::Auth(format!("Scoped token unauthorized: {}", body)),
         };
@@ -342,49 +1272,299 @@ impl<R: Serialize + Clone> StreamingIngestClient<R> {
         self.send_with_token_strategy(builder, policy).await
     }
 
+    /// Opens (or reopens) `channel_name` on the client's pipe.
+    ///
+    /// Reopening a channel resumes from its last committed offset token and
+    /// invalidates any other writer holding the same channel.
     pub async fn open_channel(
         &mut self,
         channel_name: &str,
     ) -> Result<StreamingIngestChannel<R>, Error> {
+        self.open_channel_with_options(channel_name, &OpenChannelOptions::default())
+            .await
+    }
+
+    /// Like [`open_channel`](Self::open_channel), with `options` sent as the
+    /// request body and applied as this channel's overrides of the client
+    /// configuration.
+    pub async fn open_channel_with_options(
+        &mut self,
+        channel_name: &str,
+        options: &OpenChannelOptions,
+    ) -> Result<StreamingIngestChannel<R>, Error> {
+        validate_identifier(channel_name)?;
         let ingest_host = self.ingest_host.as_ref().expect("Ingest host not set");
-        let base = if ingest_host.contains("://") {
-            ingest_host.trim_end_matches('/').to_string()
-        } else {
-            format!("https://{}", ingest_host)
+        let url = endpoints::channel(ingest_host, self.pipe_ref(), channel_name)?;
+
+        let body = Bytes::from(serde_json::to_vec(options)?);
+        let span = channel_span(self, channel_name);
+        let max_conflict_retries = self
+            .auth_config
+            .channel_open_conflict_retries
+            .unwrap_or(DEFAULT_OPEN_CONFLICT_RETRIES);
+        let mut attempt = 0;
+        let resp = loop {
+            let result = async {
+                let response = self
+                    .send_with_scoped_token(OperationKind::OpenChannel, |client, scoped| {
+                        client
+                            .put(url.clone())
+                            .header("Authorization", format!("Bearer {}", scoped))
+                            .header("Content-Type", "application/json")
+                            .header("User-Agent", USER_AGENT)
+                            .body(body.clone())
+                    })
+                    .await?;
+                // Another client is opening the same channel; whichever
+                // open Snowflake serialized first wins, so retrying opens it
+                // after the other one.
+                if response.status() == StatusCode::CONFLICT {
+                    let body = self.read_error_text(response).await?;
+                    return Err(Error::ChannelConflict(Box::new(
+                        ChannelConflict::from_body(channel_name, &body),
+                    )));
+                }
+                let response = response.error_for_status()?;
+                self.read_json::<OpenChannelResponse>(response).await
+            }
+            .instrument(info_span!(parent: &span, "open_channel"))
+            .await;
+            match result {
+                Err(err @ Error::ChannelConflict(_)) if attempt < max_conflict_retries => {
+                    attempt += 1;
+                    let delay = stagger::jitter(doubled(
+                        OPEN_CONFLICT_BACKOFF,
+                        attempt - 1,
+                        MAX_OPEN_CONFLICT_BACKOFF,
+                    ));
+                    span.in_scope(|| warn!("{err}; retrying in {delay:?} (attempt {attempt})"));
+                    self.auth_config.retrying(RetryEvent {
+                        operation: OperationKind::OpenChannel,
+                        attempt,
+                        error: &err,
+                        delay,
+                    });
+                    sleep(delay).await;
+                }
+                result => {
+                    break result.map_err(|e| {
+                        e.in_operation(OperationKind::OpenChannel, Some(channel_name))
+                    })?;
+                }
+            }
         };
-        let db = self.db_name.as_str();
-        let schema = self.schema_name.as_str();
-        let pipe = self.pipe_name.as_str();
 
-        let url = format!(
-            "{}/v2/streaming/databases/{db}/schemas/{schema}/pipes/{pipe}/channels/{channel_name}",
-            base
-        );
+        span.in_scope(|| {
+            info!(
+                "channel opened: name='{}' db='{}' schema='{}' pipe='{}'",
+                channel_name, self.db_name, self.schema_name, self.pipe_name
+            )
+        });
+
+        if let Some(token) = &resp.channel_status.last_committed_offset_token
+            && token.parse::<u64>().is_err()
+        {
+            return Err(Error::Config(format!(
+                "channel '{channel_name}' has the non-numeric offset token '{token}', e.g. from \
+                 another SDK; read it with SdkOffsetFormat and reopen the channel with \
+                 OpenChannelOptions::offset_token"
+            )));
+        }
+        let mut channel = StreamingIngestChannel::from_response(self, resp, channel_name);
+        channel.set_span(span);
+        channel.apply_options(options);
+        channel.check_writer_generation()?;
+        Ok(channel)
+    }
 
+    /// The raw bulk channel status response for `channel_names`, so callers
+    /// polling repeatedly can tell an unchanged response from its bytes.
+    pub(crate) async fn fetch_channel_statuses(
+        &self,
+        channel_names: &[&str],
+    ) -> Result<Vec<u8>, Error> {
+        let ingest_host = self.ingest_host.as_ref().expect("Ingest host not set");
+        let url = endpoints::bulk_channel_status(ingest_host, self.pipe_ref())?;
+        let body = serde_json::json!({ "channel_names": channel_names }).to_string();
         let response = self
-            .send_with_scoped_token(|client, scoped| {
+            .send_with_scoped_token(OperationKind::ChannelStatus, |client, scoped| {
                 client
-                    .put(&url)
+                    .post(url.clone())
                     .header("Authorization", format!("Bearer {}", scoped))
                     .header("Content-Type", "application/json")
                     .header("User-Agent", USER_AGENT)
-                    .body("{}")
+                    .body(body.clone())
             })
             .await?;
+        self.read_body(response.error_for_status()?).await
+    }
 
-        let resp = response.error_for_status()?.json().await?;
-
-        info!(
-            "channel opened: name='{}' db='{}' schema='{}' pipe='{}'",
-            channel_name, self.db_name, self.schema_name, self.pipe_name
-        );
+    /// The last committed offset token of `channel_name` as Snowflake
+    /// stores it, without opening the channel; `None` when nothing has been
+    /// committed or the channel does not exist. Channels written by other SDKs can hold tokens this client
+    /// cannot resume from; read them with
+    /// [`SdkOffsetFormat`](crate::SdkOffsetFormat), then reopen the channel
+    /// with [`OpenChannelOptions::offset_token`].
+    pub async fn committed_offset_token(
+        &self,
+        channel_name: &str,
+    ) -> Result<Option<String>, Error> {
+        validate_identifier(channel_name)?;
+        let resp = async {
+            let body = self.fetch_channel_statuses(&[channel_name]).await?;
+            serde_json::from_slice::<serde_json::Value>(&body).map_err(Error::from)
+        }
+        .await
+        .map_err(|e| e.in_operation(OperationKind::ChannelStatus, Some(channel_name)))?;
+        let Some(status) = resp
+            .get("channel_statuses")
+            .and_then(|statuses| statuses.get(channel_name))
+        else {
+            return Ok(None);
+        };
+        let status = serde_json::from_value::<ChannelStatus>(status.clone())?;
+        Ok(status.last_committed_offset_token)
+    }
 
-        Ok(StreamingIngestChannel::from_response(
-            self,
-            resp,
-            channel_name,
-        ))
+    /// Like [`open_channel`](Self::open_channel), for rows of type `S`
+    /// rather than the client's row type, so one client can serve channels
+    /// with different schemas. See [`with_row_type`](Self::with_row_type).
+    pub async fn open_channel_as<S: Serialize + Clone>(
+        &self,
+        channel_name: &str,
+    ) -> Result<StreamingIngestChannel<S>, Error> {
+        self.with_row_type::<S>().open_channel(channel_name).await
     }
 
+    /// Releases client resources. Channels must be closed individually.
     pub fn close(&self) {}
 }
+
+impl<R> StreamingIngestClient<R> {
+    /// Whether append requests carry their tokens in the body: requested with
+    /// [`ApiVersion::Preview`](crate::ApiVersion::Preview), unless the
+    /// deployment reported that it does not support it.
+    pub(crate) fn tokens_in_body(&self) -> bool {
+        self.auth_config
+            .api_version
+            .unwrap_or_default()
+            .tokens_in_body()
+            && self
+                .capabilities
+                .reported()
+                .is_none_or(Capabilities::tokens_in_body)
+    }
+
+    /// Converts a transport failure, reporting timeouts as [`Error::Timeout`]
+    /// with the configured limit.
+    pub(crate) fn transport_error(&self, err: reqwest::Error) -> Error {
+        Error::from_transport(err, self.auth_config.transport_timeouts())
+    }
+
+    /// Largest append request body the deployment accepts.
+    pub(crate) fn max_request_bytes(&self) -> usize {
+        self.capabilities
+            .reported()
+            .map_or(Capabilities::default().max_request_bytes(), |caps| {
+                caps.max_request_bytes()
+            })
+    }
+
+    /// The database, schema, and pipe this client writes to.
+    pub(crate) fn pipe_ref(&self) -> PipeRef<'_> {
+        PipeRef {
+            db: &self.db_name,
+            schema: &self.schema_name,
+            pipe: &self.pipe_name,
+        }
+    }
+
+    /// A handle to this client for rows of type `S`. Like a clone, it shares
+    /// the connection pool, tokens, telemetry, and metrics, so channels with
+    /// different row types can be opened without a client per schema.
+    pub fn with_row_type<S>(&self) -> StreamingIngestClient<S> {
+        StreamingIngestClient {
+            _marker: std::marker::PhantomData,
+            db_name: self.db_name.clone(),
+            schema_name: self.schema_name.clone(),
+            pipe_name: self.pipe_name.clone(),
+            account: self.account.clone(),
+            control_host: self.control_host.clone(),
+            auth_state: self.auth_state.clone(),
+            auth_config: self.auth_config.clone(),
+            retry_on_unauthorized: self.retry_on_unauthorized,
+            retry_plan: self.retry_plan,
+            http_client: self.http_client.clone(),
+            auth_scheme: self.auth_scheme,
+            ingest_host: self.ingest_host.clone(),
+            scoped_token: self.scoped_token.clone(),
+            scoped_token_acquired_at: self.scoped_token_acquired_at.clone(),
+            scoped_token_provider: self.scoped_token_provider.clone(),
+            scoped_token_failure: self.scoped_token_failure.clone(),
+            telemetry: self.telemetry.clone(),
+            request_limit: self.request_limit.clone(),
+            extra_headers: self.extra_headers.clone(),
+            diagnostics: self.diagnostics.clone(),
+            append_log: self.append_log.clone(),
+            metrics: self.metrics.clone(),
+            misuse: self.misuse.clone(),
+            maintenance: self.maintenance.clone(),
+            capabilities: self.capabilities.clone(),
+            clock_skew: self.clock_skew.clone(),
+            scoped_refresh_offset_secs: self.scoped_refresh_offset_secs,
+            shared_tokens: self.shared_tokens.clone(),
+            #[cfg(feature = "chaos")]
+            chaos: self.chaos.clone(),
+            #[cfg(feature = "parallel")]
+            serializer: self.serializer.clone(),
+        }
+    }
+}
+
+/// Rejects names containing URL separators (`/`, `?`, `#`, `\`, and the `:`
+/// that introduces pipe actions such as `:bulk-channel-status`), `%`, or
+/// control characters. [`endpoints`] percent-encodes everything else, but
+/// these are commonly decoded or normalized again by proxies on the way.
+fn validate_identifier(name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(Error::Config(
+            "database, schema, pipe, and channel names must not be empty".into(),
+        ));
+    }
+    match name
+        .chars()
+        .find(|c| matches!(c, '/' | '?' | '#' | '\\' | ':' | '%') || c.is_control())
+    {
+        Some(c) => Err(Error::InvalidIdentifier(name.to_string(), c)),
+        None => Ok(()),
+    }
+}
+
+/// Builds the connection pool shared by the client and all of its channels.
+fn build_http_client(config: &Config) -> Result<Client, Error> {
+    let mut builder = Client::builder();
+    if config.http2_prior_knowledge.unwrap_or(false) {
+        builder = builder.http2_prior_knowledge();
+    }
+    if let Some(adaptive) = config.http2_adaptive_window {
+        builder = builder.http2_adaptive_window(adaptive);
+    }
+    let timeouts = config.transport_timeouts();
+    if let Some(timeout) = timeouts.connect {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = timeouts.read {
+        builder = builder.read_timeout(timeout);
+    }
+    #[cfg(feature = "tls-pinning")]
+    if let Some(tls) = super::tls::pinned_tls_config(config)? {
+        builder = builder.use_preconfigured_tls(tls);
+    }
+    #[cfg(not(feature = "tls-pinning"))]
+    if let Some((field, problem)) = super::validate_pins(config).into_iter().next() {
+        return Err(Error::Config(format!("{field}: {problem}")));
+    }
+    builder
+        .build()
+        .map_err(|e| Error::Config(format!("failed to build HTTP client: {e}")))
+}
@@ -0,0 +1,28 @@
+//! Control-plane JWTs signed by a key held outside the process, such as a
+//! KMS or HashiCorp Vault's Transit engine.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::Error;
+
+/// Future returned by [`JwtSigner`] methods.
+pub type JwtSignerFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// Signs the client's key-pair JWTs in place of a local private key. Set one
+/// with [`Config::jwt_signer`](crate::Config::jwt_signer); the client still
+/// builds the claims and refreshes the JWT, and calls the signer each time.
+/// Requires the `crypto` feature to take effect.
+///
+/// See [`vault::VaultTransitSigner`](crate::vault::VaultTransitSigner) for a
+/// signer backed by Vault Transit.
+pub trait JwtSigner: Send + Sync {
+    /// Fingerprint of the signing key's public key as Snowflake shows it in
+    /// `RSA_PUBLIC_KEY_FP` (`SHA256:<base64>`). `Config::public_key_fp`, when
+    /// set, is used instead.
+    fn public_key_fp(&self) -> JwtSignerFuture<'_, String>;
+
+    /// RS256 signature (RSASSA-PKCS1-v1_5 with SHA-256) of `message`, the
+    /// JWT's encoded header and claims.
+    fn sign<'a>(&'a self, message: &'a [u8]) -> JwtSignerFuture<'a, Vec<u8>>;
+}
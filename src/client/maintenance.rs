@@ -0,0 +1,122 @@
+//! Riding out Snowflake maintenance windows, announced as `503 Service
+//! Unavailable` with a long `Retry-After`, instead of failing requests.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::Config;
+
+/// Shortest `Retry-After` on a 503 that counts as maintenance, by default.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 30;
+/// Longest wait between probes, by default.
+const DEFAULT_PROBE_INTERVAL_SECS: u64 = 60;
+/// How long a request waits out maintenance before failing, by default.
+const DEFAULT_MAX_WAIT_SECS: u64 = 60 * 60;
+/// Shortest wait between probes, so `Retry-After: 0` does not re-send in a
+/// tight loop; a shorter probe interval still caps it.
+const MIN_PROBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Shared by a client, its clones, and its channels, so a window is
+/// reported once however many requests wait it out.
+pub(crate) struct MaintenanceWindow {
+    retry_after: Duration,
+    probe_interval: Duration,
+    max_wait: Duration,
+    /// When the current window was first seen.
+    started: Mutex<Option<Instant>>,
+}
+
+impl MaintenanceWindow {
+    pub(crate) fn new(config: &Config) -> Self {
+        let or_default =
+            |value: Option<Duration>, default| value.unwrap_or(Duration::from_secs(default));
+        Self {
+            retry_after: or_default(config.maintenance_retry_after, DEFAULT_RETRY_AFTER_SECS),
+            probe_interval: or_default(
+                config.maintenance_probe_interval,
+                DEFAULT_PROBE_INTERVAL_SECS,
+            ),
+            max_wait: or_default(config.maintenance_max_wait, DEFAULT_MAX_WAIT_SECS),
+            started: Mutex::new(None),
+        }
+    }
+
+    /// Whether a 503 asking to wait `retry_after` announces maintenance.
+    pub(crate) fn applies(&self, retry_after: Duration) -> bool {
+        !self.max_wait.is_zero() && retry_after >= self.retry_after
+    }
+
+    /// Wait before the next probe of a request that has waited `waited` so
+    /// far, or `None` once that would exceed the maximum wait.
+    pub(crate) fn next_probe(&self, retry_after: Duration, waited: Duration) -> Option<Duration> {
+        let delay = retry_after.max(MIN_PROBE_DELAY).min(self.probe_interval);
+        (waited + delay <= self.max_wait).then_some(delay)
+    }
+
+    /// Records a maintenance response; true when it opens a window.
+    pub(crate) fn begin(&self) -> bool {
+        let mut started = self.started.lock().unwrap();
+        let opened = started.is_none();
+        started.get_or_insert_with(Instant::now);
+        opened
+    }
+
+    /// Closes the window on any other response, returning how long it
+    /// lasted when one was open.
+    pub(crate) fn end(&self) -> Option<Duration> {
+        self.started
+            .lock()
+            .unwrap()
+            .take()
+            .map(|started| started.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_waits_are_not_maintenance() {
+        let config = Config::from_values(
+            "user",
+            None,
+            "acct",
+            "http://localhost",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let window = MaintenanceWindow::new(&config);
+        assert!(!window.applies(Duration::from_secs(5)));
+        assert!(window.applies(Duration::from_secs(600)));
+        assert_eq!(
+            window.next_probe(Duration::from_secs(600), Duration::ZERO),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            window.next_probe(Duration::from_secs(600), Duration::from_secs(3590)),
+            None
+        );
+        assert_eq!(
+            window.next_probe(Duration::ZERO, Duration::ZERO),
+            Some(MIN_PROBE_DELAY)
+        );
+
+        assert!(window.begin());
+        assert!(!window.begin());
+        assert!(window.end().is_some());
+        assert_eq!(window.end(), None);
+
+        let disabled = MaintenanceWindow::new(&Config {
+            maintenance_max_wait: Some(Duration::from_secs(0)),
+            ..config
+        });
+        assert!(!disabled.applies(Duration::from_secs(600)));
+    }
+}
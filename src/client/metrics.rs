@@ -0,0 +1,267 @@
+//! Append volume observed by a client, and billing projections from it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+const SECS_PER_HOUR: f64 = 3600.0;
+const BYTES_PER_GB: f64 = (1u64 << 30) as f64;
+
+/// Counters shared by a client, its clones, and its channels.
+pub(crate) struct MetricsRecorder {
+    started: Instant,
+    requests: AtomicU64,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    token_refreshes: AtomicU64,
+    token_refresh_failures: AtomicU64,
+    token_refresh_coalesced: AtomicU64,
+    token_refresh_nanos: AtomicU64,
+}
+
+impl Default for MetricsRecorder {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: AtomicU64::new(0),
+            rows: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            token_refreshes: AtomicU64::new(0),
+            token_refresh_failures: AtomicU64::new(0),
+            token_refresh_coalesced: AtomicU64::new(0),
+            token_refresh_nanos: AtomicU64::new(0),
+        }
+    }
+}
+
+impl MetricsRecorder {
+    pub(crate) fn append(&self, rows: usize, bytes: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.rows.fetch_add(rows as u64, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// A scoped token fetch that took `elapsed`.
+    pub(crate) fn token_refresh(&self, elapsed: Duration, ok: bool) {
+        self.token_refreshes.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.token_refresh_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.token_refresh_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// A caller that waited for another caller's refresh instead of
+    /// fetching a token itself.
+    pub(crate) fn token_refresh_coalesced(&self) {
+        self.token_refresh_coalesced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientMetrics {
+        ClientMetrics {
+            requests: self.requests.load(Ordering::Relaxed),
+            rows: self.rows.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            uptime: self.started.elapsed(),
+            token_refresh: self.token_refresh_metrics(),
+        }
+    }
+
+    pub(crate) fn token_refresh_metrics(&self) -> TokenRefreshMetrics {
+        TokenRefreshMetrics {
+            attempts: self.token_refreshes.load(Ordering::Relaxed),
+            failures: self.token_refresh_failures.load(Ordering::Relaxed),
+            coalesced: self.token_refresh_coalesced.load(Ordering::Relaxed),
+            time_in_refresh: Duration::from_nanos(self.token_refresh_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Append volume over a period, returned by
+/// [`StreamingIngestClient::metrics`](crate::StreamingIngestClient::metrics).
+///
+/// The fields are public so a hypothetical workload can be described by hand
+/// and passed to [`cost_estimate`](Self::cost_estimate) for capacity planning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientMetrics {
+    /// Append requests Snowflake accepted.
+    pub requests: u64,
+    /// Rows in those requests.
+    pub rows: u64,
+    /// Request body bytes, i.e. uncompressed NDJSON.
+    pub bytes: u64,
+    /// Time the client has existed; the period the counts cover.
+    pub uptime: Duration,
+    /// Scoped token refreshes over the same period.
+    pub token_refresh: TokenRefreshMetrics,
+}
+
+/// Scoped token refreshes made by a client, its clones, and its channels,
+/// returned by
+/// [`StreamingIngestClient::token_refresh_metrics`](crate::StreamingIngestClient::token_refresh_metrics)
+/// and as part of [`ClientMetrics`], for tracing latency spikes to
+/// authentication.
+///
+/// Concurrent callers that find the token stale share one refresh: one of
+/// them fetches the token and the others wait for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenRefreshMetrics {
+    /// Scoped token fetches, including the first one.
+    pub attempts: u64,
+    /// Fetches that failed.
+    pub failures: u64,
+    /// Callers that waited for another caller's fetch instead of making
+    /// their own.
+    pub coalesced: u64,
+    /// Total time spent fetching tokens.
+    pub time_in_refresh: Duration,
+}
+
+impl ClientMetrics {
+    /// Mean rows per append request.
+    pub fn avg_batch_rows(&self) -> f64 {
+        ratio(self.rows as f64, self.requests as f64)
+    }
+
+    /// Mean body bytes per append request.
+    pub fn avg_batch_bytes(&self) -> f64 {
+        ratio(self.bytes as f64, self.requests as f64)
+    }
+
+    /// Append requests per second over the uptime.
+    pub fn requests_per_sec(&self) -> f64 {
+        ratio(self.requests as f64, self.uptime.as_secs_f64())
+    }
+
+    /// Body bytes per second over the uptime.
+    pub fn bytes_per_sec(&self) -> f64 {
+        ratio(self.bytes as f64, self.uptime.as_secs_f64())
+    }
+
+    /// Projects billing for this workload with the default [`CostModel`].
+    pub fn cost_estimate(&self) -> CostEstimate {
+        self.cost_estimate_with(&CostModel::default())
+    }
+
+    /// Projects billing for this workload, assuming it continues at the
+    /// observed rate with one client running the whole time.
+    pub fn cost_estimate_with(&self, model: &CostModel) -> CostEstimate {
+        let client_hours = self.uptime.as_secs_f64() / SECS_PER_HOUR;
+        let gb_ingested = self.bytes as f64 / BYTES_PER_GB;
+        let gb_per_hour = self.bytes_per_sec() * SECS_PER_HOUR / BYTES_PER_GB;
+        let credits_per_hour = model.credits_per_client_hour + gb_per_hour * model.credits_per_gb;
+        CostEstimate {
+            client_seconds: self.uptime.as_secs_f64(),
+            gb_ingested,
+            credits_so_far: client_hours * model.credits_per_client_hour
+                + gb_ingested * model.credits_per_gb,
+            gb_per_hour,
+            credits_per_hour,
+            credits_per_day: credits_per_hour * 24.0,
+            credits_per_month: credits_per_hour * 24.0 * 30.0,
+        }
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Credit rates a [`CostEstimate`] is computed with.
+///
+/// The defaults follow Snowflake's published rates for the throughput-based
+/// billing of the REST API at the time of writing; set the fields from your
+/// own contract or the current credit consumption table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostModel {
+    /// Credits per GB (2^30 bytes) of uncompressed data ingested.
+    pub credits_per_gb: f64,
+    /// Credits per hour each client is active (client-seconds billing).
+    pub credits_per_client_hour: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            credits_per_gb: 0.0037,
+            credits_per_client_hour: 0.0,
+        }
+    }
+}
+
+impl CostModel {
+    /// The classic, per-client billing: 0.01 credits per client hour and no
+    /// per-GB charge (migration compute is not modelled).
+    pub fn classic() -> Self {
+        Self {
+            credits_per_gb: 0.0,
+            credits_per_client_hour: 0.01,
+        }
+    }
+}
+
+/// Billing projection from [`ClientMetrics::cost_estimate`]. All figures are
+/// estimates from client-side byte counts, which can differ from what
+/// Snowflake meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostEstimate {
+    /// Seconds the client has been active.
+    pub client_seconds: f64,
+    /// Uncompressed GB appended so far.
+    pub gb_ingested: f64,
+    /// Credits for the observed period.
+    pub credits_so_far: f64,
+    /// Observed throughput.
+    pub gb_per_hour: f64,
+    /// Projected credits per hour at the observed throughput.
+    pub credits_per_hour: f64,
+    /// `credits_per_hour` over 24 hours.
+    pub credits_per_day: f64,
+    /// `credits_per_hour` over 30 days.
+    pub credits_per_month: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_credits_from_throughput_and_client_time() {
+        let metrics = ClientMetrics {
+            requests: 100,
+            rows: 10_000,
+            bytes: 1 << 30,
+            uptime: Duration::from_secs(1800),
+            ..ClientMetrics::default()
+        };
+        assert_eq!(metrics.avg_batch_rows(), 100.0);
+        assert_eq!(metrics.avg_batch_bytes(), (1u64 << 30) as f64 / 100.0);
+
+        let estimate = metrics.cost_estimate_with(&CostModel {
+            credits_per_gb: 0.5,
+            credits_per_client_hour: 1.0,
+        });
+        assert_eq!(estimate.gb_ingested, 1.0);
+        assert_eq!(estimate.gb_per_hour, 2.0);
+        assert_eq!(estimate.credits_so_far, 0.5 + 0.5);
+        assert_eq!(estimate.credits_per_hour, 1.0 + 2.0 * 0.5);
+        assert_eq!(
+            estimate.credits_per_month,
+            estimate.credits_per_hour * 720.0
+        );
+
+        let idle = ClientMetrics::default().cost_estimate();
+        assert_eq!(idle.credits_per_hour, 0.0);
+        assert_eq!(
+            ClientMetrics::default()
+                .cost_estimate_with(&CostModel::classic())
+                .credits_per_day,
+            0.24
+        );
+    }
+}
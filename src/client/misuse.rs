@@ -0,0 +1,60 @@
+//! Runtime checks for suspicious usage, each reported once as a
+//! [`Warning::Misuse`].
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tracing::warn;
+
+use crate::{Config, Lint, Warning};
+
+/// Consecutive `append_row` calls, each started within [`SINGLE_ROW_GAP`] of
+/// the previous one returning, before [`Lint::SingleRowAppends`] fires.
+pub(crate) const SINGLE_ROW_STREAK: u32 = 100;
+pub(crate) const SINGLE_ROW_GAP: Duration = Duration::from_millis(5);
+/// A batch split into at least this many requests by `max_rows_per_request`,
+/// averaging fewer than [`CHUNKING_AVG_BYTES`] each, fires
+/// [`Lint::ChunkingThrash`].
+pub(crate) const CHUNKING_REQUESTS: usize = 10;
+pub(crate) const CHUNKING_AVG_BYTES: usize = 64 * 1024;
+/// Remaining lifetime of a `jwt_token` at which
+/// [`Lint::StaticTokenExpiring`] fires.
+pub(crate) const STATIC_TOKEN_MARGIN_SECS: i64 = 5 * 60;
+
+/// Shared by a client, its clones, and its channels.
+pub(crate) struct MisuseDetector {
+    enabled: bool,
+    reported: Mutex<HashSet<(Lint, String)>>,
+}
+
+impl MisuseDetector {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            reported: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Logs and passes on `lint` for `subject` (a channel name or config
+    /// field) unless it was already reported for that subject.
+    pub(crate) fn report(&self, config: &Config, lint: Lint, subject: &str) {
+        if !self.enabled {
+            return;
+        }
+        let first = self
+            .reported
+            .lock()
+            .unwrap()
+            .insert((lint, subject.to_string()));
+        if !first {
+            return;
+        }
+        let warning = Warning::Misuse {
+            lint,
+            subject: subject.to_string(),
+        };
+        warn!("{}", warning);
+        config.warn(warning);
+    }
+}
@@ -1,35 +1,107 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
 
-use crate::Config;
+#[cfg(feature = "crypto")]
 use crate::client::crypto::JwtContext;
+use crate::telemetry::TaggedSink;
+use crate::{AuthScheme, Config, RetryPlan};
 use reqwest::Client;
-use std::time::Duration;
 
+pub(crate) mod append_log;
+mod body;
+mod capabilities;
+mod clock;
+#[cfg(feature = "crypto")]
 pub(crate) mod crypto;
+mod diagnostics;
+mod headers;
 mod impls;
+mod jwt_signer;
+mod maintenance;
+mod metrics;
+mod misuse;
+pub(crate) mod stagger;
+mod tls;
+mod token_cache;
+mod token_info;
+mod token_provider;
 
+pub use capabilities::{Capabilities, ServerApiVersion};
+#[cfg(feature = "crypto")]
+pub use crypto::{AuthDebugReport, KeyMetadata, debug_auth};
+pub(crate) use headers::parse_default_headers;
+pub use jwt_signer::{JwtSigner, JwtSignerFuture};
+pub use metrics::{ClientMetrics, CostEstimate, CostModel, TokenRefreshMetrics};
+pub(crate) use misuse::{CHUNKING_AVG_BYTES, CHUNKING_REQUESTS, SINGLE_ROW_GAP, SINGLE_ROW_STREAK};
+pub(crate) use tls::validate_pins;
+pub use token_cache::TokenCache;
+pub use token_info::{TokenInfo, TokenSnapshot};
+pub use token_provider::{ScopedTokenFuture, ScopedTokenProvider};
+
+/// Client bound to a single Snowpipe Streaming pipe.
+///
+/// Construction performs ingest host discovery and scoped token exchange, so a
+/// successfully created client is ready to [open channels](Self::open_channel).
+/// Clones share the HTTP connection pool and tokens.
 #[derive(Clone)]
 pub struct StreamingIngestClient<R> {
-    _marker: PhantomData<R>,
+    _marker: PhantomData<fn() -> R>,
+    /// Target database.
     pub db_name: String,
+    /// Target schema.
     pub schema_name: String,
+    /// Target pipe.
     pub pipe_name: String,
+    /// Snowflake account identifier.
     pub account: String,
     control_host: String,
     auth_state: AuthTokenState,
-    auth_config: Config,
+    pub(crate) auth_config: Config,
     retry_on_unauthorized: bool,
-    backoff_delay: Duration,
+    retry_plan: RetryPlan,
     http_client: Client,
-    auth_token_type: String,
+    auth_scheme: AuthScheme,
+    /// Ingest host returned by discovery.
     pub ingest_host: Option<String>,
+    /// Scoped token used for ingest-plane requests.
     pub scoped_token: Arc<Mutex<Option<String>>>,
+    scoped_token_acquired_at: Arc<Mutex<Option<jiff::Timestamp>>>,
+    scoped_token_provider: Option<Arc<dyn ScopedTokenProvider>>,
+    /// Held while fetching a scoped token so concurrent refreshes collapse into
+    /// one; records the last failure for the refresh cooldown.
+    scoped_token_failure: Arc<Mutex<Option<(std::time::Instant, String)>>>,
+    pub(crate) telemetry: Arc<TaggedSink>,
+    request_limit: Option<Arc<Semaphore>>,
+    pub(crate) extra_headers: headers::ExtraHeaders,
+    pub(crate) diagnostics: Arc<diagnostics::DiagnosticsRecorder>,
+    pub(crate) append_log: Arc<append_log::AppendLog>,
+    pub(crate) metrics: Arc<metrics::MetricsRecorder>,
+    pub(crate) misuse: Arc<misuse::MisuseDetector>,
+    maintenance: Arc<maintenance::MaintenanceWindow>,
+    capabilities: Arc<capabilities::CapabilityProbe>,
+    clock_skew: Arc<clock::ClockSkewProbe>,
+    /// Seconds added to the scoped token refresh margin, drawn once from
+    /// `Config::refresh_jitter`.
+    scoped_refresh_offset_secs: u64,
+    /// Tokens shared with other clients through a [`TokenCache`].
+    shared_tokens: Option<Arc<token_cache::SharedTokens>>,
+    #[cfg(feature = "chaos")]
+    pub(crate) chaos: Option<Arc<crate::chaos::FaultInjector>>,
+    /// Dedicated pool for `append_rows_parallel` when
+    /// `Config::serialization_threads` is set; rayon's global pool otherwise.
+    #[cfg(feature = "parallel")]
+    pub(crate) serializer: Option<Arc<rayon::ThreadPool>>,
 }
 
 #[derive(Clone)]
 enum AuthTokenState {
+    #[cfg(feature = "crypto")]
     Managed(Arc<Mutex<JwtContext>>),
-    Provided { token: String },
+    Provided {
+        token: String,
+    },
+    /// Scoped tokens come from a [`ScopedTokenProvider`]; there is no
+    /// control-plane credential.
+    Brokered,
 }
@@ -0,0 +1,43 @@
+//! Random offsets that keep a fleet of clients from acting in lockstep.
+//!
+//! Replicas deployed together construct their clients at the same instant,
+//! and tokens minted at the same instant expire at the same instant, so
+//! without jitter every refresh hits the control plane at once.
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+/// A random number in `[0, 1)`.
+pub(crate) fn random_unit() -> f64 {
+    // The low 53 bits of a v4 UUID are random and fill an f64 mantissa.
+    let bits = Uuid::new_v4().as_u128() as u64 & ((1 << 53) - 1);
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// A random duration in `[0, max)`.
+pub(crate) fn jitter(max: Duration) -> Duration {
+    max.mul_f64(random_unit())
+}
+
+/// [`jitter`] of up to `max` in whole seconds; zero when unset.
+pub(crate) fn jitter_secs(max: Option<Duration>) -> u64 {
+    let max = max.unwrap_or_default();
+    jitter(max).as_secs().min(max.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_stays_in_range() {
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+        assert_eq!(jitter_secs(None), 0);
+        let max = Duration::from_secs(10);
+        let draws = (0..200).map(|_| jitter(max)).collect::<Vec<_>>();
+        assert!(draws.iter().all(|d| *d < max));
+        assert!(draws.iter().any(|d| *d != draws[0]), "draws should vary");
+        assert!((0..200).all(|_| jitter_secs(Some(Duration::from_secs(3))) <= 3));
+    }
+}
@@ -0,0 +1,329 @@
+//! Pinning the public keys of the control and ingest hosts' certificates, on
+//! top of the usual chain validation, for deployments that must not trust
+//! every CA.
+//!
+//! A pin is the base64-encoded SHA-256 digest of a DER SubjectPublicKeyInfo,
+//! as printed by
+//! `openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`.
+
+use base64::Engine as _;
+
+use crate::{Config, Error};
+
+/// Digests of the public keys accepted for one host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PinSet(Vec<[u8; 32]>);
+
+impl PinSet {
+    /// Decodes `pins`, rejecting anything that is not a base64 SHA-256
+    /// digest, and an empty list, which no certificate could match.
+    pub(crate) fn parse(pins: &[String]) -> Result<Self, Error> {
+        if pins.is_empty() {
+            return Err(Error::Config("must list at least one pin".into()));
+        }
+        let engine = base64::engine::general_purpose::STANDARD;
+        pins.iter()
+            .map(|pin| {
+                engine
+                    .decode(pin.trim())
+                    .ok()
+                    .and_then(|digest| <[u8; 32]>::try_from(digest).ok())
+                    .ok_or_else(|| {
+                        Error::Config(format!(
+                            "pin '{pin}' is not a base64-encoded SHA-256 digest"
+                        ))
+                    })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// The pins for `field`, if set.
+    pub(crate) fn from_field(field: &Option<Vec<String>>) -> Result<Option<Self>, Error> {
+        field.as_deref().map(Self::parse).transpose()
+    }
+
+    #[cfg_attr(not(feature = "tls-pinning"), allow(dead_code))]
+    fn contains(&self, digest: &[u8; 32]) -> bool {
+        self.0.contains(digest)
+    }
+}
+
+/// Rejects pins that cannot take effect: malformed ones, and any at all
+/// without the `tls-pinning` feature.
+pub(crate) fn validate_pins(config: &Config) -> Vec<(&'static str, String)> {
+    let mut issues = Vec::new();
+    for (field, pins) in [
+        ("control_host_pins", &config.control_host_pins),
+        ("ingest_host_pins", &config.ingest_host_pins),
+    ] {
+        match PinSet::from_field(pins) {
+            Err(Error::Config(problem)) => issues.push((field, problem)),
+            Ok(Some(_)) if !cfg!(feature = "tls-pinning") => {
+                issues.push((field, "pinning needs the `tls-pinning` feature".into()))
+            }
+            _ => {}
+        }
+    }
+    issues
+}
+
+#[cfg(feature = "tls-pinning")]
+pub(crate) use pinned::pinned_tls_config;
+
+#[cfg(feature = "tls-pinning")]
+mod pinned {
+    use std::sync::Arc;
+
+    use rustls::client::WebPkiServerVerifier;
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore};
+    use sha2::{Digest, Sha256};
+    use x509_cert::der::{Decode, Encode};
+
+    use super::PinSet;
+    use crate::{Config, Error};
+
+    /// Checks the chain as usual, then requires a pinned key somewhere in
+    /// it. Pins are looked up by host: the control host's, else the ingest
+    /// host's, since the client talks to no other host.
+    #[derive(Debug)]
+    pub(super) struct PinnedVerifier {
+        pub(super) inner: Arc<dyn ServerCertVerifier>,
+        pub(super) control_host: Option<String>,
+        pub(super) control: Option<PinSet>,
+        pub(super) ingest: Option<PinSet>,
+    }
+
+    impl PinnedVerifier {
+        fn pins_for(&self, server_name: &ServerName<'_>) -> Option<&PinSet> {
+            let host = server_name.to_str();
+            if self.control_host.as_deref() == Some(host.as_ref()) {
+                self.control.as_ref()
+            } else {
+                self.ingest.as_ref()
+            }
+        }
+    }
+
+    impl ServerCertVerifier for PinnedVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer<'_>,
+            intermediates: &[CertificateDer<'_>],
+            server_name: &ServerName<'_>,
+            ocsp_response: &[u8],
+            now: UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            let verified = self.inner.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            )?;
+            let Some(pins) = self.pins_for(server_name) else {
+                return Ok(verified);
+            };
+            for cert in std::iter::once(end_entity).chain(intermediates) {
+                if pins.contains(&spki_sha256(cert)?) {
+                    return Ok(verified);
+                }
+            }
+            tracing::error!(
+                "certificate for {} matches none of its pinned public keys",
+                server_name.to_str()
+            );
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            self.inner.verify_tls12_signature(message, cert, dss)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            self.inner.verify_tls13_signature(message, cert, dss)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+            self.inner.supported_verify_schemes()
+        }
+    }
+
+    /// SHA-256 of the DER SubjectPublicKeyInfo of `cert`.
+    pub(super) fn spki_sha256(cert: &CertificateDer<'_>) -> Result<[u8; 32], rustls::Error> {
+        let bad_encoding = |_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding);
+        let cert = x509_cert::Certificate::from_der(cert).map_err(bad_encoding)?;
+        let spki = cert
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(bad_encoding)?;
+        Ok(Sha256::digest(spki).into())
+    }
+
+    /// A rustls configuration enforcing `Config::control_host_pins` and
+    /// `Config::ingest_host_pins`, or `None` when neither is set.
+    pub(crate) fn pinned_tls_config(config: &Config) -> Result<Option<ClientConfig>, Error> {
+        let control = PinSet::from_field(&config.control_host_pins)?;
+        let ingest = PinSet::from_field(&config.ingest_host_pins)?;
+        if control.is_none() && ingest.is_none() {
+            return Ok(None);
+        }
+        let tls_error = |e: rustls::Error| Error::Config(format!("failed to configure TLS: {e}"));
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .map_err(|e| Error::Config(format!("failed to configure TLS: {e}")))?;
+        let control_host = config
+            .control_host()
+            .ok()
+            .and_then(|url| reqwest::Url::parse(&url).ok())
+            .and_then(|url| url.host_str().map(str::to_string));
+        let mut tls = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                inner,
+                control_host,
+                control,
+                ingest,
+            }))
+            .with_no_client_auth();
+        tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Some(tls))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `tests/fixtures/pinning_cert.der` and the pin `openssl` prints for it.
+    #[cfg(feature = "tls-pinning")]
+    const CERT_DER: &[u8] = include_bytes!("../../tests/fixtures/pinning_cert.der");
+    const CERT_PIN: &str = include_str!("../../tests/fixtures/pinning_cert.pin");
+
+    #[test]
+    fn pins_must_be_sha256_digests() {
+        assert!(PinSet::parse(&[CERT_PIN.to_string()]).is_ok());
+        assert!(PinSet::parse(&[]).is_err());
+        let err = PinSet::parse(&["abc".to_string()]).unwrap_err();
+        assert!(matches!(err, Error::Config(msg) if msg.contains("'abc'")));
+
+        let mut config = Config::from_values(
+            "user",
+            None,
+            "acct",
+            "http://localhost",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        config.control_host_pins = Some(vec![CERT_PIN.to_string()]);
+        config.ingest_host_pins = Some(vec!["abc".to_string()]);
+        let fields = validate_pins(&config)
+            .into_iter()
+            .map(|(field, _)| field)
+            .collect::<Vec<_>>();
+        if cfg!(feature = "tls-pinning") {
+            assert_eq!(fields, ["ingest_host_pins"]);
+        } else {
+            assert_eq!(fields, ["control_host_pins", "ingest_host_pins"]);
+        }
+    }
+
+    #[cfg(feature = "tls-pinning")]
+    #[test]
+    fn only_pinned_keys_are_accepted() {
+        use std::sync::Arc;
+
+        use rustls::client::danger::{
+            HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+        };
+        use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+        use rustls::{DigitallySignedStruct, SignatureScheme};
+
+        use super::pinned::{PinnedVerifier, spki_sha256};
+
+        /// Stands in for chain validation, which the fixture cannot pass.
+        #[derive(Debug)]
+        struct AcceptAll;
+
+        impl ServerCertVerifier for AcceptAll {
+            fn verify_server_cert(
+                &self,
+                _: &CertificateDer<'_>,
+                _: &[CertificateDer<'_>],
+                _: &ServerName<'_>,
+                _: &[u8],
+                _: UnixTime,
+            ) -> Result<ServerCertVerified, rustls::Error> {
+                Ok(ServerCertVerified::assertion())
+            }
+
+            fn verify_tls12_signature(
+                &self,
+                _: &[u8],
+                _: &CertificateDer<'_>,
+                _: &DigitallySignedStruct,
+            ) -> Result<HandshakeSignatureValid, rustls::Error> {
+                Ok(HandshakeSignatureValid::assertion())
+            }
+
+            fn verify_tls13_signature(
+                &self,
+                _: &[u8],
+                _: &CertificateDer<'_>,
+                _: &DigitallySignedStruct,
+            ) -> Result<HandshakeSignatureValid, rustls::Error> {
+                Ok(HandshakeSignatureValid::assertion())
+            }
+
+            fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+                Vec::new()
+            }
+        }
+
+        let cert = CertificateDer::from(CERT_DER);
+        let pin = PinSet::parse(&[CERT_PIN.to_string()]).unwrap();
+        assert_eq!(pin.0, [spki_sha256(&cert).unwrap()]);
+
+        let other = PinSet::parse(&["A".repeat(43) + "="]).unwrap();
+        let verifier = PinnedVerifier {
+            inner: Arc::new(AcceptAll),
+            control_host: Some("acct.snowflakecomputing.com".into()),
+            control: Some(pin),
+            ingest: Some(other),
+        };
+        let verify = |host: &'static str| {
+            verifier.verify_server_cert(
+                &cert,
+                &[],
+                &ServerName::try_from(host).unwrap(),
+                &[],
+                UnixTime::now(),
+            )
+        };
+        assert!(verify("acct.snowflakecomputing.com").is_ok());
+        assert!(verify("ingest.snowflakecomputing.com").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Tokens shared by clients that authenticate as the same user on the same
+//! account, so a service with many pipes mints one control-plane JWT and one
+//! scoped token instead of one per client.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::Mutex;
+use xxhash_rust::xxh3::Xxh3;
+
+#[cfg(feature = "crypto")]
+use super::crypto::JwtContext;
+use crate::{AuthScheme, Config};
+
+/// A cache of control-plane JWTs, scoped tokens, and discovered ingest hosts,
+/// shared by the clients built from configs that use it.
+///
+/// Clients share an entry when they have the same control host, account,
+/// user (or login), auth scheme, credential (token, private key, or key
+/// fingerprint), and configured ingest host; other settings,
+/// such as the JWT lifetime and refresh margin, come from the first client
+/// built for the entry. Clones share the same cache. Set one on a config with
+/// [`Config::token_cache`], or use the process-wide [`TokenCache::global`]
+/// with `Config::share_tokens`.
+///
+/// ```
+/// use snowpipe_streaming::{Config, TokenCache};
+///
+/// let cache = TokenCache::new();
+/// let config = Config::from_values(
+///     "MY_USER", None, "MY_ACCOUNT", "", None, None, None, None, None, None,
+/// )
+/// .token_cache(&cache);
+/// # let _ = config;
+/// ```
+#[derive(Clone, Default)]
+pub struct TokenCache {
+    entries: Arc<std::sync::Mutex<HashMap<CacheKey, Arc<SharedTokens>>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    control_host: String,
+    account: String,
+    user: String,
+    auth_scheme: AuthScheme,
+    credential: u128,
+    ingest_host: Option<String>,
+}
+
+/// Token state shared by the clients of one cache entry.
+#[derive(Default)]
+pub(crate) struct SharedTokens {
+    #[cfg(feature = "crypto")]
+    pub(crate) jwt: OnceLock<Arc<Mutex<JwtContext>>>,
+    pub(crate) scoped_token: Arc<Mutex<Option<String>>>,
+    pub(crate) scoped_token_acquired_at: Arc<Mutex<Option<jiff::Timestamp>>>,
+    pub(crate) scoped_token_failure: Arc<Mutex<Option<(std::time::Instant, String)>>>,
+    pub(crate) ingest_host: OnceLock<String>,
+}
+
+impl TokenCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide cache used by configs with `share_tokens` set.
+    pub fn global() -> &'static TokenCache {
+        static GLOBAL: OnceLock<TokenCache> = OnceLock::new();
+        GLOBAL.get_or_init(TokenCache::new)
+    }
+
+    /// Number of distinct credentials cached.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every cached token. Clients already built keep the tokens they
+    /// share; clients built afterwards start a new entry.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// The entry for clients built from `config` against `control_host`.
+    pub(crate) fn entry(&self, config: &Config, control_host: &str) -> Arc<SharedTokens> {
+        let key = CacheKey {
+            control_host: control_host.to_string(),
+            account: config.account.to_uppercase(),
+            user: config
+                .login
+                .as_deref()
+                .unwrap_or(&config.user)
+                .to_uppercase(),
+            auth_scheme: config.effective_auth_scheme(),
+            credential: credential_digest(config),
+            ingest_host: config.ingest_host.clone(),
+        };
+        self.entries.lock().unwrap().entry(key).or_default().clone()
+    }
+}
+
+/// Digest of the credential `config` authenticates with, so clients with
+/// different keys or tokens for the same user never share tokens. A key file
+/// is hashed by its contents, so a rotated key starts a new entry.
+fn credential_digest(config: &Config) -> u128 {
+    let key_file = config
+        .private_key_path
+        .as_ref()
+        .map(|path| std::fs::read(path).unwrap_or_else(|_| path.clone().into_bytes()));
+    let parts = [
+        config.jwt_token.as_ref().map(|s| s.as_bytes()),
+        config
+            .programmatic_access_token
+            .as_ref()
+            .map(|s| s.as_bytes()),
+        config.private_key.as_ref().map(|s| s.as_bytes()),
+        key_file.as_deref(),
+        config.private_key_source.as_ref().map(|s| s.as_bytes()),
+        config.public_key_fp.as_ref().map(|s| s.as_bytes()),
+    ];
+    let mut hasher = Xxh3::new();
+    // Configs sharing a signer (clones of one config) share its tokens.
+    let signer = config
+        .jwt_signer
+        .as_ref()
+        .map(|signer| Arc::as_ptr(signer) as *const () as usize);
+    hasher.update(&signer.unwrap_or_default().to_le_bytes());
+    for part in parts {
+        match part {
+            Some(bytes) => {
+                hasher.update(&[1]);
+                hasher.update(&(bytes.len() as u64).to_le_bytes());
+                hasher.update(bytes);
+            }
+            None => hasher.update(&[0]),
+        }
+    }
+    hasher.digest128()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_keyed_by_credential() {
+        let cache = TokenCache::new();
+        let config = |user: &str| {
+            Config::from_values(user, None, "acct", "", None, None, None, None, None, None)
+        };
+        let first = cache.entry(&config("alice"), "https://a");
+        assert!(Arc::ptr_eq(
+            &first,
+            &cache.entry(&config("ALICE"), "https://a")
+        ));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &cache.entry(&config("bob"), "https://a")
+        ));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &cache.entry(&config("alice"), "https://b")
+        ));
+        assert_eq!(cache.len(), 3);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn different_keys_for_one_user_get_separate_entries() {
+        let cache = TokenCache::new();
+        let key_path =
+            std::env::temp_dir().join(format!("token-cache-key-{}.p8", std::process::id()));
+        let config = |key: Option<&str>, token: Option<&str>| {
+            let mut config = Config::from_values(
+                "alice",
+                None,
+                "acct",
+                "",
+                token.map(str::to_string),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
+            config.private_key = key.map(str::to_string);
+            config
+        };
+        let first = cache.entry(&config(Some("key-a"), None), "https://a");
+        assert!(Arc::ptr_eq(
+            &first,
+            &cache.entry(&config(Some("key-a"), None), "https://a")
+        ));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &cache.entry(&config(Some("key-b"), None), "https://a")
+        ));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &cache.entry(&config(None, Some("oauth-token")), "https://a")
+        ));
+
+        let from_file = || Config {
+            private_key_path: Some(key_path.to_string_lossy().into_owned()),
+            ..config(None, None)
+        };
+        std::fs::write(&key_path, "key-a").unwrap();
+        let before = cache.entry(&from_file(), "https://a");
+        std::fs::write(&key_path, "rotated").unwrap();
+        assert!(!Arc::ptr_eq(
+            &before,
+            &cache.entry(&from_file(), "https://a")
+        ));
+        std::fs::remove_file(&key_path).unwrap();
+        assert_eq!(cache.len(), 5);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Read-only views of the credentials a client currently holds.
+
+use base64::Engine as _;
+use jiff::Timestamp;
+
+/// Point-in-time view of one credential held by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSnapshot {
+    /// When the token was issued (or acquired, if the token carries no `iat`).
+    /// `None` until a token exists.
+    pub issued_at: Option<Timestamp>,
+    /// When the token expires, when it can be determined from the token itself
+    /// or from the client's own signing parameters.
+    pub expires_at: Option<Timestamp>,
+    /// `true` for the ingest-scoped token, `false` for the control-plane JWT.
+    pub scoped: bool,
+    /// `true` when the client mints and refreshes the token itself.
+    pub managed: bool,
+}
+
+/// Both credentials used by a [`StreamingIngestClient`](crate::StreamingIngestClient).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Control-plane JWT used for discovery and token exchange.
+    pub control: TokenSnapshot,
+    /// Scoped token used for channel and row requests.
+    pub scoped: TokenSnapshot,
+}
+
+impl TokenSnapshot {
+    #[cfg(feature = "crypto")]
+    pub(crate) fn from_millis(
+        issued_at: u64,
+        expires_at: u64,
+        scoped: bool,
+        managed: bool,
+    ) -> Self {
+        Self {
+            issued_at: timestamp_from_millis(issued_at),
+            expires_at: timestamp_from_millis(expires_at),
+            scoped,
+            managed,
+        }
+    }
+
+    /// Builds a snapshot for an opaque token, reading `iat`/`exp` when the
+    /// token happens to be a JWT and falling back to `acquired_at` otherwise.
+    pub(crate) fn from_token(
+        token: Option<&str>,
+        acquired_at: Option<Timestamp>,
+        scoped: bool,
+        managed: bool,
+    ) -> Self {
+        let (iat, exp) = token.map(jwt_times).unwrap_or_default();
+        Self {
+            issued_at: iat.or(acquired_at),
+            expires_at: exp,
+            scoped,
+            managed,
+        }
+    }
+}
+
+fn timestamp_from_millis(millis: u64) -> Option<Timestamp> {
+    if millis == 0 {
+        return None;
+    }
+    Timestamp::from_millisecond(i64::try_from(millis).ok()?).ok()
+}
+
+/// Claims may be in seconds (per RFC 7519) or milliseconds; values too large
+/// to be plausible seconds are treated as milliseconds.
+pub(super) fn claim_timestamp(value: &serde_json::Value) -> Option<Timestamp> {
+    const MILLIS_THRESHOLD: u64 = 100_000_000_000;
+    let raw = value.as_u64()?;
+    if raw >= MILLIS_THRESHOLD {
+        timestamp_from_millis(raw)
+    } else {
+        Timestamp::from_second(i64::try_from(raw).ok()?).ok()
+    }
+}
+
+/// Decodes a JWT's header and claims without verifying its signature.
+pub(super) fn decode_jwt(token: &str) -> Option<(serde_json::Value, serde_json::Value)> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(_), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let decode = |part: &str| {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(part.trim_end_matches('='))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+    };
+    Some((decode(header)?, decode(payload)?))
+}
+
+pub(super) fn jwt_times(token: &str) -> (Option<Timestamp>, Option<Timestamp>) {
+    match decode_jwt(token) {
+        Some((_, claims)) => (
+            claims.get("iat").and_then(claim_timestamp),
+            claims.get("exp").and_then(claim_timestamp),
+        ),
+        None => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_jwt(claims: serde_json::Value) -> String {
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        format!(
+            "{}.{}.sig",
+            engine.encode(r#"{"alg":"none"}"#),
+            engine.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn reads_second_and_millisecond_claims() {
+        let secs = fake_jwt(serde_json::json!({"iat": 1_700_000_000u64, "exp": 1_700_003_600u64}));
+        let snap = TokenSnapshot::from_token(Some(&secs), None, true, false);
+        assert_eq!(
+            snap.issued_at,
+            Some(Timestamp::from_second(1_700_000_000).unwrap())
+        );
+        assert_eq!(
+            snap.expires_at,
+            Some(Timestamp::from_second(1_700_003_600).unwrap())
+        );
+
+        let millis = fake_jwt(serde_json::json!({"iat": 1_700_000_000_000u64}));
+        let snap = TokenSnapshot::from_token(Some(&millis), None, false, false);
+        assert_eq!(
+            snap.issued_at,
+            Some(Timestamp::from_second(1_700_000_000).unwrap())
+        );
+        assert_eq!(snap.expires_at, None);
+    }
+
+    #[test]
+    fn opaque_token_falls_back_to_acquired_at() {
+        let acquired = Timestamp::from_second(1_700_000_000).unwrap();
+        let snap = TokenSnapshot::from_token(Some("opaque"), Some(acquired), true, false);
+        assert_eq!(snap.issued_at, Some(acquired));
+        assert_eq!(snap.expires_at, None);
+
+        let none = TokenSnapshot::from_token(None, None, true, false);
+        assert_eq!(none.issued_at, None);
+    }
+}
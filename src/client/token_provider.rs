@@ -0,0 +1,33 @@
+//! Externally supplied scoped tokens for token broker deployments.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::Error;
+
+/// Future returned by [`ScopedTokenProvider::scoped_token`].
+pub type ScopedTokenFuture<'a> = Pin<Box<dyn Future<Output = Result<String, Error>> + Send + 'a>>;
+
+/// Source of ingest-plane scoped tokens for clients built with
+/// [`StreamingIngestClient::new_with_token_provider`].
+///
+/// The provider is called once during construction and again whenever the
+/// ingest host rejects the current token, so it should return a fresh (or at
+/// least still valid) token on every call. Any `Fn() -> impl Future` closure
+/// returning `Result<String, Error>` implements this trait.
+///
+/// [`StreamingIngestClient::new_with_token_provider`]: crate::StreamingIngestClient::new_with_token_provider
+pub trait ScopedTokenProvider: Send + Sync {
+    /// Fetches a scoped token, e.g. from a central credential service.
+    fn scoped_token(&self) -> ScopedTokenFuture<'_>;
+}
+
+impl<F, Fut> ScopedTokenProvider for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, Error>> + Send + 'static,
+{
+    fn scoped_token(&self) -> ScopedTokenFuture<'_> {
+        Box::pin(self())
+    }
+}
@@ -0,0 +1,234 @@
+//! How often channels poll channel status while waiting for pushed rows to
+//! commit: in `close`, `flush_and_wait`, at `Config::max_uncommitted_offsets`,
+//! and when closing a [`ChannelPool`](crate::ChannelPool).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::Config;
+
+/// Where a commit wait stands after a status poll, passed to
+/// [`CommitWaitStrategy::next_poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CommitProgress {
+    /// Status polls so far in this wait.
+    pub polls: u32,
+    /// Polls in a row, up to and including the latest, that saw no new
+    /// commits.
+    pub unchanged_polls: u32,
+    /// Time since the wait began.
+    pub elapsed: Duration,
+    /// Pushed offsets still awaiting commit.
+    pub uncommitted_offsets: usize,
+}
+
+/// Decides how long a commit wait sleeps between status polls. Set one with
+/// [`Config::commit_wait_strategy`]; the default is [`FixedInterval`] of
+/// 100ms.
+///
+/// ```
+/// use std::time::Duration;
+/// use snowpipe_streaming::{CommitProgress, CommitWaitStrategy, Config, OnStatusChange};
+///
+/// /// Polls quickly for the first second, then every 2s.
+/// struct Eager;
+///
+/// impl CommitWaitStrategy for Eager {
+///     fn next_poll(&self, progress: &CommitProgress) -> Duration {
+///         match progress.elapsed < Duration::from_secs(1) {
+///             true => Duration::from_millis(50),
+///             false => Duration::from_secs(2),
+///         }
+///     }
+/// }
+///
+/// let config = Config::from_values(
+///     "MY_USER", None, "MY_ACCOUNT", "", Some("jwt".into()), None, None, None, None, None,
+/// );
+/// let eager = config.clone().commit_wait_strategy(Eager);
+/// let adaptive = config.commit_wait_strategy(OnStatusChange::new(
+///     Duration::from_millis(50),
+///     Duration::from_secs(5),
+/// ));
+/// # let _ = (eager, adaptive);
+/// ```
+pub trait CommitWaitStrategy: Send + Sync {
+    /// Delay before the next status poll. Waits with a deadline never sleep
+    /// past it.
+    fn next_poll(&self, progress: &CommitProgress) -> Duration;
+}
+
+/// Polls at a fixed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedInterval(pub Duration);
+
+impl Default for FixedInterval {
+    fn default() -> Self {
+        Self(Duration::from_millis(100))
+    }
+}
+
+impl CommitWaitStrategy for FixedInterval {
+    fn next_poll(&self, _progress: &CommitProgress) -> Duration {
+        self.0
+    }
+}
+
+/// Starts at `initial` and doubles the delay after every poll, up to `max`,
+/// for long waits where an early answer matters less than fewer requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    initial: Duration,
+    max: Duration,
+}
+
+impl ExponentialBackoff {
+    /// Backoff from `initial` up to `max`.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+}
+
+impl CommitWaitStrategy for ExponentialBackoff {
+    fn next_poll(&self, progress: &CommitProgress) -> Duration {
+        doubled(self.initial, progress.polls.saturating_sub(1), self.max)
+    }
+}
+
+/// Follows the status responses: polls again after `min` as long as each
+/// poll sees new commits, and doubles the delay, up to `max`, with every
+/// poll that sees none, so a stalled pipe is not polled hot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnStatusChange {
+    min: Duration,
+    max: Duration,
+}
+
+impl OnStatusChange {
+    /// Polls every `min` while commits advance, backing off to `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self { min, max }
+    }
+}
+
+impl CommitWaitStrategy for OnStatusChange {
+    fn next_poll(&self, progress: &CommitProgress) -> Duration {
+        doubled(self.min, progress.unchanged_polls, self.max)
+    }
+}
+
+/// `base` doubled `times` times, capped at `max`.
+pub(crate) fn doubled(base: Duration, times: u32, max: Duration) -> Duration {
+    base.checked_mul(1 << times.min(31)).unwrap_or(max).min(max)
+}
+
+/// One commit wait: counts polls and asks the configured strategy how long
+/// to sleep after each.
+pub(crate) struct CommitWaiter {
+    strategy: Arc<dyn CommitWaitStrategy>,
+    start: Instant,
+    polls: u32,
+    unchanged_polls: u32,
+    uncommitted_offsets: usize,
+}
+
+impl CommitWaiter {
+    /// Starts a wait on `uncommitted_offsets` pushed offsets.
+    pub(crate) fn new(config: &Config, uncommitted_offsets: usize) -> Self {
+        Self {
+            strategy: config
+                .commit_wait
+                .clone()
+                .unwrap_or_else(|| Arc::new(FixedInterval::default())),
+            start: Instant::now(),
+            polls: 0,
+            unchanged_polls: 0,
+            uncommitted_offsets,
+        }
+    }
+
+    /// Records a poll that left `uncommitted_offsets` awaiting commit and
+    /// returns how long to sleep before the next one.
+    pub(crate) fn next_poll(&mut self, uncommitted_offsets: usize) -> Duration {
+        self.polls += 1;
+        if uncommitted_offsets < self.uncommitted_offsets {
+            self.unchanged_polls = 0;
+        } else {
+            self.unchanged_polls += 1;
+        }
+        self.uncommitted_offsets = uncommitted_offsets;
+        self.strategy.next_poll(&CommitProgress {
+            polls: self.polls,
+            unchanged_polls: self.unchanged_polls,
+            elapsed: self.start.elapsed(),
+            uncommitted_offsets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_strategies_double_up_to_their_cap() {
+        let ms = Duration::from_millis;
+        let progress = |polls, unchanged_polls| CommitProgress {
+            polls,
+            unchanged_polls,
+            elapsed: Duration::ZERO,
+            uncommitted_offsets: 1,
+        };
+        let exponential = ExponentialBackoff::new(ms(100), ms(1000));
+        let delays = (1..=6)
+            .map(|polls| exponential.next_poll(&progress(polls, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            [ms(100), ms(200), ms(400), ms(800), ms(1000), ms(1000)]
+        );
+        assert_eq!(exponential.next_poll(&progress(u32::MAX, 0)), ms(1000));
+
+        let on_change = OnStatusChange::new(ms(50), ms(500));
+        assert_eq!(on_change.next_poll(&progress(7, 0)), ms(50));
+        assert_eq!(on_change.next_poll(&progress(7, 2)), ms(200));
+        assert_eq!(on_change.next_poll(&progress(7, 9)), ms(500));
+    }
+
+    #[test]
+    fn waiter_counts_polls_without_new_commits() {
+        struct Echo;
+        impl CommitWaitStrategy for Echo {
+            fn next_poll(&self, progress: &CommitProgress) -> Duration {
+                Duration::from_secs(progress.unchanged_polls.into())
+            }
+        }
+        let config = Config::from_values(
+            "user",
+            None,
+            "acct",
+            "http://localhost",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            CommitWaiter::new(&config, 3).next_poll(3),
+            Duration::from_millis(100)
+        );
+        let config = config.commit_wait_strategy(Echo);
+        let mut waiter = CommitWaiter::new(&config, 3);
+        let unchanged = [3, 3, 2, 2, 2, 0]
+            .into_iter()
+            .map(|left| waiter.next_poll(left).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(unchanged, [1, 2, 0, 1, 2, 0]);
+        assert_eq!(waiter.polls, 6);
+    }
+}
@@ -0,0 +1,201 @@
+//! Dropping rows whose key was appended recently, for sources that deliver
+//! at least once into tables without MERGE-based deduplication.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::partition::{BatchPartitioner, ColumnPartitioner};
+
+type RowKeyFn = dyn Fn(&[u8]) -> Option<String> + Send + Sync;
+
+/// In-memory window of the keys of recently appended rows. A channel with
+/// one set (see
+/// [`StreamingIngestChannel::set_dedup_window`](crate::StreamingIngestChannel::set_dedup_window))
+/// drops rows whose key is in the window, or repeats an earlier row of the
+/// same batch, before sending.
+///
+/// The window keeps the `capacity` most recently seen keys; a duplicate
+/// counts as a use, so keys that keep arriving stay in it. With
+/// [`with_ttl`](Self::with_ttl), keys also expire once they were last seen
+/// that long ago. Keys are remembered once their request is accepted, so
+/// rows of a failed append are not dropped when it is retried. Rows without
+/// a key are never dropped.
+///
+/// This is a best-effort filter: it is lost on restart and does not span
+/// channels or processes.
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use std::time::Duration;
+/// use snowpipe_streaming::DedupWindow;
+///
+/// let window = DedupWindow::by_column("event_id", NonZeroUsize::new(100_000).unwrap())
+///     .with_ttl(Duration::from_secs(600));
+/// assert!(window.is_empty());
+/// ```
+#[derive(Clone)]
+pub struct DedupWindow {
+    key: Arc<RowKeyFn>,
+    capacity: usize,
+    ttl: Option<Duration>,
+    /// Each key with the stamp and time of its latest use.
+    seen: HashMap<String, (u64, Instant)>,
+    /// Keys in order of use; entries whose stamp is stale were used again
+    /// later and are skipped.
+    order: VecDeque<(u64, String)>,
+    next_stamp: u64,
+    dropped: u64,
+}
+
+impl DedupWindow {
+    /// Keys each row, one row serialized as JSON, with `key`; `None` leaves
+    /// the row out of deduplication.
+    pub fn new<F>(capacity: NonZeroUsize, key: F) -> Self
+    where
+        F: Fn(&[u8]) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            key: Arc::new(key),
+            capacity: capacity.get(),
+            ttl: None,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            next_stamp: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Keys each row by the value of a top-level column, such as `event_id`.
+    pub fn by_column(column: impl Into<String>, capacity: NonZeroUsize) -> Self {
+        let column = ColumnPartitioner::new(column);
+        Self::new(capacity, move |row| column.partition_key(row))
+    }
+
+    /// Forgets keys last seen more than `ttl` ago.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Keys currently remembered, including any that have expired but not
+    /// yet been evicted.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Rows dropped as duplicates so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub(crate) fn key(&self, row: &[u8]) -> Option<String> {
+        (self.key)(row)
+    }
+
+    /// Whether `key` is in the window; a hit refreshes it.
+    pub(crate) fn check(&mut self, key: &str, now: Instant) -> bool {
+        let live = self.seen.get(key).is_some_and(|(_, seen_at)| {
+            self.ttl
+                .is_none_or(|ttl| now.saturating_duration_since(*seen_at) <= ttl)
+        });
+        if live {
+            self.touch(key.to_string(), now);
+        }
+        live
+    }
+
+    pub(crate) fn count_dropped(&mut self, rows: usize) {
+        self.dropped += rows as u64;
+    }
+
+    /// Adds the keys of rows Snowflake accepted.
+    pub(crate) fn remember(&mut self, keys: impl IntoIterator<Item = String>, now: Instant) {
+        for key in keys {
+            self.touch(key, now);
+        }
+    }
+
+    fn touch(&mut self, key: String, now: Instant) {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.seen.insert(key.clone(), (stamp, now));
+        self.order.push_back((stamp, key));
+        while self.seen.len() > self.capacity {
+            self.evict_oldest();
+        }
+        // Drop stale entries left behind by refreshed keys.
+        if self.order.len() > 2 * self.capacity {
+            let seen = &self.seen;
+            self.order
+                .retain(|(stamp, key)| seen.get(key).is_some_and(|(s, _)| s == stamp));
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((stamp, key)) = self.order.pop_front() {
+            if self.seen.get(&key).is_some_and(|(s, _)| *s == stamp) {
+                self.seen.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DedupWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupWindow")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("len", &self.seen.len())
+            .field("dropped", &self.dropped)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(capacity: usize) -> DedupWindow {
+        DedupWindow::by_column("id", NonZeroUsize::new(capacity).unwrap())
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_key() {
+        let now = Instant::now();
+        let mut window = window(2);
+        assert_eq!(window.key(br#"{"id":1,"v":"x"}"#).as_deref(), Some("1"));
+        assert_eq!(window.key(br#"{"v":"x"}"#), None);
+
+        window.remember(["a".to_string(), "b".to_string()], now);
+        assert!(window.check("a", now), "refreshes a");
+        window.remember(["c".to_string()], now);
+        assert!(window.check("a", now));
+        assert!(!window.check("b", now), "b was least recently used");
+        assert!(window.check("c", now));
+        assert_eq!(window.len(), 2);
+        for _ in 0..10 {
+            window.check("a", now);
+        }
+        assert!(window.order.len() <= 4);
+    }
+
+    #[test]
+    fn keys_expire_after_the_ttl() {
+        let now = Instant::now();
+        let mut window = window(10).with_ttl(Duration::from_secs(60));
+        window.remember(["a".to_string()], now);
+        assert!(window.check("a", now + Duration::from_secs(60)));
+        assert!(!window.check("a", now + Duration::from_secs(121)));
+    }
+}
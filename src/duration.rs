@@ -0,0 +1,219 @@
+//! Durations in configuration: a bare number in the key's unit, as earlier
+//! releases read them, or a string such as `"250ms"`, `"2m"`, or `"1h 30m"`.
+//! Keys read with [`secs`] take bare numbers as seconds, and keys read with
+//! [`millis`] as milliseconds.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+/// Parses `value` as a number in `unit`s or a duration string, explaining
+/// what was expected on failure.
+pub(crate) fn parse(value: &str, unit: Duration) -> Result<Duration, String> {
+    let value = value.trim();
+    if let Ok(n) = value.parse::<f64>() {
+        return from_units(n, unit);
+    }
+    value
+        .parse::<jiff::SignedDuration>()
+        .ok()
+        .and_then(|d| Duration::try_from(d).ok())
+        .ok_or_else(|| {
+            format!(
+                "invalid duration '{value}': expected a number of {} or a duration such as \
+                 \"250ms\", \"2m\", or \"1h 30m\"",
+                unit_name(unit)
+            )
+        })
+}
+
+fn from_units(n: f64, unit: Duration) -> Result<Duration, String> {
+    Duration::try_from_secs_f64(unit.as_secs_f64() * n).map_err(|_| {
+        format!(
+            "invalid duration {n}: expected a non-negative number of {}",
+            unit_name(unit)
+        )
+    })
+}
+
+fn unit_name(unit: Duration) -> &'static str {
+    match unit {
+        UNIT_MS => "milliseconds",
+        _ => "seconds",
+    }
+}
+
+const UNIT_MS: Duration = Duration::from_millis(1);
+
+struct DurationVisitor(Duration);
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a number of {} or a duration string such as \"250ms\"",
+            unit_name(self.0)
+        )
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Duration, E> {
+        Ok(match self.0 {
+            UNIT_MS => Duration::from_millis(n),
+            _ => Duration::from_secs(n),
+        })
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Duration, E> {
+        from_units(n as f64, self.0).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Duration, E> {
+        from_units(n, self.0).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Duration, E> {
+        parse(value, self.0).map_err(E::custom)
+    }
+}
+
+struct OptionVisitor(Duration);
+
+impl<'de> Visitor<'de> for OptionVisitor {
+    type Value = Option<Duration>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        DurationVisitor(self.0).expecting(f)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer
+            .deserialize_any(DurationVisitor(self.0))
+            .map(Some)
+    }
+}
+
+fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    unit: Duration,
+) -> Result<Option<Duration>, D::Error> {
+    deserializer.deserialize_option(OptionVisitor(unit))
+}
+
+/// Writes whole units as a bare number, so older releases can read the
+/// value back, and anything finer as a duration string.
+fn serialize<S: Serializer>(
+    value: &Option<Duration>,
+    serializer: S,
+    unit: Duration,
+) -> Result<S::Ok, S::Error> {
+    let Some(duration) = value else {
+        return serializer.serialize_none();
+    };
+    if (duration.as_nanos() % unit.as_nanos()) == 0 {
+        let units = duration.as_nanos() / unit.as_nanos();
+        return serializer.serialize_u64(units.try_into().unwrap_or(u64::MAX));
+    }
+    let friendly = jiff::SignedDuration::try_from(*duration).map_err(serde::ser::Error::custom)?;
+    serializer.collect_str(&format_args!("{friendly:#}"))
+}
+
+/// `#[serde(with)]` for `Option<Duration>` keys whose bare numbers are
+/// seconds.
+pub(crate) mod secs {
+    use std::time::Duration;
+
+    use serde::{Deserializer, Serializer};
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        super::deserialize(deserializer, Duration::from_secs(1))
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize(value, serializer, Duration::from_secs(1))
+    }
+}
+
+/// `#[serde(with)]` for `Option<Duration>` keys whose bare numbers are
+/// milliseconds.
+pub(crate) mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserializer, Serializer};
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        super::deserialize(deserializer, super::UNIT_MS)
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize(value, serializer, super::UNIT_MS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_keep_their_unit_and_strings_are_durations() {
+        let secs = Duration::from_secs(1);
+        assert_eq!(parse("30", secs), Ok(Duration::from_secs(30)));
+        assert_eq!(parse("1.5", secs), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse("250", UNIT_MS), Ok(Duration::from_millis(250)));
+        assert_eq!(parse("250ms", secs), Ok(Duration::from_millis(250)));
+        assert_eq!(parse("2m", secs), Ok(Duration::from_secs(120)));
+        assert_eq!(parse("1h 30m", secs), Ok(Duration::from_secs(5400)));
+        let err = parse("2 fortnights", secs).unwrap_err();
+        assert!(
+            err.contains("'2 fortnights'") && err.contains("seconds"),
+            "{err}"
+        );
+        assert!(parse("-5", secs).is_err());
+        assert!(parse("-5s", secs).is_err());
+
+        #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
+        struct Keys {
+            #[serde(default, with = "secs")]
+            a_secs: Option<Duration>,
+            #[serde(default, with = "millis")]
+            b_ms: Option<Duration>,
+        }
+        let keys: Keys = serde_json::from_str(r#"{"a_secs": "250ms", "b_ms": 40}"#).unwrap();
+        assert_eq!(
+            keys,
+            Keys {
+                a_secs: Some(Duration::from_millis(250)),
+                b_ms: Some(Duration::from_millis(40)),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&keys).unwrap(),
+            r#"{"a_secs":"250ms","b_ms":40}"#
+        );
+        let missing: Keys = serde_json::from_str("{}").unwrap();
+        assert_eq!(missing.a_secs, None);
+        let err = serde_json::from_str::<Keys>(r#"{"a_secs": "soon"}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid duration 'soon'"), "{err}");
+    }
+}
@@ -0,0 +1,232 @@
+//! Request URLs for the control plane and the ingest host.
+//!
+//! Identifiers are appended as single path segments, so quoted names with
+//! dots, spaces, quotes, or non-ASCII characters are percent-encoded rather
+//! than interpolated into the path as-is.
+
+use reqwest::Url;
+
+use crate::errors::Error;
+
+/// The database, schema, and pipe a request targets.
+#[derive(Clone, Copy)]
+pub(crate) struct PipeRef<'a> {
+    pub(crate) db: &'a str,
+    pub(crate) schema: &'a str,
+    pub(crate) pipe: &'a str,
+}
+
+/// `GET` — returns the ingest host for the account.
+pub(crate) fn hostname(control_host: &str) -> Result<Url, Error> {
+    join(parse_base(control_host)?, &["v2", "streaming", "hostname"])
+}
+
+/// `POST` — exchanges a JWT or access token for a scoped token.
+pub(crate) fn oauth_token(control_host: &str) -> Result<Url, Error> {
+    join(parse_base(control_host)?, &["oauth", "token"])
+}
+
+/// `PUT` opens a channel, `DELETE` drops it.
+pub(crate) fn channel(ingest_host: &str, pipe: PipeRef<'_>, channel: &str) -> Result<Url, Error> {
+    join(
+        ingest_base(ingest_host)?,
+        &[
+            "v2",
+            "streaming",
+            "databases",
+            pipe.db,
+            "schemas",
+            pipe.schema,
+            "pipes",
+            pipe.pipe,
+            "channels",
+            channel,
+        ],
+    )
+}
+
+/// `POST` — reports the status of the channels named in the body.
+pub(crate) fn bulk_channel_status(ingest_host: &str, pipe: PipeRef<'_>) -> Result<Url, Error> {
+    join(
+        ingest_base(ingest_host)?,
+        &[
+            "v2",
+            "streaming",
+            "databases",
+            pipe.db,
+            "schemas",
+            pipe.schema,
+            "pipes",
+            &format!("{}:bulk-channel-status", pipe.pipe),
+        ],
+    )
+}
+
+/// `POST` — appends rows to a channel. With [`ApiVersion::V2`] the tokens go
+/// in the query string (see [`rows_with_tokens`]); with
+/// [`ApiVersion::Preview`] they go in the body.
+///
+/// [`ApiVersion::V2`]: crate::ApiVersion::V2
+/// [`ApiVersion::Preview`]: crate::ApiVersion::Preview
+pub(crate) fn rows(ingest_host: &str, pipe: PipeRef<'_>, channel: &str) -> Result<Url, Error> {
+    join(
+        ingest_base(ingest_host)?,
+        &[
+            "v2",
+            "streaming",
+            "data",
+            "databases",
+            pipe.db,
+            "schemas",
+            pipe.schema,
+            "pipes",
+            pipe.pipe,
+            "channels",
+            channel,
+            "rows",
+        ],
+    )
+}
+
+/// [`rows`], appending at `offset_token` after `continuation_token`.
+pub(crate) fn rows_with_tokens(
+    ingest_host: &str,
+    pipe: PipeRef<'_>,
+    channel: &str,
+    continuation_token: &str,
+    offset_token: &str,
+) -> Result<Url, Error> {
+    let mut url = rows(ingest_host, pipe, channel)?;
+    url.query_pairs_mut()
+        .append_pair("continuationToken", continuation_token)
+        .append_pair("offsetToken", offset_token);
+    Ok(url)
+}
+
+/// `HEAD` — the ingest host root, used to open a connection ahead of time.
+pub(crate) fn ingest_root(ingest_host: &str) -> Result<Url, Error> {
+    join(ingest_base(ingest_host)?, &[])
+}
+
+/// The ingest host as discovered (a bare host name) or configured (possibly
+/// with a scheme), as a base URL.
+fn ingest_base(ingest_host: &str) -> Result<Url, Error> {
+    if ingest_host.contains("://") {
+        parse_base(ingest_host)
+    } else {
+        parse_base(&format!("https://{}", ingest_host))
+    }
+}
+
+fn parse_base(base: &str) -> Result<Url, Error> {
+    Url::parse(base).map_err(|e| Error::Config(format!("Invalid host URL '{}': {}", base, e)))
+}
+
+/// Appends `segments` to the path of `base`, percent-encoding each one.
+fn join(mut base: Url, segments: &[&str]) -> Result<Url, Error> {
+    if base.cannot_be_a_base() {
+        return Err(Error::Config(format!(
+            "Host URL '{}' cannot have a path",
+            base
+        )));
+    }
+    if let Ok(mut path) = base.path_segments_mut() {
+        path.pop_if_empty().extend(segments);
+    }
+    Ok(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPE: PipeRef<'static> = PipeRef {
+        db: "db",
+        schema: "schema",
+        pipe: "pipe",
+    };
+
+    #[test]
+    fn control_plane_endpoints() {
+        assert_eq!(
+            hostname("https://acct.snowflakecomputing.com")
+                .unwrap()
+                .as_str(),
+            "https://acct.snowflakecomputing.com/v2/streaming/hostname"
+        );
+        assert_eq!(
+            oauth_token("http://127.0.0.1:8080/").unwrap().as_str(),
+            "http://127.0.0.1:8080/oauth/token"
+        );
+        assert!(matches!(hostname(""), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn ingest_root_endpoint() {
+        assert_eq!(
+            ingest_root("ingest.example").unwrap().as_str(),
+            "https://ingest.example/"
+        );
+        assert_eq!(
+            ingest_root("http://localhost:1234").unwrap().as_str(),
+            "http://localhost:1234/"
+        );
+    }
+
+    #[test]
+    fn channel_endpoint_encodes_identifiers() {
+        assert_eq!(
+            channel("ingest.example", PIPE, "ch").unwrap().as_str(),
+            "https://ingest.example/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch"
+        );
+        let quoted = PipeRef {
+            db: "\"My DB\"",
+            schema: "sch.v2",
+            pipe: "pipé",
+        };
+        assert_eq!(
+            channel("http://localhost:1234/", quoted, "events 2024")
+                .unwrap()
+                .as_str(),
+            "http://localhost:1234/v2/streaming/databases/%22My%20DB%22/schemas/sch.v2/pipes/pip%C3%A9/channels/events%202024"
+        );
+    }
+
+    #[test]
+    fn bulk_channel_status_endpoint_suffixes_the_pipe() {
+        assert_eq!(
+            bulk_channel_status("ingest.example", PIPE)
+                .unwrap()
+                .as_str(),
+            "https://ingest.example/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status"
+        );
+        let spaced = PipeRef {
+            pipe: "my pipe",
+            ..PIPE
+        };
+        assert_eq!(
+            bulk_channel_status("ingest.example", spaced)
+                .unwrap()
+                .path(),
+            "/v2/streaming/databases/db/schemas/schema/pipes/my%20pipe:bulk-channel-status"
+        );
+    }
+
+    #[test]
+    fn rows_endpoint_encodes_tokens_as_query_pairs() {
+        let url = rows_with_tokens("ingest.example", PIPE, "ch ü", "a+b/c=", "7").unwrap();
+        assert_eq!(
+            url.path(),
+            "/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/ch%20%C3%BC/rows"
+        );
+        assert_eq!(
+            url.query(),
+            Some("continuationToken=a%2Bb%2Fc%3D&offsetToken=7")
+        );
+        let pairs = url.query_pairs().collect::<Vec<_>>();
+        assert_eq!(pairs[0].1, "a+b/c=");
+
+        let url = rows("ingest.example", PIPE, "ch").unwrap();
+        assert_eq!(url.query(), None);
+    }
+}
@@ -1,21 +1,380 @@
 use reqwest::StatusCode;
 
+/// Errors returned by the client and its channels.
 #[derive(Debug)]
 pub enum Error {
+    /// Reading configuration, key material, or a tailed file from disk
+    /// failed, or (with the `chaos` feature) an injected connection drop.
     Io(std::io::Error),
+    /// A configuration file, row, or response body was not valid JSON.
     Json(serde_json::Error),
+    /// Snowflake answered with a non-success status (status, response body).
     Http(reqwest::StatusCode, String),
+    /// Transport-level failure, or a non-success status surfaced by `reqwest`.
     Reqwest(reqwest::Error),
+    /// The control plane refused to return an ingest host (status, response body).
     IngestHostDiscovery(StatusCode, String),
+    /// A request body exceeded the per-request limit (actual bytes, max bytes).
     DataTooLarge(usize, usize),
+    /// An external JWT generation process exited unsuccessfully.
     JwtError(std::process::Output),
+    /// Missing or inconsistent configuration.
     Config(String),
+    /// An operation did not finish within the given duration: a channel's
+    /// commits did not catch up before `close` gave up, or a request hit
+    /// `Config::connect_timeout`, `Config::read_timeout`, or
+    /// `RetryPlan::attempt_timeout`.
     Timeout(std::time::Duration),
+    /// The private key could not be read, decrypted, or parsed.
     Key(String),
+    /// Signing the key-pair JWT failed.
     JwtSign(String),
+    /// Key material was not valid UTF-8.
     Utf8Error(std::string::FromUtf8Error),
+    /// Snowflake rejected the credentials, even after refreshing them.
     Auth(String),
+    /// Snowflake returned a response this client could not interpret.
     UnexpectedResponse(String),
+    /// A row queued with `append_row_async` was not appended; the message
+    /// describes the underlying failure.
+    AppendFailed(String),
+    /// Another writer reopened the channel (named here), invalidating this
+    /// handle's continuation token. Reopen the channel, or set
+    /// `Config::fenced_channel_suffix` to continue on a new channel automatically.
+    ChannelFenced(String),
+    /// Another client was opening the same channel at the same time, and
+    /// opening it still conflicted after `Config::channel_open_conflict_retries`
+    /// retries.
+    ChannelConflict(Box<ChannelConflict>),
+    /// A writer with a newer `Config::writer_generation` owns the channel
+    /// (this writer's generation, newer generation).
+    WriterFenced(u32, u32),
+    /// A change-data-capture event could not be mapped to a row.
+    InvalidCdcEvent(String),
+    /// A dynamic row was not a JSON object; nothing was appended.
+    InvalidRow(String),
+    /// A database, schema, pipe, or channel name contains a character that
+    /// cannot appear in a request path (name, offending character).
+    InvalidIdentifier(String, char),
+    /// The channel (named here) was quarantined after too many of its rows
+    /// were rejected; see `StreamingIngestChannel::set_quarantine_threshold`.
+    ChannelQuarantined(String),
+    /// The channel (named here) already had the maximum number of pushed
+    /// offsets awaiting commit (the limit), with
+    /// `Config::fail_on_uncommitted_limit` set. Retry once commits catch up.
+    UncommittedLimit(String, usize),
+    /// A response body exceeded `Config::max_response_bytes` (status, limit);
+    /// typically a proxy's error page. The body was not read past the limit.
+    ResponseTooLarge(StatusCode, usize),
+    /// A transport, I/O, or response decoding failure on the request path,
+    /// with the step it happened in. Match on [`Error::root`] to inspect the
+    /// underlying error.
+    Context(Box<ErrorContext>, Box<Error>),
+}
+
+/// A channel open that raced another opener of the same channel, carried by
+/// [`Error::ChannelConflict`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelConflict {
+    /// The channel name.
+    pub channel: String,
+    /// Snowflake's description of the conflict.
+    pub message: String,
+    /// What the response said about the other opener (e.g. its client ID or
+    /// when it opened the channel): the fields of a JSON response body other
+    /// than its error code and message. Empty when Snowflake sent none.
+    pub opener: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ChannelConflict {
+    /// Reads a `409 Conflict` response body to opening `channel`.
+    pub(crate) fn from_body(channel: &str, body: &str) -> Self {
+        let mut opener = match serde_json::from_str(body) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => serde_json::Map::new(),
+        };
+        let message = match opener.remove("message") {
+            Some(serde_json::Value::String(message)) => message,
+            _ => body.to_string(),
+        };
+        opener.remove("code");
+        Self {
+            channel: channel.to_string(),
+            message,
+            opener,
+        }
+    }
+}
+
+/// A step on the request path, as recorded in an [`ErrorContext`] and
+/// passed to [`Config::on_retry`](crate::Config::on_retry) observers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OperationKind {
+    /// Looking up the ingest host from the control plane.
+    DiscoverIngestHost,
+    /// Exchanging the control-plane JWT for a scoped token.
+    ExchangeScopedToken,
+    /// Opening a connection to the ingest host ahead of the first request.
+    WarmUp,
+    /// Opening (or reopening) a channel.
+    OpenChannel,
+    /// Appending rows to a channel.
+    AppendRows,
+    /// Polling a channel's status, e.g. for its committed offset token.
+    ChannelStatus,
+    /// Dropping a channel once its rows are committed, as the last step of
+    /// `close`.
+    CloseChannel,
+    /// Dropping a channel without waiting for its rows to commit.
+    DropChannel,
+}
+
+impl OperationKind {
+    /// The step as it appears in error messages, e.g. `"append rows"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OperationKind::DiscoverIngestHost => "discover ingest host",
+            OperationKind::ExchangeScopedToken => "exchange scoped token",
+            OperationKind::WarmUp => "warm up",
+            OperationKind::OpenChannel => "open channel",
+            OperationKind::AppendRows => "append rows",
+            OperationKind::ChannelStatus => "get channel status",
+            OperationKind::CloseChannel => "close channel",
+            OperationKind::DropChannel => "drop channel",
+        }
+    }
+}
+
+impl std::fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A request the client is about to retry, passed to
+/// [`Config::on_retry`](crate::Config::on_retry) observers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RetryEvent<'a> {
+    /// The step being retried.
+    pub operation: OperationKind,
+    /// The attempt that failed (the first attempt is 1).
+    pub attempt: u32,
+    /// Why the attempt failed, e.g. `Error::Http(429, ..)`.
+    pub error: &'a Error,
+    /// How long the client waits before the next attempt.
+    pub delay: std::time::Duration,
+}
+
+/// Where on the request path an [`Error::Context`] failure happened.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    operation: Option<OperationKind>,
+    channel: Option<String>,
+    attempt: Option<u32>,
+    url: Option<String>,
+}
+
+impl ErrorContext {
+    /// The step that failed, e.g. `"append rows"` or `"open channel"`.
+    pub fn operation(&self) -> Option<&str> {
+        self.operation.map(OperationKind::as_str)
+    }
+
+    /// The step that failed.
+    pub fn operation_kind(&self) -> Option<OperationKind> {
+        self.operation
+    }
+
+    /// Channel the step was acting on.
+    pub fn channel(&self) -> Option<&str> {
+        self.channel.as_deref()
+    }
+
+    /// Attempt number of the failed request, counting retries after a 401
+    /// or 429 (the first attempt is 1).
+    pub fn attempt(&self) -> Option<u32> {
+        self.attempt
+    }
+
+    /// Request URL, with continuation tokens redacted.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.operation().unwrap_or("request"))?;
+        if let Some(channel) = &self.channel {
+            write!(f, " on channel '{}'", channel)?;
+        }
+        write!(f, " failed")?;
+        if let Some(attempt) = self.attempt {
+            write!(f, " (attempt {})", attempt)?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " at {}", url)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// Where the error happened, for errors raised on the request path.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context(context, _) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without any [`Error::Context`] wrapper.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context(_, inner) => inner.root(),
+            other => other,
+        }
+    }
+
+    /// Whether retrying the failed operation may succeed: timeouts, connection
+    /// and I/O failures, throttling (429), server errors (5xx), a full
+    /// [`Error::UncommittedLimit`], and an [`Error::ChannelConflict`]. An
+    /// oversized response is retryable when its status is. Authentication,
+    /// configuration, and data errors are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self.root() {
+            Error::Timeout(_)
+            | Error::Io(_)
+            | Error::UncommittedLimit(..)
+            | Error::ChannelConflict(_) => true,
+            Error::Http(status, _) | Error::ResponseTooLarge(status, _) => {
+                is_retryable_status(*status)
+            }
+            Error::Reqwest(err) => {
+                err.is_timeout()
+                    || err.is_connect()
+                    || err.status().is_some_and(is_retryable_status)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether Snowflake rejected the request's rows as invalid: a 4xx answer
+    /// other than 401 (credentials) or 429 (throttling).
+    pub(crate) fn is_rejection(&self) -> bool {
+        let status = match self.root() {
+            Error::Http(status, _) => Some(*status),
+            Error::Reqwest(err) => err.status(),
+            _ => None,
+        };
+        status.is_some_and(|status| {
+            status.is_client_error()
+                && status != StatusCode::UNAUTHORIZED
+                && status != StatusCode::TOO_MANY_REQUESTS
+        })
+    }
+
+    /// Converts a transport failure, reporting a timeout as [`Error::Timeout`]
+    /// with the configured limit it hit.
+    pub(crate) fn from_transport(err: reqwest::Error, timeouts: TransportTimeouts) -> Error {
+        let limit = if err.is_connect() {
+            timeouts.connect
+        } else {
+            timeouts.read
+        };
+        match limit {
+            Some(limit) if err.is_timeout() => {
+                let url = err.url().map(scrub_url);
+                Error::Timeout(limit).with_context(|context| context.url = url)
+            }
+            _ => Error::Reqwest(err),
+        }
+    }
+
+    /// Records the step (and channel) a transport, I/O, or timeout failure
+    /// happened in. An operation recorded closer to the failure is kept.
+    pub(crate) fn in_operation(self, operation: OperationKind, channel: Option<&str>) -> Error {
+        self.with_context(|context| {
+            context.operation.get_or_insert(operation);
+            if let Some(channel) = channel {
+                context.channel.get_or_insert_with(|| channel.to_string());
+            }
+        })
+    }
+
+    /// Records the request URL, for failures (such as decoding a response)
+    /// that do not carry one.
+    pub(crate) fn at_url(self, url: &str) -> Error {
+        self.with_context(|context| {
+            if context.url.is_none() {
+                context.url = reqwest::Url::parse(url).ok().map(|url| scrub_url(&url));
+            }
+        })
+    }
+
+    /// Records which attempt of a request a transport or I/O failure ended.
+    pub(crate) fn on_attempt(self, attempt: u32) -> Error {
+        self.with_context(|context| {
+            context.attempt.get_or_insert(attempt);
+        })
+    }
+
+    /// Wraps transport, I/O, and timeout failures in [`Error::Context`] (or
+    /// updates an existing wrapper); other errors already say what went wrong.
+    fn with_context(self, update: impl FnOnce(&mut ErrorContext)) -> Error {
+        match self {
+            Error::Context(mut context, inner) => {
+                update(&mut context);
+                Error::Context(context, inner)
+            }
+            Error::Reqwest(_) | Error::Io(_) | Error::Timeout(_) | Error::Json(_) => {
+                let mut context = ErrorContext::default();
+                // reqwest prints the full URL, tokens included; keep only the
+                // scrubbed copy in the context.
+                let inner = match self {
+                    Error::Reqwest(err) => {
+                        context.url = err.url().map(scrub_url);
+                        Error::Reqwest(err.without_url())
+                    }
+                    other => other,
+                };
+                update(&mut context);
+                Error::Context(Box::new(context), Box::new(inner))
+            }
+            other => other,
+        }
+    }
+}
+
+/// Connect and read timeouts configured on the client's connection pool.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TransportTimeouts {
+    pub(crate) connect: Option<std::time::Duration>,
+    pub(crate) read: Option<std::time::Duration>,
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Renders `url` with the values of token query parameters (such as
+/// `continuationToken`) redacted. Offset tokens are not secrets and are kept.
+fn scrub_url(url: &reqwest::Url) -> String {
+    let mut scrubbed = url.clone();
+    if url.query().is_some() {
+        let pairs = url
+            .query_pairs()
+            .map(|(key, value)| {
+                let lower = key.to_ascii_lowercase();
+                let secret = lower.contains("token") && lower != "offsettoken";
+                (key, if secret { "REDACTED".into() } else { value })
+            })
+            .collect::<Vec<_>>();
+        scrubbed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    scrubbed.to_string()
 }
 
 impl From<std::io::Error> for Error {
@@ -42,12 +401,14 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+#[cfg(feature = "crypto")]
 impl From<pkcs8::Error> for Error {
     fn from(err: pkcs8::Error) -> Self {
         Error::Key(format!("PKCS#8 error: {}", err))
     }
 }
 
+#[cfg(feature = "crypto")]
 impl From<rsa::pkcs1::Error> for Error {
     fn from(err: rsa::pkcs1::Error) -> Self {
         Error::Key(format!("PKCS#1 error: {}", err))
@@ -89,6 +450,53 @@ impl std::fmt::Display for Error {
             Error::JwtSign(msg) => write!(f, "JWT signing error: {}", msg),
             Error::Auth(msg) => write!(f, "Authentication failed: {}", msg),
             Error::UnexpectedResponse(msg) => write!(f, "Unexpected response from server: {}", msg),
+            Error::AppendFailed(msg) => write!(f, "Append failed: {}", msg),
+            Error::WriterFenced(ours, newer) => write!(
+                f,
+                "Writer generation {} has been superseded by generation {}",
+                ours, newer
+            ),
+            Error::InvalidCdcEvent(msg) => write!(f, "Invalid CDC event: {}", msg),
+            Error::InvalidRow(msg) => write!(f, "Invalid row: {}", msg),
+            Error::InvalidIdentifier(name, c) => {
+                write!(f, "Invalid identifier '{}': {:?} is not allowed", name, c)
+            }
+            Error::ChannelQuarantined(channel) => write!(
+                f,
+                "Channel '{}' is quarantined after too many rejected rows",
+                channel
+            ),
+            Error::UncommittedLimit(channel, limit) => write!(
+                f,
+                "Channel '{}' has {} pushed offsets awaiting commit, the configured maximum",
+                channel, limit
+            ),
+            Error::ResponseTooLarge(status, limit) => write!(
+                f,
+                "Response body exceeded {} bytes: status={}",
+                limit, status
+            ),
+            Error::ChannelFenced(channel) => write!(
+                f,
+                "Channel '{}' was reopened by another writer; its continuation token is no longer valid",
+                channel
+            ),
+            Error::ChannelConflict(conflict) => {
+                write!(
+                    f,
+                    "Channel '{}' is being opened by another client: {}",
+                    conflict.channel, conflict.message
+                )?;
+                if !conflict.opener.is_empty() {
+                    write!(
+                        f,
+                        " (opener: {})",
+                        serde_json::Value::Object(conflict.opener.clone())
+                    )?;
+                }
+                Ok(())
+            }
+            Error::Context(context, inner) => write!(f, "{}: {}", context, inner),
         }
     }
 }
@@ -99,6 +507,7 @@ impl std::error::Error for Error {
             Error::Io(e) => Some(e),
             Error::Json(e) => Some(e),
             Error::Reqwest(e) => Some(e),
+            Error::Context(_, inner) => Some(inner.as_ref()),
             _ => None,
         }
     }
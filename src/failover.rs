@@ -0,0 +1,249 @@
+//! Ingestion into two Snowflake accounts, e.g. in different regions, for
+//! business continuity.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::{ChannelReport, Error, StreamingIngestChannel, StreamingIngestClient, Warning};
+
+/// How a [`FailoverClient`] uses its secondary account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverMode {
+    /// Every append is sent to both accounts at once and succeeds when
+    /// either accepts it; a failure on one side is reported as
+    /// [`Warning::ReplicaAppendFailed`]. Both accounts must be reachable
+    /// when a channel is opened.
+    Replicate,
+    /// Appends go to the primary until it has failed with retryable errors
+    /// (see [`Error::is_retryable`]) for at least `after`; the channel is then
+    /// reopened on the secondary, which takes the failed rows and every
+    /// later append. A channel does not move back to the primary.
+    Failover {
+        /// How long the primary must keep failing before failing over.
+        after: Duration,
+    },
+}
+
+/// One of the accounts behind a [`FailoverClient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailoverAccount {
+    /// The account appends go to first.
+    Primary,
+    /// The standby account.
+    Secondary,
+}
+
+/// A pair of clients for the same pipe on two accounts; channels opened from
+/// it replicate or fail over appends according to a [`FailoverMode`].
+///
+/// Offset tokens are per account: a channel on the secondary continues from
+/// the secondary's own committed offset, not the primary's. When resuming
+/// exactly-once after a failover, track committed offsets per account; rows
+/// pushed to the primary but not committed before it failed may or may not
+/// land there once it recovers (see [`Warning::FailedOver`]).
+pub struct FailoverClient<R> {
+    primary: StreamingIngestClient<R>,
+    secondary: StreamingIngestClient<R>,
+    mode: FailoverMode,
+}
+
+/// A channel opened through a [`FailoverClient`], named the same on both
+/// accounts.
+pub struct FailoverChannel<R> {
+    name: String,
+    mode: FailoverMode,
+    primary_client: StreamingIngestClient<R>,
+    secondary_client: StreamingIngestClient<R>,
+    primary: StreamingIngestChannel<R>,
+    /// Always open when replicating; opened on failover otherwise.
+    secondary: Option<StreamingIngestChannel<R>>,
+    /// When the primary started failing with retryable errors.
+    unavailable_since: Option<Instant>,
+}
+
+impl<R: Serialize + Clone> FailoverClient<R> {
+    /// Wraps clients built for the same database, schema, and pipe on two
+    /// accounts.
+    pub fn new(
+        primary: StreamingIngestClient<R>,
+        secondary: StreamingIngestClient<R>,
+        mode: FailoverMode,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            mode,
+        }
+    }
+
+    /// The client's failover mode.
+    pub fn mode(&self) -> FailoverMode {
+        self.mode
+    }
+
+    /// Opens `channel_name` on the primary, and on the secondary too when
+    /// replicating.
+    pub async fn open_channel(&mut self, channel_name: &str) -> Result<FailoverChannel<R>, Error> {
+        let (primary, secondary) = match self.mode {
+            FailoverMode::Replicate => {
+                let (primary, secondary) = tokio::try_join!(
+                    self.primary.open_channel(channel_name),
+                    self.secondary.open_channel(channel_name)
+                )?;
+                (primary, Some(secondary))
+            }
+            FailoverMode::Failover { .. } => (self.primary.open_channel(channel_name).await?, None),
+        };
+        Ok(FailoverChannel {
+            name: channel_name.to_string(),
+            mode: self.mode,
+            primary_client: self.primary.clone(),
+            secondary_client: self.secondary.clone(),
+            primary,
+            secondary,
+            unavailable_since: None,
+        })
+    }
+}
+
+impl<R: Serialize + Clone> FailoverChannel<R> {
+    /// The account appends currently go to; always the primary when
+    /// replicating.
+    pub fn active(&self) -> FailoverAccount {
+        match (self.mode, &self.secondary) {
+            (FailoverMode::Failover { .. }, Some(_)) => FailoverAccount::Secondary,
+            _ => FailoverAccount::Primary,
+        }
+    }
+
+    /// The channel on `account`, if open there.
+    pub fn channel(&self, account: FailoverAccount) -> Option<&StreamingIngestChannel<R>> {
+        match account {
+            FailoverAccount::Primary => Some(&self.primary),
+            FailoverAccount::Secondary => self.secondary.as_ref(),
+        }
+    }
+
+    /// Appends one row; see [`append_rows_iter`](Self::append_rows_iter).
+    pub async fn append_row(&mut self, row: &R) -> Result<(), Error> {
+        self.append_rows_iter([row.clone()]).await.map(|_| ())
+    }
+
+    /// Appends rows according to the [`FailoverMode`], returning the body
+    /// bytes sent to the account that accepted them (the primary, when both
+    /// did).
+    ///
+    /// While the primary is failing but the failover delay has not passed,
+    /// its error is returned and the caller should retry.
+    pub async fn append_rows_iter<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let after = match self.mode {
+            FailoverMode::Replicate => return self.replicate(rows).await,
+            FailoverMode::Failover { after } => after,
+        };
+        if let Some(secondary) = self.secondary.as_mut() {
+            return secondary.append_rows_iter(rows).await;
+        }
+        match self.primary.append_rows_iter(rows.clone()).await {
+            Ok(bytes) => {
+                self.unavailable_since = None;
+                Ok(bytes)
+            }
+            Err(err) if err.is_retryable() => {
+                let unavailable_for = self
+                    .unavailable_since
+                    .get_or_insert_with(Instant::now)
+                    .elapsed();
+                if unavailable_for < after {
+                    return Err(err);
+                }
+                warn!(
+                    "channel '{}': primary unavailable for {:?} ({}); failing over",
+                    self.name, unavailable_for, err
+                );
+                let secondary = self
+                    .secondary
+                    .insert(self.secondary_client.open_channel(&self.name).await?);
+                let (committed, pushed) = self.primary.abandon();
+                self.primary_client.auth_config.warn(Warning::FailedOver {
+                    channel: self.name.clone(),
+                    unavailable_for,
+                    committed,
+                    pushed,
+                });
+                secondary.append_rows_iter(rows).await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn replicate(&mut self, rows: Vec<R>) -> Result<usize, Error> {
+        let secondary = self
+            .secondary
+            .as_mut()
+            .expect("replicating channels are open on both accounts");
+        let (primary, secondary) = tokio::join!(
+            self.primary.append_rows_iter(rows.clone()),
+            secondary.append_rows_iter(rows)
+        );
+        match (primary, secondary) {
+            (Ok(bytes), Ok(_)) => Ok(bytes),
+            (Ok(bytes), Err(err)) => {
+                self.replica_failed(FailoverAccount::Secondary, &err);
+                Ok(bytes)
+            }
+            (Err(err), Ok(bytes)) => {
+                self.replica_failed(FailoverAccount::Primary, &err);
+                Ok(bytes)
+            }
+            (Err(err), Err(_)) => Err(err),
+        }
+    }
+
+    fn replica_failed(&self, account: FailoverAccount, err: &Error) {
+        warn!(
+            "channel '{}': append to {:?} account failed: {}",
+            self.name, account, err
+        );
+        self.primary_client
+            .auth_config
+            .warn(Warning::ReplicaAppendFailed {
+                channel: self.name.clone(),
+                account,
+                error: err.to_string(),
+            });
+    }
+
+    /// Closes the channel on every account it is open on, returning the
+    /// report of the active account. When replicating, a failure to close on
+    /// one account is reported as [`Warning::ReplicaAppendFailed`] as long as
+    /// the other closes.
+    pub async fn close(&mut self) -> Result<ChannelReport, Error> {
+        let failed_over = self.active() == FailoverAccount::Secondary;
+        let Some(secondary) = self.secondary.as_mut() else {
+            return self.primary.close().await;
+        };
+        if failed_over {
+            return secondary.close().await;
+        }
+        let (primary, secondary) = tokio::join!(self.primary.close(), secondary.close());
+        match (primary, secondary) {
+            (Ok(report), Ok(_)) => Ok(report),
+            (Ok(report), Err(err)) => {
+                self.replica_failed(FailoverAccount::Secondary, &err);
+                Ok(report)
+            }
+            (Err(err), Ok(report)) => {
+                self.replica_failed(FailoverAccount::Primary, &err);
+                Ok(report)
+            }
+            (Err(err), Err(_)) => Err(err),
+        }
+    }
+}
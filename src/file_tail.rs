@@ -0,0 +1,279 @@
+//! Tailing newline-delimited JSON files into a channel, for log shipping.
+//!
+//! The byte offset just past the last line of each batch is the batch's
+//! offset token, so the channel's committed token is exactly where a
+//! restarted shipper should continue reading.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::{Error, StreamingIngestChannel};
+
+const DEFAULT_MAX_BATCH_ROWS: usize = 10_000;
+const DEFAULT_MAX_BATCH_BYTES: usize = 4 * 1024 * 1024;
+
+/// Reads complete NDJSON lines from a file that is still being written,
+/// tracking the byte offset of the next unread line.
+///
+/// A line is read only once its terminating newline has been written, so a
+/// writer caught mid-line is picked up on a later read. Blank lines are
+/// skipped. [`ship`](Self::ship) appends each batch with
+/// [`append_rows_at`](StreamingIngestChannel::append_rows_at) at the offset
+/// just past its last line, and [`resume`](Self::resume) opens the file at
+/// the channel's committed offset, so after a crash rows pushed but not
+/// committed are read and sent again while committed rows are not.
+///
+/// One source tails one file; a file that shrinks below the current offset
+/// (truncated or replaced by rotation) fails with [`Error::Config`].
+///
+/// ```no_run
+/// # async fn run(
+/// #     channel: &mut snowpipe_streaming::StreamingIngestChannel<serde_json::Value>,
+/// # ) -> Result<(), snowpipe_streaming::Error> {
+/// use snowpipe_streaming::FileTailSource;
+///
+/// let mut source = FileTailSource::resume("/var/log/app/events.ndjson", channel).await?;
+/// loop {
+///     if source.ship(channel).await? == 0 {
+///         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+///     }
+/// }
+/// # }
+/// ```
+pub struct FileTailSource {
+    path: PathBuf,
+    reader: BufReader<File>,
+    offset: u64,
+    max_batch_rows: usize,
+    max_batch_bytes: usize,
+    skip_invalid_lines: bool,
+    skipped_lines: u64,
+}
+
+/// Rows read by [`FileTailSource::read_batch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailBatch {
+    /// One JSON value per line read.
+    pub rows: Vec<serde_json::Value>,
+    /// Byte offset just past the last line read, to use as the batch's
+    /// offset token.
+    pub end_offset: u64,
+}
+
+impl FileTailSource {
+    /// Opens `path` to read from byte `offset`, which must be zero or the
+    /// start of a line, such as a committed offset token.
+    pub fn open(path: impl AsRef<Path>, offset: u64) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let source = Self {
+            path,
+            reader: BufReader::new(file),
+            offset,
+            max_batch_rows: DEFAULT_MAX_BATCH_ROWS,
+            max_batch_bytes: DEFAULT_MAX_BATCH_BYTES,
+            skip_invalid_lines: false,
+            skipped_lines: 0,
+        };
+        source.check_not_truncated()?;
+        Ok(source)
+    }
+
+    /// Opens `path` at `channel`'s last committed offset token.
+    pub async fn resume<R>(
+        path: impl AsRef<Path>,
+        channel: &mut StreamingIngestChannel<R>,
+    ) -> Result<Self, Error>
+    where
+        R: serde::Serialize + Clone,
+    {
+        let committed = channel.get_latest_committed_offset_token().await;
+        Self::open(path, committed)
+    }
+
+    /// Caps the lines per batch (default 10,000). Keep it at or below the
+    /// channel's `max_rows_per_request` so each batch fits in one request.
+    pub fn max_batch_rows(mut self, rows: usize) -> Self {
+        self.max_batch_rows = rows.max(1);
+        self
+    }
+
+    /// Ends a batch once it holds this many bytes (default 4MB); the line
+    /// that crosses the cap is still included.
+    pub fn max_batch_bytes(mut self, bytes: usize) -> Self {
+        self.max_batch_bytes = bytes.max(1);
+        self
+    }
+
+    /// Skips lines that are not JSON objects instead of failing with
+    /// [`Error::InvalidRow`]; [`skipped_lines`](Self::skipped_lines) counts
+    /// them.
+    pub fn skip_invalid_lines(mut self, skip: bool) -> Self {
+        self.skip_invalid_lines = skip;
+        self
+    }
+
+    /// The file being tailed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Byte offset of the next unread line.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Number of invalid lines skipped so far.
+    pub fn skipped_lines(&self) -> u64 {
+        self.skipped_lines
+    }
+
+    /// Reads the complete lines written since the last read, up to the batch
+    /// caps, or `None` if there are none yet.
+    ///
+    /// A line that is not a JSON object fails with [`Error::InvalidRow`]
+    /// naming its offset; the batch is discarded and the next read starts
+    /// again at its first line.
+    pub fn read_batch(&mut self) -> Result<Option<TailBatch>, Error> {
+        self.check_not_truncated()?;
+        let start = self.offset;
+        let mut rows = Vec::new();
+        let mut bytes = 0;
+        let mut line = Vec::new();
+        while rows.len() < self.max_batch_rows && bytes < self.max_batch_bytes {
+            line.clear();
+            let read = self.reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            if line.last() != Some(&b'\n') {
+                // Partial line: leave it for a later read.
+                self.reader.seek(SeekFrom::Start(self.offset))?;
+                break;
+            }
+            let line_offset = self.offset;
+            self.offset += read as u64;
+            bytes += read;
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            match serde_json::from_slice::<serde_json::Value>(&line) {
+                Ok(row) if row.is_object() => rows.push(row),
+                _ if self.skip_invalid_lines => self.skipped_lines += 1,
+                _ => {
+                    self.offset = start;
+                    self.reader.seek(SeekFrom::Start(start))?;
+                    return Err(Error::InvalidRow(format!(
+                        "line at byte {} of {} is not a JSON object",
+                        line_offset,
+                        self.path.display()
+                    )));
+                }
+            }
+        }
+        Ok((self.offset > start).then_some(TailBatch {
+            rows,
+            end_offset: self.offset,
+        }))
+    }
+
+    /// Reads a batch and appends it to `channel` at its end offset,
+    /// returning the number of rows sent (zero when nothing new was written
+    /// or every new line was blank or skipped).
+    ///
+    /// If the append fails, the batch is read again on the next call.
+    pub async fn ship(
+        &mut self,
+        channel: &mut StreamingIngestChannel<serde_json::Value>,
+    ) -> Result<usize, Error> {
+        let start = self.offset;
+        let Some(batch) = self.read_batch()? else {
+            return Ok(0);
+        };
+        let rows = batch.rows.len();
+        if rows == 0 {
+            return Ok(0);
+        }
+        if let Err(err) = channel.append_rows_at(batch.end_offset, batch.rows).await {
+            self.offset = start;
+            self.reader.seek(SeekFrom::Start(start))?;
+            return Err(err);
+        }
+        Ok(rows)
+    }
+
+    fn check_not_truncated(&self) -> Result<(), Error> {
+        let len = self.reader.get_ref().metadata()?.len();
+        if len < self.offset {
+            return Err(Error::Config(format!(
+                "{} is {} bytes, shorter than the tail offset {}; it was truncated or rotated",
+                self.path.display(),
+                len,
+                self.offset
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "snowpipe-tail-{}-{name}.ndjson",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn reads_complete_lines_and_resumes_at_offsets() {
+        let path = temp_path("lines");
+        std::fs::write(&path, "{\"id\":1}\n\n{\"id\":2}\n{\"id\":").unwrap();
+        let mut source = FileTailSource::open(&path, 0).unwrap().max_batch_rows(1);
+
+        let first = source.read_batch().unwrap().unwrap();
+        assert_eq!(first.rows, [serde_json::json!({"id": 1})]);
+        assert_eq!(first.end_offset, 9);
+        let second = source.read_batch().unwrap().unwrap();
+        assert_eq!(second.rows, [serde_json::json!({"id": 2})]);
+        assert_eq!(second.end_offset, 19);
+        assert_eq!(source.read_batch().unwrap(), None, "partial line waits");
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"3}\n").unwrap();
+        let third = source.read_batch().unwrap().unwrap();
+        assert_eq!(third.rows, [serde_json::json!({"id": 3})]);
+
+        let mut resumed = FileTailSource::open(&path, 9).unwrap();
+        assert_eq!(resumed.read_batch().unwrap().unwrap().rows.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_and_truncated_files_are_reported() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, "{\"id\":1}\nnot json\n{\"id\":2}\n").unwrap();
+        let mut strict = FileTailSource::open(&path, 0).unwrap();
+        let err = strict.read_batch().unwrap_err();
+        assert!(err.to_string().contains("byte 9"), "{err}");
+        assert_eq!(strict.offset(), 0);
+
+        let mut lenient = FileTailSource::open(&path, 0)
+            .unwrap()
+            .skip_invalid_lines(true);
+        assert_eq!(lenient.read_batch().unwrap().unwrap().rows.len(), 2);
+        assert_eq!(lenient.skipped_lines(), 1);
+
+        std::fs::write(&path, "{}\n").unwrap();
+        assert!(matches!(lenient.read_batch(), Err(Error::Config(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
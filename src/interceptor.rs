@@ -0,0 +1,60 @@
+//! Hooks on the raw HTTP requests sent to Snowflake, for deployments that
+//! reach it through a gateway requiring its own signatures or headers (e.g.
+//! an internally signed API gateway in front of PrivateLink).
+
+use reqwest::{Request, RequestBuilder};
+
+use crate::Error;
+
+/// Sees each request to Snowflake after it is fully built (auth, extra
+/// headers, body) and just before it is sent, including every retry
+/// attempt, so signatures always cover the bytes on the wire.
+///
+/// Returning an error vetoes the request: it is not sent, and the operation
+/// fails with that error, which is retried only if
+/// [`Error::is_retryable`] says so. Interceptors run inline on the request
+/// path and should not block.
+///
+/// ```
+/// use snowpipe_streaming::{Config, Error, RequestInterceptor};
+///
+/// struct GatewaySigner {
+///     key: Vec<u8>,
+/// }
+///
+/// impl RequestInterceptor for GatewaySigner {
+///     fn intercept(&self, request: &mut reqwest::Request) -> Result<(), Error> {
+///         let body = request.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+///         let signature = format!("{}:{}", self.key.len(), body.len()); // your MAC here
+///         let value = signature
+///             .parse()
+///             .map_err(|_| Error::Config("unencodable signature".into()))?;
+///         request.headers_mut().insert("x-gateway-signature", value);
+///         Ok(())
+///     }
+/// }
+///
+/// let config = Config::from_values(
+///     "MY_USER", None, "MY_ACCOUNT", "", Some("jwt".into()), None, None, None, None, None,
+/// )
+/// .request_interceptor(GatewaySigner { key: b"secret".to_vec() });
+/// # let _ = config;
+/// ```
+pub trait RequestInterceptor: Send + Sync {
+    /// Inspects or amends `request` before it is sent; an error vetoes it.
+    fn intercept(&self, request: &mut Request) -> Result<(), Error>;
+}
+
+/// Runs `interceptor`, if any, on the request `builder` describes.
+pub(crate) fn intercept(
+    interceptor: Option<&dyn RequestInterceptor>,
+    builder: RequestBuilder,
+) -> Result<RequestBuilder, Error> {
+    let Some(interceptor) = interceptor else {
+        return Ok(builder);
+    };
+    let (client, request) = builder.build_split();
+    let mut request = request?;
+    interceptor.intercept(&mut request)?;
+    Ok(RequestBuilder::from_parts(client, request))
+}
@@ -0,0 +1,46 @@
+//! Private keys kept in the operating system's credential store (Windows
+//! Credential Manager, macOS Keychain, or the Linux kernel keyring), for
+//! deployments that forbid key material on disk.
+//!
+//! Store the PEM once with [`store_private_key`], then point the client at it
+//! with `private_key_source = "os-store:<alias>"`. The key is read from the
+//! store each time a JWT is signed and never written elsewhere.
+
+use crate::Error;
+
+/// Service name the keys are stored under; the alias is the account name.
+pub const SERVICE: &str = "snowpipe-streaming";
+
+/// Saves `pem` in the OS credential store under `alias`, replacing any key
+/// already stored there.
+pub fn store_private_key(alias: &str, pem: &str) -> Result<(), Error> {
+    entry(alias)?
+        .set_password(pem)
+        .map_err(|e| keystore_error(alias, e))
+}
+
+/// Removes the key stored under `alias`.
+pub fn delete_private_key(alias: &str) -> Result<(), Error> {
+    entry(alias)?
+        .delete_credential()
+        .map_err(|e| keystore_error(alias, e))
+}
+
+pub(crate) fn read_private_key(alias: &str) -> Result<String, Error> {
+    entry(alias)?
+        .get_password()
+        .map_err(|e| keystore_error(alias, e))
+}
+
+fn entry(alias: &str) -> Result<keyring::Entry, Error> {
+    keyring::Entry::new(SERVICE, alias).map_err(|e| keystore_error(alias, e))
+}
+
+fn keystore_error(alias: &str, err: keyring::Error) -> Error {
+    match err {
+        keyring::Error::NoEntry => Error::Key(format!(
+            "no private key stored under '{alias}' in the OS credential store (service '{SERVICE}')"
+        )),
+        other => Error::Key(format!("OS credential store failed for '{alias}': {other}")),
+    }
+}
@@ -1,12 +1,124 @@
+//! Rust client for Snowflake's Snowpipe Streaming REST API.
+//!
+//! A [`StreamingIngestClient`] is bound to one pipe: on construction it
+//! authenticates (generating key-pair JWTs locally from the [`Config`]),
+//! discovers the ingest host, and obtains a scoped token. Each
+//! [`StreamingIngestChannel`] opened from it appends newline-delimited JSON
+//! rows and, on close, waits until Snowflake reports them committed.
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), snowpipe_streaming::Error> {
+//! # let server = wiremock::MockServer::start().await;
+//! # let ok = |b: serde_json::Value| wiremock::ResponseTemplate::new(200).set_body_json(b);
+//! # let status = serde_json::json!({"channel_statuses": {"ch": {"last_committed_offset_token": "5"}}});
+//! # for (verb, route, resp) in [
+//! #     ("GET", "/hostname$", wiremock::ResponseTemplate::new(200).set_body_string(server.uri())),
+//! #     ("POST", "/oauth/token$", wiremock::ResponseTemplate::new(200).set_body_string("scoped")),
+//! #     ("PUT", "/channels/ch$", ok(serde_json::json!({"next_continuation_token": "c0", "channel_status": {}}))),
+//! #     ("POST", "/rows$", ok(serde_json::json!({"next_continuation_token": "c1"}))),
+//! #     ("POST", ":bulk-channel-status$", ok(status)),
+//! #     ("DELETE", "/channels/ch$", wiremock::ResponseTemplate::new(200)),
+//! # ] {
+//! #     wiremock::Mock::given(wiremock::matchers::method(verb))
+//! #         .and(wiremock::matchers::path_regex(route))
+//! #         .respond_with(resp)
+//! #         .mount(&server)
+//! #         .await;
+//! # }
+//! # let config = snowpipe_streaming::Config::from_values(
+//! #     "user", None, "acct", server.uri(), Some("jwt".into()), None, None, None, None, None,
+//! # );
+//! use snowpipe_streaming::StreamingIngestClient;
+//!
+//! #[derive(serde::Serialize, Clone)]
+//! struct Row {
+//!     id: u64,
+//! }
+//!
+//! let mut client =
+//!     StreamingIngestClient::<Row>::new("svc", "MY_DB", "MY_SCHEMA", "MY_PIPE", config).await?;
+//! let mut channel = client.open_channel("ch").await?;
+//! channel.append_row(&Row { id: 1 }).await?;
+//! channel.append_rows_iter((2..=5).map(|id| Row { id })).await?;
+//! channel.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Runnable end-to-end scenarios (buffering, exactly-once resumption,
+//! multiple channels, error recovery) live in the `examples/` directory.
+
+#![warn(missing_docs)]
+
+mod ack;
+#[cfg(feature = "crypto")]
+pub mod auth;
+pub mod cdc;
 mod channel;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 mod client;
+mod commit_wait;
 mod config;
+mod dedup;
+mod duration;
+mod endpoints;
 mod errors;
+mod failover;
+mod file_tail;
+mod interceptor;
+#[cfg(feature = "os-keystore")]
+pub mod keystore;
+mod offset_token;
+mod partition;
+mod pool;
+#[cfg(feature = "replay")]
+pub mod replay;
+mod retry;
+mod router;
+mod sampler;
+#[cfg(feature = "tower")]
+mod service;
+pub mod telemetry;
 mod types;
-pub use channel::StreamingIngestChannel;
-pub use client::StreamingIngestClient;
-pub use config::Config;
-pub use errors::Error;
+#[cfg(feature = "vault")]
+pub mod vault;
+mod warning;
+pub use ack::AckTracker;
+pub use channel::{
+    AppendHandle, AppendReport, ChannelReport, ChannelStatusSnapshot, DeadlineReport,
+    DryRunChannel, DryRunRequest, FlushReport, SchemaDrift, StreamingIngestChannel,
+};
+#[cfg(feature = "crypto")]
+pub use client::{AuthDebugReport, KeyMetadata, debug_auth};
+pub use client::{
+    Capabilities, ClientMetrics, CostEstimate, CostModel, JwtSigner, JwtSignerFuture,
+    ScopedTokenFuture, ScopedTokenProvider, ServerApiVersion, StreamingIngestClient, TokenCache,
+    TokenInfo, TokenRefreshMetrics, TokenSnapshot,
+};
+pub use commit_wait::{
+    CommitProgress, CommitWaitStrategy, ExponentialBackoff, FixedInterval, OnStatusChange,
+};
+pub use config::{
+    AccountFormat, ApiVersion, AuthScheme, Config, ConfigBuilder, ConfigIssue, HeaderProvider,
+    LogPolicy, PassphraseProvider, RedactedConfig, RefreshPolicy, RetryHandler, WarningHandler,
+};
+pub use dedup::DedupWindow;
+pub use errors::{ChannelConflict, Error, ErrorContext, OperationKind, RetryEvent};
+pub use failover::{FailoverAccount, FailoverChannel, FailoverClient, FailoverMode};
+pub use file_tail::{FileTailSource, TailBatch};
+pub use interceptor::RequestInterceptor;
+pub use offset_token::{OffsetToken, OffsetTokenBuilder, SdkOffsetFormat, SourceProgress};
+pub use partition::{BatchPartitioner, ColumnPartitioner};
+pub use pool::{ChannelPool, Ordering};
+pub use retry::{RetryPlan, RetryPlanBuilder};
+pub use router::{Route, Router};
+pub use sampler::Sampler;
+#[cfg(feature = "tower")]
+pub use service::{Batch, ChannelService};
+pub use types::OpenChannelOptions;
+pub use warning::{Lint, Warning};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,480 @@
+//! Source positions carried through a channel shared by several sources,
+//! per-source commit progress, and the offset tokens of other Snowpipe
+//! Streaming SDKs.
+
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// Digits in an encoded sequence: enough for any `u64`.
+const SEQUENCE_DIGITS: usize = 20;
+
+/// A position in one of several sources (Kafka partitions, Kinesis shards,
+/// tailed files) feeding a single channel: a source ID and that source's
+/// sequence number.
+///
+/// It encodes as `<source_id>:<sequence>` with the sequence zero-padded to
+/// 20 digits, so the encoded tokens of one source sort in sequence order as
+/// plain strings, and parses back with [`str::parse`]. The channel's own
+/// offset tokens stay numeric; hold the positions each append request
+/// covered in an [`AckTracker`](crate::AckTracker) and fold the released
+/// ones into a [`SourceProgress`].
+///
+/// ```
+/// use snowpipe_streaming::OffsetToken;
+///
+/// let token = OffsetToken::builder().source("orders-3").sequence(42).build()?;
+/// assert_eq!(token.to_string(), "orders-3:00000000000000000042");
+/// assert_eq!("orders-3:00000000000000000042".parse::<OffsetToken>()?, token);
+/// # Ok::<(), snowpipe_streaming::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OffsetToken {
+    source_id: String,
+    sequence: u64,
+}
+
+impl OffsetToken {
+    /// Starts an [`OffsetTokenBuilder`].
+    pub fn builder() -> OffsetTokenBuilder {
+        OffsetTokenBuilder::default()
+    }
+
+    /// The source this position belongs to.
+    pub fn source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    /// The position within the source.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+impl std::fmt::Display for OffsetToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{:0width$}",
+            self.source_id,
+            self.sequence,
+            width = SEQUENCE_DIGITS
+        )
+    }
+}
+
+impl std::str::FromStr for OffsetToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            Error::Config(format!(
+                "offset token '{s}' is not '<source_id>:<sequence>'"
+            ))
+        };
+        let (source_id, sequence) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let sequence = sequence.parse::<u64>().map_err(|_| invalid())?;
+        OffsetToken::builder()
+            .source(source_id)
+            .sequence(sequence)
+            .build()
+    }
+}
+
+/// Builds an [`OffsetToken`]; start one with [`OffsetToken::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct OffsetTokenBuilder {
+    source_id: String,
+    sequence: u64,
+}
+
+impl OffsetTokenBuilder {
+    /// Source ID; must not be empty.
+    pub fn source(mut self, source_id: impl Into<String>) -> Self {
+        self.source_id = source_id.into();
+        self
+    }
+
+    /// Position within the source; 0 when unset.
+    pub fn sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Fails with [`Error::Config`] when the source ID is empty.
+    pub fn build(self) -> Result<OffsetToken, Error> {
+        if self.source_id.is_empty() {
+            return Err(Error::Config("offset token source ID is empty".into()));
+        }
+        Ok(OffsetToken {
+            source_id: self.source_id,
+            sequence: self.sequence,
+        })
+    }
+}
+
+/// Highest committed sequence of each source feeding a channel, built from
+/// the [`OffsetToken`]s released as Snowflake commits.
+///
+/// ```
+/// use snowpipe_streaming::{AckTracker, OffsetToken, SourceProgress};
+///
+/// let token = |source: &str, sequence| {
+///     OffsetToken::builder().source(source).sequence(sequence).build()
+/// };
+/// let mut acks = AckTracker::new();
+/// // Channel offset 1 carried shard-a up to 10 and shard-b up to 4.
+/// acks.track(1, vec![token("shard-a", 10)?, token("shard-b", 4)?]);
+/// acks.track(2, vec![token("shard-a", 12)?]);
+///
+/// let mut committed = SourceProgress::new();
+/// committed.extend(acks.release(1).into_iter().flatten());
+/// assert_eq!(committed.committed("shard-a"), Some(10));
+/// assert!(!committed.is_committed(&token("shard-a", 12)?));
+/// # Ok::<(), snowpipe_streaming::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceProgress {
+    sources: BTreeMap<String, u64>,
+}
+
+impl SourceProgress {
+    /// Progress with no sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raises `token`'s source to its sequence; lower sequences are ignored.
+    pub fn record(&mut self, token: &OffsetToken) {
+        let sequence = self
+            .sources
+            .entry(token.source_id.clone())
+            .or_insert(token.sequence);
+        *sequence = (*sequence).max(token.sequence);
+    }
+
+    /// Highest recorded sequence of `source_id`.
+    pub fn committed(&self, source_id: &str) -> Option<u64> {
+        self.sources.get(source_id).copied()
+    }
+
+    /// Whether `token`'s source has reached its sequence.
+    pub fn is_committed(&self, token: &OffsetToken) -> bool {
+        self.committed(&token.source_id)
+            .is_some_and(|committed| committed >= token.sequence)
+    }
+
+    /// Sequences each source in `pushed` is ahead of this progress by,
+    /// omitting sources that are caught up. A source recorded only in
+    /// `pushed` counts from zero.
+    pub fn lag<'a>(&self, pushed: &'a SourceProgress) -> BTreeMap<&'a str, u64> {
+        pushed
+            .sources
+            .iter()
+            .filter_map(|(source_id, &sequence)| {
+                let committed = self.committed(source_id).unwrap_or(0);
+                (sequence > committed).then(|| (source_id.as_str(), sequence - committed))
+            })
+            .collect()
+    }
+
+    /// The latest position of every source, in source order; for persisting
+    /// a checkpoint to resume from.
+    pub fn tokens(&self) -> impl Iterator<Item = OffsetToken> + '_ {
+        self.sources
+            .iter()
+            .map(|(source_id, &sequence)| OffsetToken {
+                source_id: source_id.clone(),
+                sequence,
+            })
+    }
+}
+
+impl Extend<OffsetToken> for SourceProgress {
+    fn extend<I: IntoIterator<Item = OffsetToken>>(&mut self, tokens: I) {
+        for token in tokens {
+            self.record(&token);
+        }
+    }
+}
+
+impl FromIterator<OffsetToken> for SourceProgress {
+    fn from_iter<I: IntoIterator<Item = OffsetToken>>(tokens: I) -> Self {
+        let mut progress = Self::new();
+        progress.extend(tokens);
+        progress
+    }
+}
+
+/// Separators tried by [`SdkOffsetFormat::detect`], in order.
+const DELIMITERS: [char; 5] = [':', '-', '_', '/', '|'];
+
+/// Offset token layouts written by Snowflake's Java and Python ingest SDKs
+/// and their examples, for taking over their channels without reprocessing.
+///
+/// [`parse`](Self::parse) reads a channel's committed token (see
+/// [`StreamingIngestClient::committed_offset_token`](crate::StreamingIngestClient::committed_offset_token))
+/// into the position of each source to resume from, and
+/// [`emit`](Self::emit) writes positions back in the same layout, e.g. for
+/// a checkpoint the old consumers still read.
+///
+/// ```
+/// use snowpipe_streaming::SdkOffsetFormat;
+///
+/// let token = r#"{"shard-0001": 1042, "shard-0002": "77"}"#;
+/// let format = SdkOffsetFormat::detect(token).unwrap();
+/// let progress = format.parse(token, "unused")?;
+/// assert_eq!(progress.committed("shard-0002"), Some(77));
+///
+/// let progress = SdkOffsetFormat::Decimal.parse("1042", "orders-3")?;
+/// assert_eq!(progress.committed("orders-3"), Some(1042));
+/// assert_eq!(SdkOffsetFormat::Delimited('-').emit(&progress)?, "orders-3-1042");
+/// # Ok::<(), snowpipe_streaming::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdkOffsetFormat {
+    /// A decimal sequence, e.g. `"1042"`: what the SDK examples write with
+    /// `String.valueOf(offset)` or `str(offset)`, and what the Kafka
+    /// connector writes for its one channel per partition.
+    Decimal,
+    /// A source ID and a decimal sequence joined by a separator, e.g.
+    /// `"3-1042"` for partition 3, or this crate's [`OffsetToken`] encoding
+    /// with `':'`.
+    Delimited(char),
+    /// A JSON object from source IDs to sequences, which may be numbers or
+    /// decimal strings, e.g. `{"shard-0001": 1042}`.
+    Json,
+}
+
+impl SdkOffsetFormat {
+    /// Guesses the layout of `token`: `Json` for an object, `Decimal` for a
+    /// number, else `Delimited` by the first of `:`, `-`, `_`, `/`, `|`
+    /// followed by a number at the end of the token.
+    pub fn detect(token: &str) -> Option<Self> {
+        let token = token.trim();
+        if token.starts_with('{') {
+            return Some(Self::Json);
+        }
+        if parse_sequence(token).is_some() {
+            return Some(Self::Decimal);
+        }
+        DELIMITERS
+            .into_iter()
+            .find(|&separator| {
+                token
+                    .rsplit_once(separator)
+                    .is_some_and(|(source, sequence)| {
+                        !source.is_empty() && parse_sequence(sequence).is_some()
+                    })
+            })
+            .map(Self::Delimited)
+    }
+
+    /// Reads `token` as the position of each source. A `Decimal` token
+    /// names no source, so its sequence is recorded for `source_id`.
+    pub fn parse(self, token: &str, source_id: &str) -> Result<SourceProgress, Error> {
+        let invalid =
+            || Error::Config(format!("offset token '{token}' is not {}", self.describe()));
+        let trimmed = token.trim();
+        let mut progress = SourceProgress::new();
+        match self {
+            Self::Decimal => {
+                let sequence = parse_sequence(trimmed).ok_or_else(invalid)?;
+                progress.record(
+                    &OffsetToken::builder()
+                        .source(source_id)
+                        .sequence(sequence)
+                        .build()?,
+                );
+            }
+            Self::Delimited(separator) => {
+                let (source, sequence) = trimmed.rsplit_once(separator).ok_or_else(invalid)?;
+                let sequence = parse_sequence(sequence).ok_or_else(invalid)?;
+                progress.record(
+                    &OffsetToken::builder()
+                        .source(source)
+                        .sequence(sequence)
+                        .build()
+                        .map_err(|_| invalid())?,
+                );
+            }
+            Self::Json => {
+                let sources = serde_json::from_str::<BTreeMap<String, serde_json::Value>>(trimmed)
+                    .map_err(|_| invalid())?;
+                for (source, sequence) in sources {
+                    let sequence = match &sequence {
+                        serde_json::Value::Number(n) => n.as_u64(),
+                        serde_json::Value::String(s) => parse_sequence(s),
+                        _ => None,
+                    }
+                    .ok_or_else(invalid)?;
+                    progress.record(
+                        &OffsetToken::builder()
+                            .source(source)
+                            .sequence(sequence)
+                            .build()
+                            .map_err(|_| invalid())?,
+                    );
+                }
+            }
+        }
+        Ok(progress)
+    }
+
+    /// Writes `progress` in this layout. `Decimal` and `Delimited` hold one
+    /// source, so other progress fails with [`Error::Config`].
+    pub fn emit(self, progress: &SourceProgress) -> Result<String, Error> {
+        if self == Self::Json {
+            let sources = progress
+                .sources
+                .iter()
+                .map(|(source, &sequence)| (source.clone(), serde_json::Value::from(sequence)))
+                .collect::<serde_json::Map<_, _>>();
+            return Ok(serde_json::Value::Object(sources).to_string());
+        }
+        let mut sources = progress.sources.iter();
+        let (Some((source, sequence)), None) = (sources.next(), sources.next()) else {
+            return Err(Error::Config(format!(
+                "{} holds exactly one source, not {}",
+                self.describe(),
+                progress.sources.len()
+            )));
+        };
+        Ok(match self {
+            Self::Delimited(separator) => format!("{source}{separator}{sequence}"),
+            _ => sequence.to_string(),
+        })
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Self::Decimal => "a decimal sequence".into(),
+            Self::Delimited(separator) => format!("'<source_id>{separator}<sequence>'"),
+            Self::Json => "a JSON object of source sequences".into(),
+        }
+    }
+}
+
+/// A sequence written as decimal digits, possibly zero-padded.
+fn parse_sequence(s: &str) -> Option<u64> {
+    let s = s.trim();
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(source: &str, sequence: u64) -> OffsetToken {
+        OffsetToken::builder()
+            .source(source)
+            .sequence(sequence)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn encoded_tokens_sort_by_sequence_and_parse_back() {
+        let mut encoded = [9, 10, 100, 2]
+            .map(|sequence| token("topic:3", sequence).to_string())
+            .to_vec();
+        encoded.sort();
+        let parsed = encoded
+            .iter()
+            .map(|s| s.parse::<OffsetToken>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parsed, [2, 9, 10, 100].map(|n| token("topic:3", n)));
+        assert_eq!(parsed[0].source_id(), "topic:3");
+
+        for bad in ["", "no-sequence", ":5", "a:-1", "a:x"] {
+            assert!(
+                matches!(bad.parse::<OffsetToken>(), Err(Error::Config(_))),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn progress_compares_sources_independently() {
+        let pushed: SourceProgress = [token("a", 10), token("b", 7), token("c", 3)]
+            .into_iter()
+            .collect();
+        let mut committed = SourceProgress::new();
+        committed.record(&token("a", 10));
+        committed.record(&token("b", 5));
+        committed.record(&token("b", 2));
+
+        assert_eq!(committed.committed("b"), Some(5));
+        assert!(committed.is_committed(&token("a", 9)));
+        assert!(!committed.is_committed(&token("c", 1)));
+        assert_eq!(committed.lag(&pushed), BTreeMap::from([("b", 2), ("c", 3)]));
+        assert_eq!(
+            committed.tokens().collect::<Vec<_>>(),
+            [token("a", 10), token("b", 5)]
+        );
+    }
+
+    #[test]
+    fn sdk_tokens_round_trip() {
+        for (token, format) in [
+            ("1042", SdkOffsetFormat::Decimal),
+            ("000042", SdkOffsetFormat::Decimal),
+            (
+                "orders-3:00000000000000000042",
+                SdkOffsetFormat::Delimited(':'),
+            ),
+            ("3-1042", SdkOffsetFormat::Delimited('-')),
+            ("topic_a|9", SdkOffsetFormat::Delimited('|')),
+            (r#"{"a": 1, "b": "2"}"#, SdkOffsetFormat::Json),
+        ] {
+            assert_eq!(SdkOffsetFormat::detect(token), Some(format), "{token}");
+            format.parse(token, "src").unwrap();
+        }
+        assert_eq!(SdkOffsetFormat::detect("batch"), None);
+        assert_eq!(SdkOffsetFormat::detect("-5"), None);
+
+        let progress = SdkOffsetFormat::Delimited(':')
+            .parse("topic:3:42", "unused")
+            .unwrap();
+        assert_eq!(progress.committed("topic:3"), Some(42));
+        assert_eq!(
+            SdkOffsetFormat::Delimited(':').emit(&progress).unwrap(),
+            "topic:3:42"
+        );
+        assert_eq!(SdkOffsetFormat::Decimal.emit(&progress).unwrap(), "42");
+
+        let json = SdkOffsetFormat::Json
+            .parse(r#"{"a": 1, "b": "0002"}"#, "unused")
+            .unwrap();
+        assert_eq!(
+            json.tokens().collect::<Vec<_>>(),
+            [token("a", 1), token("b", 2)]
+        );
+        assert_eq!(
+            SdkOffsetFormat::Json.emit(&json).unwrap(),
+            r#"{"a":1,"b":2}"#
+        );
+        assert!(SdkOffsetFormat::Decimal.emit(&json).is_err());
+        assert!(
+            SdkOffsetFormat::Decimal
+                .emit(&SourceProgress::new())
+                .is_err()
+        );
+
+        for (bad, format) in [
+            ("x", SdkOffsetFormat::Decimal),
+            ("-1", SdkOffsetFormat::Decimal),
+            (":5", SdkOffsetFormat::Delimited(':')),
+            (r#"{"a": -1}"#, SdkOffsetFormat::Json),
+            ("[1]", SdkOffsetFormat::Json),
+        ] {
+            assert!(
+                matches!(format.parse(bad, "src"), Err(Error::Config(_))),
+                "{bad}"
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Grouping rows within a batch by target partition before they are sent.
+//!
+//! Snowflake clusters micro-partitions by arrival, so a request whose rows
+//! share a date (or another clustering column) prunes better than one that
+//! interleaves many values.
+
+use std::fmt;
+
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+
+/// Decides which partition each row of a batch belongs to; rows of a batch
+/// are regrouped so each partition's rows are contiguous before the batch is
+/// split into requests.
+///
+/// Set one for all channels with `Config::partition_column`, or per channel
+/// with
+/// [`StreamingIngestChannel::set_batch_partitioner`](crate::StreamingIngestChannel::set_batch_partitioner).
+pub trait BatchPartitioner: Send + Sync {
+    /// The partition key of `row`, one row serialized as JSON. Rows with the
+    /// same key are grouped together; `None` is a partition of its own.
+    fn partition_key(&self, row: &[u8]) -> Option<String>;
+}
+
+/// Partitions rows by the value of a top-level column, such as
+/// `event_date`. Rows without the column, or that are not JSON objects, share
+/// the `None` partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnPartitioner {
+    column: String,
+}
+
+impl ColumnPartitioner {
+    /// Partitions by `column`.
+    pub fn new(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+        }
+    }
+
+    /// The partition column.
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+}
+
+impl BatchPartitioner for ColumnPartitioner {
+    fn partition_key(&self, row: &[u8]) -> Option<String> {
+        column_value(row, &self.column).map(|value| value.to_string())
+    }
+}
+
+/// The value of top-level `column` in `row`, one row serialized as JSON;
+/// `None` when the row lacks it or is not a JSON object.
+pub(crate) fn column_value(row: &[u8], column: &str) -> Option<serde_json::Value> {
+    let mut de = serde_json::Deserializer::from_slice(row);
+    Field(column).deserialize(&mut de).ok().flatten()
+}
+
+/// Reads one top-level field of a JSON object, skipping the others.
+struct Field<'a>(&'a str);
+
+impl<'de> DeserializeSeed<'de> for Field<'_> {
+    type Value = Option<serde_json::Value>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Field<'_> {
+    type Value = Option<serde_json::Value>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut found = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if found.is_none() && key == self.0 {
+                found = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_value_is_the_key() {
+        let by_date = ColumnPartitioner::new("event_date");
+        assert_eq!(
+            by_date.partition_key(br#"{"id":1,"event_date":"2024-05-01","tags":{"a":[1]}}"#),
+            Some("\"2024-05-01\"".into())
+        );
+        assert_eq!(
+            ColumnPartitioner::new("day").partition_key(br#"{"day":7}"#),
+            Some("7".into())
+        );
+        assert_eq!(by_date.partition_key(br#"{"id":1}"#), None);
+        assert_eq!(by_date.partition_key(b"42"), None);
+    }
+}
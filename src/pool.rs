@@ -0,0 +1,285 @@
+//! Groups of channels on one pipe that share an ingestion workload.
+
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use bytes::BytesMut;
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::channel::serialize_rows;
+use crate::{ChannelReport, Error, StreamingIngestChannel, StreamingIngestClient};
+
+/// How long [`ChannelPool::close`] waits for commits, as
+/// [`StreamingIngestChannel::close`] does.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Ordering guarantee for rows appended through a [`ChannelPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ordering {
+    /// Rows are committed in append order. The pool holds exactly one
+    /// channel, so throughput is bounded by sequential requests on it.
+    #[default]
+    Strict,
+    /// Each append is split across all channels in the pool and the parts are
+    /// sent in parallel. Order is kept within a channel but not across them,
+    /// so use this only when the target table does not depend on arrival order
+    /// (e.g. rows carry their own timestamps or keys). Offset tokens are per
+    /// channel, so exactly-once resumption must track every channel.
+    Relaxed,
+}
+
+/// A set of channels named `<prefix>_<index>` that rows are appended through
+/// according to an [`Ordering`].
+pub struct ChannelPool<R> {
+    ordering: Ordering,
+    channels: Vec<StreamingIngestChannel<R>>,
+    /// Serialization buffer shared by every append through the pool.
+    buffer: BytesMut,
+}
+
+impl<R: Serialize + Clone> StreamingIngestClient<R> {
+    /// Opens `size` channels named `<name_prefix>_0` .. `<name_prefix>_{size-1}`.
+    ///
+    /// [`Ordering::Strict`] pools must have exactly one channel; asking for more
+    /// returns `Error::Config`.
+    pub async fn open_channel_pool(
+        &mut self,
+        name_prefix: &str,
+        size: usize,
+        ordering: Ordering,
+    ) -> Result<ChannelPool<R>, Error> {
+        match (ordering, size) {
+            (_, 0) => {
+                return Err(Error::Config("channel pool size must be at least 1".into()));
+            }
+            (Ordering::Strict, 2..) => {
+                return Err(Error::Config(format!(
+                    "strict ordering requires a single channel (requested {size}); use Ordering::Relaxed to spread rows across channels"
+                )));
+            }
+            _ => {}
+        }
+        let mut channels = Vec::with_capacity(size);
+        for index in 0..size {
+            channels.push(self.open_channel(&format!("{name_prefix}_{index}")).await?);
+        }
+        Ok(ChannelPool {
+            ordering,
+            channels,
+            buffer: BytesMut::new(),
+        })
+    }
+}
+
+impl<R: Serialize + Clone> ChannelPool<R> {
+    /// The pool's ordering guarantee.
+    pub fn ordering(&self) -> Ordering {
+        self.ordering
+    }
+
+    /// Channels in the pool, in index order.
+    pub fn channels(&self) -> &[StreamingIngestChannel<R>] {
+        &self.channels
+    }
+
+    /// Pre-allocates the pool's serialization buffer, like
+    /// [`StreamingIngestChannel::reserve_batch_capacity`]. Rows appended
+    /// through the pool are serialized once, into this buffer, before being
+    /// split across channels.
+    pub fn reserve_batch_capacity(&mut self, bytes: usize) {
+        self.buffer.reserve(bytes);
+    }
+
+    /// Appends rows according to the pool's [`Ordering`]. Returns the number
+    /// of body bytes sent.
+    ///
+    /// With [`Ordering::Relaxed`] the rows are split into one contiguous part
+    /// per channel. If any part fails, the others may still have been
+    /// appended.
+    pub async fn append_rows_iter<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let serialized_rows = serialize_rows(&mut self.buffer, rows)?;
+        match self.ordering {
+            Ordering::Strict => {
+                self.channels[0]
+                    .append_serialized_rows(serialized_rows)
+                    .await
+            }
+            Ordering::Relaxed => {
+                let per_channel = serialized_rows.len().div_ceil(self.channels.len()).max(1);
+                let mut parts = Vec::with_capacity(self.channels.len());
+                let mut rest = serialized_rows;
+                while !rest.is_empty() {
+                    let tail = rest.split_off(per_channel.min(rest.len()));
+                    parts.push(rest);
+                    rest = tail;
+                }
+                let sends = self
+                    .channels
+                    .iter_mut()
+                    .zip(parts)
+                    .map(|(channel, part)| channel.append_serialized_rows(part));
+                let written = futures_util::future::try_join_all(sends).await?;
+                Ok(written.into_iter().sum())
+            }
+        }
+    }
+
+    /// Index of the channel that rows keyed by `key` are routed to.
+    ///
+    /// The mapping depends only on the key and the pool size, so processes
+    /// running the same build on the same platform agree on it, including
+    /// after a restart, and growing the pool moves only the keys that land on
+    /// the new channels. Keys are hashed through their `Hash` impls, whose
+    /// byte encoding (integer endianness, string and slice delimiters) is not
+    /// guaranteed to match across platforms or Rust releases.
+    pub fn channel_index_for<K: Hash + ?Sized>(&self, key: &K) -> usize {
+        let mut hasher = Fnv1a::default();
+        key.hash(&mut hasher);
+        jump_consistent_hash(hasher.finish(), self.channels.len())
+    }
+
+    /// Appends `row` to the channel `key` hashes to, so rows sharing a key
+    /// keep their order while different keys spread across channels (e.g.
+    /// CDC events partitioned by primary key).
+    ///
+    /// Per-key order holds only if rows for a key are always appended
+    /// through the keyed methods.
+    pub async fn append_row_keyed<K: Hash + ?Sized>(
+        &mut self,
+        key: &K,
+        row: &R,
+    ) -> Result<(), Error> {
+        let index = self.channel_index_for(key);
+        self.channels[index].append_row(row).await
+    }
+
+    /// Routes each row to a channel by `key_of(row)`, like
+    /// [`append_row_keyed`](Self::append_row_keyed), and sends each
+    /// channel's share in parallel, in input order. Returns the number of
+    /// body bytes sent.
+    ///
+    /// If any channel's share fails, the others may still have been appended.
+    pub async fn append_rows_keyed<I, K, F>(&mut self, rows: I, key_of: F) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+        K: Hash,
+        F: Fn(&R) -> K,
+    {
+        let mut parts: Vec<Vec<R>> = (0..self.channels.len()).map(|_| Vec::new()).collect();
+        for row in rows {
+            let index = self.channel_index_for(&key_of(&row));
+            parts[index].push(row);
+        }
+        let parts = parts
+            .into_iter()
+            .map(|part| serialize_rows(&mut self.buffer, part))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sends = self
+            .channels
+            .iter_mut()
+            .zip(parts)
+            .filter(|(_, part)| !part.is_empty())
+            .map(|(channel, part)| channel.append_serialized_rows(part));
+        let written = futures_util::future::try_join_all(sends).await?;
+        Ok(written.into_iter().sum())
+    }
+
+    /// Closes every channel, returning their reports in index order.
+    ///
+    /// Waits up to five minutes for pushed rows to commit, polling the
+    /// status of every channel that still awaits commits in one request
+    /// rather than one channel at a time, at the pace of
+    /// `Config::commit_wait_strategy`, and fails with `Error::Timeout` if
+    /// they do not.
+    pub async fn close(&mut self) -> Result<Vec<ChannelReport>, Error> {
+        for channel in &mut self.channels {
+            channel.flush().await?;
+        }
+        let start = Instant::now();
+        if let Some(mut wait) = StreamingIngestChannel::pool_commit_waiter(&self.channels) {
+            loop {
+                let left = StreamingIngestChannel::poll_outstanding(&mut self.channels).await?;
+                if left == 0 {
+                    break;
+                }
+                let elapsed = start.elapsed();
+                if elapsed >= CLOSE_TIMEOUT {
+                    return Err(Error::Timeout(CLOSE_TIMEOUT));
+                }
+                tokio::time::sleep(wait.next_poll(left).min(CLOSE_TIMEOUT - elapsed)).await;
+            }
+        }
+        let mut reports = Vec::with_capacity(self.channels.len());
+        for channel in &mut self.channels {
+            reports.push(channel.close().await?);
+        }
+        Ok(reports)
+    }
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` so the hash function itself
+/// is fixed rather than left to std.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Lamping and Veach's jump consistent hash: maps `key` to one of `buckets`
+/// so that adding a bucket moves only about `1 / buckets` of the keys.
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut bucket: i64 = -1;
+    let mut next: i64 = 0;
+    while next < buckets as i64 {
+        bucket = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    bucket as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jump_hash_is_stable_and_moves_few_keys() {
+        assert_eq!(jump_consistent_hash(0, 1), 0);
+        let assign = |buckets| {
+            (0..1000u64)
+                .map(|key| {
+                    let mut hasher = Fnv1a::default();
+                    key.hash(&mut hasher);
+                    jump_consistent_hash(hasher.finish(), buckets)
+                })
+                .collect::<Vec<_>>()
+        };
+        let four = assign(4);
+        let five = assign(5);
+        assert_eq!(four, assign(4));
+        assert!(four.iter().all(|b| *b < 4));
+        // Growing the pool only moves keys onto the new channel.
+        let moved = four.iter().zip(&five).filter(|(a, b)| a != b).count();
+        assert!(five.iter().zip(&four).all(|(b, a)| b == a || *b == 4));
+        assert!(moved < 300, "moved {moved} keys");
+    }
+}
@@ -0,0 +1,206 @@
+//! Record-and-replay of Snowflake HTTP interactions.
+//!
+//! Enabled with the `replay` feature. Attach a [`Recorder`] to a [`Config`]
+//! with [`Config::record_to`] and every request the client makes, including
+//! discovery and token exchange, is captured with its response. Save the
+//! recording as a [`Cassette`] file, then build a client with
+//! [`Config::replay_from`] to serve those responses back, in order, without a
+//! network connection. This turns a production incident into a regression
+//! test.
+//!
+//! Cassettes are sanitized as they are recorded: request headers and bodies
+//! (credentials and row data) are never stored, host names are dropped from
+//! URLs, the discovered ingest host is replaced with `ingest.replay.invalid`,
+//! and scoped tokens are replaced with `REDACTED`.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), snowpipe_streaming::Error> {
+//! use snowpipe_streaming::replay::{Cassette, Recorder};
+//! use snowpipe_streaming::{Config, StreamingIngestClient};
+//!
+//! let recorder = Recorder::new();
+//! let config = Config::from_env()?.record_to(&recorder);
+//! let mut client =
+//!     StreamingIngestClient::<serde_json::Value>::new("svc", "DB", "SCHEMA", "PIPE", config)
+//!         .await?;
+//! let mut channel = client.open_channel("ch").await?;
+//! channel.append_row(&serde_json::json!({"id": 1})).await?;
+//! recorder.save("incident.json")?;
+//!
+//! // Later, in a test:
+//! let config = Config::from_env()?.replay_from(Cassette::load("incident.json")?);
+//! # let _ = config;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Config`]: crate::Config
+//! [`Config::record_to`]: crate::Config::record_to
+//! [`Config::replay_from`]: crate::Config::replay_from
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+const REPLAY_INGEST_HOST: &str = "ingest.replay.invalid";
+const REDACTED: &str = "REDACTED";
+
+/// One request and the response Snowflake returned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTP method, e.g. `POST`.
+    pub method: String,
+    /// Request path, without the host.
+    pub path: String,
+    /// Query string, if any.
+    pub query: Option<String>,
+    /// Response status code.
+    pub status: u16,
+    /// `Content-Type` of the response, if any.
+    pub content_type: Option<String>,
+    /// Sanitized response body.
+    pub body: String,
+}
+
+/// A recorded sequence of interactions, stored as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Interactions in the order they happened.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Reads a cassette written by [`Recorder::save`] or [`Cassette::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes the cassette as pretty-printed JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Captures the interactions of every client built from a config passed to
+/// [`Config::record_to`](crate::Config::record_to). Clones share one
+/// recording.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Recorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        Cassette {
+            interactions: self.interactions.lock().unwrap().clone(),
+        }
+    }
+
+    /// Writes the interactions recorded so far to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.cassette().save(path)
+    }
+}
+
+/// How a client with a record/replay hook sends requests.
+#[derive(Debug, Clone)]
+pub(crate) enum ReplayMode {
+    Record(Recorder),
+    Replay(Arc<Mutex<VecDeque<Interaction>>>),
+}
+
+impl ReplayMode {
+    pub(crate) fn replay(cassette: Cassette) -> Self {
+        ReplayMode::Replay(Arc::new(Mutex::new(cassette.interactions.into())))
+    }
+
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, Error> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let method = request.method().to_string();
+        let path = request.url().path().to_string();
+        let query = request.url().query().map(str::to_string);
+
+        let interaction = match self {
+            ReplayMode::Record(recorder) => {
+                let response = client.execute(request).await?;
+                let status = response.status().as_u16();
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let body = response.text().await?;
+                let interaction = Interaction {
+                    method,
+                    path,
+                    query,
+                    status,
+                    content_type,
+                    body,
+                };
+                // The live client gets the real response; only the copy is sanitized.
+                let recorded = Interaction {
+                    body: sanitize_body(&interaction.path, status, &interaction.body),
+                    ..interaction.clone()
+                };
+                recorder.interactions.lock().unwrap().push(recorded);
+                interaction
+            }
+            ReplayMode::Replay(queue) => {
+                let next = queue.lock().unwrap().pop_front();
+                match next {
+                    Some(next) if next.method == method && next.path == path => next,
+                    Some(next) => {
+                        return Err(Error::UnexpectedResponse(format!(
+                            "replay: expected {} {}, but the client sent {method} {path}",
+                            next.method, next.path
+                        )));
+                    }
+                    None => {
+                        return Err(Error::UnexpectedResponse(format!(
+                            "replay: cassette exhausted before {method} {path}"
+                        )));
+                    }
+                }
+            }
+        };
+        to_response(interaction)
+    }
+}
+
+/// Replaces secrets and account-identifying hosts in a response body.
+fn sanitize_body(path: &str, status: u16, body: &str) -> String {
+    let success = (200..300).contains(&status);
+    if success && path.ends_with("/oauth/token") {
+        REDACTED.to_string()
+    } else if success && path.ends_with("/v2/streaming/hostname") {
+        REPLAY_INGEST_HOST.to_string()
+    } else {
+        body.to_string()
+    }
+}
+
+fn to_response(interaction: Interaction) -> Result<reqwest::Response, Error> {
+    let mut builder = http::Response::builder().status(interaction.status);
+    if let Some(content_type) = &interaction.content_type {
+        builder = builder.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+    let response = builder
+        .body(interaction.body)
+        .map_err(|e| Error::UnexpectedResponse(format!("replay: invalid interaction: {e}")))?;
+    Ok(reqwest::Response::from(response))
+}
@@ -0,0 +1,429 @@
+//! Back-off schedules for throttled requests.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::Error;
+
+/// How throttled (429) requests are retried: how many attempts in total, and
+/// how long to wait before each retry, plus how long any one attempt may
+/// take.
+///
+/// The delay starts at `initial_backoff` and is multiplied by `multiplier`
+/// after every retry, up to `max_backoff`. The default makes two attempts two
+/// seconds apart, with no attempt timeout. Set it as `Config::retry`; in a
+/// config file it is either a preset name or a section whose missing keys
+/// come from `preset` (or the default). Its `initial_backoff_ms` and
+/// `max_backoff_ms` take milliseconds or a duration string, and
+/// `attempt_timeout` seconds or a duration string:
+///
+/// ```json
+/// { "retry": { "preset": "conservative", "max_attempts": 5, "max_backoff_ms": "1m", "attempt_timeout": "30s" } }
+/// ```
+///
+/// ```
+/// use std::time::Duration;
+/// use snowpipe_streaming::RetryPlan;
+///
+/// let plan = RetryPlan::builder()
+///     .max_attempts(4)
+///     .initial_backoff(Duration::from_millis(250))
+///     .multiplier(2.0)
+///     .max_backoff(Duration::from_secs(1))
+///     .attempt_timeout(Duration::from_secs(10))
+///     .build()?;
+/// assert_eq!(plan.delay(3), Duration::from_secs(1));
+/// # Ok::<(), snowpipe_streaming::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RetryPlanRepr", into = "RetryPlanSpec")]
+pub struct RetryPlan {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    attempt_timeout: Option<Duration>,
+}
+
+// Plans are validated on construction, so `multiplier` is never NaN.
+impl Eq for RetryPlan {}
+
+impl Default for RetryPlan {
+    fn default() -> Self {
+        Self {
+            max_attempts: 2,
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 1.0,
+            attempt_timeout: None,
+        }
+    }
+}
+
+impl RetryPlan {
+    /// Starts a [`RetryPlanBuilder`] from the default plan.
+    pub fn builder() -> RetryPlanBuilder {
+        Self::default().to_builder()
+    }
+
+    /// Starts a [`RetryPlanBuilder`] from this plan, e.g. to adjust a preset.
+    pub fn to_builder(self) -> RetryPlanBuilder {
+        RetryPlanBuilder { plan: self }
+    }
+
+    /// Five attempts with short, doubling delays (100ms up to 2s), for
+    /// latency-sensitive writers.
+    pub fn aggressive() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 2.0,
+            attempt_timeout: None,
+        }
+    }
+
+    /// Three attempts with long, doubling delays (2s up to 30s), to back off
+    /// hard from a throttled account.
+    pub fn conservative() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            attempt_timeout: None,
+        }
+    }
+
+    /// A single attempt: a 429 fails the request immediately.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            multiplier: 1.0,
+            attempt_timeout: None,
+        }
+    }
+
+    /// Attempts in total, including the first.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Delay before the first retry.
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// Upper bound on any delay.
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// Growth factor applied to the delay after each retry.
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// Limit on each attempt of a request, including 401 and 429 retries,
+    /// from sending it until its response has arrived. Unlimited when unset.
+    pub fn attempt_timeout(&self) -> Option<Duration> {
+        self.attempt_timeout
+    }
+
+    /// Delay before retry number `retry` (starting at 1).
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.powi(retry.saturating_sub(1) as i32);
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// This plan starting at `backoff`, raising `max_backoff` to it if
+    /// needed; used for `OpenChannelOptions::rate_limit_backoff`.
+    pub(crate) fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self.max_backoff = self.max_backoff.max(backoff);
+        self
+    }
+
+    /// This plan with `limit` on each attempt; used for
+    /// `OpenChannelOptions::attempt_timeout`.
+    pub(crate) fn with_attempt_timeout(mut self, limit: Duration) -> Self {
+        self.attempt_timeout = Some(limit);
+        self
+    }
+
+    fn validate(self) -> Result<Self, Error> {
+        let mut issues = Vec::new();
+        if self.max_attempts < 1 {
+            issues.push("max_attempts must be at least 1");
+        }
+        if !(self.multiplier >= 1.0 && self.multiplier.is_finite()) {
+            issues.push("multiplier must be a finite number of at least 1");
+        }
+        if self.max_backoff < self.initial_backoff {
+            issues.push("max_backoff must be at least initial_backoff");
+        }
+        if self.attempt_timeout == Some(Duration::ZERO) {
+            issues.push("attempt_timeout must be greater than zero");
+        }
+        match issues.is_empty() {
+            true => Ok(self),
+            false => Err(Error::Config(format!(
+                "invalid retry plan: {}",
+                issues.join("; ")
+            ))),
+        }
+    }
+}
+
+impl std::str::FromStr for RetryPlan {
+    type Err = Error;
+
+    /// Parses a preset name (`default`, `aggressive`, `conservative`, or
+    /// `none`) or a JSON retry section, as read from `SNOWFLAKE_RETRY`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let s = s.trim();
+        let parsed = match s.starts_with('{') {
+            true => serde_json::from_str(s),
+            false => serde_json::from_value(serde_json::Value::String(s.to_string())),
+        };
+        parsed.map_err(|e| Error::Config(format!("invalid retry plan '{}': {}", s, e)))
+    }
+}
+
+/// Builds a validated [`RetryPlan`]; start one with [`RetryPlan::builder`]
+/// or [`RetryPlan::to_builder`].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPlanBuilder {
+    plan: RetryPlan,
+}
+
+impl RetryPlanBuilder {
+    /// Attempts in total, including the first; at least 1.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.plan.max_attempts = attempts;
+        self
+    }
+
+    /// Delay before the first retry.
+    pub fn initial_backoff(mut self, delay: Duration) -> Self {
+        self.plan.initial_backoff = delay;
+        self
+    }
+
+    /// Upper bound on any delay; at least `initial_backoff`.
+    pub fn max_backoff(mut self, delay: Duration) -> Self {
+        self.plan.max_backoff = delay;
+        self
+    }
+
+    /// Growth factor applied to the delay after each retry; at least 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.plan.multiplier = multiplier;
+        self
+    }
+
+    /// Limit on each attempt of a request, greater than zero; a hung attempt
+    /// then fails with [`Error::Timeout`] instead of holding up the retries.
+    pub fn attempt_timeout(mut self, limit: Duration) -> Self {
+        self.plan.attempt_timeout = Some(limit);
+        self
+    }
+
+    /// Checks the settings, failing with [`Error::Config`] listing every
+    /// problem.
+    pub fn build(self) -> Result<RetryPlan, Error> {
+        self.plan.validate()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum RetryPreset {
+    Default,
+    Aggressive,
+    Conservative,
+    None,
+}
+
+impl From<RetryPreset> for RetryPlan {
+    fn from(preset: RetryPreset) -> Self {
+        match preset {
+            RetryPreset::Default => Self::default(),
+            RetryPreset::Aggressive => Self::aggressive(),
+            RetryPreset::Conservative => Self::conservative(),
+            RetryPreset::None => Self::none(),
+        }
+    }
+}
+
+/// Config file form of a [`RetryPlan`].
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RetryPlanSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preset: Option<RetryPreset>,
+    max_attempts: Option<u32>,
+    #[serde(default, with = "crate::duration::millis")]
+    initial_backoff_ms: Option<Duration>,
+    #[serde(default, with = "crate::duration::millis")]
+    max_backoff_ms: Option<Duration>,
+    multiplier: Option<f64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::duration::secs"
+    )]
+    attempt_timeout: Option<Duration>,
+}
+
+/// A preset name or a [`RetryPlanSpec`] section.
+enum RetryPlanRepr {
+    Preset(RetryPreset),
+    Spec(RetryPlanSpec),
+}
+
+impl<'de> Deserialize<'de> for RetryPlanRepr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ReprVisitor;
+
+        impl<'de> Visitor<'de> for ReprVisitor {
+            type Value = RetryPlanRepr;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a retry preset name or a retry plan section")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                RetryPreset::deserialize(name.into_deserializer()).map(RetryPlanRepr::Preset)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                RetryPlanSpec::deserialize(MapAccessDeserializer::new(map)).map(RetryPlanRepr::Spec)
+            }
+        }
+
+        deserializer.deserialize_any(ReprVisitor)
+    }
+}
+
+impl TryFrom<RetryPlanRepr> for RetryPlan {
+    type Error = Error;
+
+    fn try_from(repr: RetryPlanRepr) -> Result<Self, Error> {
+        let spec = match repr {
+            RetryPlanRepr::Preset(preset) => return Ok(preset.into()),
+            RetryPlanRepr::Spec(spec) => spec,
+        };
+        let mut builder = RetryPlan::from(spec.preset.unwrap_or(RetryPreset::Default)).to_builder();
+        if let Some(attempts) = spec.max_attempts {
+            builder = builder.max_attempts(attempts);
+        }
+        if let Some(delay) = spec.initial_backoff_ms {
+            builder = builder.initial_backoff(delay);
+        }
+        if let Some(delay) = spec.max_backoff_ms {
+            builder = builder.max_backoff(delay);
+        }
+        if let Some(multiplier) = spec.multiplier {
+            builder = builder.multiplier(multiplier);
+        }
+        if let Some(limit) = spec.attempt_timeout {
+            builder = builder.attempt_timeout(limit);
+        }
+        builder.build()
+    }
+}
+
+impl From<RetryPlan> for RetryPlanSpec {
+    fn from(plan: RetryPlan) -> Self {
+        Self {
+            preset: None,
+            max_attempts: Some(plan.max_attempts),
+            initial_backoff_ms: Some(plan.initial_backoff),
+            max_backoff_ms: Some(plan.max_backoff),
+            multiplier: Some(plan.multiplier),
+            attempt_timeout: plan.attempt_timeout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_up_to_the_cap() {
+        let plan = RetryPlan::aggressive();
+        let delays: Vec<_> = (1..=6).map(|n| plan.delay(n).as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1600, 2000]);
+        assert_eq!(RetryPlan::default().delay(1), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn builder_reports_every_problem() {
+        let err = RetryPlan::builder()
+            .max_attempts(0)
+            .multiplier(0.5)
+            .max_backoff(Duration::from_secs(1))
+            .attempt_timeout(Duration::ZERO)
+            .build()
+            .unwrap_err()
+            .to_string();
+        for field in [
+            "max_attempts",
+            "multiplier",
+            "max_backoff",
+            "attempt_timeout",
+        ] {
+            assert!(err.contains(field), "{err}");
+        }
+        assert!(RetryPlan::builder().multiplier(f64::NAN).build().is_err());
+    }
+
+    #[test]
+    fn deserializes_presets_and_sections() {
+        let preset: RetryPlan = serde_json::from_str(r#""conservative""#).unwrap();
+        assert_eq!(preset, RetryPlan::conservative());
+
+        let section: RetryPlan =
+            serde_json::from_str(r#"{"preset": "aggressive", "max_attempts": 8}"#).unwrap();
+        assert_eq!(section.max_attempts(), 8);
+        assert_eq!(section.initial_backoff(), Duration::from_millis(100));
+
+        let round_trip: RetryPlan =
+            serde_json::from_value(serde_json::to_value(section).unwrap()).unwrap();
+        assert_eq!(round_trip, section);
+
+        let invalid = serde_json::from_str::<RetryPlan>(r#"{"multiplier": 0.5}"#);
+        assert!(invalid.unwrap_err().to_string().contains("multiplier"));
+        let unknown = serde_json::from_str::<RetryPlan>(r#"{"max_retries": 3}"#);
+        assert!(unknown.unwrap_err().to_string().contains("max_retries"));
+        let preset = serde_json::from_str::<RetryPlan>(r#""reckless""#);
+        assert!(preset.unwrap_err().to_string().contains("reckless"));
+
+        assert_eq!("none".parse::<RetryPlan>().unwrap(), RetryPlan::none());
+        let env: RetryPlan = r#"{"max_attempts": 4}"#.parse().unwrap();
+        assert_eq!(env.max_attempts(), 4);
+
+        let delays: RetryPlan =
+            serde_json::from_str(r#"{"initial_backoff_ms": 250, "max_backoff_ms": "1m"}"#).unwrap();
+        assert_eq!(delays.initial_backoff(), Duration::from_millis(250));
+        assert_eq!(delays.max_backoff(), Duration::from_secs(60));
+
+        let timed: RetryPlan =
+            serde_json::from_str(r#"{"preset": "none", "attempt_timeout": "30s"}"#).unwrap();
+        assert_eq!(timed.attempt_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(timed.max_attempts(), 1);
+        let round_trip: RetryPlan =
+            serde_json::from_value(serde_json::to_value(timed).unwrap()).unwrap();
+        assert_eq!(round_trip, timed);
+    }
+}
@@ -0,0 +1,287 @@
+//! Routing one stream of rows to channels on several pipes, for a single
+//! process ingesting into many tables.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use serde::Serialize;
+
+use crate::channel::serialize_rows;
+use crate::partition::column_value;
+use crate::{ChannelReport, Config, Error, StreamingIngestChannel, StreamingIngestClient};
+
+type RouteFn<R> = dyn Fn(&R) -> Option<Route> + Send + Sync;
+
+/// A pipe and a channel on it that a [`Router`] sends rows to.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Route {
+    pipe: String,
+    channel: String,
+}
+
+impl Route {
+    /// Rows for `channel` on `pipe`.
+    pub fn new(pipe: impl Into<String>, channel: impl Into<String>) -> Self {
+        Self {
+            pipe: pipe.into(),
+            channel: channel.into(),
+        }
+    }
+
+    /// The target pipe.
+    pub fn pipe(&self) -> &str {
+        &self.pipe
+    }
+
+    /// The channel name on the pipe.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.pipe, self.channel)
+    }
+}
+
+/// Sends rows whose top-level `column` equals `value` to `route`.
+#[derive(Debug, Clone)]
+struct RouteRule {
+    column: String,
+    value: serde_json::Value,
+    route: Route,
+}
+
+/// Appends rows to channels on the pipes of one database and schema, picking
+/// each row's [`Route`] by a closure or by rules on its fields.
+///
+/// A row's route is, in order: the route closure's answer
+/// ([`with_route_fn`](Self::with_route_fn)); the first matching rule
+/// ([`with_rule`](Self::with_rule)); the default route
+/// ([`with_default_route`](Self::with_default_route)). An append with a row
+/// that has none fails with [`Error::InvalidRow`] before anything is sent.
+///
+/// The router opens a client for each pipe, from its [`Config`], and a
+/// channel for each route the first time a row is routed there. Register a
+/// client built some other way (e.g. with a token provider) with
+/// [`with_client`](Self::with_client).
+///
+/// ```no_run
+/// # async fn run(config: snowpipe_streaming::Config) -> Result<(), snowpipe_streaming::Error> {
+/// use snowpipe_streaming::{Route, Router};
+///
+/// let mut router = Router::<serde_json::Value>::new("svc", "MY_DB", "MY_SCHEMA", config)
+///     .with_rule("kind", "order", Route::new("ORDERS_PIPE", "orders"))
+///     .with_rule("kind", "refund", Route::new("REFUNDS_PIPE", "refunds"))
+///     .with_default_route(Route::new("EVENTS_PIPE", "events"));
+/// router
+///     .append_rows_iter([
+///         serde_json::json!({"kind": "order", "id": 1}),
+///         serde_json::json!({"kind": "click", "id": 2}),
+///     ])
+///     .await?;
+/// router.close().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Router<R> {
+    client_name: String,
+    db_name: String,
+    schema_name: String,
+    config: Config,
+    route_fn: Option<Arc<RouteFn<R>>>,
+    rules: Vec<RouteRule>,
+    default_route: Option<Route>,
+    clients: HashMap<String, StreamingIngestClient<R>>,
+    channels: BTreeMap<Route, StreamingIngestChannel<R>>,
+    /// Serialization buffer shared by every append through the router.
+    buffer: BytesMut,
+}
+
+impl<R: Serialize + Clone> Router<R> {
+    /// A router with no routes, whose clients are named `client_name` and
+    /// target pipes in `db_name.schema_name`.
+    pub fn new(client_name: &str, db_name: &str, schema_name: &str, config: Config) -> Self {
+        Self {
+            client_name: client_name.to_string(),
+            db_name: db_name.to_string(),
+            schema_name: schema_name.to_string(),
+            config,
+            route_fn: None,
+            rules: Vec::new(),
+            default_route: None,
+            clients: HashMap::new(),
+            channels: BTreeMap::new(),
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Routes each row with `route`; rows it returns `None` for fall through
+    /// to the rules and the default route.
+    pub fn with_route_fn<F>(mut self, route: F) -> Self
+    where
+        F: Fn(&R) -> Option<Route> + Send + Sync + 'static,
+    {
+        self.route_fn = Some(Arc::new(route));
+        self
+    }
+
+    /// Routes rows whose top-level `column` equals `value` to `route`. Rules
+    /// are tried in the order they were added.
+    pub fn with_rule(
+        mut self,
+        column: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+        route: Route,
+    ) -> Self {
+        self.rules.push(RouteRule {
+            column: column.into(),
+            value: value.into(),
+            route,
+        });
+        self
+    }
+
+    /// Routes rows that no closure or rule routes.
+    pub fn with_default_route(mut self, route: Route) -> Self {
+        self.default_route = Some(route);
+        self
+    }
+
+    /// Uses `client` for its pipe instead of opening one from the router's
+    /// config.
+    pub fn with_client(mut self, client: StreamingIngestClient<R>) -> Self {
+        self.clients.insert(client.pipe_name.clone(), client);
+        self
+    }
+
+    /// The route `row` would be appended to.
+    pub fn route_of(&self, row: &R) -> Result<Route, Error> {
+        if let Some(route) = self.route_fn.as_ref().and_then(|route_fn| route_fn(row)) {
+            return Ok(route);
+        }
+        let serialized = if self.rules.is_empty() {
+            Vec::new()
+        } else {
+            serde_json::to_vec(row)?
+        };
+        self.resolve(None, &serialized)
+            .ok_or_else(|| Error::InvalidRow("row matches no route".into()))
+    }
+
+    /// Channels opened so far, in route order.
+    pub fn channels(&self) -> impl Iterator<Item = (&Route, &StreamingIngestChannel<R>)> {
+        self.channels.iter()
+    }
+
+    /// The channel opened for `route`, if any row has been routed there.
+    pub fn channel(&self, route: &Route) -> Option<&StreamingIngestChannel<R>> {
+        self.channels.get(route)
+    }
+
+    /// Appends `row` to its route's channel.
+    pub async fn append_row(&mut self, row: &R) -> Result<(), Error> {
+        let route = self.route_of(row)?;
+        self.open(&route).await?.append_row(row).await
+    }
+
+    /// Routes each row and sends each channel's share in parallel, in input
+    /// order. Returns the number of body bytes sent.
+    ///
+    /// If any channel's share fails, the others may still have been appended.
+    pub async fn append_rows_iter<I>(&mut self, rows: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let mut chosen = Vec::new();
+        let route_fn = self.route_fn.clone();
+        let rows = serialize_rows(
+            &mut self.buffer,
+            rows.into_iter().inspect(|row| {
+                if let Some(route_fn) = &route_fn {
+                    chosen.push(route_fn(row));
+                }
+            }),
+        )?;
+
+        let mut indices: HashMap<Route, Vec<usize>> = HashMap::new();
+        for index in 0..rows.len() {
+            let route = self
+                .resolve(
+                    chosen.get_mut(index).and_then(Option::take),
+                    rows.row(index),
+                )
+                .ok_or_else(|| {
+                    Error::InvalidRow(format!("row {index} of the batch matches no route"))
+                })?;
+            indices.entry(route).or_default().push(index);
+        }
+        let mut parts = HashMap::with_capacity(indices.len());
+        for (route, indices) in indices {
+            self.open(&route).await?;
+            parts.insert(route, rows.select(&indices));
+        }
+
+        let sends = self.channels.iter_mut().filter_map(|(route, channel)| {
+            parts
+                .remove(route)
+                .map(|part| channel.append_serialized_rows(part))
+        });
+        let written = futures_util::future::try_join_all(sends).await?;
+        Ok(written.into_iter().sum())
+    }
+
+    /// Closes every channel, returning their reports in route order. Later
+    /// appends open their channels again.
+    pub async fn close(&mut self) -> Result<Vec<(Route, ChannelReport)>, Error> {
+        let mut reports = Vec::with_capacity(self.channels.len());
+        while let Some(mut entry) = self.channels.first_entry() {
+            let report = entry.get_mut().close().await?;
+            reports.push((entry.remove_entry().0, report));
+        }
+        Ok(reports)
+    }
+
+    /// The route for a row: `chosen` by the route closure, else the first
+    /// rule matching `row` (serialized), else the default route.
+    fn resolve(&self, chosen: Option<Route>, row: &[u8]) -> Option<Route> {
+        chosen
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .find(|rule| column_value(row, &rule.column).as_ref() == Some(&rule.value))
+                    .map(|rule| rule.route.clone())
+            })
+            .or_else(|| self.default_route.clone())
+    }
+
+    /// The channel for `route`, opening it (and its pipe's client) if needed.
+    async fn open(&mut self, route: &Route) -> Result<&mut StreamingIngestChannel<R>, Error> {
+        if !self.channels.contains_key(route) {
+            if !self.clients.contains_key(&route.pipe) {
+                let client = StreamingIngestClient::new(
+                    &self.client_name,
+                    &self.db_name,
+                    &self.schema_name,
+                    &route.pipe,
+                    self.config.clone(),
+                )
+                .await?;
+                self.clients.insert(route.pipe.clone(), client);
+            }
+            let client = self
+                .clients
+                .get_mut(&route.pipe)
+                .expect("client inserted above");
+            let channel = client.open_channel(&route.channel).await?;
+            self.channels.insert(route.clone(), channel);
+        }
+        Ok(self
+            .channels
+            .get_mut(route)
+            .expect("channel inserted above"))
+    }
+}
@@ -0,0 +1,146 @@
+//! Keeping a fraction of rows, for high-volume telemetry where only a
+//! sample of events needs to land in the table.
+
+use std::fmt;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+use crate::Error;
+use crate::client::stagger::random_unit;
+
+type SampleFn = dyn Fn(&[u8]) -> bool + Send + Sync;
+
+#[derive(Clone)]
+enum Rule {
+    OneIn(u64),
+    Probability(f64),
+    Custom(Arc<SampleFn>),
+}
+
+/// Decides which rows a channel keeps. A channel with one set (see
+/// [`StreamingIngestChannel::set_sampler`](crate::StreamingIngestChannel::set_sampler))
+/// drops the other rows of every `append_row*` call before they are
+/// buffered or sent, and counts them.
+///
+/// ```
+/// use std::num::NonZeroU64;
+/// use snowpipe_streaming::Sampler;
+///
+/// let every_tenth = Sampler::one_in(NonZeroU64::new(10).unwrap());
+/// let a_quarter = Sampler::probability(0.25)?;
+/// let errors_only = Sampler::new(|row| row.windows(7).any(|w| w == b"\"error\""));
+/// assert_eq!(every_tenth.sampled_out(), 0);
+/// # let _ = (a_quarter, errors_only);
+/// # Ok::<(), snowpipe_streaming::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Sampler {
+    rule: Rule,
+    seen: u64,
+    kept: u64,
+    sampled_out: u64,
+}
+
+impl Sampler {
+    /// Keeps the rows, one row serialized as JSON, for which `keep` returns
+    /// true.
+    pub fn new<F>(keep: F) -> Self
+    where
+        F: Fn(&[u8]) -> bool + Send + Sync + 'static,
+    {
+        Self::with_rule(Rule::Custom(Arc::new(keep)))
+    }
+
+    /// Keeps the first of every `n` rows, deterministically.
+    pub fn one_in(n: NonZeroU64) -> Self {
+        Self::with_rule(Rule::OneIn(n.get()))
+    }
+
+    /// Keeps each row independently with probability `p`, which must be
+    /// between 0 and 1.
+    pub fn probability(p: f64) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(Error::Config(format!(
+                "sampling probability {p} must be between 0 and 1"
+            )));
+        }
+        Ok(Self::with_rule(Rule::Probability(p)))
+    }
+
+    fn with_rule(rule: Rule) -> Self {
+        Self {
+            rule,
+            seen: 0,
+            kept: 0,
+            sampled_out: 0,
+        }
+    }
+
+    /// Rows kept so far.
+    pub fn kept(&self) -> u64 {
+        self.kept
+    }
+
+    /// Rows dropped by sampling so far.
+    pub fn sampled_out(&self) -> u64 {
+        self.sampled_out
+    }
+
+    /// Whether to keep `row`, counting it either way.
+    pub(crate) fn keep(&mut self, row: &[u8]) -> bool {
+        let keep = match &self.rule {
+            Rule::OneIn(n) => self.seen.is_multiple_of(*n),
+            Rule::Probability(p) => random_unit() < *p,
+            Rule::Custom(keep) => keep(row),
+        };
+        self.seen += 1;
+        if keep {
+            self.kept += 1;
+        } else {
+            self.sampled_out += 1;
+        }
+        keep
+    }
+}
+
+impl fmt::Debug for Sampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = match &self.rule {
+            Rule::OneIn(n) => format!("1/{n}"),
+            Rule::Probability(p) => format!("p={p}"),
+            Rule::Custom(_) => "custom".to_string(),
+        };
+        f.debug_struct("Sampler")
+            .field("rule", &rule)
+            .field("kept", &self.kept)
+            .field("sampled_out", &self.sampled_out)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_in_n_keeps_the_first_of_each_group() {
+        let mut sampler = Sampler::one_in(NonZeroU64::new(3).unwrap());
+        let kept = (0..7).map(|_| sampler.keep(b"{}")).collect::<Vec<_>>();
+        assert_eq!(kept, [true, false, false, true, false, false, true]);
+        assert_eq!((sampler.kept(), sampler.sampled_out()), (3, 4));
+    }
+
+    #[test]
+    fn probability_bounds() {
+        assert!(Sampler::probability(1.5).is_err());
+        assert!(Sampler::probability(f64::NAN).is_err());
+        let mut none = Sampler::probability(0.0).unwrap();
+        let mut all = Sampler::probability(1.0).unwrap();
+        assert!((0..100).all(|_| !none.keep(b"{}") && all.keep(b"{}")));
+        let mut half = Sampler::probability(0.5).unwrap();
+        for _ in 0..1000 {
+            half.keep(b"{}");
+        }
+        assert!((300..700).contains(&half.kept()), "{half:?}");
+    }
+}
@@ -0,0 +1,193 @@
+//! A `tower::Service` over a channel, so standard tower middleware (rate
+//! limits, concurrency limits, load shedding, timeouts) can wrap ingestion.
+
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tower_service::Service;
+
+use crate::{Error, StreamingIngestChannel};
+
+/// Rows a [`ChannelService`] appends in one call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch<R> {
+    rows: Vec<R>,
+    offset_token: Option<u64>,
+}
+
+impl<R> Batch<R> {
+    /// Rows appended like
+    /// [`append_rows_iter`](StreamingIngestChannel::append_rows_iter), split
+    /// into as many requests as they need.
+    pub fn new(rows: impl IntoIterator<Item = R>) -> Self {
+        Self {
+            rows: rows.into_iter().collect(),
+            offset_token: None,
+        }
+    }
+
+    /// Rows appended in one request carrying `offset_token`, like
+    /// [`append_rows_at`](StreamingIngestChannel::append_rows_at).
+    pub fn at(offset_token: u64, rows: impl IntoIterator<Item = R>) -> Self {
+        Self {
+            offset_token: Some(offset_token),
+            ..Self::new(rows)
+        }
+    }
+
+    /// Number of rows in the batch.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the batch has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// Appends [`Batch`]es to a channel as a `tower::Service`, responding with
+/// the body bytes sent. Requires the `tower` feature.
+///
+/// At most `capacity` batches are accepted and not yet appended at a time;
+/// `poll_ready` is pending while that many are waiting for the channel or in
+/// flight, so middleware such as `ConcurrencyLimit` or `LoadShed` sees the
+/// channel's back-pressure. A batch held at `Config::max_uncommitted_offsets`
+/// keeps its slot until commits catch up. Batches are appended one at a time
+/// by a task spawned in [`new`](Self::new), in the order `call` received
+/// them. A response future only waits for that task, so dropping it, e.g.
+/// when a tower `Timeout` fires, discards the outcome but does not cancel
+/// the append.
+///
+/// ```no_run
+/// # async fn run(
+/// #     channel: snowpipe_streaming::StreamingIngestChannel<serde_json::Value>,
+/// # ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use std::num::NonZeroUsize;
+/// use std::time::Duration;
+///
+/// use snowpipe_streaming::{Batch, ChannelService};
+/// use tower::{Service, ServiceBuilder, ServiceExt};
+///
+/// let service = ChannelService::new(channel, NonZeroUsize::new(4).unwrap());
+/// let mut service = ServiceBuilder::new()
+///     .load_shed()
+///     .rate_limit(100, Duration::from_secs(1))
+///     .service(service);
+/// let rows = vec![serde_json::json!({ "id": 1 })];
+/// let bytes = service.ready().await?.call(Batch::new(rows)).await?;
+/// # let _ = bytes;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChannelService<R> {
+    jobs: mpsc::UnboundedSender<Job<R>>,
+    worker: JoinHandle<StreamingIngestChannel<R>>,
+    slots: Arc<Semaphore>,
+    acquiring: Option<Pin<Box<dyn Future<Output = OwnedSemaphorePermit> + Send>>>,
+    slot: Option<OwnedSemaphorePermit>,
+}
+
+impl<R> ChannelService<R>
+where
+    R: Serialize + Clone + Send + 'static,
+{
+    /// Wraps `channel`, accepting up to `capacity` batches before
+    /// `poll_ready` waits for one to be appended. Must be called from within
+    /// a Tokio runtime. Dropping the service lets accepted batches finish.
+    pub fn new(channel: StreamingIngestChannel<R>, capacity: NonZeroUsize) -> Self {
+        let (jobs, rx) = mpsc::unbounded_channel();
+        Self {
+            jobs,
+            worker: tokio::spawn(run(channel, rx)),
+            slots: Arc::new(Semaphore::new(capacity.get())),
+            acquiring: None,
+            slot: None,
+        }
+    }
+}
+
+impl<R> ChannelService<R> {
+    /// Waits for every accepted batch to be appended, then returns the
+    /// channel, e.g. to close it.
+    pub async fn into_inner(self) -> StreamingIngestChannel<R> {
+        let Self { jobs, worker, .. } = self;
+        drop(jobs);
+        match worker.await {
+            Ok(channel) => channel,
+            Err(e) => match e.try_into_panic() {
+                Ok(panic) => std::panic::resume_unwind(panic),
+                Err(e) => panic!("channel service worker was cancelled: {e}"),
+            },
+        }
+    }
+}
+
+/// A batch queued for the worker; the slot is released once it is appended.
+struct Job<R> {
+    batch: Batch<R>,
+    done: oneshot::Sender<Result<usize, Error>>,
+    slot: OwnedSemaphorePermit,
+}
+
+/// Appends queued batches in order until the service is dropped, then hands
+/// the channel back.
+async fn run<R: Serialize + Clone>(
+    mut channel: StreamingIngestChannel<R>,
+    mut jobs: mpsc::UnboundedReceiver<Job<R>>,
+) -> StreamingIngestChannel<R> {
+    while let Some(Job { batch, done, slot }) = jobs.recv().await {
+        let result = match batch.offset_token {
+            Some(offset_token) => channel.append_rows_at(offset_token, batch.rows).await,
+            None => channel.append_rows_iter(batch.rows).await,
+        };
+        drop(slot);
+        let _ = done.send(result);
+    }
+    channel
+}
+
+impl<R> Service<Batch<R>> for ChannelService<R>
+where
+    R: Serialize + Clone + Send + 'static,
+{
+    type Response = usize;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<usize, Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if self.slot.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        let slots = self.slots.clone();
+        let acquiring = self.acquiring.get_or_insert_with(|| {
+            Box::pin(async move { slots.acquire_owned().await.expect("slots are never closed") })
+        });
+        let slot = std::task::ready!(acquiring.as_mut().poll(cx));
+        self.acquiring = None;
+        self.slot = Some(slot);
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, batch: Batch<R>) -> Self::Future {
+        let slot = self
+            .slot
+            .take()
+            .expect("ChannelService::call before poll_ready returned Ready");
+        let (done, rx) = oneshot::channel();
+        let _ = self.jobs.send(Job { batch, done, slot });
+        Box::pin(async move {
+            rx.await.unwrap_or_else(|_| {
+                Err(Error::AppendFailed(
+                    "channel service worker stopped before the batch was appended".into(),
+                ))
+            })
+        })
+    }
+}
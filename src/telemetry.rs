@@ -0,0 +1,482 @@
+//! Structured client events and the sinks that receive them.
+//!
+//! Every auth refresh, retry decision, and completed append is reported as a
+//! [`TelemetryEvent`] to the client's [`TelemetrySink`]. The default
+//! [`TracingTelemetry`] sink logs them through `tracing`; supply your own sink
+//! via [`StreamingIngestClient::new_with_telemetry`] to forward events to
+//! StatsD, CloudWatch EMF, or any other system without parsing log lines.
+//!
+//! [`StreamingIngestClient::new_with_telemetry`]: crate::StreamingIngestClient::new_with_telemetry
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, info, trace, warn};
+use uuid::Uuid;
+
+use crate::{ApiVersion, AuthScheme, RetryPlan};
+
+/// User-supplied key/value labels (e.g. `env`, `tenant`, `source`) sent with
+/// every event from a client or channel. Set client-wide with `Config::tags`
+/// and per channel with
+/// [`StreamingIngestChannel::set_tags`](crate::StreamingIngestChannel::set_tags).
+pub type Tags = BTreeMap<String, String>;
+
+/// Which credential an auth-related event refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Control-plane JWT used for discovery and token exchange.
+    Control,
+    /// Scoped token used for ingest-plane requests.
+    Scoped,
+}
+
+/// How a client obtains its credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Control-plane JWTs are generated from the private key and refreshed
+    /// automatically.
+    Managed,
+    /// A fixed token from the configuration (`jwt_token` or
+    /// `programmatic_access_token`) is used as given.
+    Provided,
+    /// Scoped tokens come from a
+    /// [`ScopedTokenProvider`](crate::ScopedTokenProvider); the control plane
+    /// is never contacted.
+    Brokered,
+}
+
+/// Effective settings of a newly constructed client, reported once in
+/// [`TelemetryEvent::ClientInitialized`] so a fleet's configuration can be
+/// audited from its logs. Contains no credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClientSettings {
+    /// Control-plane base URL; `None` in [`AuthMode::Brokered`] when it
+    /// cannot be derived.
+    pub control_host: Option<String>,
+    /// Ingest host, configured or discovered.
+    pub ingest_host: String,
+    /// Authentication scheme of control-plane requests.
+    pub auth_scheme: AuthScheme,
+    /// Where credentials come from.
+    pub auth_mode: AuthMode,
+    /// REST API revision appends use.
+    pub api_version: ApiVersion,
+    /// Attempts and back-off for throttled requests.
+    pub retry: RetryPlan,
+    /// Whether a 401 refreshes credentials and retries once.
+    pub retry_on_unauthorized: bool,
+    /// Remaining JWT lifetime at which a new one is generated;
+    /// `None` unless the mode is [`AuthMode::Managed`].
+    pub jwt_refresh_margin: Option<Duration>,
+    /// Remaining scoped token lifetime at which a new one is fetched.
+    pub scoped_token_refresh_margin: Duration,
+    /// Age at which the scoped token is replaced regardless of its expiry.
+    pub scoped_token_max_age: Option<Duration>,
+    /// Fail-fast window after a failed scoped token fetch.
+    pub token_refresh_cooldown: Duration,
+    /// Upper bound on the random offset by which refreshes happen early.
+    pub refresh_jitter: Option<Duration>,
+    /// Cargo features the crate was built with.
+    pub features: Vec<&'static str>,
+}
+
+/// Cargo features the crate was built with, in `Cargo.toml` order.
+pub(crate) fn enabled_features() -> Vec<&'static str> {
+    [
+        ("crypto", cfg!(feature = "crypto")),
+        ("chaos", cfg!(feature = "chaos")),
+        ("dotenv", cfg!(feature = "dotenv")),
+        ("replay", cfg!(feature = "replay")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("os-keystore", cfg!(feature = "os-keystore")),
+        ("vault", cfg!(feature = "vault")),
+        ("tls-pinning", cfg!(feature = "tls-pinning")),
+        ("compression", cfg!(feature = "compression")),
+        ("tower", cfg!(feature = "tower")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// An observable event emitted by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TelemetryEvent {
+    /// A new control-plane JWT is being generated because the cached one is
+    /// within the refresh safety margin.
+    JwtRefresh {
+        /// Remaining lifetime of the cached JWT.
+        remaining_secs: u64,
+        /// Configured refresh margin.
+        margin_secs: u64,
+    },
+    /// `jwt_exp` was outside the supported range and was clamped.
+    JwtLifetimeClamped {
+        /// Requested lifetime.
+        original_secs: u64,
+        /// Lifetime actually used.
+        effective_secs: u64,
+    },
+    /// A new scoped token is being fetched before use because the cached one
+    /// is within its refresh margin or older than `scoped_token_max_age`.
+    ScopedTokenRefresh {
+        /// Time since the cached token was acquired.
+        age_secs: u64,
+        /// Remaining lifetime of the cached token, when its expiry is known.
+        remaining_secs: Option<u64>,
+    },
+    /// Fetching a scoped token failed; further attempts fail fast until
+    /// `cooldown` has passed.
+    ScopedTokenRefreshFailed {
+        /// Wait before the next attempt.
+        cooldown: Duration,
+    },
+    /// A request was rejected with 401; the token is refreshed and the request
+    /// retried once.
+    UnauthorizedRetry {
+        /// Credential that was rejected.
+        token: TokenKind,
+    },
+    /// A request was rejected with 401 again after refreshing; the failure is
+    /// surfaced to the caller.
+    UnauthorizedFailure {
+        /// Credential that was rejected.
+        token: TokenKind,
+    },
+    /// A request was throttled with 429 and is retried after `delay`.
+    RateLimited {
+        /// Credential the throttled request carried.
+        token: TokenKind,
+        /// Back-off before the retry.
+        delay: Duration,
+    },
+    /// A request was answered with `503` and a `Retry-After` long enough to
+    /// mean scheduled maintenance (`Config::maintenance_retry_after`).
+    /// Requests are held and re-sent periodically instead of failing; emitted
+    /// once per window, however many requests wait it out.
+    MaintenanceStarted {
+        /// Wait requested by the first maintenance response.
+        retry_after: Duration,
+    },
+    /// Snowflake answered a request normally after a maintenance window;
+    /// held requests resume.
+    MaintenanceEnded {
+        /// Time since the window was first seen.
+        elapsed: Duration,
+    },
+    /// An append request was accepted by Snowflake.
+    AppendCompleted {
+        /// Channel the rows were appended to.
+        channel: String,
+        /// Random ID of the request, also in the channel's
+        /// [`AppendReport`](crate::AppendReport).
+        batch_id: Uuid,
+        /// Offset token assigned to the request.
+        offset: u64,
+        /// Rows in the request body.
+        rows: usize,
+        /// Request body size in bytes.
+        bytes: usize,
+        /// Time from sending the request to receiving the response.
+        elapsed: Duration,
+        /// XXH3-64 of the request's rows, when `Config::batch_checksum` or
+        /// `Config::checksum_column` is set (see
+        /// [`AppendReport::checksum`](crate::AppendReport::checksum)).
+        checksum: Option<u64>,
+    },
+    /// A channel status response reported Snowflake's average processing
+    /// latency, the server-side time from receiving rows to committing them.
+    /// Feed it into a histogram to track ingestion lag apart from request
+    /// latency.
+    ServerLatency {
+        /// Channel the status belongs to.
+        channel: String,
+        /// Average processing latency reported by Snowflake.
+        latency: Duration,
+    },
+    /// A response carried rate-limit or diagnostic headers. Track
+    /// [`ResponseHeaders::rate_limit_remaining`] to see how close the client
+    /// runs to server-imposed limits before requests are throttled.
+    RateLimitHeaders {
+        /// Credential the request carried.
+        token: TokenKind,
+        /// HTTP status of the response.
+        status: u16,
+        /// Headers captured from the response.
+        headers: ResponseHeaders,
+    },
+    /// A client finished construction and is ready to open channels. Emitted
+    /// once per client; logged by [`TracingTelemetry`] as `client.initialized`.
+    ClientInitialized {
+        /// The client's effective settings.
+        settings: Box<ClientSettings>,
+    },
+}
+
+/// Header name prefixes captured by [`ResponseHeaders`].
+const CAPTURED_PREFIXES: [&str; 3] = ["x-ratelimit-", "ratelimit-", "x-snowflake-"];
+/// Individual headers captured by [`ResponseHeaders`].
+const CAPTURED_HEADERS: [&str; 2] = ["retry-after", "x-request-id"];
+
+/// Rate-limit (`X-RateLimit-*`, `RateLimit-*`, `Retry-After`) and diagnostic
+/// (`X-Snowflake-*`, `X-Request-Id`) headers from a Snowflake response, with
+/// lowercase names in the order they were received.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseHeaders {
+    headers: Vec<(String, String)>,
+}
+
+impl ResponseHeaders {
+    pub(crate) fn capture(headers: &reqwest::header::HeaderMap) -> Self {
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| {
+                let name = name.as_str();
+                CAPTURED_HEADERS.contains(&name)
+                    || CAPTURED_PREFIXES
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix))
+            })
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        Self { headers }
+    }
+
+    /// Whether no rate-limit or diagnostic headers were present.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Value of the captured header `name` (case-insensitive).
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Captured headers as `(name, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Request quota for the current window, from `X-RateLimit-Limit` or
+    /// `RateLimit-Limit`.
+    pub fn rate_limit_limit(&self) -> Option<u64> {
+        self.number(["x-ratelimit-limit", "ratelimit-limit"])
+    }
+
+    /// Requests left in the current window, from `X-RateLimit-Remaining` or
+    /// `RateLimit-Remaining`.
+    pub fn rate_limit_remaining(&self) -> Option<u64> {
+        self.number(["x-ratelimit-remaining", "ratelimit-remaining"])
+    }
+
+    /// Wait requested by the server, from a `Retry-After` header given in
+    /// seconds.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.number(["retry-after"]).map(Duration::from_secs)
+    }
+
+    fn number<const N: usize>(&self, names: [&str; N]) -> Option<u64> {
+        names
+            .iter()
+            .find_map(|name| self.get(name)?.trim().parse().ok())
+    }
+}
+
+/// Receives [`TelemetryEvent`]s from a client and its channels.
+///
+/// Sinks are called inline on the request path, so they should hand events
+/// off quickly (e.g. push into a channel or update atomics) rather than block.
+pub trait TelemetrySink: Send + Sync {
+    /// Records a single event.
+    fn record(&self, event: &TelemetryEvent);
+
+    /// Records an event with the [`Tags`] of the client or channel that
+    /// emitted it. Every event is delivered through this method; the default
+    /// drops the tags and calls [`record`](Self::record). Override it to use
+    /// the tags as metric labels.
+    fn record_with_tags(&self, event: &TelemetryEvent, tags: &Tags) {
+        let _ = tags;
+        self.record(event);
+    }
+}
+
+/// The client's sink paired with the tags of the client or channel emitting
+/// events.
+pub(crate) struct TaggedSink {
+    sink: Arc<dyn TelemetrySink>,
+    tags: Tags,
+}
+
+impl TaggedSink {
+    pub(crate) fn new(sink: Arc<dyn TelemetrySink>, tags: Tags) -> Self {
+        Self { sink, tags }
+    }
+
+    pub(crate) fn tags(&self) -> &Tags {
+        &self.tags
+    }
+
+    /// The same sink with `tags` added, replacing existing values for the
+    /// same keys.
+    pub(crate) fn with_tags(&self, tags: Tags) -> Self {
+        let mut merged = self.tags.clone();
+        merged.extend(tags);
+        Self::new(self.sink.clone(), merged)
+    }
+}
+
+impl TelemetrySink for TaggedSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.sink.record_with_tags(event, &self.tags);
+    }
+}
+
+/// Default sink that logs events with `tracing`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingTelemetry;
+
+impl TelemetrySink for TracingTelemetry {
+    /// Logs the event inside a `telemetry` span whose `tags` field lists the
+    /// tags as `key=value` pairs.
+    fn record_with_tags(&self, event: &TelemetryEvent, tags: &Tags) {
+        if tags.is_empty() {
+            return self.record(event);
+        }
+        let tags = tags
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        let span = tracing::info_span!("telemetry", tags = %tags);
+        let _entered = span.enter();
+        self.record(event);
+    }
+
+    fn record(&self, event: &TelemetryEvent) {
+        match event {
+            TelemetryEvent::JwtRefresh {
+                remaining_secs,
+                margin_secs,
+            } => info!(
+                remaining_seconds = remaining_secs,
+                margin_seconds = margin_secs,
+                "refreshing JWT because remaining lifetime is within safety margin"
+            ),
+            TelemetryEvent::JwtLifetimeClamped {
+                original_secs,
+                effective_secs,
+            } => warn!(
+                original_seconds = original_secs,
+                effective_seconds = effective_secs,
+                "jwt_exp outside supported range - clamped for safety"
+            ),
+            TelemetryEvent::ScopedTokenRefresh {
+                age_secs,
+                remaining_secs,
+            } => info!(
+                age_seconds = age_secs,
+                remaining_seconds = remaining_secs,
+                "refreshing scoped token before use"
+            ),
+            TelemetryEvent::ScopedTokenRefreshFailed { cooldown } => warn!(
+                cooldown_seconds = cooldown.as_secs(),
+                "scoped token refresh failed; suppressing further attempts during cooldown"
+            ),
+            TelemetryEvent::UnauthorizedRetry {
+                token: TokenKind::Control,
+            } => warn!("received 401 from Snowflake; refreshing JWT and retrying"),
+            TelemetryEvent::UnauthorizedRetry {
+                token: TokenKind::Scoped,
+            } => warn!("scoped token rejected with 401; refreshing scoped token and retrying"),
+            TelemetryEvent::UnauthorizedFailure {
+                token: TokenKind::Control,
+            } => warn!("received 401 from Snowflake after retry; surfacing authentication failure"),
+            TelemetryEvent::UnauthorizedFailure {
+                token: TokenKind::Scoped,
+            } => warn!("scoped token rejected again after retry; surfacing authentication failure"),
+            TelemetryEvent::RateLimited {
+                token: TokenKind::Control,
+                delay,
+            } => warn!(
+                "received 429 TOO MANY REQUESTS; sleeping {} seconds before retry",
+                delay.as_secs()
+            ),
+            TelemetryEvent::RateLimited {
+                token: TokenKind::Scoped,
+                delay,
+            } => warn!(
+                "received 429 from ingest endpoint; sleeping {} seconds before retry",
+                delay.as_secs()
+            ),
+            TelemetryEvent::MaintenanceStarted { retry_after } => warn!(
+                retry_after_seconds = retry_after.as_secs(),
+                "Snowflake is under maintenance (503); holding requests and probing until it returns"
+            ),
+            TelemetryEvent::MaintenanceEnded { elapsed } => info!(
+                elapsed_seconds = elapsed.as_secs(),
+                "Snowflake maintenance ended; resuming requests"
+            ),
+            TelemetryEvent::AppendCompleted {
+                channel,
+                batch_id,
+                offset,
+                rows,
+                bytes,
+                elapsed,
+                checksum,
+            } => trace!(
+                "append rows ok: channel='{}' batch_id={} pushed_offset={} rows={} bytes={} elapsed={:?}{}",
+                channel,
+                batch_id,
+                offset,
+                rows,
+                bytes,
+                elapsed,
+                checksum.map_or_else(String::new, |c| format!(" checksum={c:016x}"))
+            ),
+            TelemetryEvent::ServerLatency { channel, latency } => debug!(
+                "channel '{}': snowflake processing latency {:?}",
+                channel, latency
+            ),
+            TelemetryEvent::RateLimitHeaders {
+                status, headers, ..
+            } => debug!(
+                status,
+                limit = headers.rate_limit_limit(),
+                remaining = headers.rate_limit_remaining(),
+                "response headers: {:?}",
+                headers.iter().collect::<Vec<_>>()
+            ),
+            TelemetryEvent::ClientInitialized { settings } => info!(
+                control_host = settings.control_host.as_deref(),
+                ingest_host = %settings.ingest_host,
+                auth_scheme = ?settings.auth_scheme,
+                auth_mode = ?settings.auth_mode,
+                api_version = ?settings.api_version,
+                retry_max_attempts = settings.retry.max_attempts(),
+                retry_initial_backoff_ms = settings.retry.initial_backoff().as_millis() as u64,
+                retry_max_backoff_ms = settings.retry.max_backoff().as_millis() as u64,
+                retry_multiplier = settings.retry.multiplier(),
+                retry_on_unauthorized = settings.retry_on_unauthorized,
+                jwt_refresh_margin_secs = settings.jwt_refresh_margin.map(|d| d.as_secs()),
+                scoped_token_refresh_margin_secs = settings.scoped_token_refresh_margin.as_secs(),
+                scoped_token_max_age_secs = settings.scoped_token_max_age.map(|d| d.as_secs()),
+                token_refresh_cooldown_secs = settings.token_refresh_cooldown.as_secs(),
+                refresh_jitter_secs = settings.refresh_jitter.map(|d| d.as_secs()),
+                features = %settings.features.join(","),
+                "client.initialized"
+            ),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{ApiVersion, ServerApiVersion, StreamingIngestClient, Warning};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct Appended {
+    request: wiremock::Request,
+    server_api_version: Option<ServerApiVersion>,
+    warnings: Vec<Warning>,
+}
+
+/// Appends two rows, with discovery reporting `capabilities` (and API
+/// version 2.1) when given.
+async fn append_two_rows(
+    api_version: Option<ApiVersion>,
+    capabilities: Option<&'static str>,
+) -> Appended {
+    let server = MockServer::start().await;
+    if let Some(capabilities) = capabilities {
+        Mock::given(method("GET"))
+            .and(path("/v2/streaming/hostname"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-Snowflake-Streaming-Api-Version", "2.1")
+                    .insert_header("X-Snowflake-Streaming-Capabilities", capabilities)
+                    .set_body_string(server.uri()),
+            )
+            .mount(&server)
+            .await;
+    }
+    mount_ingest_mocks(&server).await;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let mut config = token_config(&server.uri()).on_warning(move |warning| {
+        if matches!(warning, Warning::CapabilityUnavailable { .. }) {
+            sink.lock().unwrap().push(warning.clone());
+        }
+    });
+    config.api_version = api_version;
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_rows_iter([json!({"id": 1}), json!({"id": 2})])
+        .await
+        .expect("append");
+    let request = server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|r| r.url.path() == ROWS_PATH)
+        .expect("append request");
+    let warnings = warnings.lock().unwrap().clone();
+    Appended {
+        request,
+        server_api_version: client.server_api_version(),
+        warnings,
+    }
+}
+
+fn assert_tokens_in_query(request: &wiremock::Request) {
+    let query = request.url.query_pairs().collect::<Vec<_>>();
+    assert_eq!(query[0].0, "continuationToken");
+    assert_eq!(query[1], ("offsetToken".into(), "1".into()));
+    assert_eq!(request.body, b"{\"id\":1}\n{\"id\":2}");
+}
+
+#[tokio::test]
+async fn v2_sends_tokens_as_query_parameters() {
+    let appended = append_two_rows(None, None).await;
+    assert_tokens_in_query(&appended.request);
+    assert_eq!(appended.server_api_version, None);
+}
+
+#[tokio::test]
+async fn preview_sends_tokens_in_the_body() {
+    let appended = append_two_rows(Some(ApiVersion::Preview), Some("tokens-in-body")).await;
+    assert_eq!(appended.request.url.query(), None);
+    let body: serde_json::Value =
+        serde_json::from_slice(&appended.request.body).expect("JSON body");
+    assert_eq!(body["offset_token"], "1");
+    assert!(body["continuation_token"].is_string());
+    assert_eq!(body["rows"], json!([{"id": 1}, {"id": 2}]));
+    assert!(appended.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn preview_falls_back_when_the_server_lacks_the_capability() {
+    let appended = append_two_rows(Some(ApiVersion::Preview), Some("gzip")).await;
+    assert_tokens_in_query(&appended.request);
+    let version = ServerApiVersion { major: 2, minor: 1 };
+    assert_eq!(appended.server_api_version, Some(version));
+    assert_eq!(
+        appended.warnings,
+        [Warning::CapabilityUnavailable {
+            capability: "tokens-in-body",
+            server_version: version,
+        }]
+    );
+}
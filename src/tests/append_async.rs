@@ -0,0 +1,87 @@
+use crate::Error;
+use crate::tests::test_support::{
+    ROWS_PATH, client, mount_ingest_mocks, posted_bodies, token_config,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn queued_rows_are_sent_in_order_before_later_appends() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "1000"}}
+        })))
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let handles = (1..=5)
+        .map(|id| ch.append_row_async(&serde_json::json!({ "id": id })))
+        .collect::<Vec<_>>();
+    let mut offsets = Vec::new();
+    for handle in handles {
+        offsets.push(handle.await.expect("queued append"));
+    }
+    assert!(offsets.windows(2).all(|w| w[0] <= w[1]), "{offsets:?}");
+
+    // A synchronous append after fire-and-forget rows lands after them.
+    drop(ch.append_row_async(&serde_json::json!({"id": 6})));
+    ch.append_row(&serde_json::json!({"id": 7})).await.unwrap();
+
+    let ids = posted_bodies(&server)
+        .await
+        .iter()
+        .flat_map(|body| {
+            body.lines()
+                .map(|line| {
+                    serde_json::from_str::<serde_json::Value>(line).unwrap()["id"]
+                        .as_u64()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, (1..=7).collect::<Vec<_>>());
+
+    let report = ch.close().await.expect("close");
+    assert_eq!(report.rows, 7);
+}
+
+#[tokio::test]
+async fn background_failure_reaches_handles_and_flush() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let handle = ch.append_row_async(&serde_json::json!({"id": 1}));
+    let err = handle.await.unwrap_err();
+    assert!(matches!(err, Error::AppendFailed(_)), "{err:?}");
+    let err = ch.flush().await.unwrap_err();
+    assert!(matches!(err.root(), Error::Reqwest(_)), "{err:?}");
+    assert_eq!(
+        err.context().and_then(|c| c.operation()),
+        Some("append rows")
+    );
+
+    // The failure is reported once; the channel keeps working afterwards.
+    ch.flush().await.expect("nothing pending");
+    let offset = ch
+        .append_row_async(&serde_json::json!({"id": 2}))
+        .await
+        .expect("append after failure");
+    assert_eq!(offset, 1);
+}
@@ -0,0 +1,74 @@
+use crate::tests::test_support::{capture_logs, drain_logs, mount_ingest_mocks, token_config};
+use crate::{Error, LogPolicy, StreamingIngestClient};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Appends five single-row requests and closes the channel under `policy`,
+/// returning the INFO lines about appends.
+async fn append_logs(policy: LogPolicy) -> Vec<String> {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "1000"}}
+        })))
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.append_log_policy = Some(policy);
+
+    let (lines, guard) = capture_logs();
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    for id in 1..=5 {
+        ch.append_row(&serde_json::json!({ "id": id }))
+            .await
+            .expect("append");
+    }
+    ch.close().await.expect("close");
+    drop(guard);
+
+    drain_logs(lines)
+        .into_iter()
+        .filter(|line| line.contains(" INFO ") && line.contains("channel='ch'"))
+        .filter(|line| !line.contains("channel closed"))
+        .collect()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn append_logs_follow_the_configured_policy() {
+    assert!(append_logs(LogPolicy::Off).await.is_empty());
+    assert_eq!(append_logs(LogPolicy::Every).await.len(), 5);
+
+    let sampled = append_logs(LogPolicy::Sample(2)).await;
+    assert_eq!(sampled.len(), 3, "{sampled:?}");
+    assert!(sampled[1].contains("pushed_offset=3"), "{sampled:?}");
+
+    // The window outlasts the test, so the only summary is the one at close.
+    let aggregated = append_logs(LogPolicy::Aggregate(3600)).await;
+    assert_eq!(aggregated.len(), 1, "{aggregated:?}");
+    assert!(
+        aggregated[0].contains("requests=5 rows=5") && aggregated[0].contains("last_offset=5"),
+        "{aggregated:?}"
+    );
+}
+
+#[tokio::test]
+async fn zero_interval_is_rejected() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.append_log_policy = Some(LogPolicy::Sample(0));
+    let err =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .err()
+            .expect("sample interval of 0 is invalid");
+    assert!(matches!(err, Error::Config(_)), "{err}");
+}
@@ -0,0 +1,52 @@
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{AuthScheme, Error, StreamingIngestClient};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn programmatic_access_token_is_sent_with_its_token_type() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.jwt_token = None;
+    cfg.programmatic_access_token = Some("my-pat".into());
+
+    StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client construction should succeed");
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let control = requests
+        .iter()
+        .filter(|r| matches!(r.url.path(), "/v2/streaming/hostname" | "/oauth/token"))
+        .collect::<Vec<_>>();
+    assert_eq!(control.len(), 2);
+    for req in control {
+        assert_eq!(req.headers.get("authorization").unwrap(), "Bearer my-pat");
+        assert_eq!(
+            req.headers
+                .get("x-snowflake-authorization-token-type")
+                .unwrap(),
+            "PROGRAMMATIC_ACCESS_TOKEN"
+        );
+    }
+}
+
+#[tokio::test]
+async fn schemes_without_their_token_are_rejected() {
+    let mut cfg = token_config("https://example.invalid");
+    cfg.auth_scheme = Some(AuthScheme::ProgrammaticAccessToken);
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("missing PAT should be rejected");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("programmatic_access_token")));
+
+    let mut cfg = token_config("https://example.invalid");
+    cfg.jwt_token = None;
+    cfg.auth_scheme = Some(AuthScheme::Oauth);
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("missing OAuth token should be rejected");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("oauth")));
+}
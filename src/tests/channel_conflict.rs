@@ -0,0 +1,148 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::config::MAX_OPEN_CONFLICT_RETRIES;
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Config, Error, OperationKind, StreamingIngestClient};
+
+const CHANNEL_PATH: &str = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+
+const CONFLICT_BODY: &str = r#"{"code":"CHANNEL_OPEN_IN_PROGRESS","message":"channel is being opened","client_id":"replica-2","opened_at":"2026-10-17T09:00:00Z"}"#;
+
+/// Answers the first `conflicts` opens with a 409, then opens the channel;
+/// returns the number of open requests received.
+async fn mount_conflicts(server: &MockServer, conflicts: usize) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    Mock::given(method("PUT"))
+        .and(path(CHANNEL_PATH))
+        .respond_with(move |_req: &Request| {
+            if counter.fetch_add(1, Ordering::SeqCst) < conflicts {
+                ResponseTemplate::new(409).set_body_string(CONFLICT_BODY)
+            } else {
+                ResponseTemplate::new(200).set_body_string(include_str!(
+                    "../../tests/fixtures/open_channel_response.json"
+                ))
+            }
+        })
+        .with_priority(1)
+        .mount(server)
+        .await;
+    mount_ingest_mocks(server).await;
+    calls
+}
+
+#[tokio::test]
+async fn conflicting_open_is_retried() {
+    let server = MockServer::start().await;
+    let calls = mount_conflicts(&server, 2).await;
+    let retries = Arc::new(Mutex::new(Vec::new()));
+    let sink = retries.clone();
+    let config = token_config(&server.uri()).on_retry(move |retry| {
+        sink.lock().unwrap().push((
+            retry.operation,
+            retry.attempt,
+            matches!(retry.error, Error::ChannelConflict(_)),
+        ));
+    });
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+
+    client
+        .open_channel("ch")
+        .await
+        .expect("open succeeds once the other open finishes");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        *retries.lock().unwrap(),
+        [
+            (OperationKind::OpenChannel, 1, true),
+            (OperationKind::OpenChannel, 2, true)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn persistent_conflict_reports_the_other_opener() {
+    let server = MockServer::start().await;
+    let calls = mount_conflicts(&server, usize::MAX).await;
+    let config = Config {
+        channel_open_conflict_retries: Some(1),
+        ..token_config(&server.uri())
+    };
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+
+    let Err(err) = client.open_channel("ch").await else {
+        panic!("open should conflict");
+    };
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(err.is_retryable());
+    let Error::ChannelConflict(conflict) = err.root() else {
+        panic!("expected a channel conflict, got {err:?}");
+    };
+    assert_eq!(conflict.channel, "ch");
+    assert_eq!(conflict.message, "channel is being opened");
+    assert_eq!(conflict.opener["client_id"], "replica-2");
+    assert!(!conflict.opener.contains_key("code"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn conflict_backoff_is_capped() {
+    let server = MockServer::start().await;
+    let calls = mount_conflicts(&server, usize::MAX).await;
+    let delays = Arc::new(Mutex::new(Vec::new()));
+    let sink = delays.clone();
+    let config = Config {
+        channel_open_conflict_retries: Some(MAX_OPEN_CONFLICT_RETRIES),
+        ..token_config(&server.uri())
+    }
+    .on_retry(move |retry| sink.lock().unwrap().push(retry.delay));
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+
+    let open = tokio::spawn(async move { client.open_channel("ch").await.err() });
+    while !open.is_finished() {
+        tokio::time::advance(Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+    }
+    let err = open.await.unwrap().expect("open conflicts");
+    assert!(matches!(err.root(), Error::ChannelConflict(_)), "{err:?}");
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        MAX_OPEN_CONFLICT_RETRIES as usize + 1
+    );
+    let delays = delays.lock().unwrap();
+    assert_eq!(delays.len(), MAX_OPEN_CONFLICT_RETRIES as usize);
+    assert!(
+        delays.iter().all(|delay| *delay <= Duration::from_secs(10)),
+        "{delays:?}"
+    );
+}
+
+#[tokio::test]
+async fn absurd_conflict_retry_counts_are_rejected() {
+    let config = Config {
+        channel_open_conflict_retries: Some(MAX_OPEN_CONFLICT_RETRIES + 1),
+        ..token_config("https://example.invalid")
+    };
+    let issues = config.validate().unwrap_err();
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.field == "channel_open_conflict_retries"),
+        "{issues:?}"
+    );
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .err()
+        .expect("too many retries");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("channel_open_conflict_retries")));
+}
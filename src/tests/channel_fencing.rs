@@ -0,0 +1,75 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::Error;
+use crate::tests::test_support::{ROWS_PATH, client, mount_ingest_mocks, token_config};
+
+const FENCED_BODY: &str =
+    r#"{"code": "STALE_CONTINUATION_TOKEN_SEQUENCER", "message": "Channel sequencer is stale"}"#;
+
+async fn fenced_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(400).set_body_string(FENCED_BODY))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    server
+}
+
+#[tokio::test]
+async fn fenced_append_is_typed_error() {
+    let server = fenced_server().await;
+    let mut client = client::<u64>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch.append_row(&1).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::ChannelFenced(name) if name == "ch"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn fenced_append_reopens_with_suffix() {
+    let server = fenced_server().await;
+    let mut cfg = token_config(&server.uri());
+    cfg.fenced_channel_suffix = Some("replica-a".into());
+    let mut client = client::<u64>(cfg).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_row(&1)
+        .await
+        .expect("append retried on new channel");
+    assert_eq!(ch.channel_name(), "ch_replica-a_1");
+
+    let requests = server.received_requests().await.unwrap();
+    let reopened = requests
+        .iter()
+        .any(|r| r.method.as_str() == "PUT" && r.url.path().ends_with("/channels/ch_replica-a_1"));
+    assert!(
+        reopened,
+        "expected an open request for the suffixed channel"
+    );
+}
+
+#[tokio::test]
+async fn other_bad_requests_keep_status_and_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(400).set_body_string("bad row"))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<u64>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch.append_row(&1).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::Http(status, body) if status.as_u16() == 400 && body == "bad row")
+    );
+}
@@ -0,0 +1,100 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::OpenChannelOptions;
+use crate::tests::test_support::{
+    ROWS_PATH, client, mount_ingest_mocks, requests_to, rows_requests, token_config,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+#[tokio::test]
+async fn flush_interval_batches_rows_queued_over_time() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let options = OpenChannelOptions::new().flush_interval(Duration::from_millis(500));
+    let mut ch = client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel");
+
+    let mut handles = Vec::new();
+    for id in 1..=3 {
+        handles.push(ch.append_row_async(&serde_json::json!({ "id": id })));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    for handle in handles {
+        handle.await.expect("queued append");
+    }
+    assert_eq!(rows_requests(&server).await, 1);
+}
+
+#[tokio::test]
+async fn batch_limits_apply_to_the_channel_only() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let options = OpenChannelOptions::new().max_rows_per_request(NonZeroUsize::new(2).unwrap());
+    let mut limited = client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel");
+    let rows = (1..=5)
+        .map(|id| serde_json::json!({ "id": id }))
+        .collect::<Vec<_>>();
+    limited
+        .append_rows(&mut rows.clone().into_iter())
+        .await
+        .expect("append");
+    assert_eq!(rows_requests(&server).await, 3);
+
+    let mut default = client.open_channel("other").await.expect("open channel");
+    default
+        .append_rows(&mut rows.clone().into_iter())
+        .await
+        .expect("append");
+    assert_eq!(rows_requests(&server).await, 3);
+    let other_rows = ROWS_PATH.replace("/channels/ch/", "/channels/other/");
+    assert_eq!(requests_to(&server, &other_rows).await, 1);
+}
+
+#[tokio::test]
+async fn rate_limit_backoff_overrides_the_client_delay() {
+    let server = MockServer::start().await;
+    let throttled = Arc::new(AtomicBool::new(false));
+    let flag = throttled.clone();
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(move |_req: &Request| {
+            if flag.swap(true, Ordering::SeqCst) {
+                ResponseTemplate::new(200).set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+            } else {
+                ResponseTemplate::new(429)
+            }
+        })
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let options = OpenChannelOptions::new().rate_limit_backoff(Duration::from_millis(10));
+    let mut ch = client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel");
+
+    let started = std::time::Instant::now();
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append succeeds after one 429");
+    assert!(throttled.load(Ordering::SeqCst));
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "{:?}",
+        started.elapsed()
+    );
+}
@@ -0,0 +1,160 @@
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Error, Ordering, StreamingIngestClient};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// Reports every requested channel as fully committed.
+async fn mount_committed_status(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(|req: &Request| {
+            let body: serde_json::Value = req.body_json().unwrap();
+            let statuses = body["channel_names"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|name| {
+                    let name = name.as_str().unwrap().to_string();
+                    (
+                        name,
+                        serde_json::json!({"last_committed_offset_token": "1000"}),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>();
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": statuses
+            }))
+        })
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn relaxed_pool_spreads_rows_across_channels() {
+    let server = MockServer::start().await;
+    mount_committed_status(&server).await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+
+    let mut pool = client
+        .open_channel_pool("ch", 3, Ordering::Relaxed)
+        .await
+        .expect("open pool");
+    assert_eq!(pool.channels().len(), 3);
+    pool.append_rows_iter((0..9).map(|id| serde_json::json!({ "id": id })))
+        .await
+        .expect("append");
+
+    let reports = pool.close().await.expect("close pool");
+    assert_eq!(
+        reports.iter().map(|r| r.rows).collect::<Vec<_>>(),
+        vec![3, 3, 3]
+    );
+    let requests = server.received_requests().await.unwrap_or_default();
+    for index in 0..3 {
+        let rows_path = format!(
+            "/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/ch_{index}/rows"
+        );
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|r| r.url.path() == rows_path)
+                .count(),
+            1,
+            "channel ch_{index} should receive one request"
+        );
+    }
+}
+
+#[tokio::test]
+async fn strict_pool_is_limited_to_one_channel() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+
+    let err = client
+        .open_channel_pool("ch", 2, Ordering::Strict)
+        .await
+        .err()
+        .expect("strict pools hold one channel");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("strict ordering")));
+
+    let pool = client
+        .open_channel_pool("ch", 1, Ordering::Strict)
+        .await
+        .expect("single-channel strict pool");
+    assert_eq!(pool.ordering(), Ordering::Strict);
+}
+
+#[tokio::test]
+async fn keyed_rows_stay_on_one_channel_in_order() {
+    let server = MockServer::start().await;
+    mount_committed_status(&server).await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+    let mut pool = client
+        .open_channel_pool("ch", 3, Ordering::Relaxed)
+        .await
+        .expect("open pool");
+
+    let event = |seq: u64| serde_json::json!({ "pk": seq % 5, "seq": seq });
+    pool.append_rows_keyed((0..20).map(event), |row| row["pk"].as_u64())
+        .await
+        .expect("keyed batch");
+    for seq in 20..25 {
+        let row = event(seq);
+        pool.append_row_keyed(&row["pk"].as_u64(), &row)
+            .await
+            .expect("keyed row");
+    }
+
+    let mut seen: std::collections::HashMap<u64, (String, u64)> = Default::default();
+    for request in server.received_requests().await.unwrap() {
+        let path = request.url.path().to_string();
+        if !path.ends_with("/rows") {
+            continue;
+        }
+        for line in String::from_utf8_lossy(&request.body).lines() {
+            let row: serde_json::Value = serde_json::from_str(line).unwrap();
+            let (pk, seq) = (row["pk"].as_u64().unwrap(), row["seq"].as_u64().unwrap());
+            if let Some((channel, last)) = seen.get(&pk) {
+                assert_eq!(channel, &path, "key {pk} moved between channels");
+                assert!(seq > *last, "key {pk} out of order");
+            }
+            seen.insert(pk, (path.clone(), seq));
+        }
+    }
+    assert_eq!(seen.len(), 5);
+    assert!(seen.values().all(|(_, last)| *last >= 20));
+    let channels: std::collections::HashSet<_> = seen.values().map(|(c, _)| c).collect();
+    assert!(channels.len() > 1, "keys should spread across channels");
+
+    let reports = pool.close().await.expect("close pool");
+    assert_eq!(reports.iter().map(|r| r.rows).sum::<u64>(), 25);
+}
@@ -0,0 +1,56 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{capture_logs, drain_logs, mount_ingest_mocks, token_config};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+#[tokio::test]
+async fn channel_requests_log_inside_the_channel_span() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "2"}}
+        })))
+        .mount(&server)
+        .await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+
+    let (lines, guard) = capture_logs();
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+    drop(ch.append_row_async(&2));
+    ch.close().await.expect("close");
+    drop(guard);
+    let logs = drain_logs(lines);
+
+    let span = "channel{db=db schema=schema pipe=pipe channel=ch}";
+    assert!(
+        logs.iter()
+            .any(|l| l.contains(span) && l.contains("channel opened")),
+        "{logs:#?}"
+    );
+    for child in [
+        "append_rows",
+        "append_async",
+        "channel_status",
+        "close_channel",
+    ] {
+        assert!(
+            logs.iter().any(|l| l.contains(&format!("{span}:{child}"))),
+            "no log in {child}: {logs:#?}"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use crate::chaos::{ChaosConfig, ScriptedTokenProvider};
+use crate::tests::test_support::{
+    client, mount_ingest_mocks, requests_to, rows_requests, token_config,
+};
+use crate::{Error, OperationKind, StreamingIngestClient};
+use reqwest::StatusCode;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CHANNEL_PATH: &str = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+
+async fn chaos_client(
+    server: &MockServer,
+    chaos: ChaosConfig,
+) -> StreamingIngestClient<serde_json::Value> {
+    client(token_config(&server.uri())).await.with_chaos(chaos)
+}
+
+#[tokio::test]
+async fn injected_failures_and_drops_are_not_sent() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = chaos_client(&server, ChaosConfig::new(1).fail_appends(1.0)).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let err = ch
+        .append_row(&serde_json::json!({"id": 1}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Http(StatusCode::SERVICE_UNAVAILABLE, _)),
+        "unexpected error: {err:?}"
+    );
+
+    let mut client = client.with_chaos(ChaosConfig::new(1).drop_connections(1.0));
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let err = ch
+        .append_row(&serde_json::json!({"id": 1}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err.root(), Error::Io(e) if e.kind() == std::io::ErrorKind::ConnectionReset),
+        "unexpected error: {err:?}"
+    );
+
+    assert_eq!(rows_requests(&server).await, 0);
+}
+
+#[tokio::test]
+async fn same_seed_fails_the_same_appends() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut outcomes = Vec::new();
+    for _ in 0..2 {
+        let mut client = chaos_client(&server, ChaosConfig::new(99).fail_appends(0.5)).await;
+        let mut ch = client.open_channel("ch").await.expect("open channel");
+        let mut run = Vec::new();
+        for id in 0..20 {
+            run.push(
+                ch.append_row(&serde_json::json!({ "id": id }))
+                    .await
+                    .is_ok(),
+            );
+        }
+        outcomes.push(run);
+    }
+    assert_eq!(outcomes[0], outcomes[1]);
+    let successes = outcomes[0].iter().filter(|ok| **ok).count();
+    assert!(
+        (1..20).contains(&successes),
+        "expected a mix of outcomes, got {successes} successes"
+    );
+    assert_eq!(rows_requests(&server).await, successes * 2);
+}
+
+#[tokio::test]
+async fn selected_operations_are_faulted_instead_of_appends() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path(CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let failing = |operation| {
+        ChaosConfig::new(1)
+            .fail_appends(1.0)
+            .operations([operation])
+    };
+    let unavailable =
+        |err: &Error| matches!(err.root(), Error::Http(StatusCode::SERVICE_UNAVAILABLE, _));
+
+    // The 401 makes the client exchange a new scoped token, which fails.
+    let mut client = chaos_client(&server, failing(OperationKind::ExchangeScopedToken)).await;
+    let err = client.open_channel("ch").await.err().unwrap();
+    assert!(unavailable(&err), "unexpected error: {err:?}");
+    assert_eq!(requests_to(&server, "/oauth/token").await, 1);
+
+    let mut client = client.with_chaos(failing(OperationKind::OpenChannel));
+    let err = client.open_channel("ch").await.err().unwrap();
+    assert!(unavailable(&err), "unexpected error: {err:?}");
+    assert_eq!(requests_to(&server, CHANNEL_PATH).await, 1);
+
+    let mut client = client.with_chaos(failing(OperationKind::ChannelStatus));
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("appends are not selected");
+    let err = ch.close().await.unwrap_err();
+    assert!(unavailable(&err), "unexpected error: {err:?}");
+}
+
+#[tokio::test]
+async fn scripted_provider_fails_construction_until_it_recovers() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut config = token_config(&server.uri());
+    config.ingest_host = Some(server.uri());
+    let provider = ScriptedTokenProvider::failing_then(2, "recovered");
+
+    let mut attempts = 0;
+    let mut client = loop {
+        attempts += 1;
+        match StreamingIngestClient::<serde_json::Value>::new_with_token_provider(
+            "client",
+            "db",
+            "schema",
+            "pipe",
+            config.clone(),
+            Arc::new(provider.clone()),
+        )
+        .await
+        {
+            Ok(client) => break client,
+            Err(err) => assert!(matches!(err.root(), Error::Auth(_)), "{err:?}"),
+        }
+    };
+    assert_eq!((attempts, provider.calls()), (3, 3));
+    let mut ch = client.open_channel("ch").await.expect("open");
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append with the recovered token");
+}
@@ -0,0 +1,89 @@
+use std::sync::{Arc, Mutex};
+
+use wiremock::MockServer;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::StreamingIngestClient;
+use crate::telemetry::{TelemetryEvent, TelemetrySink};
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<TelemetryEvent>>,
+}
+
+impl TelemetrySink for RecordingSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+#[tokio::test]
+async fn batch_checksum_is_reported_and_written_to_rows() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut config = token_config(&server.uri());
+    config.batch_id_column = Some("BATCH_ID".into());
+    config.checksum_column = Some("BATCH_SUM".into());
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        config,
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let rows = [serde_json::json!({"id": 1}), serde_json::json!({"id": 2})];
+    ch.append_rows_iter(rows.clone()).await.expect("append");
+
+    let expected = xxh3_64(b"{\"id\":1}\n{\"id\":2}");
+    let report = ch.last_append().expect("append report").clone();
+    assert_eq!(report.checksum, Some(expected));
+    assert!(sink.events.lock().unwrap().iter().any(|event| matches!(
+        event,
+        TelemetryEvent::AppendCompleted { checksum, .. } if *checksum == Some(expected)
+    )));
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let body = requests
+        .iter()
+        .find(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body.clone()).unwrap())
+        .expect("append request");
+    let sent = body
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let checksum = format!("{expected:016x}");
+    for row in &sent {
+        assert_eq!(row["BATCH_SUM"], checksum.as_str());
+        assert_eq!(row["BATCH_ID"], report.batch_id.to_string().as_str());
+    }
+    assert_eq!(
+        ch.estimate_serialized_size(&rows[0]).unwrap(),
+        body.lines().next().unwrap().len()
+    );
+}
+
+#[tokio::test]
+async fn checksums_are_off_by_default() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+    assert_eq!(ch.last_append().unwrap().checksum, None);
+}
@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::Engine as _;
+use jiff::{SignedDuration, Timestamp};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use super::test_support::{base_config, mount_ingest_mocks};
+use crate::{StreamingIngestClient, Warning};
+
+/// `iat` claim (epoch seconds) of the JWT in a request's bearer token.
+fn jwt_iat(request: &wiremock::Request) -> i64 {
+    let auth = request
+        .headers
+        .get("authorization")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let payload = auth
+        .trim_start_matches("Bearer ")
+        .split('.')
+        .nth(1)
+        .unwrap();
+    let claims: serde_json::Value = serde_json::from_slice(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(payload)
+            .unwrap(),
+    )
+    .unwrap();
+    claims["iat"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn skewed_date_header_shifts_jwt_times() {
+    let server = MockServer::start().await;
+    let ahead = Timestamp::now() + SignedDuration::from_secs(600);
+    let date = jiff::fmt::rfc2822::DateTimePrinter::new()
+        .timestamp_to_rfc9110_string(&ahead)
+        .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(server.uri())
+                .insert_header("date", date.as_str()),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let mut config = base_config(&server.uri())
+        .on_warning(move |warning| sink.lock().unwrap().push(warning.clone()));
+    config.clock_skew_check = Some(true);
+    let client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client construction should succeed");
+
+    let skew = client.clock_skew().expect("skew measured");
+    assert!((595..=600).contains(&skew.as_secs()), "{skew:?}");
+    assert!(warnings.lock().unwrap().iter().any(|w| matches!(
+        w,
+        Warning::ClockSkew { threshold, .. } if *threshold == Duration::from_secs(30)
+    )));
+
+    // The scoped token exchange carries a JWT issued in Snowflake's time.
+    let requests = server.received_requests().await.unwrap();
+    let exchange = requests
+        .iter()
+        .find(|r| r.url.path() == "/oauth/token")
+        .unwrap();
+    let drift = jwt_iat(exchange) - Timestamp::now().as_second();
+    assert!((590..=601).contains(&drift), "{drift}");
+}
+
+#[tokio::test]
+async fn skew_check_is_opt_in() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        base_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+    assert_eq!(client.clock_skew(), None);
+}
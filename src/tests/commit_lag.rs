@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{Config, StreamingIngestClient};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+#[tokio::test]
+async fn commit_lag_alert_fires_once_and_clears_after_commit() {
+    let server = MockServer::start().await;
+    let channel_path = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path(channel_path))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/open_channel_response.json"
+        )))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/ch/rows",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .mount(&server)
+        .await;
+    // First poll reports nothing committed; later polls report both offsets.
+    let polls = Arc::new(AtomicUsize::new(0));
+    let polls_clone = polls.clone();
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(move |_req: &Request| {
+            let committed = if polls_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                "0"
+            } else {
+                "2"
+            };
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": {"ch": {"last_committed_offset_token": committed}}
+            }))
+        })
+        .mount(&server)
+        .await;
+
+    let cfg = Config::from_values(
+        "user",
+        None,
+        "acct",
+        server.uri(),
+        Some("jwt".into()),
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    assert_eq!(ch.commit_lag(), Duration::ZERO);
+
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let alerts_clone = alerts.clone();
+    ch.set_commit_lag_alert(Duration::from_millis(20), move |name, lag| {
+        alerts_clone.lock().unwrap().push((name.to_string(), lag));
+    });
+
+    ch.append_row(&serde_json::json!({"id": 1})).await.unwrap();
+    ch.append_row(&serde_json::json!({"id": 2})).await.unwrap();
+    assert!(alerts.lock().unwrap().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(ch.get_latest_committed_offset_token().await, 0);
+    assert!(ch.commit_lag() >= Duration::from_millis(30));
+    {
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 1, "alert should fire once: {alerts:?}");
+        assert_eq!(alerts[0].0, "ch");
+        assert!(alerts[0].1 > Duration::from_millis(20));
+    }
+
+    assert_eq!(ch.get_latest_committed_offset_token().await, 2);
+    assert_eq!(ch.commit_lag(), Duration::ZERO);
+    assert_eq!(alerts.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{AckTracker, CommitProgress, CommitWaitStrategy, StreamingIngestClient};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+#[derive(Default)]
+struct Recording {
+    seen: Mutex<Vec<CommitProgress>>,
+}
+
+impl CommitWaitStrategy for Arc<Recording> {
+    fn next_poll(&self, progress: &CommitProgress) -> Duration {
+        self.seen.lock().unwrap().push(*progress);
+        Duration::from_millis(10)
+    }
+}
+
+#[tokio::test]
+async fn ack_tracker_waits_at_the_strategy_pace() {
+    let server = MockServer::start().await;
+    let polls = Arc::new(AtomicUsize::new(0));
+    let counter = polls.clone();
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(move |_req: &Request| {
+            let token = match counter.fetch_add(1, Ordering::SeqCst) < 3 {
+                true => "0",
+                false => "1",
+            };
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": { "ch": { "last_committed_offset_token": token } }
+            }))
+        })
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let recording = Arc::new(Recording::default());
+    let config = token_config(&server.uri()).commit_wait_strategy(recording.clone());
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+
+    let mut acks = AckTracker::new();
+    acks.track(1, "receipt");
+    let released = acks
+        .wait_and_release(&mut ch, Duration::from_secs(5))
+        .await
+        .expect("wait");
+    assert_eq!(released, ["receipt"]);
+    assert_eq!(polls.load(Ordering::SeqCst), 4);
+
+    let seen = recording.seen.lock().unwrap().clone();
+    let unchanged = seen.iter().map(|p| p.unchanged_polls).collect::<Vec<_>>();
+    assert_eq!(unchanged, [1, 2, 3]);
+    assert!(seen.iter().all(|p| p.uncommitted_offsets == 1), "{seen:?}");
+
+    ch.close().await.expect("close");
+    assert_eq!(
+        recording.seen.lock().unwrap().len(),
+        3,
+        "nothing to wait for"
+    );
+}
@@ -0,0 +1,65 @@
+use std::num::NonZeroUsize;
+
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::DedupWindow;
+use crate::tests::test_support::{
+    ROWS_PATH, client, mount_ingest_mocks, posted_bodies, token_config,
+};
+
+fn window() -> DedupWindow {
+    DedupWindow::by_column("id", NonZeroUsize::new(100).unwrap())
+}
+
+#[tokio::test]
+async fn rows_seen_in_the_window_are_dropped() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_dedup_window(Some(window()));
+
+    ch.append_rows_iter([json!({"id": 1}), json!({"id": 2}), json!({"id": 1})])
+        .await
+        .unwrap();
+    ch.append_rows_iter([json!({"id": 2}), json!({"id": 3}), json!({"v": "no key"})])
+        .await
+        .unwrap();
+    ch.append_row(&json!({"id": 3})).await.unwrap();
+    ch.append_rows_iter([json!({"id": 1})]).await.unwrap();
+
+    assert_eq!(
+        posted_bodies(&server).await,
+        ["{\"id\":1}\n{\"id\":2}", "{\"id\":3}\n{\"v\":\"no key\"}"]
+    );
+    let window = ch.dedup_window().unwrap();
+    assert_eq!(window.dropped(), 4);
+    assert_eq!(window.len(), 3);
+}
+
+#[tokio::test]
+async fn rows_of_a_failed_append_are_sent_again() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(400).set_body_string("rejected"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_dedup_window(Some(window()));
+
+    let rows = [json!({"id": 1}), json!({"id": 2})];
+    ch.append_rows_iter(rows.clone()).await.unwrap_err();
+    ch.append_rows_iter(rows).await.expect("retry");
+
+    let bodies = posted_bodies(&server).await;
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(bodies[0], bodies[1]);
+    assert_eq!(ch.dedup_window().unwrap().dropped(), 0);
+}
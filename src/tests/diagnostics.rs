@@ -0,0 +1,61 @@
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn bundle_reports_state_without_secrets() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut cfg = token_config(&server.uri());
+    cfg.jwt_token = None;
+    cfg.programmatic_access_token = Some("super-secret-pat".into());
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append succeeds after scoped token refresh");
+
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(500))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    ch.append_row(&serde_json::json!({"id": 2}))
+        .await
+        .expect_err("server error");
+
+    let bundle = client.diagnostics().await;
+    let text = bundle.to_string();
+    assert!(!text.contains("super-secret-pat"), "{text}");
+    assert!(!text.contains("scoped-token"), "{text}");
+
+    assert_eq!(bundle["config"]["programmatic_access_token"], true);
+    assert_eq!(bundle["config"]["auth_scheme"], "PROGRAMMATIC_ACCESS_TOKEN");
+    assert_eq!(bundle["hosts"]["ingest"], server.uri());
+    assert_eq!(
+        bundle["channels"]["ch"]["last_pushed_offset_token"],
+        serde_json::json!(1)
+    );
+    let retries = bundle["recent_retries"].as_array().unwrap();
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0]["token"], "scoped");
+    assert_eq!(retries[0]["status"], 401);
+    assert_eq!(retries[0]["outcome"], "retried");
+    let errors = bundle["recent_errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["context"], "append");
+    assert!(errors[0]["error"].as_str().unwrap().contains("500"));
+}
@@ -0,0 +1,74 @@
+use crate::{DryRunChannel, Error};
+
+#[tokio::test]
+async fn dry_run_chunks_and_accounts_like_a_real_channel() {
+    let row = "x".repeat(1024 * 1024);
+    let mut channel = DryRunChannel::<String>::new("ch");
+    channel.append_row(&"first".to_string()).await.unwrap();
+    // 1MB rows are chunked 7 to a request to stay under 16MB.
+    let bytes = channel
+        .append_rows_iter(vec![row.clone(); 10])
+        .await
+        .unwrap();
+
+    let requests = channel.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests.iter().map(|r| r.offset).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(requests[0].body.as_ref(), b"\"first\"");
+    assert_eq!((requests[1].rows, requests[2].rows), (7, 3));
+    assert_eq!(requests[2].body.iter().filter(|b| **b == b'\n').count(), 2);
+    assert_eq!(bytes, requests[1].body.len() + requests[2].body.len());
+
+    let too_large = "x".repeat(17 * 1024 * 1024);
+    let err = channel.append_row(&too_large).await.unwrap_err();
+    assert!(matches!(err, Error::DataTooLarge(..)));
+
+    let report = channel.close().await.unwrap();
+    assert_eq!((report.rows, report.requests), (11, 3));
+    assert_eq!(report.bytes, 7 + bytes as u64);
+}
+
+#[tokio::test]
+async fn dry_run_writes_ndjson_to_file() {
+    let path = std::env::temp_dir().join(format!("dry-run-{}.ndjson", std::process::id()));
+    let mut channel = DryRunChannel::to_file("ch", &path).unwrap();
+    channel
+        .append_rows_iter((1..=3).map(|id| serde_json::json!({ "id": id })))
+        .await
+        .unwrap();
+    channel
+        .append_row(&serde_json::json!({ "id": 4 }))
+        .await
+        .unwrap();
+    assert!(channel.requests().is_empty());
+    let report = channel.close().await.unwrap();
+    assert_eq!((report.rows, report.requests), (4, 2));
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written, "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n{\"id\":4}\n");
+}
+
+#[tokio::test]
+async fn dry_run_reuses_serialization_buffer_across_batches() {
+    let path = std::env::temp_dir().join(format!("dry-run-reuse-{}.ndjson", std::process::id()));
+    let mut channel = DryRunChannel::to_file("ch", &path).unwrap();
+    channel.reserve_batch_capacity(1024);
+    channel.set_max_rows_per_request(std::num::NonZeroUsize::new(2));
+    for batch in 0..3 {
+        let rows = (0..3).map(|i| serde_json::json!({ "batch": batch, "i": i }));
+        assert_eq!(channel.append_rows_iter(rows).await.unwrap(), 52);
+    }
+    let report = channel.close().await.unwrap();
+    assert_eq!((report.rows, report.requests), (9, 6));
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let expected = (0..3)
+        .flat_map(|batch| (0..3).map(move |i| format!("{{\"batch\":{batch},\"i\":{i}}}\n")))
+        .collect::<String>();
+    assert_eq!(written, expected);
+}
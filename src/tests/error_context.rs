@@ -0,0 +1,101 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Config, Error, OperationKind, RetryPlan, StreamingIngestClient};
+
+#[tokio::test]
+async fn connection_failures_name_the_step_channel_and_attempt() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_host = format!("http://{}", closed.local_addr().unwrap());
+    drop(closed);
+    let mut cfg = token_config(&server.uri());
+    cfg.ingest_host = Some(closed_host.clone());
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client");
+
+    let err = client.open_channel("ch").await.err().expect("refused");
+    assert!(matches!(err.root(), Error::Reqwest(_)), "{err:?}");
+    let context = err.context().expect("context");
+    assert_eq!(context.operation(), Some("open channel"));
+    assert_eq!(context.channel(), Some("ch"));
+    assert_eq!(context.attempt(), Some(1));
+    assert!(
+        err.to_string().starts_with(&format!(
+            "open channel on channel 'ch' failed (attempt 1) at {closed_host}/v2/streaming/"
+        )),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn continuation_tokens_are_scrubbed_from_context_urls() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch
+        .append_row(&serde_json::json!({"id": 1}))
+        .await
+        .unwrap_err();
+    let context = err.context().expect("context");
+    assert_eq!(context.operation(), Some("append rows"));
+    let url = context.url().expect("url");
+    assert!(url.contains("continuationToken=REDACTED"), "{url}");
+    assert!(!url.contains("ctok-1"), "{url}");
+    assert!(url.contains("offsetToken=1"), "{url}");
+    assert!(!err.to_string().contains("ctok-1"), "{err}");
+}
+
+#[tokio::test]
+async fn continuation_tokens_are_scrubbed_from_connection_failures() {
+    let server = MockServer::builder().start().await;
+    mount_ingest_mocks(&server).await;
+    let cfg = Config {
+        retry: Some(RetryPlan::none()),
+        ..token_config(&server.uri())
+    };
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    drop(server);
+
+    let err = ch
+        .append_row(&serde_json::json!({"id": 1}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err.root(), Error::Reqwest(_)), "{err:?}");
+    let url = err.context().and_then(|c| c.url()).expect("url");
+    assert!(url.contains("continuationToken=REDACTED"), "{url}");
+    let message = err.to_string();
+    assert!(!message.contains("ctok-1"), "{message}");
+    assert!(!format!("{err:?}").contains("ctok-1"), "{err:?}");
+}
+
+#[tokio::test]
+async fn non_transport_errors_are_not_wrapped() {
+    let err = Error::Config("bad".into()).in_operation(OperationKind::OpenChannel, Some("ch"));
+    assert!(matches!(err, Error::Config(_)));
+    assert!(err.context().is_none());
+}
@@ -0,0 +1,31 @@
+use wiremock::MockServer;
+
+use crate::tests::test_support::{mount_ingest_mocks, pushed_offsets, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[tokio::test]
+async fn batches_carry_the_given_offset_token() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.max_rows_per_request = Some(3);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_rows_at(40, [38, 39, 40]).await.expect("append");
+    ch.append_row(&41).await.expect("append");
+    assert_eq!(pushed_offsets(&server).await, ["40", "41"]);
+
+    assert!(matches!(
+        ch.append_rows_at(41, [41]).await,
+        Err(Error::Config(_))
+    ));
+    assert!(matches!(
+        ch.append_rows_at(50, [42, 43, 44, 45]).await,
+        Err(Error::Config(_))
+    ));
+    assert_eq!(ch.append_rows_at(50, []).await.expect("empty batch"), 0);
+    assert_eq!(pushed_offsets(&server).await.len(), 2);
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[tokio::test]
+async fn default_provider_and_channel_headers_reach_every_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .and(header("x-gateway-key", "client-key"))
+        .and(header("x-request-id", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .and(header("x-gateway-key", "channel-key"))
+        .and(header("user-agent", "custom-agent"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let requests = counter.clone();
+    let mut cfg = token_config(&server.uri());
+    cfg.default_headers = Some(
+        [
+            ("X-Gateway-Key".to_string(), "client-key".to_string()),
+            ("User-Agent".to_string(), "custom-agent".to_string()),
+        ]
+        .into(),
+    );
+    let cfg = cfg.header_provider(move || {
+        let id = requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from(id));
+        headers
+    });
+
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+    let mut overrides = HeaderMap::new();
+    overrides.insert("x-gateway-key", HeaderValue::from_static("channel-key"));
+    ch.set_extra_headers(overrides);
+    ch.append_row(&1)
+        .await
+        .expect("append with channel headers");
+
+    // discovery, warm-up, token exchange, open, append
+    assert_eq!(counter.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn invalid_default_header_is_rejected() {
+    let mut cfg = token_config("https://example.invalid");
+    cfg.default_headers = Some([("bad header".to_string(), "v".to_string())].into());
+    let issues = cfg.validate().unwrap_err();
+    assert_eq!(issues[0].field, "default_headers");
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("invalid header name");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("bad header")));
+}
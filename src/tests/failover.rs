@@ -0,0 +1,152 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, rows_requests, token_config};
+use crate::{FailoverAccount, FailoverClient, FailoverMode, StreamingIngestClient, Warning};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+async fn server(rows_status: Option<u16>) -> MockServer {
+    let server = MockServer::start().await;
+    if let Some(status) = rows_status {
+        Mock::given(method("POST"))
+            .and(path(ROWS_PATH))
+            .respond_with(ResponseTemplate::new(status))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+    }
+    mount_ingest_mocks(&server).await;
+    server
+}
+
+async fn failover_client(
+    primary: &MockServer,
+    secondary: &MockServer,
+    mode: FailoverMode,
+) -> (FailoverClient<u64>, Arc<Mutex<Vec<Warning>>>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let config = token_config(&primary.uri())
+        .on_warning(move |warning| sink.lock().unwrap().push(warning.clone()));
+    let primary = StreamingIngestClient::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("primary client");
+    let secondary = StreamingIngestClient::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&secondary.uri()),
+    )
+    .await
+    .expect("secondary client");
+    (FailoverClient::new(primary, secondary, mode), warnings)
+}
+
+#[tokio::test]
+async fn unavailable_primary_fails_over_to_secondary() {
+    let primary = server(Some(503)).await;
+    let secondary = server(None).await;
+    let (mut client, warnings) = failover_client(
+        &primary,
+        &secondary,
+        FailoverMode::Failover {
+            after: Duration::ZERO,
+        },
+    )
+    .await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    assert_eq!(ch.active(), FailoverAccount::Primary);
+    assert!(ch.channel(FailoverAccount::Secondary).is_none());
+
+    ch.append_row(&1).await.expect("append fails over");
+    ch.append_row(&2).await.expect("append goes to secondary");
+    assert_eq!(ch.active(), FailoverAccount::Secondary);
+    assert_eq!(rows_requests(&primary).await, 1);
+    assert_eq!(rows_requests(&secondary).await, 2);
+    assert!(
+        warnings.lock().unwrap().iter().any(
+            |w| matches!(w, Warning::FailedOver { channel, pushed: 0, .. } if channel == "ch")
+        )
+    );
+}
+
+#[tokio::test]
+async fn primary_errors_surface_until_failover_delay() {
+    let primary = server(Some(503)).await;
+    let secondary = server(None).await;
+    let (mut client, _) = failover_client(
+        &primary,
+        &secondary,
+        FailoverMode::Failover {
+            after: Duration::from_secs(3600),
+        },
+    )
+    .await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch.append_row(&1).await.unwrap_err();
+    assert!(err.is_retryable(), "{err:?}");
+    assert_eq!(ch.active(), FailoverAccount::Primary);
+    assert_eq!(rows_requests(&secondary).await, 0);
+}
+
+#[tokio::test]
+async fn replication_tolerates_one_failing_account() {
+    let primary = server(Some(503)).await;
+    let secondary = server(None).await;
+    let (mut client, warnings) =
+        failover_client(&primary, &secondary, FailoverMode::Replicate).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_rows_iter([1, 2, 3])
+        .await
+        .expect("secondary accepted");
+    assert_eq!(rows_requests(&primary).await, 1);
+    assert_eq!(rows_requests(&secondary).await, 1);
+    assert!(warnings.lock().unwrap().iter().any(|w| matches!(
+        w,
+        Warning::ReplicaAppendFailed {
+            account: FailoverAccount::Primary,
+            ..
+        }
+    )));
+}
+
+#[tokio::test]
+async fn replicated_close_tolerates_an_unavailable_primary() {
+    let primary = server(None).await;
+    let secondary = server(None).await;
+    let (mut client, warnings) =
+        failover_client(&primary, &secondary, FailoverMode::Replicate).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(ResponseTemplate::new(503))
+        .with_priority(1)
+        .mount(&primary)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/channel_status_response.json"
+        )))
+        .mount(&secondary)
+        .await;
+
+    let report = ch.close().await.expect("secondary closed");
+    assert_eq!(report.rows, 1);
+    assert!(warnings.lock().unwrap().iter().any(|w| matches!(
+        w,
+        Warning::ReplicaAppendFailed {
+            account: FailoverAccount::Primary,
+            ..
+        }
+    )));
+}
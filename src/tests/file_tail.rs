@@ -0,0 +1,39 @@
+use wiremock::MockServer;
+
+use crate::FileTailSource;
+use crate::tests::test_support::{
+    client, mount_committed, mount_ingest_mocks, pushed_offsets, token_config,
+};
+
+#[tokio::test]
+async fn ships_byte_offsets_and_resumes_from_the_committed_one() {
+    let file = std::env::temp_dir().join(format!("snowpipe-ship-{}.ndjson", std::process::id()));
+    std::fs::write(&file, "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n").unwrap();
+
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    mount_committed(
+        &server,
+        "POST",
+        serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "9"}}
+        }),
+    )
+    .await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let mut source = FileTailSource::open(&file, 0).unwrap().max_batch_rows(2);
+    assert_eq!(source.ship(&mut ch).await.unwrap(), 2);
+    assert_eq!(source.ship(&mut ch).await.unwrap(), 1);
+    assert_eq!(source.ship(&mut ch).await.unwrap(), 0);
+    assert_eq!(pushed_offsets(&server).await, ["18", "27"]);
+
+    // A restart after only the first line was committed re-sends the rest.
+    let mut ch = client.open_channel("ch").await.expect("reopen channel");
+    let mut resumed = FileTailSource::resume(&file, &mut ch).await.unwrap();
+    assert_eq!(resumed.offset(), 9);
+    assert_eq!(resumed.ship(&mut ch).await.unwrap(), 2);
+    assert_eq!(pushed_offsets(&server).await, ["18", "27", "27"]);
+    std::fs::remove_file(&file).unwrap();
+}
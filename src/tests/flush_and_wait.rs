@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+#[tokio::test]
+async fn reports_each_micro_batch_and_keeps_the_channel_open() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let committed = Arc::new(AtomicU64::new(0));
+    let token = committed.clone();
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(move |_req: &Request| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": {"ch": {
+                    "last_committed_offset_token": token.load(Ordering::SeqCst).to_string()
+                }}
+            }))
+        })
+        .mount(&server)
+        .await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_rows(&mut [1, 2].into_iter())
+        .await
+        .expect("append");
+    drop(ch.append_row_async(&3));
+    let lagging = ch
+        .flush_and_wait(Duration::from_millis(250))
+        .await
+        .expect("timing out is reported, not an error");
+    assert!(!lagging.is_committed());
+    assert_eq!((lagging.rows, lagging.requests), (3, 2));
+    assert_eq!((lagging.committed_offset, lagging.pushed_offset), (0, 2));
+    assert!(lagging.commit_wait >= Duration::from_millis(250));
+
+    committed.store(2, Ordering::SeqCst);
+    let caught_up = ch.flush_and_wait(Duration::from_secs(5)).await.unwrap();
+    assert!(caught_up.is_committed());
+    assert_eq!((caught_up.rows, caught_up.requests), (0, 0));
+
+    ch.append_row(&4).await.expect("channel is still open");
+    committed.store(3, Ordering::SeqCst);
+    let next = ch.flush_and_wait(Duration::from_secs(5)).await.unwrap();
+    assert!(next.is_committed());
+    assert_eq!(next.rows, 1);
+}
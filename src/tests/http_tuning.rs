@@ -0,0 +1,227 @@
+use std::time::{Duration, Instant};
+
+use crate::tests::test_support::{ROWS_PATH, client, mount_ingest_mocks, token_config};
+use crate::{Error, OpenChannelOptions, RetryPlan, StreamingIngestClient};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn prior_knowledge_http2_round_trip() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.http2_prior_knowledge = Some(true);
+    cfg.http2_adaptive_window = Some(true);
+
+    let mut client = client::<u64>(cfg).await;
+    let mut ch = client.open_channel("ch").await.expect("open over h2c");
+    ch.append_row(&1).await.expect("append over h2c");
+}
+
+#[tokio::test]
+async fn max_concurrent_requests_serializes_channels() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+                .set_delay(Duration::from_millis(150)),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.max_concurrent_requests = Some(1);
+
+    let mut client = client::<u64>(cfg).await;
+    let mut first = client.open_channel("ch").await.expect("open channel");
+    let mut second = client.open_channel("ch").await.expect("open channel");
+
+    let started = Instant::now();
+    let (a, b) = tokio::join!(first.append_row(&1), second.append_row(&2));
+    a.expect("first append");
+    b.expect("second append");
+    assert!(
+        started.elapsed() >= Duration::from_millis(300),
+        "appends should not overlap, took {:?}",
+        started.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn zero_request_limit_is_rejected() {
+    let mut cfg = token_config("https://example.invalid");
+    cfg.max_concurrent_requests = Some(0);
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("zero limit should be rejected");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("max_concurrent_requests")));
+}
+
+#[tokio::test]
+async fn discovered_ingest_host_is_warmed_up() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let heads = || async {
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| r.method.as_str() == "HEAD" && r.url.path() == "/")
+            .count()
+    };
+
+    let warmed = client::<u64>(token_config(&server.uri())).await;
+    assert_eq!(
+        heads().await,
+        1,
+        "construction warms up the discovered host"
+    );
+
+    // Unmatched, so the mock server answers 404; only transport errors fail.
+    warmed.warm_up().await.expect("status is ignored");
+    assert_eq!(heads().await, 2);
+
+    let mut cfg = token_config(&server.uri());
+    cfg.ingest_host = Some(server.uri());
+    client::<u64>(cfg).await;
+    assert_eq!(heads().await, 2, "a configured host is not warmed up");
+}
+
+#[tokio::test]
+async fn configured_ingest_host_warms_up_on_request() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let count = |method: &'static str, path: &'static str| {
+        let server = &server;
+        async move {
+            server
+                .received_requests()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|r| r.method.as_str() == method && r.url.path() == path)
+                .count()
+        }
+    };
+
+    let mut cfg = token_config(&server.uri());
+    cfg.ingest_host = Some(server.uri());
+    let lazy = client::<u64>(cfg.clone()).await;
+    assert_eq!(count("HEAD", "/").await, 0);
+    assert_eq!(count("POST", "/oauth/token").await, 1);
+    lazy.ready().await.expect("ready");
+    assert_eq!(count("HEAD", "/").await, 1, "ready connects to the host");
+    assert_eq!(
+        count("POST", "/oauth/token").await,
+        1,
+        "a fresh scoped token is kept"
+    );
+
+    cfg.warm_up_ingest_host = Some(true);
+    client::<u64>(cfg).await;
+    assert_eq!(count("HEAD", "/").await, 2, "warmed up during construction");
+    assert_eq!(count("POST", "/oauth/token").await, 2);
+}
+
+#[tokio::test]
+async fn read_timeout_surfaces_as_retryable_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+                .set_delay(Duration::from_millis(1500)),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.read_timeout = Some(Duration::from_secs(1));
+    cfg.connect_timeout = Some(Duration::from_secs(5));
+
+    let mut client = client::<u64>(cfg).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let err = ch.append_row(&1).await.expect_err("append should time out");
+
+    assert!(
+        matches!(err.root(), Error::Timeout(limit) if *limit == Duration::from_secs(1)),
+        "{err:?}"
+    );
+    let context = err.context().expect("timeouts carry context");
+    assert_eq!(context.operation(), Some("append rows"));
+    assert_eq!(context.channel(), Some("ch"));
+    assert!(err.is_retryable());
+    assert!(!Error::Config("bad".into()).is_retryable());
+    assert!(Error::Http(reqwest::StatusCode::SERVICE_UNAVAILABLE, String::new()).is_retryable());
+}
+
+#[tokio::test]
+async fn attempt_timeout_bounds_a_hung_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(server.uri())
+                .set_delay(Duration::from_secs(5)),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.retry = Some(
+        RetryPlan::builder()
+            .attempt_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap(),
+    );
+
+    let started = Instant::now();
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("discovery should time out");
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert!(
+        matches!(err.root(), Error::Timeout(limit) if *limit == Duration::from_secs(1)),
+        "{err:?}"
+    );
+    assert_eq!(err.context().and_then(|c| c.attempt()), Some(1));
+}
+
+#[tokio::test]
+async fn channel_overrides_attempt_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut client = client::<u64>(token_config(&server.uri())).await;
+    let options = OpenChannelOptions::new().attempt_timeout(Duration::from_millis(200));
+    let mut ch = client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel");
+    let err = ch.append_row(&1).await.expect_err("append should time out");
+    assert!(
+        matches!(err.root(), Error::Timeout(limit) if *limit == Duration::from_millis(200)),
+        "{err:?}"
+    );
+    assert!(err.is_retryable());
+}
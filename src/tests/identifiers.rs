@@ -0,0 +1,64 @@
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn names_that_would_break_the_request_path_are_rejected() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+
+    let err = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "sch/ema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .err()
+    .expect("schema name with a slash");
+    assert!(
+        matches!(&err, Error::InvalidIdentifier(name, '/') if name == "sch/ema"),
+        "{err}"
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+    for (name, bad) in [
+        ("ch?x=1", '?'),
+        ("ch#1", '#'),
+        ("a%2Fb", '%'),
+        ("ch\n", '\n'),
+    ] {
+        let err = client.open_channel(name).await.err().expect("invalid name");
+        assert!(
+            matches!(&err, Error::InvalidIdentifier(n, c) if n == name && *c == bad),
+            "{err}"
+        );
+    }
+    assert!(matches!(
+        client.open_channel("").await.err(),
+        Some(Error::Config(_))
+    ));
+    let puts = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.method.as_str() == "PUT")
+        .count();
+    assert_eq!(puts, 0);
+
+    client
+        .open_channel("events.2024 Q1")
+        .await
+        .expect("dots and spaces stay in one path segment");
+}
@@ -103,7 +103,7 @@ async fn logs_deprecation_when_jwt_provided() {
 
     let mut cfg = base_config(&server.uri());
     cfg.jwt_token = Some("user-supplied-jwt".into());
-    cfg.jwt_exp_secs = Some(3600);
+    cfg.jwt_exp = Some(std::time::Duration::from_secs(3600));
 
     let client_res =
         StreamingIngestClient::<RowType>::new("client", "db", "schema", "pipe", cfg).await;
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::telemetry::{TelemetryEvent, TelemetrySink};
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Config, Error, StreamingIngestClient};
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<TelemetryEvent>>,
+}
+
+impl TelemetrySink for RecordingSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+/// Answers the first `outage` appends with a maintenance 503, then accepts
+/// them; returns the number of append requests received.
+async fn mount_outage(server: &MockServer, outage: usize) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(move |_req: &Request| {
+            if counter.fetch_add(1, Ordering::SeqCst) < outage {
+                ResponseTemplate::new(503)
+                    .insert_header("Retry-After", "600")
+                    .set_body_string("scheduled maintenance")
+            } else {
+                ResponseTemplate::new(200).set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+            }
+        })
+        .with_priority(1)
+        .mount(server)
+        .await;
+    mount_ingest_mocks(server).await;
+    calls
+}
+
+fn maintenance_config(server: &MockServer, max_wait_secs: u64) -> Config {
+    Config {
+        maintenance_probe_interval: Some(Duration::from_secs(1)),
+        maintenance_max_wait: Some(Duration::from_secs(max_wait_secs)),
+        ..token_config(&server.uri())
+    }
+}
+
+#[tokio::test]
+async fn appends_wait_out_maintenance_and_resume() {
+    let server = MockServer::start().await;
+    let calls = mount_outage(&server, 2).await;
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<u64>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        maintenance_config(&server, 60),
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_row(&1)
+        .await
+        .expect("append succeeds once maintenance ends");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let events = sink.events.lock().unwrap();
+    let maintenance = events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                TelemetryEvent::MaintenanceStarted { .. } | TelemetryEvent::MaintenanceEnded { .. }
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(maintenance.len(), 2, "{maintenance:?}");
+    assert_eq!(
+        maintenance[0],
+        &TelemetryEvent::MaintenanceStarted {
+            retry_after: Duration::from_secs(600)
+        }
+    );
+    assert!(
+        matches!(maintenance[1], TelemetryEvent::MaintenanceEnded { elapsed } if *elapsed >= Duration::from_secs(2)),
+        "{maintenance:?}"
+    );
+}
+
+#[tokio::test]
+async fn maintenance_longer_than_the_max_wait_fails() {
+    let server = MockServer::start().await;
+    let calls = mount_outage(&server, usize::MAX).await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        maintenance_config(&server, 2),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch
+        .append_row(&1)
+        .await
+        .expect_err("maintenance outlasts wait");
+    assert!(
+        matches!(err.root(), Error::Http(StatusCode::SERVICE_UNAVAILABLE, body) if body == "scheduled maintenance"),
+        "{err:?}"
+    );
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        2,
+        "one probe after the first 503"
+    );
+}
+
+#[tokio::test]
+async fn zero_probe_interval_is_rejected() {
+    let cfg = Config {
+        maintenance_probe_interval: Some(Duration::ZERO),
+        ..token_config("https://example.invalid")
+    };
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("zero probe interval");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("maintenance_probe_interval")));
+}
@@ -0,0 +1,33 @@
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn metrics_count_accepted_appends_across_channels() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+    let mut a = client.open_channel("a").await.expect("open channel");
+    let mut b = client.open_channel("b").await.expect("open channel");
+
+    a.append_rows_iter((0..3).map(|id| serde_json::json!({ "id": id })))
+        .await
+        .unwrap();
+    b.append_row(&serde_json::json!({"id": 10})).await.unwrap();
+
+    let metrics = client.metrics();
+    assert_eq!((metrics.requests, metrics.rows), (2, 4));
+    let body_bytes = r#"{"id":0}"#.len() * 3 + 2 + r#"{"id":10}"#.len();
+    assert_eq!(metrics.bytes, body_bytes as u64);
+    assert_eq!(metrics.avg_batch_rows(), 2.0);
+    let estimate = metrics.cost_estimate();
+    assert!(estimate.credits_so_far > 0.0 && estimate.credits_per_hour > 0.0);
+}
@@ -1,8 +1,78 @@
+pub(crate) mod api_version;
+pub(crate) mod append_async;
+pub(crate) mod append_log;
+pub(crate) mod auth_scheme;
+pub(crate) mod channel_conflict;
+pub(crate) mod channel_fencing;
+pub(crate) mod channel_options;
+pub(crate) mod channel_pool;
+pub(crate) mod channel_span;
+#[cfg(feature = "chaos")]
+pub(crate) mod chaos;
+pub(crate) mod checksums;
+#[cfg(feature = "crypto")]
+pub(crate) mod clock_skew;
+pub(crate) mod commit_lag;
+pub(crate) mod commit_wait;
+pub(crate) mod dedup;
+pub(crate) mod diagnostics;
+pub(crate) mod dry_run;
+pub(crate) mod error_context;
+pub(crate) mod explicit_offsets;
+pub(crate) mod extra_headers;
+pub(crate) mod failover;
+pub(crate) mod file_tail;
+pub(crate) mod flush_and_wait;
+pub(crate) mod http_tuning;
+pub(crate) mod identifiers;
+#[cfg(feature = "crypto")]
 pub(crate) mod jwt;
+pub(crate) mod maintenance;
+pub(crate) mod metrics;
+#[cfg(not(feature = "crypto"))]
+pub(crate) mod minimal_build;
+pub(crate) mod ndjson_reader;
+#[cfg(feature = "parallel")]
+pub(crate) mod parallel;
+pub(crate) mod partitioning;
+pub(crate) mod quarantine;
+#[cfg(feature = "replay")]
+pub(crate) mod replay;
+pub(crate) mod request_interceptor;
+pub(crate) mod request_limits;
+pub(crate) mod response_limits;
+#[cfg(feature = "crypto")]
 pub(crate) mod retry_401_failure;
+#[cfg(feature = "crypto")]
 pub(crate) mod retry_401_success;
+#[cfg(feature = "crypto")]
 pub(crate) mod retry_429_backoff;
+pub(crate) mod retry_hooks;
+pub(crate) mod routing;
+pub(crate) mod row_types;
+pub(crate) mod row_validation;
+pub(crate) mod sampling;
+pub(crate) mod schema_drift;
+pub(crate) mod scoped_token_refresh;
+pub(crate) mod sdk_offsets;
+pub(crate) mod serde_values;
+pub(crate) mod server_latency;
+pub(crate) mod status_polling;
+pub(crate) mod status_watch;
+pub(crate) mod streaming_body;
+pub(crate) mod telemetry;
 pub(crate) mod test_support;
+pub(crate) mod token_broker;
+pub(crate) mod token_cache;
+#[cfg(feature = "crypto")]
+pub(crate) mod token_info;
+#[cfg(feature = "tower")]
+pub(crate) mod tower_service;
+pub(crate) mod uncommitted_limit;
+#[cfg(feature = "vault")]
+pub(crate) mod vault;
+pub(crate) mod warnings;
+pub(crate) mod writer_generation;
 
 use jiff::Zoned;
 
@@ -0,0 +1,82 @@
+use wiremock::MockServer;
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, OpenChannelOptions, StreamingIngestChannel, StreamingIngestClient};
+
+async fn open(server: &MockServer, options: OpenChannelOptions) -> StreamingIngestChannel<u64> {
+    mount_ingest_mocks(server).await;
+    let mut client = StreamingIngestClient::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel")
+}
+
+async fn bodies(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn lines_are_forwarded_verbatim_in_size_bounded_chunks() {
+    let server = MockServer::start().await;
+    let mut ch = open(&server, OpenChannelOptions::new().max_batch_bytes(20)).await;
+    let input = "{\"id\":1, \"a\":\"x\"}\r\n\n  {\"id\":2}\n{\"id\":3}\n{\"id\":4}";
+
+    let sent = ch
+        .append_ndjson_reader(input.as_bytes())
+        .await
+        .expect("append");
+
+    let bodies = bodies(&server).await;
+    assert_eq!(
+        bodies,
+        [
+            "{\"id\":1, \"a\":\"x\"}",
+            "{\"id\":2}\n{\"id\":3}",
+            "{\"id\":4}",
+        ]
+    );
+    assert_eq!(sent, bodies.iter().map(String::len).sum::<usize>());
+}
+
+#[tokio::test]
+async fn invalid_line_fails_after_sending_the_lines_before_it() {
+    let server = MockServer::start().await;
+    let mut ch = open(&server, OpenChannelOptions::new()).await;
+    let input = "{\"id\":1}\n{\"id\":2}\n\n[3]\n{\"id\":4}\n";
+
+    let err = ch
+        .append_ndjson_reader(input.as_bytes())
+        .await
+        .expect_err("array line");
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg == "line 4 is not a JSON object"),
+        "{err:?}"
+    );
+    assert_eq!(bodies(&server).await, ["{\"id\":1}\n{\"id\":2}"]);
+
+    let err = ch
+        .append_ndjson_reader("{\"id\": 5\n".as_bytes())
+        .await
+        .expect_err("truncated line");
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg.starts_with("line 1 is not valid JSON")),
+        "{err:?}"
+    );
+    assert_eq!(bodies(&server).await.len(), 1);
+}
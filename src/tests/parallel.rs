@@ -0,0 +1,45 @@
+use wiremock::MockServer;
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[tokio::test]
+async fn parallel_serialization_keeps_row_order() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.serialization_threads = Some(3);
+    cfg.max_rows_per_request = Some(4);
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+
+    let rows = (1..=10).map(|id| serde_json::json!({ "id": id }));
+    let sent = ch.append_rows_parallel(rows).await.expect("append");
+
+    let requests = server.received_requests().await.unwrap();
+    let bodies: Vec<String> = requests
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body.clone()).unwrap())
+        .collect();
+    assert_eq!(bodies.len(), 3);
+    assert_eq!(sent, bodies.iter().map(String::len).sum::<usize>());
+    let expected = (1..=10)
+        .map(|id| format!("{{\"id\":{id}}}"))
+        .collect::<Vec<_>>();
+    assert_eq!(bodies.join("\n"), expected.join("\n"));
+}
+
+#[tokio::test]
+async fn zero_serialization_threads_are_rejected() {
+    let mut cfg = token_config("https://example.invalid");
+    cfg.serialization_threads = Some(0);
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("zero threads");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("serialization_threads")));
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use serde_json::json;
+use wiremock::MockServer;
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{BatchPartitioner, StreamingIngestClient};
+
+async fn sent_bodies(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body.clone()).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn partition_column_groups_rows_within_a_batch() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.partition_column = Some("day".into());
+    cfg.max_rows_per_request = Some(2);
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", cfg)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_rows_iter([
+        json!({"id": 1, "day": "mon"}),
+        json!({"id": 2, "day": "tue"}),
+        json!({"id": 3, "day": "mon"}),
+        json!({"id": 4}),
+        json!({"id": 5, "day": "tue"}),
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        sent_bodies(&server).await,
+        [
+            "{\"day\":\"mon\",\"id\":1}\n{\"day\":\"mon\",\"id\":3}",
+            "{\"day\":\"tue\",\"id\":2}\n{\"day\":\"tue\",\"id\":5}",
+            "{\"id\":4}",
+        ]
+    );
+}
+
+struct Parity;
+
+impl BatchPartitioner for Parity {
+    fn partition_key(&self, row: &[u8]) -> Option<String> {
+        let id: u64 = std::str::from_utf8(row).ok()?.parse().ok()?;
+        Some((id % 2).to_string())
+    }
+}
+
+#[tokio::test]
+async fn custom_partitioner_overrides_config() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.partition_column = Some("day".into());
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.set_batch_partitioner(Some(Arc::new(Parity)));
+    ch.append_rows_iter(1..=5).await.unwrap();
+    ch.set_batch_partitioner(None);
+    ch.append_rows_iter(1..=3).await.unwrap();
+
+    assert_eq!(sent_bodies(&server).await, ["1\n3\n5\n2\n4", "1\n2\n3"]);
+}
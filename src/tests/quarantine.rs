@@ -0,0 +1,69 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, rows_requests, token_config};
+use crate::{Error, StreamingIngestClient, Warning};
+
+#[tokio::test]
+async fn rejected_rows_quarantine_the_channel() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .and(body_string_contains("unmapped"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("column not in pipe"))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let config = token_config(&server.uri())
+        .on_warning(move |warning| sink.lock().unwrap().push(warning.clone()));
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_quarantine_threshold(0.25, 4);
+
+    let good = json!({"id": 1});
+    let bad = json!({"unmapped": 1});
+    for _ in 0..3 {
+        ch.append_row(&good).await.expect("good row");
+    }
+    // 1 of 4 rejected is at the threshold, not over it.
+    assert!(ch.append_row(&bad).await.is_err());
+    assert!(!ch.is_quarantined());
+    let err = ch.append_row(&bad).await.unwrap_err();
+    assert!(matches!(err, Error::Http(status, _) if status.as_u16() == 400));
+    assert!(ch.is_quarantined());
+
+    let appends_before = rows_requests(&server).await;
+    let err = ch.append_row(&good).await.unwrap_err();
+    assert!(matches!(&err, Error::ChannelQuarantined(name) if name == "ch"));
+    assert!(ch.append_row_async(&good).await.is_err());
+    assert_eq!(rows_requests(&server).await, appends_before);
+    assert_eq!(
+        warnings
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|w| matches!(w, Warning::ChannelQuarantined { .. }))
+            .cloned()
+            .collect::<Vec<_>>(),
+        [Warning::ChannelQuarantined {
+            channel: "ch".into(),
+            rejected: 2,
+            sent: 5,
+        }]
+    );
+
+    ch.release_quarantine();
+    ch.append_row(&good)
+        .await
+        .expect("released channel appends");
+    assert!(!ch.is_quarantined());
+}
@@ -0,0 +1,68 @@
+use wiremock::MockServer;
+
+use crate::replay::{Cassette, Recorder};
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[tokio::test]
+async fn recorded_session_replays_without_network() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+
+    let recorder = Recorder::new();
+    let cfg = token_config(&server.uri()).record_to(&recorder);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+    ch.append_row(&1).await.expect("append");
+
+    let path = std::env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+    recorder.save(&path).unwrap();
+    let cassette = Cassette::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let calls: Vec<_> = cassette
+        .interactions
+        .iter()
+        .map(|i| (i.method.as_str(), i.path.rsplit('/').next().unwrap()))
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            ("GET", "hostname"),
+            ("POST", "token"),
+            ("PUT", "ch"),
+            ("POST", "rows")
+        ]
+    );
+    assert_eq!(cassette.interactions[0].body, "ingest.replay.invalid");
+    assert_eq!(cassette.interactions[1].body, "REDACTED");
+    assert!(
+        cassette.interactions[3]
+            .query
+            .as_deref()
+            .is_some_and(|q| q.contains("offsetToken=1"))
+    );
+    let recorded = serde_json::to_string(&cassette).unwrap();
+    assert!(!recorded.contains(&server.uri()));
+    assert!(!recorded.contains("scoped-token"));
+
+    // Nothing listens here; every response comes from the cassette.
+    let cfg = token_config("http://127.0.0.1:9").replay_from(cassette.clone());
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("replayed client");
+    assert_eq!(client.ingest_host.as_deref(), Some("ingest.replay.invalid"));
+    let mut ch = client.open_channel("ch").await.expect("replayed open");
+    ch.append_row(&1).await.expect("replayed append");
+    let err = ch.append_row(&2).await.unwrap_err();
+    assert!(matches!(err, Error::UnexpectedResponse(msg) if msg.contains("exhausted")));
+
+    let cfg = token_config("http://127.0.0.1:9").replay_from(cassette);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("replayed client");
+    let err = client.open_channel("other").await.err().expect("mismatch");
+    assert!(matches!(err, Error::UnexpectedResponse(msg) if msg.contains("expected PUT")));
+}
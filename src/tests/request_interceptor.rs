@@ -0,0 +1,101 @@
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::HeaderValue;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, RequestInterceptor, StreamingIngestClient};
+
+/// Signs each request with the length of its body, and records what it saw.
+struct Signer {
+    seen: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl RequestInterceptor for Signer {
+    fn intercept(&self, request: &mut reqwest::Request) -> Result<(), Error> {
+        let body = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default();
+        let signature = HeaderValue::from(body.len());
+        request
+            .headers_mut()
+            .insert("x-gateway-signature", signature);
+        self.seen.lock().unwrap().push((
+            request.method().to_string(),
+            request.url().path().to_string(),
+        ));
+        Ok(())
+    }
+}
+
+/// Refuses appends.
+struct ReadOnly;
+
+impl RequestInterceptor for ReadOnly {
+    fn intercept(&self, request: &mut reqwest::Request) -> Result<(), Error> {
+        if request.url().path().ends_with("/rows") {
+            return Err(Error::Config("appends are not allowed here".into()));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn interceptor_signs_every_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .and(header("x-gateway-signature", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let config = token_config(&server.uri()).request_interceptor(Signer { seen: seen.clone() });
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+    ch.append_row(&1).await.expect("signed append");
+
+    let seen = seen.lock().unwrap().clone();
+    assert!(seen.iter().any(|(method, _)| method == "PUT"), "{seen:?}");
+    assert!(
+        seen.iter()
+            .any(|(method, path)| method == "POST" && path == ROWS_PATH),
+        "{seen:?}"
+    );
+    for request in server.received_requests().await.unwrap_or_default() {
+        assert!(
+            request.headers.contains_key("x-gateway-signature"),
+            "{} was not signed",
+            request.url
+        );
+    }
+}
+
+#[tokio::test]
+async fn interceptor_can_veto_requests() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let config = token_config(&server.uri()).request_interceptor(ReadOnly);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+
+    let err = ch.append_row(&1).await.expect_err("append vetoed");
+    assert!(
+        matches!(err.root(), Error::Config(msg) if msg == "appends are not allowed here"),
+        "{err:?}"
+    );
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert!(!requests.iter().any(|r| r.url.path() == ROWS_PATH));
+}
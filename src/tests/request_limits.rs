@@ -0,0 +1,100 @@
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, rows_requests, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[tokio::test]
+async fn rows_per_request_and_request_rate_are_enforced() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.max_rows_per_request = Some(2);
+    cfg.max_requests_per_second = Some(20);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+
+    let started = Instant::now();
+    ch.append_rows_iter(1..=7).await.expect("append");
+    assert_eq!(rows_requests(&server).await, 4);
+    // Four requests at 20/s need at least three 50ms gaps.
+    assert!(started.elapsed() >= Duration::from_millis(150));
+
+    ch.set_max_rows_per_request(NonZeroUsize::new(3));
+    ch.set_max_requests_per_second(None);
+    for row in 8..=12 {
+        drop(ch.append_row_async(&row));
+    }
+    ch.flush().await.expect("flush");
+    let requests = server.received_requests().await.unwrap();
+    let queued_batches: Vec<usize> = requests
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .skip(4)
+        .map(|r| String::from_utf8_lossy(&r.body).lines().count())
+        .collect();
+    assert_eq!(queued_batches.iter().sum::<usize>(), 5);
+    assert!(queued_batches.iter().all(|rows| *rows <= 3));
+}
+
+#[tokio::test]
+async fn zero_limits_are_rejected() {
+    let mut cfg = token_config("https://example.invalid");
+    cfg.max_requests_per_second = Some(0);
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .err()
+        .expect("zero rate");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("max_requests_per_second")));
+}
+
+#[tokio::test]
+async fn deadline_stops_further_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+                .set_delay(Duration::from_millis(200)),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.max_rows_per_request = Some(2);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+
+    let passed = ch
+        .append_rows_with_deadline(1..=6, Instant::now())
+        .await
+        .expect("append");
+    assert_eq!((passed.rows_sent, passed.rows_remaining), (0, 6));
+
+    // The first request outlasts the deadline, so no second one starts.
+    let report = ch
+        .append_rows_with_deadline(1..=6, Instant::now() + Duration::from_millis(100))
+        .await
+        .expect("append");
+    assert_eq!((report.rows_sent, report.rows_remaining), (2, 4));
+    assert!(!report.is_complete());
+    assert_eq!(rows_requests(&server).await, 1);
+
+    let report = ch
+        .append_rows_with_deadline(1..=6, Instant::now() + Duration::from_secs(60))
+        .await
+        .expect("append");
+    assert!(report.is_complete());
+    assert_eq!(rows_requests(&server).await, 4);
+}
@@ -0,0 +1,118 @@
+use reqwest::StatusCode;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+
+#[cfg(feature = "compression")]
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+/// `{"channel_statuses":{"ch":{"last_committed_offset_token":"7"}}}`, gzipped.
+#[cfg(feature = "compression")]
+const GZIPPED_STATUS: &[u8] = &[
+    31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 74, 206, 72, 204, 203, 75, 205, 137, 47, 46, 73, 44,
+    41, 45, 78, 45, 86, 178, 170, 6, 138, 129, 200, 156, 196, 226, 146, 248, 228, 252, 220, 220,
+    204, 146, 146, 212, 148, 248, 252, 180, 180, 226, 212, 146, 248, 146, 252, 236, 212, 60, 37,
+    43, 37, 115, 165, 218, 218, 90, 0, 15, 231, 254, 133, 63, 0, 0, 0,
+];
+
+fn error_page() -> String {
+    format!(
+        "<html><body>{}</body></html>",
+        "<p>Bad gateway</p>".repeat(1000)
+    )
+}
+
+#[tokio::test]
+async fn oversized_error_page_fails_with_its_status() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(503).set_body_string(error_page()))
+        .mount(&server)
+        .await;
+    let mut config = token_config(&server.uri());
+    config.max_response_bytes = Some(1024);
+
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .err()
+        .expect("discovery fails");
+    assert!(
+        matches!(
+            err.root(),
+            Error::ResponseTooLarge(StatusCode::SERVICE_UNAVAILABLE, 1024)
+        ),
+        "{err}"
+    );
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn oversized_rejection_body_is_not_buffered_into_the_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(ResponseTemplate::new(400).set_body_string(error_page()))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut config = token_config(&server.uri());
+    config.max_response_bytes = Some(1024);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch.append_row(&1).await.unwrap_err();
+    assert!(
+        matches!(
+            err.root(),
+            Error::ResponseTooLarge(StatusCode::BAD_REQUEST, 1024)
+        ),
+        "{err}"
+    );
+    assert!(!err.is_retryable());
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn gzip_responses_are_decompressed() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .insert_header("Content-Type", "application/json")
+                .set_body_bytes(GZIPPED_STATUS),
+        )
+        .mount(&server)
+        .await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    assert_eq!(ch.get_latest_committed_offset_token().await, 7);
+    let requests = server.received_requests().await.unwrap();
+    let status = requests
+        .iter()
+        .find(|r| r.url.path() == STATUS_PATH)
+        .expect("status polled");
+    let accept = status
+        .headers
+        .get("accept-encoding")
+        .expect("accept-encoding");
+    assert!(accept.to_str().unwrap().contains("gzip"), "{accept:?}");
+}
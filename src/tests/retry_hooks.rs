@@ -0,0 +1,151 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Config, Error, OpenChannelOptions, OperationKind, RetryPlan, StreamingIngestClient};
+
+const CHANNEL_PATH: &str = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+
+#[tokio::test]
+async fn observer_sees_operation_attempt_error_and_delay() {
+    let server = MockServer::start().await;
+    let throttled = Arc::new(AtomicBool::new(false));
+    let flag = throttled.clone();
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(move |_req: &Request| {
+            if flag.swap(true, Ordering::SeqCst) {
+                ResponseTemplate::new(200).set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+            } else {
+                ResponseTemplate::new(429).set_body_string("slow down")
+            }
+        })
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let config = token_config(&server.uri()).on_retry(move |retry| {
+        let status = match retry.error {
+            Error::Http(status, body) => format!("{} {}", status.as_u16(), body),
+            other => other.to_string(),
+        };
+        sink.lock()
+            .unwrap()
+            .push((retry.operation, retry.attempt, status, retry.delay));
+    });
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let options = OpenChannelOptions::new().rate_limit_backoff(Duration::from_millis(10));
+    let mut ch = client
+        .open_channel_with_options("ch", &options)
+        .await
+        .expect("open channel");
+    ch.append_row(&1)
+        .await
+        .expect("append succeeds after one 429");
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [(
+            OperationKind::AppendRows,
+            1,
+            "429 slow down".to_string(),
+            Duration::from_millis(10)
+        )]
+    );
+}
+
+#[tokio::test]
+async fn drop_channel_skips_the_commit_wait() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path(CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(503))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    // Nothing serves channel status, so `close` would wait on the commit.
+    ch.append_row(&1).await.expect("append");
+
+    let err = ch.drop_channel().await.unwrap_err();
+    assert_eq!(
+        err.context().and_then(|c| c.operation_kind()),
+        Some(OperationKind::DropChannel)
+    );
+    assert!(err.to_string().contains("drop channel"), "{err}");
+}
+
+#[tokio::test]
+async fn retry_section_backs_off_exponentially() {
+    let server = MockServer::start().await;
+    let throttled = Arc::new(AtomicUsize::new(0));
+    let count = throttled.clone();
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(move |_req: &Request| {
+            if count.fetch_add(1, Ordering::SeqCst) < 2 {
+                ResponseTemplate::new(429)
+            } else {
+                ResponseTemplate::new(200).set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+            }
+        })
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let delays = Arc::new(Mutex::new(Vec::new()));
+    let sink = delays.clone();
+    let config = serde_json::from_value::<Config>(serde_json::json!({
+        "user": "user",
+        "account": "acct",
+        "url": server.uri(),
+        "jwt_token": "jwt",
+        "retry": {"preset": "aggressive", "initial_backoff_ms": 10, "max_backoff_ms": 15}
+    }))
+    .expect("config with a retry section")
+    .on_retry(move |retry| sink.lock().unwrap().push(retry.delay));
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("third attempt succeeds");
+    assert_eq!(
+        *delays.lock().unwrap(),
+        [Duration::from_millis(10), Duration::from_millis(15)]
+    );
+
+    throttled.store(0, Ordering::SeqCst);
+    let mut config = token_config(&server.uri());
+    config.retry = Some(RetryPlan::none());
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let err = ch.append_row(&1).await.unwrap_err();
+    assert!(
+        matches!(err.root(), Error::Http(status, _) if status.as_u16() == 429),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,173 @@
+use crate::tests::test_support::token_config;
+use crate::{Error, Route, Router};
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const ANY_PIPE_CHANNEL_PATH: &str =
+    "^/v2/streaming/databases/db/schemas/schema/pipes/[^/]+/channels/[^/]+$";
+const ANY_PIPE_ROWS_PATH: &str =
+    "^/v2/streaming/data/databases/db/schemas/schema/pipes/[^/]+/channels/[^/]+/rows$";
+
+/// Mounts successful responses for any channel on any pipe in `db.schema`,
+/// reporting every channel as fully committed.
+async fn mount_any_pipe_mocks(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .mount(server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex(ANY_PIPE_CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/open_channel_response.json"
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(ANY_PIPE_ROWS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(":bulk-channel-status$"))
+        .respond_with(|req: &Request| {
+            let body: serde_json::Value = req.body_json().unwrap();
+            let statuses = body["channel_names"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|name| {
+                    (
+                        name.as_str().unwrap().to_string(),
+                        serde_json::json!({"last_committed_offset_token": "1000"}),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>();
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": statuses
+            }))
+        })
+        .mount(server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path_regex(ANY_PIPE_CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(server)
+        .await;
+}
+
+/// Bodies of the append requests sent to `channel` on `pipe`.
+async fn bodies_for(server: &MockServer, pipe: &str, channel: &str) -> Vec<String> {
+    let rows_path = format!(
+        "/v2/streaming/data/databases/db/schemas/schema/pipes/{pipe}/channels/{channel}/rows"
+    );
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.url.path() == rows_path)
+        .map(|r| String::from_utf8(r.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn rows_are_routed_by_closure_rules_and_default() {
+    let server = MockServer::start().await;
+    mount_any_pipe_mocks(&server).await;
+
+    let orders = Route::new("orders_pipe", "orders");
+    let events = Route::new("events_pipe", "events");
+    let mut router =
+        Router::<serde_json::Value>::new("client", "db", "schema", token_config(&server.uri()))
+            .with_route_fn(|row| {
+                row["tenant"]
+                    .as_str()
+                    .map(|tenant| Route::new("events_pipe", format!("tenant_{tenant}")))
+            })
+            .with_rule("kind", "order", orders.clone())
+            .with_default_route(events.clone());
+
+    assert_eq!(
+        router
+            .route_of(&serde_json::json!({"kind": "order"}))
+            .unwrap(),
+        orders
+    );
+    router
+        .append_rows_iter([
+            serde_json::json!({"id": 1, "kind": "order"}),
+            serde_json::json!({"id": 2, "kind": "click"}),
+            serde_json::json!({"id": 3, "kind": "order", "tenant": "a"}),
+            serde_json::json!({"id": 4, "kind": "order"}),
+        ])
+        .await
+        .expect("append");
+    router
+        .append_row(&serde_json::json!({"id": 5}))
+        .await
+        .expect("append row");
+
+    assert_eq!(
+        bodies_for(&server, "orders_pipe", "orders").await,
+        [r#"{"id":1,"kind":"order"}"#.to_string() + "\n" + r#"{"id":4,"kind":"order"}"#]
+    );
+    assert_eq!(
+        bodies_for(&server, "events_pipe", "tenant_a").await,
+        [r#"{"id":3,"kind":"order","tenant":"a"}"#]
+    );
+    assert_eq!(
+        bodies_for(&server, "events_pipe", "events").await,
+        [r#"{"id":2,"kind":"click"}"#, r#"{"id":5}"#]
+    );
+
+    let reports = router.close().await.expect("close");
+    assert_eq!(
+        reports
+            .iter()
+            .map(|(route, report)| (route.to_string(), report.rows))
+            .collect::<Vec<_>>(),
+        [
+            ("events_pipe/events".to_string(), 2),
+            ("events_pipe/tenant_a".to_string(), 1),
+            ("orders_pipe/orders".to_string(), 2),
+        ]
+    );
+    assert_eq!(router.channels().count(), 0);
+}
+
+#[tokio::test]
+async fn unroutable_rows_fail_the_batch_before_sending() {
+    let server = MockServer::start().await;
+    mount_any_pipe_mocks(&server).await;
+    let mut router =
+        Router::<serde_json::Value>::new("client", "db", "schema", token_config(&server.uri()))
+            .with_rule("kind", "order", Route::new("orders_pipe", "orders"));
+
+    let err = router
+        .append_rows_iter([
+            serde_json::json!({"kind": "order"}),
+            serde_json::json!({"kind": "refund"}),
+        ])
+        .await
+        .expect_err("second row has no route");
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg.contains("row 1")),
+        "{err}"
+    );
+    assert!(
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .is_empty(),
+        "nothing should be sent"
+    );
+}
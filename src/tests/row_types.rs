@@ -0,0 +1,63 @@
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{StreamingIngestChannel, StreamingIngestClient};
+use wiremock::MockServer;
+
+#[derive(serde::Serialize, Clone)]
+struct Order {
+    id: u64,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct Click {
+    page: &'static str,
+}
+
+#[tokio::test]
+async fn one_client_serves_channels_with_different_row_types() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<Order>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+
+    let mut orders = client.open_channel("orders").await.expect("open channel");
+    let mut clicks: StreamingIngestChannel<Click> = client
+        .open_channel_as("clicks")
+        .await
+        .expect("open channel");
+    let mut raw = client
+        .with_row_type::<serde_json::Value>()
+        .open_channel("raw")
+        .await
+        .expect("open channel");
+
+    orders.append_row(&Order { id: 1 }).await.expect("append");
+    clicks
+        .append_row(&Click { page: "/home" })
+        .await
+        .expect("append");
+    raw.append_row(&serde_json::json!({"any": "shape"}))
+        .await
+        .expect("append");
+
+    // All handles share one set of counters.
+    assert_eq!(client.metrics().requests, 3);
+    let bodies = server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.url.path().ends_with("/rows"))
+        .map(|r| String::from_utf8(r.body).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bodies,
+        ["{\"id\":1}", "{\"page\":\"/home\"}", "{\"any\":\"shape\"}"]
+    );
+}
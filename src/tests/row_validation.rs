@@ -0,0 +1,77 @@
+use serde_json::json;
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestChannel, StreamingIngestClient};
+use wiremock::MockServer;
+
+async fn open(
+    server: &MockServer,
+    batch_id_column: Option<&str>,
+) -> StreamingIngestChannel<serde_json::Value> {
+    mount_ingest_mocks(server).await;
+    let mut config = token_config(&server.uri());
+    config.batch_id_column = batch_id_column.map(str::to_string);
+    let mut client = StreamingIngestClient::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    client.open_channel("ch").await.expect("open channel")
+}
+
+#[tokio::test]
+async fn estimate_matches_the_request_body() {
+    let server = MockServer::start().await;
+    let mut ch = open(&server, Some("batch_id")).await;
+    let row = json!({"id": 1, "name": "caf\u{e9} \"quoted\""});
+
+    let estimate = ch.estimate_serialized_size(&row).unwrap();
+    assert_eq!(ch.validate_row(&row).unwrap(), estimate);
+    ch.append_row(&row).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let body = &requests
+        .iter()
+        .find(|r| r.url.path() == ROWS_PATH)
+        .expect("append request")
+        .body;
+    assert_eq!(estimate, body.len());
+}
+
+#[tokio::test]
+async fn invalid_rows_are_rejected_before_sending() {
+    let server = MockServer::start().await;
+    let mut ch = open(&server, None).await;
+
+    let err = ch.validate_row(&json!([1, 2])).unwrap_err();
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg.contains("array")),
+        "{err}"
+    );
+
+    let huge = json!({"blob": "x".repeat(16 * 1024 * 1024)});
+    let err = ch.validate_row(&huge).unwrap_err();
+    assert!(
+        matches!(err, Error::DataTooLarge(size, max) if size > max),
+        "{err}"
+    );
+
+    ch.set_schema_drift_alert(|_, _| {});
+    ch.append_row(&json!({"id": 1})).await.unwrap();
+    assert!(ch.validate_row(&json!({"id": 2})).is_ok());
+    let err = ch
+        .validate_row(&json!({"id": 3, "extra": true}))
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg.contains("extra")),
+        "{err}"
+    );
+    assert_eq!(
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| r.url.path() == ROWS_PATH)
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,52 @@
+use std::num::NonZeroU64;
+
+use serde_json::json;
+use wiremock::MockServer;
+
+use crate::Sampler;
+use crate::tests::test_support::{client, mount_ingest_mocks, posted_bodies, token_config};
+
+#[tokio::test]
+async fn sampled_out_rows_are_not_sent() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_sampler(Some(Sampler::one_in(NonZeroU64::new(2).unwrap())));
+
+    ch.append_rows_iter((1..=5).map(|id| json!({"id": id})))
+        .await
+        .unwrap();
+    ch.append_row(&json!({"id": 6})).await.unwrap();
+    ch.append_row(&json!({"id": 7})).await.unwrap();
+
+    assert_eq!(
+        posted_bodies(&server).await,
+        ["{\"id\":1}\n{\"id\":3}\n{\"id\":5}", "{\"id\":7}"]
+    );
+    let sampler = ch.sampler().unwrap();
+    assert_eq!((sampler.kept(), sampler.sampled_out()), (4, 3));
+}
+
+#[tokio::test]
+async fn custom_samplers_see_serialized_rows() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_sampler(Some(Sampler::new(|row| {
+        serde_json::from_slice::<serde_json::Value>(row).unwrap()["level"] == "error"
+    })));
+
+    let written = ch
+        .append_rows_iter([json!({"level": "debug"}), json!({"level": "info"})])
+        .await
+        .unwrap();
+    assert_eq!(written, 0);
+    ch.append_rows_iter([json!({"level": "error"}), json!({"level": "info"})])
+        .await
+        .unwrap();
+
+    assert_eq!(posted_bodies(&server).await, ["{\"level\":\"error\"}"]);
+    assert_eq!(ch.sampler().unwrap().sampled_out(), 3);
+}
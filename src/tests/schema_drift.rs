@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use wiremock::MockServer;
+
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{SchemaDrift, StreamingIngestClient, Warning};
+
+#[tokio::test]
+async fn new_fields_are_reported_once() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let config = token_config(&server.uri())
+        .on_warning(move |warning| sink.lock().unwrap().push(warning.clone()));
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let alerts_clone = alerts.clone();
+    ch.set_schema_drift_alert(move |channel, drift| {
+        alerts_clone
+            .lock()
+            .unwrap()
+            .push((channel.to_string(), drift.clone()));
+    });
+
+    ch.append_row(&json!({"id": 1, "name": "a"})).await.unwrap();
+    ch.append_rows_iter([json!({"id": 2, "full_name": "b"}), json!({"id": 3})])
+        .await
+        .unwrap();
+    ch.append_row(&json!({"id": 4, "full_name": "c"}))
+        .await
+        .unwrap();
+    drop(ch.append_row_async(&json!({"id": 5, "full_name": "d", "email": "e"})));
+    ch.flush().await.unwrap();
+
+    let renamed = SchemaDrift {
+        added: vec!["full_name".into()],
+        removed: vec!["name".into()],
+    };
+    let extended = SchemaDrift {
+        added: vec!["email".into()],
+        removed: vec![],
+    };
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        [
+            ("ch".to_string(), renamed.clone()),
+            ("ch".to_string(), extended)
+        ]
+    );
+    assert!(warnings.lock().unwrap().contains(&Warning::SchemaDrift {
+        channel: "ch".into(),
+        drift: renamed,
+    }));
+}
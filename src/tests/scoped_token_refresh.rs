@@ -0,0 +1,202 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use base64::Engine as _;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::telemetry::{TelemetryEvent, TelemetrySink};
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Config, Error, StreamingIngestClient};
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<TelemetryEvent>>,
+}
+
+impl TelemetrySink for RecordingSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+fn jwt_expiring_in(secs: i64, id: usize) -> String {
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let exp = jiff::Timestamp::now().as_second() + secs;
+    format!(
+        "{}.{}.sig{id}",
+        engine.encode(r#"{"alg":"none"}"#),
+        engine.encode(serde_json::json!({ "exp": exp }).to_string())
+    )
+}
+
+fn broker_config(server: &MockServer) -> Config {
+    let mut cfg = token_config("");
+    cfg.jwt_token = None;
+    cfg.ingest_host = Some(server.uri());
+    cfg
+}
+
+#[tokio::test]
+async fn provider_token_is_refreshed_before_expiry() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider_calls = calls.clone();
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<u64>::new_with_token_provider_and_telemetry(
+        "worker",
+        "db",
+        "schema",
+        "pipe",
+        broker_config(&server),
+        Arc::new(move || {
+            let n = provider_calls.fetch_add(1, Ordering::SeqCst) + 1;
+            // The first token is already inside the default 60s margin.
+            let lifetime = if n == 1 { 30 } else { 3600 };
+            async move { Ok(jwt_expiring_in(lifetime, n)) }
+        }),
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+
+    let mut ch = client.open_channel("ch").await.expect("open");
+    ch.append_row(&1).await.expect("append");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let events = sink.events.lock().unwrap();
+    let refreshes: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            TelemetryEvent::ScopedTokenRefresh { remaining_secs, .. } => Some(*remaining_secs),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(refreshes.len(), 1);
+    assert!(refreshes[0].is_some_and(|remaining| remaining <= 30));
+}
+
+#[tokio::test]
+async fn failed_refresh_keeps_cached_token_and_cools_down() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .and(header("authorization", "Bearer first"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .expect(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider_calls = calls.clone();
+    let sink = Arc::new(RecordingSink::default());
+    let mut cfg = broker_config(&server);
+    cfg.scoped_token_max_age = Some(std::time::Duration::from_secs(0));
+    cfg.token_refresh_cooldown = Some(std::time::Duration::from_secs(300));
+    let mut client = StreamingIngestClient::<u64>::new_with_token_provider_and_telemetry(
+        "worker",
+        "db",
+        "schema",
+        "pipe",
+        cfg,
+        Arc::new(move || {
+            let first = provider_calls.fetch_add(1, Ordering::SeqCst) == 0;
+            async move {
+                if first {
+                    Ok("first".to_string())
+                } else {
+                    Err(Error::Auth("broker unavailable".into()))
+                }
+            }
+        }),
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+
+    let mut ch = client
+        .open_channel("ch")
+        .await
+        .expect("open with cached token");
+    ch.append_row(&1).await.expect("append during cooldown");
+    ch.append_row(&2).await.expect("append during cooldown");
+    // Construction, then the one refresh attempt that failed; later requests
+    // were inside the cooldown and never reached the provider.
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let events = sink.events.lock().unwrap();
+    let count = |want: fn(&TelemetryEvent) -> bool| events.iter().filter(|e| want(e)).count();
+    assert_eq!(
+        count(|e| matches!(e, TelemetryEvent::ScopedTokenRefresh { .. })),
+        1
+    );
+    assert_eq!(
+        count(|e| matches!(e, TelemetryEvent::ScopedTokenRefreshFailed { .. })),
+        1
+    );
+}
+
+#[tokio::test]
+async fn exchanged_token_honors_max_age() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .expect(3)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut cfg = token_config(&server.uri());
+    cfg.scoped_token_max_age = Some(std::time::Duration::from_secs(0));
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", cfg)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open");
+    ch.append_row(&1).await.expect("append");
+}
+
+#[tokio::test]
+async fn concurrent_refreshes_are_coalesced_and_counted() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider_calls = calls.clone();
+    let mut client = StreamingIngestClient::<u64>::new_with_token_provider(
+        "worker",
+        "db",
+        "schema",
+        "pipe",
+        broker_config(&server),
+        Arc::new(move || {
+            let n = provider_calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let lifetime = if n == 1 { 30 } else { 3600 };
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                Ok(jwt_expiring_in(lifetime, n))
+            }
+        }),
+    )
+    .await
+    .expect("client");
+    let mut other = client.clone();
+
+    let (a, b) = tokio::join!(client.open_channel("a"), other.open_channel("b"));
+    a.expect("open a");
+    b.expect("open b");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let metrics = client.metrics().token_refresh;
+    assert_eq!(metrics, other.token_refresh_metrics());
+    assert_eq!((metrics.attempts, metrics.failures), (2, 0));
+    assert_eq!(metrics.coalesced, 1);
+    assert!(metrics.time_in_refresh >= std::time::Duration::from_millis(100));
+}
@@ -0,0 +1,86 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Error, OpenChannelOptions, SdkOffsetFormat, StreamingIngestClient};
+
+const CHANNEL_PATH: &str = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+/// A channel last committed by another SDK at partition 3, offset 1042,
+/// until it is reopened with an offset token.
+async fn mount_foreign_channel(server: &MockServer) {
+    Mock::given(method("PUT"))
+        .and(path(CHANNEL_PATH))
+        .respond_with(|req: &Request| {
+            let body: serde_json::Value = req.body_json().unwrap();
+            let committed = body["offset_token"]
+                .as_str()
+                .unwrap_or("3-1042")
+                .to_string();
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "next_continuation_token": "ctok",
+                "channel_status": {"last_committed_offset_token": committed}
+            }))
+        })
+        .with_priority(1)
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "3-1042"}}
+        })))
+        .with_priority(1)
+        .mount(server)
+        .await;
+    mount_ingest_mocks(server).await;
+}
+
+#[tokio::test]
+async fn channels_of_other_sdks_are_resumed_from_their_tokens() {
+    let server = MockServer::start().await;
+    mount_foreign_channel(&server).await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+
+    let err = client
+        .open_channel("ch")
+        .await
+        .err()
+        .expect("non-numeric token");
+    assert!(
+        matches!(&err, Error::Config(msg) if msg.contains("'3-1042'")),
+        "{err}"
+    );
+
+    let token = client
+        .committed_offset_token("ch")
+        .await
+        .unwrap()
+        .expect("committed token");
+    let format = SdkOffsetFormat::detect(&token).expect("known format");
+    assert_eq!(format, SdkOffsetFormat::Delimited('-'));
+    let progress = format.parse(&token, "unused").unwrap();
+    assert_eq!(progress.committed("3"), Some(1042));
+    assert_eq!(client.committed_offset_token("other").await.unwrap(), None);
+
+    let mut ch = client
+        .open_channel_with_options("ch", &OpenChannelOptions::new().offset_token(1042))
+        .await
+        .expect("reopen with a numeric token");
+    ch.append_row(&1).await.expect("append");
+    let status = ch.status_watch().borrow().clone();
+    assert_eq!(
+        (status.committed_offset, status.pushed_offset),
+        (1042, 1043)
+    );
+}
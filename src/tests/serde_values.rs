@@ -0,0 +1,30 @@
+use crate::Error;
+use crate::tests::test_support::{client, mount_ingest_mocks, posted_bodies, token_config};
+use serde_json::json;
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn appends_objects_and_rejects_other_values() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = client::<serde_json::Value>(token_config(&server.uri())).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let err = ch
+        .append_serde_values(vec![json!({"id": 1}), json!([1, 2]), json!("x")])
+        .await
+        .expect_err("arrays are not rows");
+    assert!(
+        matches!(&err, Error::InvalidRow(msg) if msg == "row 1 is a JSON array, not an object"),
+        "{err}"
+    );
+    assert!(posted_bodies(&server).await.is_empty());
+
+    ch.append_serde_values(vec![json!({"id": 1}), json!({"id": 2, "tags": ["a"]})])
+        .await
+        .expect("append");
+    assert_eq!(
+        posted_bodies(&server).await,
+        ["{\"id\":1}\n{\"id\":2,\"tags\":[\"a\"]}"]
+    );
+}
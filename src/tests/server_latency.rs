@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::test_support::{mount_ingest_mocks, token_config};
+use crate::StreamingIngestClient;
+use crate::telemetry::{TelemetryEvent, TelemetrySink};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<TelemetryEvent>>,
+}
+
+impl TelemetrySink for RecordingSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+#[tokio::test]
+async fn status_polls_report_server_latency() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {
+                "last_committed_offset_token": "1",
+                "snowflake_avg_processing_latency_ms": 1250
+            }}
+        })))
+        .mount(&server)
+        .await;
+
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+        sink.clone(),
+    )
+    .await
+    .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    // The open response fixture reports zero latency.
+    assert_eq!(ch.server_latency(), Some(Duration::ZERO));
+
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append");
+    assert_eq!(ch.get_latest_committed_offset_token().await, 1);
+    assert_eq!(ch.server_latency(), Some(Duration::from_millis(1250)));
+
+    let report = ch.close().await.expect("close");
+    assert_eq!(report.server_latency, Some(Duration::from_millis(1250)));
+
+    let latencies: Vec<Duration> = sink
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            TelemetryEvent::ServerLatency { channel, latency } if channel == "ch" => Some(*latency),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(latencies.first(), Some(&Duration::ZERO));
+    assert!(
+        latencies[1..]
+            .iter()
+            .all(|l| *l == Duration::from_millis(1250))
+    );
+    assert!(latencies.len() >= 2);
+}
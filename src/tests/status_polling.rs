@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Ordering, StreamingIngestClient};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+/// Reports every requested channel as committed up to offset 0 for the
+/// first `stalled` polls, then up to 1000.
+async fn mount_stalled_status(server: &MockServer, stalled: usize) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(move |req: &Request| {
+            let token = match counter.fetch_add(1, AtomicOrdering::SeqCst) < stalled {
+                true => "0",
+                false => "1000",
+            };
+            let body: serde_json::Value = req.body_json().unwrap();
+            let statuses = body["channel_names"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|name| {
+                    let status = serde_json::json!({ "last_committed_offset_token": token });
+                    (name.as_str().unwrap().to_string(), status)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "channel_statuses": statuses }))
+        })
+        .mount(server)
+        .await;
+    calls
+}
+
+#[tokio::test]
+async fn unchanged_status_is_not_republished() {
+    let server = MockServer::start().await;
+    let calls = mount_stalled_status(&server, 2).await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+    let mut status = ch.status_watch();
+    status.borrow_and_update();
+
+    assert_eq!(ch.get_latest_committed_offset_token().await, 0);
+    assert_eq!(ch.get_latest_committed_offset_token().await, 0);
+    assert!(
+        !status.has_changed().unwrap(),
+        "no commits, no new snapshot"
+    );
+
+    assert_eq!(ch.get_latest_committed_offset_token().await, 1000);
+    assert!(status.has_changed().unwrap());
+    assert_eq!(status.borrow_and_update().uncommitted_offsets, 0);
+    assert_eq!(calls.load(AtomicOrdering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn pool_close_polls_only_channels_awaiting_commits() {
+    let server = MockServer::start().await;
+    mount_stalled_status(&server, 2).await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut pool = client
+        .open_channel_pool("ch", 3, Ordering::Relaxed)
+        .await
+        .expect("open pool");
+    let row = serde_json::json!({ "id": 1 });
+    let index = pool.channel_index_for(&"key");
+    pool.append_row_keyed(&"key", &row).await.expect("append");
+
+    let reports = pool.close().await.expect("close pool");
+    assert_eq!(reports.iter().map(|r| r.rows).sum::<u64>(), 1);
+
+    let polls = server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.url.path() == STATUS_PATH)
+        .map(|r| r.body_json::<serde_json::Value>().unwrap()["channel_names"].clone())
+        .collect::<Vec<_>>();
+    let expected = serde_json::json!([format!("ch_{index}")]);
+    assert_eq!(polls, vec![expected; 3]);
+}
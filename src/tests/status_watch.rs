@@ -0,0 +1,65 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+#[tokio::test]
+async fn subscribers_follow_pushes_and_commits() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {
+                "last_committed_offset_token": "1",
+                "snowflake_avg_processing_latency_ms": 40
+            }}
+        })))
+        .mount(&server)
+        .await;
+    let mut client = StreamingIngestClient::<u64>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    let mut status = ch.status_watch();
+    assert_eq!(status.borrow_and_update().channel_name, "ch");
+    assert_eq!(status.borrow().pushed_offset, 0);
+
+    ch.append_row(&1).await.expect("append");
+    ch.append_row(&2).await.expect("append");
+    assert!(status.has_changed().unwrap());
+    {
+        let pushed = status.borrow_and_update();
+        assert_eq!((pushed.committed_offset, pushed.pushed_offset), (0, 2));
+        assert_eq!(pushed.uncommitted_offsets, 2);
+    }
+
+    ch.get_latest_committed_offset_token().await;
+    status.changed().await.unwrap();
+    let committed = status.borrow_and_update().clone();
+    assert_eq!(
+        (committed.committed_offset, committed.pushed_offset),
+        (1, 2)
+    );
+    assert_eq!(committed.uncommitted_offsets, 1);
+    assert_eq!(
+        committed.server_latency,
+        Some(std::time::Duration::from_millis(40))
+    );
+
+    drop(ch);
+    assert!(
+        status.changed().await.is_err(),
+        "sender gone with the channel"
+    );
+}
@@ -0,0 +1,39 @@
+use crate::StreamingIngestClient;
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn batched_rows_are_sent_as_ndjson_with_content_length() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut client = StreamingIngestClient::<serde_json::Value>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    let rows = (1..=3).map(|id| serde_json::json!({ "id": id }));
+    let sent = ch.append_rows_iter(rows).await.expect("append rows");
+
+    let expected = "{\"id\":1}\n{\"id\":2}\n{\"id\":3}";
+    assert_eq!(sent, expected.len());
+    let requests = server.received_requests().await.unwrap_or_default();
+    let posts = requests
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .collect::<Vec<_>>();
+    assert_eq!(posts.len(), 1);
+    assert_eq!(std::str::from_utf8(&posts[0].body).unwrap(), expected);
+    assert_eq!(
+        posts[0]
+            .headers
+            .get("content-length")
+            .and_then(|v| v.to_str().ok()),
+        Some(expected.len().to_string().as_str())
+    );
+}
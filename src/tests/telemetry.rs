@@ -0,0 +1,408 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::telemetry::{
+    AuthMode, ResponseHeaders, Tags, TelemetryEvent, TelemetrySink, TokenKind, TracingTelemetry,
+};
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config, with_captured_logs};
+use crate::{AuthScheme, Config, Error, RetryPlan, StreamingIngestClient};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<TelemetryEvent>>,
+}
+
+impl TelemetrySink for RecordingSink {
+    fn record(&self, event: &TelemetryEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+#[tokio::test]
+async fn custom_sink_receives_retry_and_append_events() {
+    let server = MockServer::start().await;
+    let channel_path = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+    let rows_path = "/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/ch/rows";
+
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path(channel_path))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/open_channel_response.json"
+        )))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(rows_path))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(rows_path))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/channel_status_response.json"
+        )))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path(channel_path))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let cfg = Config::from_values(
+        "user",
+        None,
+        "acct",
+        server.uri(),
+        Some("jwt".into()),
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        cfg,
+        sink.clone(),
+    )
+    .await
+    .expect("client construction should succeed");
+
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append succeeds after scoped token refresh");
+
+    let report = ch.close().await.expect("close");
+    assert_eq!((report.rows, report.requests, report.retries), (1, 1, 1));
+
+    let events = sink.events.lock().unwrap();
+    assert!(
+        events.contains(&TelemetryEvent::UnauthorizedRetry {
+            token: TokenKind::Scoped
+        }),
+        "expected scoped 401 retry event, got {:?}",
+        events
+    );
+    let appended = events
+        .iter()
+        .find_map(|e| match e {
+            TelemetryEvent::AppendCompleted {
+                channel,
+                offset,
+                rows,
+                bytes,
+                ..
+            } => Some((channel.clone(), *offset, *rows, *bytes)),
+            _ => None,
+        })
+        .expect("append event recorded");
+    assert_eq!(appended, ("ch".to_string(), 1, 1, r#"{"id":1}"#.len()));
+}
+
+#[tokio::test]
+async fn rate_limit_headers_reach_telemetry_and_diagnostics() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("X-RateLimit-Limit", "100")
+                .insert_header("X-RateLimit-Remaining", "0")
+                .insert_header("Retry-After", "2"),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-RateLimit-Remaining", "99")
+                .insert_header("X-Unrelated", "ignored")
+                .set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                )),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append succeeds after 429 retry");
+
+    let captured: Vec<(u16, ResponseHeaders)> = sink
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|e| match e {
+            TelemetryEvent::RateLimitHeaders {
+                token: TokenKind::Scoped,
+                status,
+                headers,
+            } => Some((*status, headers.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(captured.len(), 2);
+    let (status, throttled) = &captured[0];
+    assert_eq!(*status, 429);
+    assert_eq!(
+        (
+            throttled.rate_limit_limit(),
+            throttled.rate_limit_remaining()
+        ),
+        (Some(100), Some(0))
+    );
+    assert_eq!(throttled.retry_after(), Some(Duration::from_secs(2)));
+    let (status, accepted) = &captured[1];
+    assert_eq!((*status, accepted.rate_limit_remaining()), (200, Some(99)));
+    assert_eq!(accepted.get("x-unrelated"), None);
+
+    let diagnostics = client.diagnostics().await;
+    assert_eq!(
+        diagnostics["recent_retries"][0]["headers"]["x-ratelimit-remaining"],
+        "0"
+    );
+}
+
+#[derive(Default)]
+struct TaggedRecordingSink {
+    events: Mutex<Vec<(TelemetryEvent, Tags)>>,
+}
+
+impl TelemetrySink for TaggedRecordingSink {
+    fn record(&self, _event: &TelemetryEvent) {
+        unreachable!("events are delivered with tags");
+    }
+
+    fn record_with_tags(&self, event: &TelemetryEvent, tags: &Tags) {
+        self.events
+            .lock()
+            .unwrap()
+            .push((event.clone(), tags.clone()));
+    }
+}
+
+#[tokio::test]
+async fn client_and_channel_tags_reach_the_sink() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.tags = Some(Tags::from([
+        ("env".to_string(), "prod".to_string()),
+        ("tenant".to_string(), "shared".to_string()),
+    ]));
+    let sink = Arc::new(TaggedRecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        cfg,
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+
+    let mut tenant_ch = client.open_channel("ch").await.expect("open channel");
+    tenant_ch.set_tags(Tags::from([("tenant".to_string(), "acme".to_string())]));
+    tenant_ch
+        .append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append");
+    let mut shared_ch = client.open_channel("other").await.expect("open channel");
+    shared_ch
+        .append_row(&serde_json::json!({"id": 2}))
+        .await
+        .expect("append");
+
+    let appends: Vec<(String, Tags)> = sink
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(event, tags)| match event {
+            TelemetryEvent::AppendCompleted { channel, .. } => {
+                Some((channel.clone(), tags.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    let tags = |pairs: [(&str, &str); 2]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Tags>()
+    };
+    assert_eq!(
+        appends,
+        [
+            (
+                "ch".to_string(),
+                tags([("env", "prod"), ("tenant", "acme")])
+            ),
+            (
+                "other".to_string(),
+                tags([("env", "prod"), ("tenant", "shared")])
+            ),
+        ]
+    );
+    assert_eq!(tenant_ch.tags()["tenant"], "acme");
+}
+
+#[tokio::test]
+async fn batch_ids_reach_rows_telemetry_and_report() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut cfg = token_config(&server.uri());
+    cfg.batch_id_column = Some("BATCH_ID".into());
+    let sink = Arc::new(RecordingSink::default());
+    let mut client = StreamingIngestClient::<serde_json::Value>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        cfg,
+        sink.clone(),
+    )
+    .await
+    .expect("client construction should succeed");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_rows_iter([serde_json::json!({"id": 1}), serde_json::json!({})])
+        .await
+        .expect("append");
+    let report = ch.last_append().expect("append report").clone();
+    assert_eq!((report.offset, report.rows), (1, 2));
+
+    let body = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body).unwrap())
+        .expect("append request");
+    let id = report.batch_id;
+    assert_eq!(
+        body,
+        format!("{{\"id\":1,\"BATCH_ID\":\"{id}\"}}\n{{\"BATCH_ID\":\"{id}\"}}")
+    );
+    assert_eq!(report.bytes, body.len());
+    assert!(sink.events.lock().unwrap().iter().any(|e| matches!(
+        e,
+        TelemetryEvent::AppendCompleted { batch_id, .. } if *batch_id == id
+    )));
+
+    ch.append_row(&serde_json::json!({"id": 3})).await.unwrap();
+    assert_ne!(ch.last_append().unwrap().batch_id, id);
+    let err = ch.append_row(&serde_json::json!(4)).await.unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err}");
+}
+
+#[tokio::test]
+async fn client_initialized_reports_effective_settings() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut config = token_config(&server.uri());
+    config.retry = Some(RetryPlan::aggressive());
+    config.scoped_token_max_age = Some(Duration::from_secs(600));
+    let sink = Arc::new(RecordingSink::default());
+    StreamingIngestClient::<u64>::new_with_telemetry(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        config,
+        sink.clone(),
+    )
+    .await
+    .expect("client");
+
+    let events = sink.events.lock().unwrap();
+    let initialized = events
+        .iter()
+        .filter_map(|e| match e {
+            TelemetryEvent::ClientInitialized { settings } => Some(settings),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(initialized.len(), 1, "{events:?}");
+    let settings = initialized[0];
+    assert_eq!(settings.ingest_host, server.uri());
+    assert_eq!(
+        settings.control_host.as_deref(),
+        Some(server.uri().as_str())
+    );
+    assert_eq!(settings.auth_scheme, AuthScheme::KeypairJwt);
+    assert_eq!(settings.auth_mode, AuthMode::Provided);
+    assert_eq!(settings.retry, RetryPlan::aggressive());
+    assert_eq!(settings.jwt_refresh_margin, None);
+    assert_eq!(
+        settings.scoped_token_refresh_margin,
+        Duration::from_secs(60)
+    );
+    assert_eq!(
+        settings.scoped_token_max_age,
+        Some(Duration::from_secs(600))
+    );
+    assert_eq!(
+        settings.features.contains(&"crypto"),
+        cfg!(feature = "crypto")
+    );
+
+    let event = TelemetryEvent::ClientInitialized {
+        settings: settings.clone(),
+    };
+    let (logs, ()) = with_captured_logs(|| TracingTelemetry.record(&event));
+    let line = logs.concat();
+    assert!(line.contains("client.initialized"), "{line}");
+    assert!(line.contains("retry_max_attempts=5"), "{line}");
+    assert!(line.contains("auth_mode=Provided"), "{line}");
+}
@@ -1,7 +1,13 @@
-use crate::Config;
+// Shared helpers; most consumers exercise key-pair auth and need `crypto`.
+#![cfg_attr(not(feature = "crypto"), allow(dead_code))]
+
+use crate::{Config, StreamingIngestClient};
+use serde::Serialize;
 use std::sync::{Arc, Mutex};
 use tracing::subscriber::{DefaultGuard, set_default};
 use tracing_subscriber::{Registry, fmt, layer::SubscriberExt};
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
 pub const FIXTURE_PRIVATE_KEY: &str = include_str!("../../tests/fixtures/id_rsa.pem");
 
@@ -20,6 +26,134 @@ pub fn base_config(server_uri: &str) -> Config {
     )
 }
 
+pub const ROWS_PATH: &str =
+    "/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/ch/rows";
+const CHANNEL_PATH: &str = "/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/ch";
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+const ANY_CHANNEL_PATH: &str =
+    "^/v2/streaming/databases/db/schemas/schema/pipes/pipe/channels/[^/]+$";
+const ANY_ROWS_PATH: &str =
+    "^/v2/streaming/data/databases/db/schemas/schema/pipes/pipe/channels/[^/]+/rows$";
+
+/// Config authenticating with a pre-generated token, so no key is needed.
+pub fn token_config(server_uri: &str) -> Config {
+    Config::from_values(
+        "user",
+        None,
+        "acct",
+        server_uri,
+        Some("jwt".into()),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+/// Mounts successful discovery, token, open, append, and close responses for
+/// any channel on `db.schema.pipe` (named `ch` in most tests).
+pub async fn mount_ingest_mocks(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .mount(server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex(ANY_CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/open_channel_response.json"
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(ANY_ROWS_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path_regex(ANY_CHANNEL_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(server)
+        .await;
+}
+
+/// Answers `PUT` (open) or `POST` (bulk status) for channel `ch` with `body`,
+/// ahead of [`mount_ingest_mocks`], to report a committed offset.
+pub async fn mount_committed(server: &MockServer, method_name: &str, body: serde_json::Value) {
+    let route = if method_name == "PUT" {
+        CHANNEL_PATH
+    } else {
+        STATUS_PATH
+    };
+    Mock::given(method(method_name))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .with_priority(1)
+        .mount(server)
+        .await;
+}
+
+/// Client for `db.schema.pipe` built from `config`.
+pub async fn client<R: Serialize + Clone>(config: Config) -> StreamingIngestClient<R> {
+    StreamingIngestClient::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client construction should succeed")
+}
+
+/// Bodies of the append requests posted to [`ROWS_PATH`], in order.
+pub async fn posted_bodies(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .map(|r| String::from_utf8(r.body.clone()).unwrap())
+        .collect()
+}
+
+/// Offset tokens of the append requests posted to [`ROWS_PATH`], in order.
+pub async fn pushed_offsets(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .filter_map(|r| {
+            r.url
+                .query_pairs()
+                .find(|(k, _)| k == "offsetToken")
+                .map(|(_, v)| v.into_owned())
+        })
+        .collect()
+}
+
+/// Number of requests the server received on `request_path`.
+pub async fn requests_to(server: &MockServer, request_path: &str) -> usize {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == request_path)
+        .count()
+}
+
+/// Number of append requests posted to [`ROWS_PATH`].
+pub async fn rows_requests(server: &MockServer) -> usize {
+    requests_to(server, ROWS_PATH).await
+}
+
 struct VecWriter {
     lines: Arc<Mutex<Vec<String>>>,
 }
@@ -55,8 +189,10 @@ pub fn capture_logs() -> (Arc<Mutex<Vec<String>>>, DefaultGuard) {
     (lines, guard)
 }
 
+/// Takes the captured lines. Spans still held (e.g. by an open channel)
+/// keep the subscriber, and so `lines`, alive.
 pub fn drain_logs(lines: Arc<Mutex<Vec<String>>>) -> Vec<String> {
-    Arc::try_unwrap(lines).unwrap().into_inner().unwrap()
+    std::mem::take(&mut *lines.lock().unwrap())
 }
 
 pub fn with_captured_logs<F, T>(f: F) -> (Vec<String>, T)
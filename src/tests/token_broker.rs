@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wiremock::matchers::{header, method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Error, ScopedTokenProvider, StreamingIngestClient};
+
+fn counting_provider(calls: Arc<AtomicUsize>) -> Arc<dyn ScopedTokenProvider> {
+    Arc::new(move || {
+        let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        async move { Ok(format!("broker-{n}")) }
+    })
+}
+
+#[tokio::test]
+async fn provider_tokens_are_used_and_refreshed_on_401() {
+    let server = MockServer::start().await;
+    for (verb, route) in [("GET", "/v2/streaming/hostname"), ("POST", "/oauth/token")] {
+        Mock::given(method(verb))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("PUT"))
+        .and(path_regex("/channels/ch$"))
+        .and(header("authorization", "Bearer broker-1"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .and(header("authorization", "Bearer broker-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!(
+            "../../tests/fixtures/append_rows_response.json"
+        )))
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut cfg = token_config("");
+    cfg.jwt_token = None;
+    cfg.ingest_host = Some(server.uri());
+    let mut client = StreamingIngestClient::<u64>::new_with_token_provider(
+        "worker",
+        "db",
+        "schema",
+        "pipe",
+        cfg,
+        counting_provider(calls.clone()),
+    )
+    .await
+    .expect("client");
+    assert_eq!(client.ingest_host.as_deref(), Some(server.uri().as_str()));
+
+    let mut ch = client.open_channel("ch").await.expect("open after refresh");
+    ch.append_row(&1).await.expect("append");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn provider_errors_and_missing_ingest_host_fail_construction() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let err = StreamingIngestClient::<u64>::new_with_token_provider(
+        "worker",
+        "db",
+        "schema",
+        "pipe",
+        token_config(""),
+        counting_provider(calls.clone()),
+    )
+    .await
+    .err()
+    .expect("ingest_host required");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("ingest_host")));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    let mut cfg = token_config("");
+    cfg.ingest_host = Some("https://ingest.example.invalid".into());
+    let failing: Arc<dyn ScopedTokenProvider> =
+        Arc::new(|| async { Err(Error::Auth("broker unavailable".into())) });
+    let err = StreamingIngestClient::<u64>::new_with_token_provider(
+        "worker", "db", "schema", "pipe", cfg, failing,
+    )
+    .await
+    .err()
+    .expect("provider error surfaces");
+    assert!(matches!(err, Error::Auth(msg) if msg.contains("broker unavailable")));
+}
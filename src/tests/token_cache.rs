@@ -0,0 +1,59 @@
+use super::test_support::{mount_ingest_mocks, token_config};
+use crate::{StreamingIngestClient, TokenCache};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Expects discovery and the scoped token exchange `times` times each.
+async fn expect_control_plane_calls(server: &MockServer, times: u64) {
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .with_priority(1)
+        .expect(times)
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .with_priority(1)
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn clients_sharing_a_cache_exchange_tokens_once() {
+    let server = MockServer::start().await;
+    expect_control_plane_calls(&server, 1).await;
+    mount_ingest_mocks(&server).await;
+
+    let cache = TokenCache::new();
+    let config = token_config(&server.uri()).token_cache(&cache);
+    let mut orders =
+        StreamingIngestClient::<u64>::new("orders", "db", "schema", "pipe", config.clone())
+            .await
+            .expect("first client");
+    let events = StreamingIngestClient::<u64>::new("events", "db", "schema", "pipe2", config)
+        .await
+        .expect("second client");
+    assert_eq!(cache.len(), 1);
+    assert_eq!(events.ingest_host, orders.ingest_host);
+
+    orders.open_channel("ch").await.expect("open channel");
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn separate_caches_do_not_share() {
+    let server = MockServer::start().await;
+    expect_control_plane_calls(&server, 2).await;
+    mount_ingest_mocks(&server).await;
+
+    for _ in 0..2 {
+        let config = token_config(&server.uri()).token_cache(&TokenCache::new());
+        StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client");
+    }
+    server.verify().await;
+}
@@ -0,0 +1,47 @@
+use crate::StreamingIngestClient;
+use crate::tests::test_support::base_config;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn reports_managed_jwt_and_scoped_token_times() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v2/streaming/hostname"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(server.uri()))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("scoped-token"))
+        .mount(&server)
+        .await;
+
+    #[derive(serde::Serialize, Clone)]
+    struct Row;
+
+    let before = jiff::Timestamp::now();
+    let client = StreamingIngestClient::<Row>::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        base_config(&server.uri()),
+    )
+    .await
+    .expect("client construction should succeed");
+
+    let info = client.current_token_info().await;
+
+    assert!(info.control.managed && !info.control.scoped);
+    let issued = info.control.issued_at.expect("control JWT issued");
+    let expires = info.control.expires_at.expect("control JWT expiry known");
+    assert_eq!(expires.duration_since(issued).as_secs(), 120);
+
+    assert!(info.scoped.scoped);
+    let acquired = info.scoped.issued_at.expect("scoped token acquired");
+    assert!(acquired >= before, "scoped token acquired after start");
+    assert_eq!(info.scoped.expires_at, None, "opaque token has no expiry");
+}
@@ -0,0 +1,129 @@
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+use tower::{Service, ServiceBuilder, ServiceExt};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::tests::test_support::{ROWS_PATH, mount_ingest_mocks, token_config};
+use crate::{Batch, ChannelService, StreamingIngestClient};
+
+async fn service(server: &MockServer, capacity: usize) -> ChannelService<u64> {
+    mount_ingest_mocks(server).await;
+    let mut client = StreamingIngestClient::new(
+        "client",
+        "db",
+        "schema",
+        "pipe",
+        token_config(&server.uri()),
+    )
+    .await
+    .expect("client");
+    let channel = client.open_channel("ch").await.expect("open channel");
+    ChannelService::new(channel, NonZeroUsize::new(capacity).unwrap())
+}
+
+/// Bodies and offset tokens of the append requests received, in order.
+async fn appends(server: &MockServer) -> Vec<(String, String)> {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.url.path() == ROWS_PATH)
+        .map(|r| {
+            let offset = r
+                .url
+                .query_pairs()
+                .find(|(k, _)| k == "offsetToken")
+                .map(|(_, v)| v.into_owned())
+                .unwrap_or_default();
+            (String::from_utf8(r.body).unwrap(), offset)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn poll_ready_waits_while_accepted_batches_are_pending() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(ROWS_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(include_str!(
+                    "../../tests/fixtures/append_rows_response.json"
+                ))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let mut svc = service(&server, 1).await;
+
+    let first = svc.ready().await.expect("ready").call(Batch::new([1, 2]));
+    let full = tokio::time::timeout(Duration::from_millis(100), svc.ready()).await;
+    assert!(full.is_err(), "no slot while the first batch is pending");
+
+    let sent = first.await.expect("first batch");
+    let second = svc
+        .ready()
+        .await
+        .expect("slot freed")
+        .call(Batch::at(40, [3]));
+    assert_eq!(second.await.expect("second batch"), 1);
+
+    let channel = svc.into_inner().await;
+    assert_eq!(channel.last_append().map(|report| report.offset), Some(40));
+    let appends = appends(&server).await;
+    assert_eq!(appends.len(), 2, "{appends:?}");
+    assert_eq!(appends[0].0, "1\n2");
+    assert_eq!(sent, appends[0].0.len());
+    assert_eq!(appends[1], ("3".to_string(), "40".to_string()));
+}
+
+#[tokio::test]
+async fn middleware_composes_and_batches_keep_call_order() {
+    let server = MockServer::start().await;
+    let svc = service(&server, 4).await;
+    let mut svc = ServiceBuilder::new().concurrency_limit(2).service(svc);
+
+    let mut pending = Vec::new();
+    for id in 0..6 {
+        pending.push(svc.ready().await.expect("ready").call(Batch::new([id])));
+        if pending.len() == 2 {
+            for batch in pending.drain(..) {
+                batch.await.expect("append");
+            }
+        }
+    }
+    for batch in pending {
+        batch.await.expect("append");
+    }
+
+    let bodies = appends(&server)
+        .await
+        .into_iter()
+        .map(|(body, _)| body)
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, ["0", "1", "2", "3", "4", "5"]);
+}
+
+#[tokio::test]
+async fn dropped_response_futures_still_advance_the_channel() {
+    let server = MockServer::start().await;
+    let mut svc = service(&server, 2).await;
+
+    drop(svc.ready().await.expect("ready").call(Batch::new([1])));
+    let second = svc.ready().await.expect("ready").call(Batch::new([2]));
+    second.await.expect("second batch");
+
+    svc.into_inner().await;
+    let appends = appends(&server).await;
+    assert_eq!(appends.len(), 2, "{appends:?}");
+    assert_eq!(appends[0].0, "1");
+    assert_eq!(appends[1].0, "2");
+    assert_ne!(
+        appends[0].1, appends[1].1,
+        "offsets advance past the dropped batch"
+    );
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::tests::test_support::{mount_ingest_mocks, token_config};
+use crate::{Error, StreamingIngestClient};
+
+const STATUS_PATH: &str =
+    "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status";
+
+/// Serves the committed token held in the returned counter.
+async fn mount_committed(server: &MockServer) -> Arc<AtomicU64> {
+    let committed = Arc::new(AtomicU64::new(0));
+    let token = committed.clone();
+    Mock::given(method("POST"))
+        .and(path(STATUS_PATH))
+        .respond_with(move |_req: &Request| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "channel_statuses": {"ch": {
+                    "last_committed_offset_token": token.load(Ordering::SeqCst).to_string()
+                }}
+            }))
+        })
+        .mount(server)
+        .await;
+    committed
+}
+
+async fn status_polls(server: &MockServer) -> usize {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == STATUS_PATH)
+        .count()
+}
+
+#[tokio::test]
+async fn fail_mode_errors_once_the_limit_is_reached() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let committed = mount_committed(&server).await;
+    let mut config = token_config(&server.uri());
+    config.max_uncommitted_offsets = Some(2);
+    config.fail_on_uncommitted_limit = Some(true);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+
+    ch.append_row(&1).await.expect("first append");
+    ch.append_row(&2).await.expect("second append");
+    assert_eq!(status_polls(&server).await, 0, "under the limit");
+
+    let err = ch.append_row(&3).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::UncommittedLimit(ch, 2) if ch == "ch"),
+        "{err}"
+    );
+    assert!(err.is_retryable());
+
+    committed.store(1, Ordering::SeqCst);
+    ch.append_row(&3).await.expect("room after a commit");
+    assert_eq!(status_polls(&server).await, 2);
+}
+
+#[tokio::test]
+async fn wait_mode_polls_until_commits_catch_up() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let committed = mount_committed(&server).await;
+    let mut config = token_config(&server.uri());
+    config.max_uncommitted_offsets = Some(1);
+    let mut client = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client");
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("first append");
+
+    let commit = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        committed.store(1, Ordering::SeqCst);
+    });
+    ch.append_row(&2)
+        .await
+        .expect("append waits for the commit");
+    commit.await.unwrap();
+    assert!(status_polls(&server).await >= 2, "polled while full");
+}
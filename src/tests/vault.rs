@@ -0,0 +1,175 @@
+use crate::Error;
+use crate::vault::VaultConfigSource;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn loads_config_and_key_from_kv_and_renews_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/kv/data/snowflake/ingest"))
+        .and(header("X-Vault-Token", "s.token"))
+        .and(header("X-Vault-Namespace", "team"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": {
+                "data": {"user": "svc", "account": "acct", "jwt_exp": 120, "pem": "KEY"},
+                "metadata": {"version": 3}
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/token/renew-self"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "auth": {"client_token": "s.token", "lease_duration": 3600, "renewable": true}
+        })))
+        .mount(&server)
+        .await;
+
+    let vault = VaultConfigSource::new(format!("{}/", server.uri()), "s.token")
+        .namespace("team")
+        .mount("kv");
+    assert_eq!(
+        vault.private_key("snowflake/ingest", "pem").await.unwrap(),
+        "KEY"
+    );
+    let err = vault
+        .load_config("/snowflake/ingest/")
+        .await
+        .err()
+        .expect("unknown keys are rejected");
+    assert!(
+        matches!(&err, Error::Config(msg) if msg.contains("pem")),
+        "{err}"
+    );
+    assert!(matches!(
+        vault.private_key("snowflake/ingest", "missing").await,
+        Err(Error::Key(_))
+    ));
+    assert!(matches!(
+        vault.load_config("snowflake/other").await,
+        Err(Error::Http(status, _)) if status == 404
+    ));
+    assert_eq!(
+        vault.renew_token().await.unwrap(),
+        std::time::Duration::from_secs(3600)
+    );
+}
+
+#[tokio::test]
+async fn config_secret_without_extra_keys_loads() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/secret/data/snowflake"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": {"data": {"user": "svc", "account": "acct", "jwt_exp": 120}}
+        })))
+        .mount(&server)
+        .await;
+    let config = VaultConfigSource::new(server.uri(), "s.token")
+        .load_config("snowflake")
+        .await
+        .unwrap();
+    assert_eq!(
+        (config.user.as_str(), config.jwt_exp),
+        ("svc", Some(std::time::Duration::from_secs(120)))
+    );
+}
+
+#[cfg(feature = "crypto")]
+#[tokio::test]
+async fn transit_signer_signs_control_plane_jwts() {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use pkcs8::{DecodePrivateKey as _, EncodePublicKey as _};
+    use wiremock::Request;
+
+    use crate::tests::test_support::{FIXTURE_PRIVATE_KEY, mount_ingest_mocks};
+    use crate::vault::VaultTransitSigner;
+    use crate::{Config, StreamingIngestClient};
+
+    let private_key = rsa::RsaPrivateKey::from_pkcs8_pem(FIXTURE_PRIVATE_KEY).unwrap();
+    let public_pem = private_key
+        .to_public_key()
+        .to_public_key_pem(pkcs8::LineEnding::LF)
+        .unwrap();
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/transit/keys/snowflake-ingest"))
+        .and(header("X-Vault-Token", "s.token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": {
+                "type": "rsa-2048",
+                "latest_version": 2,
+                "keys": {"1": {"public_key": "retired"}, "2": {"public_key": public_pem}}
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/transit/sign/snowflake-ingest/sha2-256"))
+        .respond_with(|req: &Request| {
+            let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+            let input = STANDARD.decode(body["input"].as_str().unwrap()).unwrap();
+            let key =
+                jsonwebtoken::EncodingKey::from_rsa_pem(FIXTURE_PRIVATE_KEY.as_bytes()).unwrap();
+            let signature =
+                jsonwebtoken::crypto::sign(&input, &key, jsonwebtoken::Algorithm::RS256).unwrap();
+            let signature = STANDARD.encode(URL_SAFE_NO_PAD.decode(signature).unwrap());
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {"signature": format!("vault:v2:{signature}"), "key_version": 2}
+            }))
+        })
+        .mount(&server)
+        .await;
+    mount_ingest_mocks(&server).await;
+
+    let vault = VaultConfigSource::new(server.uri(), "s.token");
+    let config = Config::from_values(
+        "user",
+        None,
+        "acct",
+        server.uri(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(120),
+    )
+    .jwt_signer(VaultTransitSigner::new(&vault, "snowflake-ingest"));
+    StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client authenticates with a Transit-signed JWT");
+
+    let requests = server.received_requests().await.unwrap();
+    let discovery = requests
+        .iter()
+        .find(|r| r.url.path() == "/v2/streaming/hostname")
+        .expect("ingest host discovery");
+    let jwt = discovery.headers["authorization"]
+        .to_str()
+        .unwrap()
+        .strip_prefix("Bearer ")
+        .unwrap();
+    let claims = jsonwebtoken::decode::<serde_json::Value>(
+        jwt,
+        &jsonwebtoken::DecodingKey::from_rsa_pem(public_pem.as_bytes()).unwrap(),
+        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS256),
+    )
+    .expect("signature verifies against the Transit public key")
+    .claims;
+    let fingerprint =
+        crate::client::crypto::compute_fingerprint(&private_key.to_public_key()).unwrap();
+    assert_eq!(claims["iss"], format!("ACCT.USER.{fingerprint}"));
+    assert_eq!(claims["sub"], "ACCT.USER");
+
+    let sign = requests
+        .iter()
+        .find(|r| r.url.path().starts_with("/v1/transit/sign/"))
+        .expect("sign request");
+    let body: serde_json::Value = serde_json::from_slice(&sign.body).unwrap();
+    assert_eq!(body["signature_algorithm"], "pkcs1v15");
+    assert_eq!(body["key_version"], 2);
+}
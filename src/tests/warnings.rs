@@ -0,0 +1,188 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::test_support::{mount_ingest_mocks, token_config};
+use crate::{Config, Lint, StreamingIngestClient, Warning};
+use wiremock::MockServer;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn collect_warnings(config: Config) -> (Config, Arc<Mutex<Vec<Warning>>>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let config = config.on_warning(move |warning| sink.lock().unwrap().push(warning.clone()));
+    (config, warnings)
+}
+
+#[tokio::test]
+async fn handler_receives_deprecation_and_commit_lag_warnings() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "0"}}
+        })))
+        .mount(&server)
+        .await;
+
+    let (config, warnings) = collect_warnings(token_config(&server.uri()));
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+    assert_eq!(
+        warnings.lock().unwrap().as_slice(),
+        [Warning::DeprecatedConfig {
+            field: "jwt_token",
+            advice: "supply a private key so the library can refresh automatically",
+        }]
+    );
+
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.set_commit_lag_alert(Duration::ZERO, |_, _| {});
+    ch.append_row(&serde_json::json!({"id": 1}))
+        .await
+        .expect("append");
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    ch.get_latest_committed_offset_token().await;
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    match &warnings[1] {
+        Warning::CommitLagExceeded {
+            channel,
+            lag,
+            threshold,
+        } => {
+            assert_eq!(channel, "ch");
+            assert!(lag > threshold);
+        }
+        other => panic!("unexpected warning {other:?}"),
+    }
+    assert!(warnings[1].to_string().contains("exceeds threshold"));
+}
+
+#[cfg(feature = "crypto")]
+#[tokio::test]
+async fn handler_receives_clamped_jwt_lifetime() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+
+    let mut config = super::test_support::base_config(&server.uri());
+    config.jwt_exp = Some(Duration::from_secs(7200));
+    let (config, warnings) = collect_warnings(config);
+    StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client construction should succeed");
+
+    assert_eq!(
+        warnings.lock().unwrap().as_slice(),
+        [Warning::JwtLifetimeClamped {
+            original_secs: 7200,
+            effective_secs: 3600,
+        }]
+    );
+}
+
+fn misuse(warnings: &Mutex<Vec<Warning>>) -> Vec<(Lint, String)> {
+    warnings
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|warning| match warning {
+            Warning::Misuse { lint, subject } => Some((*lint, subject.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn misuse_is_reported_once_per_channel() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/v2/streaming/databases/db/schemas/schema/pipes/pipe:bulk-channel-status",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "channel_statuses": {"ch": {"last_committed_offset_token": "1000"}}
+        })))
+        .mount(&server)
+        .await;
+    let mut config = token_config(&server.uri());
+    config.max_rows_per_request = Some(1);
+    let (config, warnings) = collect_warnings(config);
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    for id in 0..150 {
+        ch.append_row(&serde_json::json!({ "id": id }))
+            .await
+            .expect("append");
+    }
+    ch.append_rows_iter((0..10).map(|id| serde_json::json!({ "id": id })))
+        .await
+        .expect("append");
+    ch.close().await.expect("close");
+    drop(ch);
+    drop(client.open_channel("dropped").await.expect("open channel"));
+
+    let reported = misuse(&warnings);
+    assert_eq!(
+        reported,
+        [
+            (Lint::SingleRowAppends, "ch".to_string()),
+            (Lint::ChunkingThrash, "ch".to_string()),
+            (Lint::ChannelNotClosed, "dropped".to_string()),
+        ]
+    );
+    let text = warnings.lock().unwrap().last().unwrap().to_string();
+    assert!(text.starts_with("channel_not_closed (dropped): "), "{text}");
+}
+
+#[tokio::test]
+async fn misuse_warnings_can_be_disabled() {
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let mut config = token_config(&server.uri());
+    config.misuse_warnings = Some(false);
+    let (config, warnings) = collect_warnings(config);
+    let mut client =
+        StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+            .await
+            .expect("client construction should succeed");
+    drop(client.open_channel("ch").await.expect("open channel"));
+    assert!(misuse(&warnings).is_empty());
+}
+
+#[tokio::test]
+async fn expiring_static_token_is_reported() {
+    use base64::Engine as _;
+
+    let server = MockServer::start().await;
+    mount_ingest_mocks(&server).await;
+    let exp = jiff::Timestamp::now().as_second() + 60;
+    let encode = |json: &str| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json);
+    let token = format!(
+        "{}.{}.sig",
+        encode(r#"{"alg":"none"}"#),
+        encode(&format!(r#"{{"exp":{exp}}}"#))
+    );
+    let mut config = token_config(&server.uri());
+    config.jwt_token = Some(token);
+    let (config, warnings) = collect_warnings(config);
+    StreamingIngestClient::<serde_json::Value>::new("client", "db", "schema", "pipe", config)
+        .await
+        .expect("client construction should succeed");
+
+    assert_eq!(
+        misuse(&warnings),
+        [(Lint::StaticTokenExpiring, "jwt_token".to_string())]
+    );
+}
@@ -0,0 +1,124 @@
+use wiremock::MockServer;
+
+use crate::tests::test_support::{client, mount_committed, mount_ingest_mocks, token_config};
+use crate::{Config, Error, StreamingIngestClient};
+
+fn token(generation: u64, sequence: u64) -> String {
+    ((generation << 40) + sequence).to_string()
+}
+
+fn generation_config(server: &MockServer, generation: u32) -> Config {
+    let mut cfg = token_config(&server.uri());
+    cfg.writer_generation = Some(generation);
+    cfg
+}
+
+#[tokio::test]
+async fn new_generation_takes_over_with_higher_offsets() {
+    let server = MockServer::start().await;
+    mount_committed(
+        &server,
+        "PUT",
+        serde_json::json!({
+            "next_continuation_token": "ctok",
+            "channel_status": {"last_committed_offset_token": token(1, 7)}
+        }),
+    )
+    .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut client = client::<u64>(generation_config(&server, 2)).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append");
+    ch.append_row(&2).await.expect("append");
+
+    let offsets: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path().ends_with("/rows"))
+        .filter_map(|r| {
+            r.url
+                .query_pairs()
+                .find(|(k, _)| k == "offsetToken")
+                .map(|(_, v)| v.into_owned())
+        })
+        .collect();
+    assert_eq!(offsets, [token(2, 1), token(2, 2)]);
+}
+
+#[tokio::test]
+async fn stale_generation_refuses_to_open() {
+    let server = MockServer::start().await;
+    mount_committed(
+        &server,
+        "PUT",
+        serde_json::json!({
+            "next_continuation_token": "ctok",
+            "channel_status": {"last_committed_offset_token": token(3, 1)}
+        }),
+    )
+    .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut client = client::<u64>(generation_config(&server, 2)).await;
+    let err = client.open_channel("ch").await.err().expect("fenced");
+    assert!(matches!(err, Error::WriterFenced(2, 3)), "{err:?}");
+}
+
+#[tokio::test]
+async fn newer_generation_seen_in_status_stops_appends() {
+    let server = MockServer::start().await;
+    let mut status: serde_json::Value = serde_json::from_str(include_str!(
+        "../../tests/fixtures/channel_status_response.json"
+    ))
+    .unwrap();
+    status["channel_statuses"]["ch"]["last_committed_offset_token"] = token(5, 1).into();
+    mount_committed(&server, "POST", status).await;
+    mount_ingest_mocks(&server).await;
+
+    let mut client = client::<u64>(generation_config(&server, 4)).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("append before takeover");
+    ch.get_latest_committed_offset_token().await;
+
+    let err = ch.append_row(&2).await.unwrap_err();
+    assert!(matches!(err, Error::WriterFenced(4, 5)), "{err:?}");
+    let err = ch.append_row_async(&3).await.unwrap_err();
+    assert!(matches!(err, Error::WriterFenced(4, 5)), "{err:?}");
+}
+
+#[tokio::test]
+async fn generations_must_fit_in_the_offset_token() {
+    let server = MockServer::start().await;
+    let highest = (1 << 24) - 1;
+    mount_committed(
+        &server,
+        "PUT",
+        serde_json::json!({
+            "next_continuation_token": "ctok",
+            "channel_status": {"last_committed_offset_token": token(highest, 1)}
+        }),
+    )
+    .await;
+    mount_ingest_mocks(&server).await;
+
+    let mut client = client::<u64>(generation_config(&server, highest as u32)).await;
+    let mut ch = client.open_channel("ch").await.expect("open channel");
+    ch.append_row(&1).await.expect("highest generation appends");
+
+    let too_high = generation_config(&server, 1 << 24);
+    let issues = too_high.validate().unwrap_err();
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.field == "writer_generation"),
+        "{issues:?}"
+    );
+    let err = StreamingIngestClient::<u64>::new("client", "db", "schema", "pipe", too_high)
+        .await
+        .err()
+        .expect("generation too high");
+    assert!(matches!(err, Error::Config(msg) if msg.contains("writer_generation")));
+}
@@ -1,33 +1,181 @@
-use serde::Deserialize;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use tracing::debug;
+
+/// Options for opening a channel, passed to
+/// [`StreamingIngestClient::open_channel_with_options`].
+///
+/// `offset_token` is sent as the request body; the default options serialize
+/// to `{}`, which is what
+/// [`open_channel`](crate::StreamingIngestClient::open_channel) sends. The
+/// other settings override the client's configuration for this channel only,
+/// e.g. to give latency-critical channels short flush intervals while bulk
+/// backfill channels batch heavily.
+///
+/// ```
+/// use snowpipe_streaming::OpenChannelOptions;
+///
+/// let options = OpenChannelOptions::new().offset_token(41);
+/// assert_eq!(serde_json::to_string(&options).unwrap(), r#"{"offset_token":"41"}"#);
+/// ```
+///
+/// [`StreamingIngestClient::open_channel_with_options`]: crate::StreamingIngestClient::open_channel_with_options
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OpenChannelOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset_token: Option<String>,
+    #[serde(skip)]
+    pub(crate) retry_on_unauthorized: Option<bool>,
+    #[serde(skip)]
+    pub(crate) rate_limit_backoff: Option<Duration>,
+    #[serde(skip)]
+    pub(crate) attempt_timeout: Option<Duration>,
+    #[serde(skip)]
+    pub(crate) max_rows_per_request: Option<NonZeroUsize>,
+    #[serde(skip)]
+    pub(crate) max_requests_per_second: Option<NonZeroU32>,
+    #[serde(skip)]
+    pub(crate) max_batch_bytes: Option<usize>,
+    #[serde(skip)]
+    pub(crate) flush_interval: Option<Duration>,
+}
+
+impl OpenChannelOptions {
+    /// Options that open the channel as-is, resuming from its last committed
+    /// offset token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the channel's committed offset token when it is opened, e.g. to
+    /// resume from an offset recorded outside Snowflake. Appends continue
+    /// from `token + 1`.
+    pub fn offset_token(mut self, token: u64) -> Self {
+        self.offset_token = Some(token.to_string());
+        self
+    }
+
+    /// Whether this channel's requests are retried once after a 401,
+    /// overriding `Config::retry_on_unauthorized`.
+    pub fn retry_on_unauthorized(mut self, retry: bool) -> Self {
+        self.retry_on_unauthorized = Some(retry);
+        self
+    }
+
+    /// How long this channel waits before its first retry of a request
+    /// throttled with 429, instead of the `initial_backoff` of the client's
+    /// [`RetryPlan`](crate::RetryPlan).
+    pub fn rate_limit_backoff(mut self, delay: Duration) -> Self {
+        self.rate_limit_backoff = Some(delay);
+        self
+    }
+
+    /// Limit on each attempt of this channel's requests, overriding
+    /// [`RetryPlan::attempt_timeout`](crate::RetryPlan::attempt_timeout).
+    pub fn attempt_timeout(mut self, limit: Duration) -> Self {
+        self.attempt_timeout = Some(limit);
+        self
+    }
+
+    /// Upper bound on rows per append request, overriding
+    /// `Config::max_rows_per_request`.
+    pub fn max_rows_per_request(mut self, max_rows: NonZeroUsize) -> Self {
+        self.max_rows_per_request = Some(max_rows);
+        self
+    }
+
+    /// Upper bound on append requests per second, overriding
+    /// `Config::max_requests_per_second`.
+    pub fn max_requests_per_second(mut self, per_second: NonZeroU32) -> Self {
+        self.max_requests_per_second = Some(per_second);
+        self
+    }
+
+    /// Largest request body the background appender builds from rows queued
+    /// with `append_row_async`, and `append_ndjson_reader` from the lines it
+    /// reads, up to the 16MB limit (the default).
+    pub fn max_batch_bytes(mut self, bytes: usize) -> Self {
+        self.max_batch_bytes = Some(bytes);
+        self
+    }
+
+    /// How long the background appender waits for more queued rows before
+    /// sending a batch. By default it sends whatever is queued immediately.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+}
+
+// Response types ignore fields they do not know and capture them in `extra`,
+// so fields added by newer server releases never fail a request mid-ingest.
+// Only the continuation token is required: without it no further request can
+// be made on the channel.
 
 #[derive(Deserialize)]
 pub struct AppendRowsResponse {
     pub next_continuation_token: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Deserialize)]
 pub struct OpenChannelResponse {
     pub next_continuation_token: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub channel_status: ChannelStatus,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
 pub struct ChannelStatus {
+    #[serde(deserialize_with = "null_as_default")]
     database_name: String,
+    #[serde(deserialize_with = "null_as_default")]
     schema_name: String,
+    #[serde(deserialize_with = "null_as_default")]
     pipe_name: String,
+    #[serde(deserialize_with = "null_as_default")]
     channel_name: String,
+    #[serde(deserialize_with = "null_as_default")]
     channel_status_code: String,
     pub last_committed_offset_token: Option<String>,
+    #[serde(deserialize_with = "null_as_default")]
     created_on_ms: u64,
-    rows_inserted: Option<i32>,
-    rows_parsed: Option<i32>,
-    rows_errors: Option<i32>,
+    rows_inserted: Option<i64>,
+    rows_parsed: Option<i64>,
+    rows_errors: Option<i64>,
     last_error_offset_upper_bound: Option<String>,
     last_error_message: Option<String>,
     last_error_timestamp: Option<u64>, // timestamp_utc
-    snowflake_avg_processing_latency_ms: Option<i32>,
+    pub(crate) snowflake_avg_processing_latency_ms: Option<i32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Reads an explicit `null` as the field's default, like a missing field.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Logs the names of response fields this client does not recognize.
+pub(crate) fn debug_unknown_fields(response: &str, extra: &Map<String, Value>) {
+    if !extra.is_empty() {
+        debug!(
+            "{} response has unrecognized fields: {:?}",
+            response,
+            extra.keys().collect::<Vec<_>>()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +201,15 @@ mod tests {
         assert_eq!(resp.channel_status.channel_name, "ch");
     }
 
+    #[test]
+    fn serialize_open_channel_options() {
+        let body = serde_json::to_value(OpenChannelOptions::new()).unwrap();
+        assert_eq!(body, serde_json::json!({}));
+
+        let body = serde_json::to_value(OpenChannelOptions::new().offset_token(7)).unwrap();
+        assert_eq!(body, serde_json::json!({ "offset_token": "7" }));
+    }
+
     #[test]
     fn parse_append_rows_response() {
         let json = r#"{ "next_continuation_token": "ctok-2" }"#;
@@ -85,4 +242,47 @@ mod tests {
         assert_eq!(status.channel_name, "ch");
         assert_eq!(status.last_committed_offset_token.as_deref(), Some("1"));
     }
+
+    #[test]
+    fn unknown_fields_are_captured_not_rejected() {
+        let json = r#"{
+          "next_continuation_token": "ctok-1",
+          "server_hint": {"retry_after_ms": 5},
+          "channel_status": {
+            "channel_name": "ch",
+            "last_committed_offset_token": "3",
+            "rows_inserted": 5000000000,
+            "compaction_state": "IDLE"
+          }
+        }"#;
+        let resp: OpenChannelResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.extra["server_hint"]["retry_after_ms"], 5);
+        assert_eq!(resp.channel_status.extra["compaction_state"], "IDLE");
+        assert_eq!(resp.channel_status.rows_inserted, Some(5_000_000_000));
+
+        let resp: AppendRowsResponse =
+            serde_json::from_str(r#"{"next_continuation_token": "c", "accepted_rows": 2}"#)
+                .unwrap();
+        assert_eq!(resp.extra["accepted_rows"], 2);
+    }
+
+    #[test]
+    fn nulls_and_missing_fields_use_defaults() {
+        let json = r#"{"next_continuation_token": "ctok-1", "channel_status": null}"#;
+        let resp: OpenChannelResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.channel_status.last_committed_offset_token, None);
+
+        let json = r#"{"next_continuation_token": "ctok-1"}"#;
+        let resp: OpenChannelResponse = serde_json::from_str(json).unwrap();
+        assert!(resp.channel_status.extra.is_empty());
+
+        let status: ChannelStatus = serde_json::from_str(
+            r#"{"channel_name": null, "created_on_ms": null, "rows_errors": null}"#,
+        )
+        .unwrap();
+        assert_eq!(status.channel_name, "");
+        assert_eq!(status.created_on_ms, 0);
+
+        assert!(serde_json::from_str::<AppendRowsResponse>("{}").is_err());
+    }
 }
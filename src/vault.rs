@@ -0,0 +1,313 @@
+//! Configuration and private keys kept in HashiCorp Vault's KV v2 secrets
+//! engine, so neither has to be on disk, and JWTs signed by Vault's Transit
+//! engine, so the private key never leaves Vault.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), snowpipe_streaming::Error> {
+//! use snowpipe_streaming::vault::VaultConfigSource;
+//!
+//! let token = std::env::var("VAULT_TOKEN").unwrap_or_default();
+//! let vault = VaultConfigSource::new("https://vault.internal:8200", token).namespace("data-platform");
+//! // A secret whose keys are `Config` fields, e.g. user, account, private_key.
+//! let config = vault.load_config("snowflake/ingest").await?;
+//! // Keep the Vault token alive for as long as the process runs.
+//! let _renewal = vault.spawn_token_renewal();
+//! # let _ = config;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{Config, Error};
+
+const DEFAULT_MOUNT: &str = "secret";
+#[cfg(feature = "crypto")]
+const DEFAULT_TRANSIT_MOUNT: &str = "transit";
+/// Wait before retrying a failed renewal.
+const RENEWAL_RETRY: Duration = Duration::from_secs(30);
+
+/// Reads [`Config`] values and private keys from a Vault KV v2 mount.
+#[derive(Clone)]
+pub struct VaultConfigSource {
+    addr: String,
+    token: String,
+    namespace: Option<String>,
+    mount: String,
+    http: reqwest::Client,
+}
+
+impl VaultConfigSource {
+    /// Talks to the Vault server at `addr` with `token`, reading from the
+    /// `secret` mount.
+    pub fn new(addr: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            namespace: None,
+            mount: DEFAULT_MOUNT.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Sends requests to this Vault Enterprise namespace.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Reads from this KV v2 mount instead of `secret`.
+    pub fn mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Builds a [`Config`] from the secret at `path`, whose keys are config
+    /// fields (as in [`Config::from_file`], including the unknown-key check).
+    /// Numbers and booleans must be stored as JSON values, not strings.
+    pub async fn load_config(&self, path: &str) -> Result<Config, Error> {
+        Config::from_json(Value::Object(self.read_secret(path).await?))
+    }
+
+    /// Reads a private key PEM from the `field` of the secret at `path`, for
+    /// keys stored apart from the rest of the configuration. Assign it to
+    /// `Config::private_key`.
+    pub async fn private_key(&self, path: &str, field: &str) -> Result<String, Error> {
+        match self.read_secret(path).await?.remove(field) {
+            Some(Value::String(pem)) => Ok(pem),
+            _ => Err(Error::Key(format!(
+                "Vault secret '{path}' has no string field '{field}'"
+            ))),
+        }
+    }
+
+    /// Extends the Vault token's lease (`auth/token/renew-self`) and returns
+    /// its new duration.
+    pub async fn renew_token(&self) -> Result<Duration, Error> {
+        let body = self
+            .request(reqwest::Method::POST, "auth/token/renew-self")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        let secs = body
+            .pointer("/auth/lease_duration")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| {
+                Error::UnexpectedResponse("Vault renewal response has no lease_duration".into())
+            })?;
+        Ok(Duration::from_secs(secs))
+    }
+
+    /// Renews the Vault token in the background, each time two thirds of the
+    /// way through its lease, until the returned task is aborted. Failures are
+    /// logged and retried after 30 seconds.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn spawn_token_renewal(&self) -> tokio::task::JoinHandle<()> {
+        let source = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let wait = match source.renew_token().await {
+                    Ok(lease) => {
+                        info!("Vault token renewed for {:?}", lease);
+                        lease * 2 / 3
+                    }
+                    Err(err) => {
+                        warn!("Vault token renewal failed: {}", err);
+                        RENEWAL_RETRY
+                    }
+                };
+                tokio::time::sleep(wait.max(Duration::from_secs(1))).await;
+            }
+        })
+    }
+
+    async fn read_secret(&self, path: &str) -> Result<serde_json::Map<String, Value>, Error> {
+        let path = path.trim_matches('/');
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!("{}/data/{}", self.mount, path),
+            )
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Http(status, body));
+        }
+        match response.json::<Value>().await?.pointer_mut("/data/data") {
+            Some(Value::Object(data)) => Ok(std::mem::take(data)),
+            _ => Err(Error::UnexpectedResponse(format!(
+                "Vault secret '{path}' is not a KV v2 secret"
+            ))),
+        }
+    }
+
+    /// Sends `request`, returning the JSON body of a successful response.
+    #[cfg(feature = "crypto")]
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value, Error> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Http(status, body));
+        }
+        Ok(response.json::<Value>().await?)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self
+            .http
+            .request(method, format!("{}/v1/{}", self.addr, path))
+            .header("X-Vault-Token", &self.token);
+        match &self.namespace {
+            Some(namespace) => builder.header("X-Vault-Namespace", namespace),
+            None => builder,
+        }
+    }
+}
+
+/// Signs control-plane JWTs with an RSA key in Vault's Transit secrets engine
+/// (`transit/sign/<key>`), so the private key never leaves Vault. Set it with
+/// [`Config::jwt_signer`]; no `private_key` is needed. Requires the `crypto`
+/// feature as well.
+///
+/// The key must be an RSA Transit key (`rsa-2048` or larger) whose public key
+/// is registered on the Snowflake user. Its latest version is read on first
+/// use and every JWT is signed with that version, so build a new signer (and
+/// client) after rotating the key.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), snowpipe_streaming::Error> {
+/// use snowpipe_streaming::vault::{VaultConfigSource, VaultTransitSigner};
+///
+/// let token = std::env::var("VAULT_TOKEN").unwrap_or_default();
+/// let vault = VaultConfigSource::new("https://vault.internal:8200", token);
+/// let config = vault
+///     .load_config("snowflake/ingest")
+///     .await?
+///     .jwt_signer(VaultTransitSigner::new(&vault, "snowflake-ingest"));
+/// # let _ = config;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "crypto")]
+#[derive(Clone)]
+pub struct VaultTransitSigner {
+    vault: VaultConfigSource,
+    mount: String,
+    key: String,
+    version: std::sync::Arc<tokio::sync::OnceCell<TransitKeyVersion>>,
+}
+
+/// The Transit key version JWTs are signed with.
+#[cfg(feature = "crypto")]
+struct TransitKeyVersion {
+    version: u64,
+    fingerprint: String,
+}
+
+#[cfg(feature = "crypto")]
+impl VaultTransitSigner {
+    /// Signs with the Transit key named `key`, talking to Vault as `vault`
+    /// does (address, token, and namespace), on the `transit` mount.
+    pub fn new(vault: &VaultConfigSource, key: impl Into<String>) -> Self {
+        Self {
+            vault: vault.clone(),
+            mount: DEFAULT_TRANSIT_MOUNT.to_string(),
+            key: key.into(),
+            version: Default::default(),
+        }
+    }
+
+    /// Uses this Transit mount instead of `transit`.
+    pub fn mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Reads the key's latest version and its public key fingerprint, once.
+    async fn key_version(&self) -> Result<&TransitKeyVersion, Error> {
+        self.version
+            .get_or_try_init(|| async {
+                let path = format!("{}/keys/{}", self.mount, self.key);
+                let body = self
+                    .vault
+                    .send(self.vault.request(reqwest::Method::GET, &path))
+                    .await?;
+                let version = body
+                    .pointer("/data/latest_version")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| {
+                        Error::UnexpectedResponse(format!(
+                            "Vault Transit key '{}' has no latest_version",
+                            self.key
+                        ))
+                    })?;
+                let pem = body
+                    .pointer(&format!("/data/keys/{version}/public_key"))
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        Error::Key(format!(
+                            "Vault Transit key '{}' has no public key; use an RSA key type",
+                            self.key
+                        ))
+                    })?;
+                let public_key =
+                    <rsa::RsaPublicKey as pkcs8::DecodePublicKey>::from_public_key_pem(pem)
+                        .map_err(|e| {
+                            Error::Key(format!(
+                                "Vault Transit key '{}' is not an RSA key: {e}",
+                                self.key
+                            ))
+                        })?;
+                Ok(TransitKeyVersion {
+                    version,
+                    fingerprint: crate::client::crypto::compute_fingerprint(&public_key)?,
+                })
+            })
+            .await
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl crate::JwtSigner for VaultTransitSigner {
+    fn public_key_fp(&self) -> crate::JwtSignerFuture<'_, String> {
+        Box::pin(async move { Ok(self.key_version().await?.fingerprint.clone()) })
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> crate::JwtSignerFuture<'a, Vec<u8>> {
+        use base64::Engine as _;
+        Box::pin(async move {
+            let engine = base64::engine::general_purpose::STANDARD;
+            let version = self.key_version().await?.version;
+            let path = format!("{}/sign/{}/sha2-256", self.mount, self.key);
+            let request =
+                self.vault
+                    .request(reqwest::Method::POST, &path)
+                    .json(&serde_json::json!({
+                        "input": engine.encode(message),
+                        "key_version": version,
+                        "signature_algorithm": "pkcs1v15",
+                    }));
+            let body = self.vault.send(request).await?;
+            // Signatures read `vault:v<version>:<base64>`.
+            let signature = body
+                .pointer("/data/signature")
+                .and_then(Value::as_str)
+                .and_then(|signature| signature.rsplit(':').next())
+                .ok_or_else(|| {
+                    Error::UnexpectedResponse("Vault Transit response has no signature".into())
+                })?;
+            engine
+                .decode(signature)
+                .map_err(|e| Error::JwtSign(format!("Vault Transit signature is not base64: {e}")))
+        })
+    }
+}
@@ -0,0 +1,301 @@
+//! Conditions worth attention that do not fail an operation.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::telemetry::TokenKind;
+use crate::{FailoverAccount, SchemaDrift, ServerApiVersion};
+
+/// A non-fatal condition reported to the handler registered with
+/// [`Config::on_warning`](crate::Config::on_warning).
+///
+/// Each warning is also logged at `WARN` (or `INFO`, for routine token
+/// refreshes) through `tracing`; the handler lets applications react to them
+/// programmatically, e.g. by alerting or failing a health check. `Display`
+/// renders a one-line description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// `jwt_exp` was outside the supported range and was clamped.
+    JwtLifetimeClamped {
+        /// Requested lifetime.
+        original_secs: u64,
+        /// Lifetime actually used.
+        effective_secs: u64,
+    },
+    /// A deprecated configuration option is in use.
+    DeprecatedConfig {
+        /// Configuration field.
+        field: &'static str,
+        /// What to use instead.
+        advice: &'static str,
+    },
+    /// A token was within its refresh margin when it was next needed, so a new
+    /// one is being fetched.
+    TokenNearExpiry {
+        /// Credential that is about to expire.
+        token: TokenKind,
+        /// Remaining lifetime of the cached token.
+        remaining_secs: u64,
+    },
+    /// A channel's commit lag exceeded the threshold set with
+    /// [`StreamingIngestChannel::set_commit_lag_alert`](crate::StreamingIngestChannel::set_commit_lag_alert).
+    CommitLagExceeded {
+        /// Channel name.
+        channel: String,
+        /// Current commit lag.
+        lag: Duration,
+        /// Configured threshold.
+        threshold: Duration,
+    },
+    /// `close` has been waiting at least another minute for Snowflake to
+    /// commit pushed offsets.
+    LongCommitWait {
+        /// Channel name.
+        channel: String,
+        /// Time spent waiting so far.
+        waited: Duration,
+        /// Last committed offset token.
+        committed: u64,
+        /// Last pushed offset token.
+        pushed: u64,
+    },
+    /// A committed offset token carried a newer writer generation; the
+    /// channel refuses further appends.
+    WriterSuperseded {
+        /// Channel name.
+        channel: String,
+        /// This writer's generation.
+        generation: u32,
+        /// Newer generation seen.
+        newer: u32,
+    },
+    /// Suspicious usage detected at runtime; reported once per lint and
+    /// subject. Disable these checks with `Config::misuse_warnings`.
+    Misuse {
+        /// What was detected.
+        lint: Lint,
+        /// Channel name, or the configuration field involved.
+        subject: String,
+    },
+    /// The deployment does not support a feature the configuration asks for,
+    /// so the client falls back to the baseline behaviour.
+    CapabilityUnavailable {
+        /// Missing capability, e.g. `tokens-in-body`.
+        capability: &'static str,
+        /// API version the deployment reported.
+        server_version: ServerApiVersion,
+    },
+    /// A channel fenced by another writer was reopened under a new name
+    /// (see `Config::fenced_channel_suffix`).
+    ChannelReopened {
+        /// Name of the fenced channel.
+        from: String,
+        /// Name of the reopened channel.
+        to: String,
+    },
+    /// The share of a channel's rows Snowflake rejected exceeded the threshold
+    /// set with
+    /// [`StreamingIngestChannel::set_quarantine_threshold`](crate::StreamingIngestChannel::set_quarantine_threshold);
+    /// further appends fail with [`Error::ChannelQuarantined`](crate::Error::ChannelQuarantined).
+    ChannelQuarantined {
+        /// Channel name.
+        channel: String,
+        /// Rows rejected since the threshold was set or the channel released.
+        rejected: u64,
+        /// Rows sent over the same period.
+        sent: u64,
+    },
+    /// Incoming rows carry fields the channel's earlier rows did not; see
+    /// [`StreamingIngestChannel::set_schema_drift_alert`](crate::StreamingIngestChannel::set_schema_drift_alert).
+    SchemaDrift {
+        /// Channel name.
+        channel: String,
+        /// Fields added and removed.
+        drift: SchemaDrift,
+    },
+    /// A [`FailoverChannel`](crate::FailoverChannel) moved from the primary
+    /// account to the secondary. The secondary continues from its own
+    /// committed offset; rows pushed to the primary past `committed` may or
+    /// may not be committed there.
+    FailedOver {
+        /// Channel name.
+        channel: String,
+        /// How long the primary had been failing.
+        unavailable_for: Duration,
+        /// Last offset token the primary reported committed.
+        committed: u64,
+        /// Last offset token pushed to the primary.
+        pushed: u64,
+    },
+    /// The local clock differs from Snowflake's by more than
+    /// `Config::max_clock_skew`; locally generated JWTs are shifted to
+    /// compensate, but the host's time sync should be fixed.
+    ClockSkew {
+        /// Offset of Snowflake's clock from the local one; positive when the
+        /// local clock is behind.
+        skew: jiff::SignedDuration,
+        /// Configured threshold.
+        threshold: Duration,
+    },
+    /// A replicating [`FailoverChannel`](crate::FailoverChannel) failed to
+    /// append to (or close on) one account while the other succeeded, so the
+    /// accounts have diverged.
+    ReplicaAppendFailed {
+        /// Channel name.
+        channel: String,
+        /// Account that failed.
+        account: FailoverAccount,
+        /// The failure.
+        error: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::JwtLifetimeClamped {
+                original_secs,
+                effective_secs,
+            } => write!(
+                f,
+                "jwt_exp {original_secs} outside supported range; clamped to {effective_secs}"
+            ),
+            Warning::DeprecatedConfig { field, advice } => {
+                write!(f, "{field} configuration is deprecated; {advice}")
+            }
+            Warning::TokenNearExpiry {
+                token,
+                remaining_secs,
+            } => {
+                let name = match token {
+                    TokenKind::Control => "JWT",
+                    TokenKind::Scoped => "scoped token",
+                };
+                write!(f, "{name} expires in {remaining_secs}s; refreshing")
+            }
+            Warning::CommitLagExceeded {
+                channel,
+                lag,
+                threshold,
+            } => write!(
+                f,
+                "channel '{channel}' commit lag {lag:?} exceeds threshold {threshold:?}"
+            ),
+            Warning::LongCommitWait {
+                channel,
+                waited,
+                committed,
+                pushed,
+            } => write!(
+                f,
+                "channel '{channel}' close still waiting after {waited:?}; committed={committed} pushed={pushed}"
+            ),
+            Warning::WriterSuperseded {
+                channel,
+                generation,
+                newer,
+            } => write!(
+                f,
+                "channel '{channel}': writer generation {generation} superseded by {newer}"
+            ),
+            Warning::Misuse { lint, subject } => {
+                write!(f, "{} ({subject}): {}", lint.name(), lint.advice())
+            }
+            Warning::CapabilityUnavailable {
+                capability,
+                server_version,
+            } => write!(
+                f,
+                "server API {server_version} does not support {capability}; falling back"
+            ),
+            Warning::ChannelReopened { from, to } => {
+                write!(
+                    f,
+                    "channel '{from}' was fenced by another writer; reopened as '{to}'"
+                )
+            }
+            Warning::ChannelQuarantined {
+                channel,
+                rejected,
+                sent,
+            } => write!(
+                f,
+                "channel '{channel}' quarantined: {rejected} of {sent} rows rejected"
+            ),
+            Warning::SchemaDrift { channel, drift } => write!(
+                f,
+                "channel '{channel}' schema drift: added {:?}, removed {:?}",
+                drift.added, drift.removed
+            ),
+            Warning::FailedOver {
+                channel,
+                unavailable_for,
+                committed,
+                pushed,
+            } => write!(
+                f,
+                "channel '{channel}' failed over to the secondary account after {unavailable_for:?}; primary committed={committed} pushed={pushed}"
+            ),
+            Warning::ClockSkew { skew, threshold } => write!(
+                f,
+                "local clock differs from Snowflake by {skew:?} (threshold {threshold:?})"
+            ),
+            Warning::ReplicaAppendFailed {
+                channel,
+                account,
+                error,
+            } => write!(
+                f,
+                "channel '{channel}' diverged: {account:?} account failed: {error}"
+            ),
+        }
+    }
+}
+
+/// Usage patterns that work but are likely mistakes, reported with
+/// [`Warning::Misuse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Lint {
+    /// `append_row` is being called in a tight loop, sending one request per
+    /// row.
+    SingleRowAppends,
+    /// `max_rows_per_request` splits batches into many small requests.
+    ChunkingThrash,
+    /// A channel was dropped without `close`, so queued rows may not have
+    /// been sent and pushed rows may not have committed.
+    ChannelNotClosed,
+    /// A `jwt_token`, which the client cannot refresh, is about to expire.
+    StaticTokenExpiring,
+}
+
+impl Lint {
+    /// Short identifier, e.g. `single_row_appends`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::SingleRowAppends => "single_row_appends",
+            Lint::ChunkingThrash => "chunking_thrash",
+            Lint::ChannelNotClosed => "channel_not_closed",
+            Lint::StaticTokenExpiring => "static_token_expiring",
+        }
+    }
+
+    /// What to do instead.
+    pub fn advice(&self) -> &'static str {
+        match self {
+            Lint::SingleRowAppends => {
+                "append_row sends one request per row; batch rows with append_rows_iter or queue them with append_row_async"
+            }
+            Lint::ChunkingThrash => {
+                "max_rows_per_request splits each batch into many small requests; raise it or leave it unset to fill requests up to 16MB"
+            }
+            Lint::ChannelNotClosed => {
+                "channel dropped without close(); call close() so queued rows are sent and pushed rows commit"
+            }
+            Lint::StaticTokenExpiring => {
+                "jwt_token is about to expire and cannot be refreshed; supply a private key or a scoped token provider"
+            }
+        }
+    }
+}
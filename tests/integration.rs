@@ -343,7 +343,7 @@ async fn append_rows_error_is_mapped() {
         })
         .await
         .expect_err("expected error");
-    match err {
+    match err.root() {
         snowpipe_streaming::Error::Http(_, _) => {}
         snowpipe_streaming::Error::Reqwest(e)
             if e.status() == Some(reqwest::StatusCode::PAYLOAD_TOO_LARGE) => {}
@@ -511,8 +511,13 @@ async fn chunk_size_guard_two_large_rows_yield_two_posts() {
         RowType { id: 2, ..big },
     ]
     .into_iter();
-    let _ = ch.append_rows(&mut iter).await.expect("append_rows");
-    ch.close().await.expect("close");
+    let bytes = ch.append_rows(&mut iter).await.expect("append_rows");
+    let report = ch.close().await.expect("close");
+    assert_eq!(report.rows, 2);
+    assert_eq!(report.requests, 2);
+    assert_eq!(report.bytes, bytes as u64);
+    assert_eq!(report.retries, 0);
+    assert!(report.duration >= report.commit_wait);
 
     let reqs = server.received_requests().await.unwrap_or_default();
     let rows_posts = reqs